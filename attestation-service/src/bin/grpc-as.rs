@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
@@ -29,6 +30,53 @@ pub struct Cli {
     /// Socket that the server will listen on to accept requests.
     #[arg(short, long, default_value = "127.0.0.1:3000")]
     pub socket: SocketAddr,
+
+    /// Path to the PEM encoded certificate chain of the server. Both the
+    /// certificate and the private key are needed to enable TLS.
+    #[arg(long, requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key of the server.
+    #[arg(long, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Path to the PEM encoded CA certificate that issues the client
+    /// certificates of mutual TLS. Clients without a certificate can still
+    /// call the attestation and challenge RPCs.
+    #[arg(long, requires = "tls_cert")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// Subject common name of a client certificate that is allowed to call
+    /// the admin RPCs, i.e. to manage policies and reference values. Can be
+    /// specified multiple times. If none is given, the admin RPCs are
+    /// disabled.
+    #[arg(long = "admin-client-cn", requires = "tls_client_ca")]
+    pub admin_client_cns: Vec<String>,
+
+    /// Leave the admin RPCs unauthenticated when no admin client common
+    /// name is given. Only meant for local testing.
+    #[arg(long, conflicts_with = "admin_client_cns")]
+    pub insecure_admin: bool,
+
+    /// Tenant of the client certificates with a subject common name, as
+    /// `<common name>=<tenant>`. Can be specified multiple times. Clients
+    /// that are not mapped to a tenant call for the default tenant.
+    #[arg(
+        long = "tenant-client-cn",
+        value_parser = grpc::admin::parse_client_tenant,
+        requires = "tls_client_ca"
+    )]
+    pub tenant_client_cns: Vec<(String, String)>,
+
+    /// Tenant of the client certificates with a subject organization name,
+    /// as `<organization>=<tenant>`. Can be specified multiple times. A
+    /// tenant by the common name takes precedence.
+    #[arg(
+        long = "tenant-client-org",
+        value_parser = grpc::admin::parse_client_tenant,
+        requires = "tls_client_ca"
+    )]
+    pub tenant_client_orgs: Vec<(String, String)>,
 }
 
 #[tokio::main]
@@ -65,7 +113,18 @@ loglevel: {env_filter}
 
     let cli = Cli::parse();
 
-    let server = grpc::start(cli.socket, cli.config_file);
+    let tls = grpc::admin::TlsConfig {
+        cert: cli.tls_cert,
+        key: cli.tls_key,
+        client_ca: cli.tls_client_ca,
+    };
+    let admin = grpc::admin::AdminConfig {
+        allowed_cns: cli.admin_client_cns,
+        insecure: cli.insecure_admin,
+        tenant_cns: cli.tenant_client_cns.into_iter().collect(),
+        tenant_organizations: cli.tenant_client_orgs.into_iter().collect(),
+    };
+    let server = grpc::start(cli.socket, cli.config_file, tls, admin);
     tokio::try_join!(server)?;
 
     Ok(())
@@ -0,0 +1,332 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Authentication of the admin RPCs, i.e. the policy and reference value
+//! management, by client certificates of mutual TLS. The client certificate
+//! also tells the tenant of a call, if it is mapped to one.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use attestation_service::tenant::DEFAULT_TENANT;
+use openssl::{nid::Nid, x509::X509};
+use thiserror::Error;
+use tonic::{
+    transport::{Certificate, Identity, ServerTlsConfig},
+    Request, Status,
+};
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Admin clients and client tenants need a client CA certificate to verify the clients")]
+    NoClientCa,
+    #[error("A client CA certificate needs the server certificate and key for TLS")]
+    NoServerIdentity,
+    #[error("No valid client certificate is given")]
+    NoClientCertificate,
+    #[error("No admin client is configured with --admin-client-cn, the admin RPCs are disabled")]
+    Disabled,
+    #[error("Client {0} is not allowed to call admin RPCs")]
+    NotAllowed(String),
+}
+
+impl From<AdminError> for Status {
+    fn from(e: AdminError) -> Self {
+        match e {
+            AdminError::NotAllowed(_) | AdminError::Disabled => {
+                Status::permission_denied(e.to_string())
+            }
+            AdminError::NoClientCertificate => Status::unauthenticated(e.to_string()),
+            _ => Status::internal(e.to_string()),
+        }
+    }
+}
+
+/// TLS configuration of `grpc-as`.
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    /// Paths to the PEM encoded certificate chain and private key of the
+    /// server. TLS is enabled if both are given.
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+
+    /// Path to the PEM encoded CA certificate that issues the client
+    /// certificates. Clients may still connect without a certificate.
+    pub client_ca: Option<PathBuf>,
+}
+
+fn read(path: &PathBuf) -> Result<Vec<u8>, AdminError> {
+    std::fs::read(path).map_err(|source| AdminError::Read {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+impl TlsConfig {
+    pub fn to_server_tls_config(&self) -> Result<Option<ServerTlsConfig>, AdminError> {
+        let (Some(cert), Some(key)) = (&self.cert, &self.key) else {
+            if self.client_ca.is_some() {
+                return Err(AdminError::NoServerIdentity);
+            }
+            return Ok(None);
+        };
+
+        let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(read(cert)?, read(key)?));
+        if let Some(client_ca) = &self.client_ca {
+            tls = tls
+                .client_ca_root(Certificate::from_pem(read(client_ca)?))
+                .client_auth_optional(true);
+        }
+
+        Ok(Some(tls))
+    }
+}
+
+/// Admin and tenant configuration of `grpc-as`.
+#[derive(Clone, Debug, Default)]
+pub struct AdminConfig {
+    /// Subject common names of the client certificates that are allowed to
+    /// call the admin RPCs.
+    pub allowed_cns: Vec<String>,
+
+    /// Leave the admin RPCs unauthenticated if no common name is allowed.
+    pub insecure: bool,
+
+    /// Tenants of the client certificates, by their subject common name.
+    pub tenant_cns: HashMap<String, String>,
+
+    /// Tenants of the client certificates, by their subject organization
+    /// name. A tenant by the common name takes precedence.
+    pub tenant_organizations: HashMap<String, String>,
+}
+
+/// Admin authentication of `grpc-as`. Without any allowed common name, the
+/// admin RPCs are disabled unless they are explicitly left unauthenticated.
+#[derive(Clone, Debug, Default)]
+pub struct Admin {
+    config: AdminConfig,
+}
+
+impl Admin {
+    pub fn new(config: AdminConfig, tls: &TlsConfig) -> Result<Self, AdminError> {
+        let verifies_clients = !config.allowed_cns.is_empty()
+            || !config.tenant_cns.is_empty()
+            || !config.tenant_organizations.is_empty();
+        if verifies_clients && tls.client_ca.is_none() {
+            return Err(AdminError::NoClientCa);
+        }
+
+        if config.allowed_cns.is_empty() {
+            if config.insecure {
+                warn!(
+                    "--insecure-admin is given: the admin RPCs, i.e. the policy and reference \
+                    value management, are not authenticated and any client can call them"
+                );
+            } else {
+                warn!(
+                    "No --admin-client-cn is given: the admin RPCs, i.e. the policy and \
+                    reference value management, are disabled, also for a KBS that sets the \
+                    policies. Allow the client certificates with --admin-client-cn, or give \
+                    --insecure-admin for local testing"
+                );
+            }
+        }
+
+        Ok(Self { config })
+    }
+
+    /// Check that the request comes over mutual TLS with a client
+    /// certificate whose subject common name is allowed. The certificate
+    /// has already been verified against the client CA in the handshake.
+    /// Return the tenant of the admin, see [`Admin::tenant`].
+    pub fn authorize<T>(&self, request: &Request<T>) -> Result<String, AdminError> {
+        if self.config.allowed_cns.is_empty() {
+            if !self.config.insecure {
+                return Err(AdminError::Disabled);
+            }
+            return Ok(self.tenant(request));
+        }
+
+        let cn = request
+            .peer_certs()
+            .and_then(|certs| subject_entry(certs.first()?.as_ref(), Nid::COMMONNAME))
+            .ok_or(AdminError::NoClientCertificate)?;
+        if !self.config.allowed_cns.contains(&cn) {
+            return Err(AdminError::NotAllowed(cn));
+        }
+
+        Ok(self.tenant(request))
+    }
+
+    /// The tenant of a request is the one that the subject common name, or
+    /// else the organization name, of its client certificate is mapped to.
+    /// Other requests belong to the default tenant.
+    pub fn tenant<T>(&self, request: &Request<T>) -> String {
+        request
+            .peer_certs()
+            .and_then(|certs| self.client_tenant(certs.first()?.as_ref()))
+            .unwrap_or_else(|| DEFAULT_TENANT.to_string())
+    }
+
+    /// The tenant that a DER encoded client certificate is mapped to.
+    fn client_tenant(&self, der: &[u8]) -> Option<String> {
+        subject_entry(der, Nid::COMMONNAME)
+            .and_then(|cn| self.config.tenant_cns.get(&cn))
+            .or_else(|| {
+                subject_entry(der, Nid::ORGANIZATIONNAME)
+                    .and_then(|o| self.config.tenant_organizations.get(&o))
+            })
+            .cloned()
+    }
+}
+
+/// Parse a `<name>=<tenant>` mapping of a client certificate to its tenant.
+pub fn parse_client_tenant(mapping: &str) -> Result<(String, String), String> {
+    match mapping.split_once('=') {
+        Some((name, tenant)) if !name.is_empty() && !tenant.is_empty() => {
+            Ok((name.to_string(), tenant.to_string()))
+        }
+        _ => Err(format!("expected <name>=<tenant>, got {mapping}")),
+    }
+}
+
+fn subject_entry(der: &[u8], nid: Nid) -> Option<String> {
+    let cert = X509::from_der(der).ok()?;
+    let entry = cert.subject_name().entries_by_nid(nid).next()?;
+    String::from_utf8(entry.data().as_slice().to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::{
+        asn1::Asn1Time,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        pkey::PKey,
+        x509::{X509Builder, X509NameBuilder},
+    };
+
+    use super::*;
+
+    fn certificate(cn: &str, organization: Option<&str>) -> Vec<u8> {
+        let key = PKey::from_ec_key(
+            EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+        if let Some(organization) = organization {
+            name.append_entry_by_nid(Nid::ORGANIZATIONNAME, organization)
+                .unwrap();
+        }
+        let name = name.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        builder.build().to_der().unwrap()
+    }
+
+    #[test]
+    fn client_subject() {
+        let cert = certificate("admin", Some("team-a"));
+        assert_eq!(subject_entry(&cert, Nid::COMMONNAME).unwrap(), "admin");
+        assert_eq!(
+            subject_entry(&cert, Nid::ORGANIZATIONNAME).unwrap(),
+            "team-a"
+        );
+
+        let cert = certificate("admin", None);
+        assert!(subject_entry(&cert, Nid::ORGANIZATIONNAME).is_none());
+        assert!(subject_entry(b"not a certificate", Nid::COMMONNAME).is_none());
+
+        assert_eq!(Admin::default().tenant(&Request::new(())), DEFAULT_TENANT);
+    }
+
+    #[test]
+    fn client_tenant_mapping() {
+        let admin = Admin {
+            config: AdminConfig {
+                tenant_cns: HashMap::from([("kbs-b".into(), "team-b".into())]),
+                tenant_organizations: HashMap::from([("Team A".into(), "team-a".into())]),
+                ..Default::default()
+            },
+        };
+        let tenant = |cn, organization| admin.client_tenant(&certificate(cn, organization));
+        assert_eq!(tenant("kbs-a", Some("Team A")).unwrap(), "team-a");
+        assert_eq!(tenant("kbs-b", Some("Team A")).unwrap(), "team-b");
+        assert_eq!(tenant("kbs-b", None).unwrap(), "team-b");
+
+        // The organization name alone does not tell the tenant
+        assert!(tenant("kbs-c", Some("team-a")).is_none());
+        assert!(tenant("kbs-c", None).is_none());
+
+        assert_eq!(
+            parse_client_tenant("kbs-a=team-a").unwrap(),
+            ("kbs-a".to_string(), "team-a".to_string())
+        );
+        assert!(parse_client_tenant("kbs-a").is_err());
+        assert!(parse_client_tenant("=team-a").is_err());
+        assert!(parse_client_tenant("kbs-a=").is_err());
+    }
+
+    #[test]
+    fn admin_config() {
+        let tls = TlsConfig::default();
+        let allowed = AdminConfig {
+            allowed_cns: vec!["admin".into()],
+            ..Default::default()
+        };
+        assert!(Admin::new(allowed.clone(), &tls).is_err());
+        let tenants = AdminConfig {
+            tenant_organizations: HashMap::from([("Team A".into(), "team-a".into())]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Admin::new(tenants, &tls),
+            Err(AdminError::NoClientCa)
+        ));
+
+        // Without allowed clients, nobody is an admin unless the admin RPCs
+        // are explicitly left open
+        let admin = Admin::new(AdminConfig::default(), &tls).unwrap();
+        let status = Status::from(admin.authorize(&Request::new(())).unwrap_err());
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let insecure = AdminConfig {
+            insecure: true,
+            ..Default::default()
+        };
+        let admin = Admin::new(insecure, &tls).unwrap();
+        assert_eq!(admin.authorize(&Request::new(())).unwrap(), DEFAULT_TENANT);
+
+        let tls = TlsConfig {
+            client_ca: Some("ca.pem".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            tls.to_server_tls_config(),
+            Err(AdminError::NoServerIdentity)
+        ));
+
+        let admin = Admin::new(allowed, &tls).unwrap();
+        let status = Status::from(admin.authorize(&Request::new(())).unwrap_err());
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+}
@@ -1,4 +1,6 @@
 use anyhow::bail;
+use attestation_service::tenant::TenantError;
+use attestation_service::token::TokenFormat;
 use attestation_service::HashAlgorithm;
 use attestation_service::{
     config::Config, config::ConfigError, AttestationService as Service, ServiceError, Tee,
@@ -6,8 +8,10 @@ use attestation_service::{
 };
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
+use futures::stream::BoxStream;
 use std::net::SocketAddr;
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::RwLock;
@@ -16,19 +20,26 @@ use tonic::{Request, Response, Status};
 use tracing::{debug, info, instrument, Span};
 use uuid::Uuid;
 
+use self::admin::{Admin, AdminConfig, AdminError, TlsConfig};
 use crate::as_api::attestation_service_server::{AttestationService, AttestationServiceServer};
 use crate::as_api::{
-    AttestationRequest, AttestationResponse, ChallengeRequest, ChallengeResponse, SetPolicyRequest,
-    SetPolicyResponse,
+    AttestationRequest, AttestationResponse, ChallengeRequest, ChallengeResponse,
+    DeletePolicyRequest, DeletePolicyResponse, GetPolicyRequest, GetPolicyResponse,
+    ListPoliciesRequest, ListPoliciesResponse, SetPolicyRequest, SetPolicyResponse,
 };
 use crate::rvps_api::{
     reference_value_provider_service_server::{
         ReferenceValueProviderService, ReferenceValueProviderServiceServer,
     },
-    ReferenceValueQueryRequest, ReferenceValueQueryResponse, ReferenceValueRegisterRequest,
-    ReferenceValueRegisterResponse,
+    ReferenceValueDeleteRequest, ReferenceValueDeleteResponse, ReferenceValueImportRequest,
+    ReferenceValueImportResponse, ReferenceValueListRequest, ReferenceValueListResponse,
+    ReferenceValuePurgeRequest, ReferenceValuePurgeResponse, ReferenceValueQueryRequest,
+    ReferenceValueQueryResponse, ReferenceValueRegisterRequest, ReferenceValueRegisterResponse,
+    ReferenceValueWatchRequest, ReferenceValueWatchResponse,
 };
 
+pub mod admin;
+
 fn to_kbs_tee(tee: &str) -> anyhow::Result<Tee> {
     let tee = match tee {
         "sev" => Tee::Sev,
@@ -50,6 +61,16 @@ fn to_kbs_tee(tee: &str) -> anyhow::Result<Tee> {
     Ok(tee)
 }
 
+/// The status of a failed call. An exceeded quota or an unknown tenant is
+/// told apart from other failures.
+fn to_status(e: &anyhow::Error, message: String) -> Status {
+    match e.downcast_ref::<TenantError>() {
+        Some(TenantError::QuotaExceeded { .. }) => Status::resource_exhausted(message),
+        Some(TenantError::UnknownTenant(_)) => Status::permission_denied(message),
+        _ => Status::aborted(message),
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum GrpcError {
     #[error("Failed to read Attestation Service config file: {0}")]
@@ -58,14 +79,17 @@ pub enum GrpcError {
     Service(#[from] ServiceError),
     #[error("tonic transport error: {0}")]
     TonicTransport(#[from] tonic::transport::Error),
+    #[error("Failed to initialize admin authentication: {0}")]
+    Admin(#[from] AdminError),
 }
 
 pub struct AttestationServer {
     attestation_service: Service,
+    admin: Admin,
 }
 
 impl AttestationServer {
-    pub async fn new(config_path: Option<String>) -> Result<Self, GrpcError> {
+    pub async fn new(config_path: Option<String>, admin: Admin) -> Result<Self, GrpcError> {
         let config = match config_path {
             Some(path) => Config::try_from(Path::new(&path)).map_err(GrpcError::Config)?,
             None => Config::default(),
@@ -76,6 +100,7 @@ impl AttestationServer {
 
         Ok(Self {
             attestation_service: service,
+            admin,
         })
     }
 }
@@ -87,6 +112,7 @@ impl AttestationService for Arc<RwLock<AttestationServer>> {
         &self,
         request: Request<SetPolicyRequest>,
     ) -> Result<Response<SetPolicyResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
         let request: SetPolicyRequest = request.into_inner();
 
         let request_id = Uuid::new_v4().to_string();
@@ -98,19 +124,95 @@ impl AttestationService for Arc<RwLock<AttestationServer>> {
         self.write()
             .await
             .attestation_service
-            .set_policy(request.policy_id, request.policy)
+            .set_policy(&tenant, request.policy_id, request.policy)
             .await
-            .map_err(|e| Status::aborted(format!("Set Attestation Policy Failed: {e}")))?;
+            .map_err(|e| to_status(&e, format!("Set Attestation Policy Failed: {e}")))?;
 
         info!("SetPolicy succeeded.");
         Ok(Response::new(SetPolicyResponse {}))
     }
 
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn list_attestation_policies(
+        &self,
+        request: Request<ListPoliciesRequest>,
+    ) -> Result<Response<ListPoliciesResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+
+        info!("ListPolicies API called.");
+
+        let policies = self
+            .read()
+            .await
+            .attestation_service
+            .list_policies(&tenant)
+            .await
+            .map_err(|e| Status::aborted(format!("List Attestation Policies Failed: {e:#}")))?;
+
+        info!("ListPolicies succeeded.");
+        Ok(Response::new(ListPoliciesResponse { policies }))
+    }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn get_attestation_policy(
+        &self,
+        request: Request<GetPolicyRequest>,
+    ) -> Result<Response<GetPolicyResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request: GetPolicyRequest = request.into_inner();
+
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+
+        info!("GetPolicy API called.");
+        debug!("GetPolicyInput: {request:#?}");
+
+        let policy = self
+            .read()
+            .await
+            .attestation_service
+            .get_policy(&tenant, request.policy_id)
+            .await
+            .map_err(|e| Status::aborted(format!("Get Attestation Policy Failed: {e:#}")))?;
+
+        info!("GetPolicy succeeded.");
+        Ok(Response::new(GetPolicyResponse { policy }))
+    }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn delete_attestation_policy(
+        &self,
+        request: Request<DeletePolicyRequest>,
+    ) -> Result<Response<DeletePolicyResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request: DeletePolicyRequest = request.into_inner();
+
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+
+        info!("DeletePolicy API called.");
+        debug!("DeletePolicyInput: {request:#?}");
+
+        self.write()
+            .await
+            .attestation_service
+            .delete_policy(&tenant, request.policy_id)
+            .await
+            .map_err(|e| Status::aborted(format!("Delete Attestation Policy Failed: {e:#}")))?;
+
+        info!("DeletePolicy succeeded.");
+        Ok(Response::new(DeletePolicyResponse {}))
+    }
+
     #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
     async fn attestation_evaluate(
         &self,
         request: Request<AttestationRequest>,
     ) -> Result<Response<AttestationResponse>, Status> {
+        let tenant = self.read().await.admin.tenant(&request);
         let request: AttestationRequest = request.into_inner();
 
         let request_id = Uuid::new_v4().to_string();
@@ -123,10 +225,10 @@ impl AttestationService for Arc<RwLock<AttestationServer>> {
         for verification_request in request.verification_requests {
             let tee = to_kbs_tee(&verification_request.tee)
                 .map_err(|e| Status::aborted(format!("parse TEE type: {e}")))?;
-            let evidence = URL_SAFE_NO_PAD
+            let raw_evidence = URL_SAFE_NO_PAD
                 .decode(verification_request.evidence)
                 .map_err(|e| Status::aborted(format!("Illegal input Evidence: {e}")))?;
-            let evidence: TeeEvidence = serde_json::from_slice(&evidence)
+            let evidence: TeeEvidence = serde_json::from_slice(&raw_evidence)
                 .map_err(|e| Status::aborted(format!("failed to parse tee evidence: {e}")))?;
 
             let runtime_data = match verification_request.runtime_data {
@@ -182,6 +284,7 @@ impl AttestationService for Arc<RwLock<AttestationServer>> {
 
             verification_requests.push(VerificationRequest {
                 evidence,
+                raw_evidence,
                 tee,
                 runtime_data,
                 runtime_data_hash_algorithm,
@@ -193,13 +296,30 @@ impl AttestationService for Arc<RwLock<AttestationServer>> {
             false => request.policy_ids,
         };
 
+        let token_format = match request.token_format.is_empty() {
+            true => TokenFormat::default(),
+            false => TokenFormat::from_str(&request.token_format)
+                .map_err(|e| Status::aborted(format!("parse token format failed: {e}")))?,
+        };
+
+        let nonce = match request.nonce.is_empty() {
+            true => None,
+            false => Some(request.nonce),
+        };
+
         let attestation_token = self
             .read()
             .await
             .attestation_service
-            .evaluate(verification_requests, policy_ids)
+            .evaluate(
+                &tenant,
+                verification_requests,
+                policy_ids,
+                token_format,
+                nonce,
+            )
             .await
-            .map_err(|e| Status::aborted(format!("Attestation evaluation failed: {e:?}")))?;
+            .map_err(|e| to_status(&e, format!("Attestation evaluation failed: {e:?}")))?;
 
         debug!(token = attestation_token, "Attestation Token");
         info!("AttestationEvaluate succeeded.");
@@ -250,19 +370,29 @@ impl ReferenceValueProviderService for Arc<RwLock<AttestationServer>> {
     #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
     async fn query_reference_value(
         &self,
-        _request: Request<ReferenceValueQueryRequest>,
+        request: Request<ReferenceValueQueryRequest>,
     ) -> Result<Response<ReferenceValueQueryResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request = request.into_inner();
         let request_id = Uuid::new_v4().to_string();
         Span::current().record("request_id", tracing::field::display(&request_id));
         info!("GetReferenceValue API called.");
 
-        let values = self
+        let mut values = self
             .read()
             .await
             .attestation_service
-            .query_reference_values()
+            .query_reference_values(&tenant)
             .await
             .map_err(|e| Status::aborted(format!("Failed to query reference values: {e}")))?;
+        if !request.prefixes.is_empty() {
+            values.retain(|name, _| {
+                request
+                    .prefixes
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+            });
+        }
 
         let res = ReferenceValueQueryResponse {
             reference_value_results: serde_json::to_string(&values).map_err(|e| {
@@ -279,6 +409,7 @@ impl ReferenceValueProviderService for Arc<RwLock<AttestationServer>> {
         &self,
         request: Request<ReferenceValueRegisterRequest>,
     ) -> Result<Response<ReferenceValueRegisterResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
         let request = request.into_inner();
         let request_id = Uuid::new_v4().to_string();
         Span::current().record("request_id", tracing::field::display(&request_id));
@@ -288,25 +419,173 @@ impl ReferenceValueProviderService for Arc<RwLock<AttestationServer>> {
         self.write()
             .await
             .attestation_service
-            .register_reference_value(&request.message)
+            .register_reference_value(&tenant, &request.message)
             .await
-            .map_err(|e| Status::aborted(format!("Register reference value: {e}")))?;
+            .map_err(|e| to_status(&e, format!("Register reference value: {e}")))?;
 
         info!("RegisterReferenceValue succeeded.");
         let res = ReferenceValueRegisterResponse {};
         Ok(Response::new(res))
     }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn delete_reference_value(
+        &self,
+        request: Request<ReferenceValueDeleteRequest>,
+    ) -> Result<Response<ReferenceValueDeleteResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request = request.into_inner();
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+        info!("DeleteReferenceValue API called.");
+
+        let mut server = self.write().await;
+        let deleted = match (request.name.is_empty(), request.prefix.is_empty()) {
+            (false, true) => {
+                debug!("deleting reference value: {}", request.name);
+                let existed = server
+                    .attestation_service
+                    .delete_reference_value(&tenant, &request.name)
+                    .await
+                    .map_err(|e| Status::aborted(format!("Delete reference value: {e}")))?;
+                match existed {
+                    true => vec![request.name],
+                    false => vec![],
+                }
+            }
+            (true, false) => {
+                debug!("deleting reference values by prefix: {}", request.prefix);
+                server
+                    .attestation_service
+                    .delete_reference_values_by_prefix(&tenant, &request.prefix)
+                    .await
+                    .map_err(|e| Status::aborted(format!("Delete reference values: {e}")))?
+            }
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Exactly one of `name` and `prefix` must be given",
+                ))
+            }
+        };
+
+        info!("DeleteReferenceValue succeeded.");
+        let res = ReferenceValueDeleteResponse { deleted };
+        Ok(Response::new(res))
+    }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn list_reference_values(
+        &self,
+        request: Request<ReferenceValueListRequest>,
+    ) -> Result<Response<ReferenceValueListResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+        info!("ListReferenceValues API called.");
+
+        let values = self
+            .read()
+            .await
+            .attestation_service
+            .list_reference_values(&tenant)
+            .await
+            .map_err(|e| Status::aborted(format!("Failed to list reference values: {e}")))?;
+
+        let res = ReferenceValueListResponse {
+            reference_values: serde_json::to_string(&values).map_err(|e| {
+                Status::aborted(format!("Failed to serialize reference values: {e}"))
+            })?,
+        };
+
+        info!("ListReferenceValues succeeded.");
+        Ok(Response::new(res))
+    }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn purge_expired_reference_values(
+        &self,
+        request: Request<ReferenceValuePurgeRequest>,
+    ) -> Result<Response<ReferenceValuePurgeResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+        info!("PurgeExpiredReferenceValues API called.");
+
+        let purged = self
+            .write()
+            .await
+            .attestation_service
+            .purge_expired_reference_values(&tenant)
+            .await
+            .map_err(|e| Status::aborted(format!("Purge expired reference values: {e}")))?;
+
+        info!("PurgeExpiredReferenceValues succeeded.");
+        let res = ReferenceValuePurgeResponse { purged };
+        Ok(Response::new(res))
+    }
+
+    #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+    async fn import_reference_values(
+        &self,
+        request: Request<ReferenceValueImportRequest>,
+    ) -> Result<Response<ReferenceValueImportResponse>, Status> {
+        let tenant = self.read().await.admin.authorize(&request)?;
+        let request = request.into_inner();
+        let request_id = Uuid::new_v4().to_string();
+        Span::current().record("request_id", tracing::field::display(&request_id));
+        info!("ImportReferenceValues API called.");
+
+        let rvs = serde_json::from_str(&request.reference_values)
+            .map_err(|e| Status::invalid_argument(format!("Parse reference values: {e}")))?;
+        let imported = self
+            .write()
+            .await
+            .attestation_service
+            .import_reference_values(&tenant, rvs)
+            .await
+            .map_err(|e| to_status(&e, format!("Import reference values: {e}")))?;
+
+        info!("ImportReferenceValues succeeded.");
+        let res = ReferenceValueImportResponse { imported };
+        Ok(Response::new(res))
+    }
+
+    type WatchReferenceValuesStream =
+        BoxStream<'static, Result<ReferenceValueWatchResponse, Status>>;
+
+    async fn watch_reference_values(
+        &self,
+        _request: Request<ReferenceValueWatchRequest>,
+    ) -> Result<Response<Self::WatchReferenceValuesStream>, Status> {
+        Err(Status::unimplemented(
+            "Watching reference values is only supported by the RVPS",
+        ))
+    }
 }
 
-pub async fn start(socket: SocketAddr, config_path: Option<String>) -> Result<(), GrpcError> {
+pub async fn start(
+    socket: SocketAddr,
+    config_path: Option<String>,
+    tls: TlsConfig,
+    admin_config: AdminConfig,
+) -> Result<(), GrpcError> {
     info!(
         "Starting gRPC Attestation Service. Listening on socket: {}",
         &socket
     );
 
-    let attestation_server = Arc::new(RwLock::new(AttestationServer::new(config_path).await?));
+    let admin = Admin::new(admin_config, &tls)?;
+    let attestation_server = Arc::new(RwLock::new(
+        AttestationServer::new(config_path, admin).await?,
+    ));
+
+    let mut server = Server::builder();
+    if let Some(tls) = tls.to_server_tls_config()? {
+        info!("TLS is enabled");
+        server = server.tls_config(tls)?;
+    }
 
-    Server::builder()
+    server
         .add_service(AttestationServiceServer::new(attestation_server.clone()))
         .add_service(ReferenceValueProviderServiceServer::new(attestation_server))
         .serve(socket)
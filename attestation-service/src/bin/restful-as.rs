@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, path::Path, sync::Arc};
+use std::{net::SocketAddr, path::Path, path::PathBuf, sync::Arc};
 
 use actix_cors::Cors;
 use actix_web::{http::header, web, App, HttpServer};
@@ -16,7 +16,9 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info};
 use tracing_subscriber::{fmt::Subscriber, EnvFilter};
 
-use crate::restful::{attestation, get_challenge, get_policies, set_policy};
+use crate::restful::{
+    admin::Admin, attestation, delete_policy, get_challenge, get_history, get_policies, set_policy,
+};
 
 mod restful;
 
@@ -48,6 +50,20 @@ pub struct Cli {
     /// Can be specified multiple times or comma-separated
     #[arg(short = 'r', long = "allowed_origin", value_delimiter = ',', num_args = 1..)]
     pub allowed_origin: Vec<String>,
+
+    /// Path to a PEM encoded RSA, EC or Ed25519 public key that admin JWTs
+    /// are verified with. Can be specified multiple times. If given, the
+    /// policy and history endpoints require a bearer token signed with one of the keys,
+    /// while the attestation and challenge endpoints stay open. The `tenant` claim
+    /// of a token selects the tenant of the call, also on the attestation endpoint.
+    /// If none is given, the policy and history endpoints are disabled.
+    #[arg(long = "admin-public-key")]
+    pub admin_public_keys: Vec<PathBuf>,
+
+    /// Leave the policy and history endpoints unauthenticated when no
+    /// admin public key is given. Only meant for local testing.
+    #[arg(long, conflicts_with = "admin_public_keys")]
+    pub insecure_admin: bool,
 }
 
 #[derive(EnumString, AsRefStr)]
@@ -59,8 +75,14 @@ enum WebApi {
     #[strum(serialize = "/policy")]
     Policy,
 
+    #[strum(serialize = "/policy/{policy_id}")]
+    PolicyId,
+
     #[strum(serialize = "/challenge")]
     Challenge,
+
+    #[strum(serialize = "/history")]
+    History,
 }
 
 #[derive(Error, Debug)]
@@ -81,6 +103,8 @@ pub enum RestfulError {
     SetHttpsCert(#[source] openssl::error::ErrorStack),
     #[error("io error: {0}")]
     IO(#[from] std::io::Error),
+    #[error("Failed to initialize admin authentication: {0}")]
+    Admin(#[from] restful::admin::AdminError),
     #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
 }
@@ -160,6 +184,7 @@ loglevel: {env_filter}
     let attestation_service = AttestationService::new(config).await?;
 
     let allowed_origin = cli.allowed_origin.clone();
+    let admin = web::Data::new(Admin::new(&cli.admin_public_keys, cli.insecure_admin)?);
 
     let attestation_service = web::Data::new(Arc::new(RwLock::new(attestation_service)));
     let server = HttpServer::new(move || {
@@ -171,8 +196,15 @@ loglevel: {env_filter}
                     .route(web::post().to(set_policy))
                     .route(web::get().to(get_policies)),
             )
+            .service(
+                web::resource(WebApi::PolicyId.as_ref())
+                    .route(web::get().to(get_policies))
+                    .route(web::delete().to(delete_policy)),
+            )
             .service(web::resource(WebApi::Challenge.as_ref()).route(web::post().to(get_challenge)))
+            .service(web::resource(WebApi::History.as_ref()).route(web::get().to(get_history)))
             .app_data(web::Data::clone(&attestation_service))
+            .app_data(web::Data::clone(&admin))
     });
 
     let server = match (cli.https_prikey, cli.https_pubkey_cert) {
@@ -0,0 +1,287 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Authentication of the admin endpoints, i.e. the policy management, by
+//! JWT bearer tokens signed with one of the configured keys. The `tenant`
+//! claim of the token tells the tenant of a call.
+
+use std::path::Path;
+
+use actix_web::{http::header::AUTHORIZATION, HttpRequest};
+use attestation_service::tenant::DEFAULT_TENANT;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("Failed to read admin public key {path}: {source}")]
+    ReadPublicKey {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Admin public key {0} is not an RSA, EC or Ed25519 public key in PEM")]
+    ParsePublicKey(String),
+    #[error("No admin public key is configured, the admin endpoints are disabled")]
+    Disabled,
+    #[error("No admin bearer token is given")]
+    NoToken,
+    #[error("Admin token verification failed: {0}")]
+    JwtVerificationFailed(String),
+}
+
+struct AdminKey {
+    key: DecodingKey,
+    algorithms: &'static [Algorithm],
+}
+
+impl AdminKey {
+    fn from_pem(pem: &[u8]) -> Option<Self> {
+        if let Ok(key) = DecodingKey::from_rsa_pem(pem) {
+            return Some(Self {
+                key,
+                algorithms: &[
+                    Algorithm::RS256,
+                    Algorithm::RS384,
+                    Algorithm::RS512,
+                    Algorithm::PS256,
+                    Algorithm::PS384,
+                    Algorithm::PS512,
+                ],
+            });
+        }
+        if let Ok(key) = DecodingKey::from_ec_pem(pem) {
+            return Some(Self {
+                key,
+                algorithms: &[Algorithm::ES256, Algorithm::ES384],
+            });
+        }
+        if let Ok(key) = DecodingKey::from_ed_pem(pem) {
+            return Some(Self {
+                key,
+                algorithms: &[Algorithm::EdDSA],
+            });
+        }
+
+        None
+    }
+}
+
+/// Admin authentication of `restful-as`. Without any public key, the admin
+/// endpoints are disabled unless they are explicitly left unauthenticated.
+#[derive(Default)]
+pub struct Admin {
+    keys: Vec<AdminKey>,
+    insecure: bool,
+}
+
+impl Admin {
+    pub fn new(public_keys: &[impl AsRef<Path>], insecure: bool) -> Result<Self, AdminError> {
+        if public_keys.is_empty() {
+            if insecure {
+                warn!("No admin public key is given, the admin endpoints are not authenticated");
+            } else {
+                warn!("No admin public key is given, the admin endpoints are disabled");
+            }
+        }
+
+        let keys = public_keys
+            .iter()
+            .map(|path| {
+                let path = path.as_ref().display().to_string();
+                let pem = std::fs::read(&path).map_err(|source| AdminError::ReadPublicKey {
+                    path: path.clone(),
+                    source,
+                })?;
+                AdminKey::from_pem(&pem).ok_or(AdminError::ParsePublicKey(path))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { keys, insecure })
+    }
+
+    /// Check that the request carries a bearer token that is signed with
+    /// one of the admin keys and has not expired. Return the tenant of the
+    /// token, i.e. its `tenant` claim or the default tenant if there is none.
+    pub fn validate_auth(&self, request: &HttpRequest) -> Result<String, AdminError> {
+        if self.keys.is_empty() {
+            if !self.insecure {
+                return Err(AdminError::Disabled);
+            }
+            return Ok(DEFAULT_TENANT.to_string());
+        }
+
+        let token = bearer_token(request).ok_or(AdminError::NoToken)?;
+        self.validate_token(token)
+    }
+
+    /// The tenant of a request to an open endpoint. Such a request belongs
+    /// to the default tenant unless it carries a valid bearer token.
+    pub fn tenant(&self, request: &HttpRequest) -> Result<String, AdminError> {
+        match bearer_token(request) {
+            Some(_) => self.validate_auth(request),
+            None => Ok(DEFAULT_TENANT.to_string()),
+        }
+    }
+
+    fn validate_token(&self, token: &str) -> Result<String, AdminError> {
+        let header =
+            decode_header(token).map_err(|e| AdminError::JwtVerificationFailed(e.to_string()))?;
+        let validation = Validation::new(header.alg);
+
+        let mut error = format!("no admin key for algorithm {:?}", header.alg);
+        for key in self
+            .keys
+            .iter()
+            .filter(|key| key.algorithms.contains(&header.alg))
+        {
+            match decode::<Value>(token, &key.key, &validation) {
+                Ok(data) => {
+                    let tenant = data.claims.get("tenant").and_then(Value::as_str);
+                    return Ok(tenant.unwrap_or(DEFAULT_TENANT).to_string());
+                }
+                Err(e) => error = e.to_string(),
+            }
+        }
+
+        Err(AdminError::JwtVerificationFailed(error))
+    }
+}
+
+fn bearer_token(request: &HttpRequest) -> Option<&str> {
+    request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+        pkey::PKey,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    fn key_pair(dir: &Path, name: &str) -> (EncodingKey, std::path::PathBuf) {
+        let key = PKey::from_ec_key(
+            EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, key.public_key_to_pem().unwrap()).unwrap();
+        let encoding_key =
+            EncodingKey::from_ec_pem(&key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        (encoding_key, path)
+    }
+
+    fn token(key: &EncodingKey, exp: i64) -> String {
+        encode(
+            &Header::new(Algorithm::ES256),
+            &json!({ "sub": "admin", "exp": exp }),
+            key,
+        )
+        .unwrap()
+    }
+
+    fn bearer(token: String) -> HttpRequest {
+        TestRequest::default()
+            .insert_header((AUTHORIZATION, format!("Bearer {token}")))
+            .to_http_request()
+    }
+
+    #[test]
+    fn validate_admin_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, first_path) = key_pair(dir.path(), "first.pem");
+        let (second, second_path) = key_pair(dir.path(), "second.pem");
+        let (other, _) = key_pair(dir.path(), "other.pem");
+        let admin = Admin::new(&[first_path, second_path], false).unwrap();
+
+        let exp = time::OffsetDateTime::now_utc().unix_timestamp() + 60;
+        for key in [&first, &second] {
+            let request = bearer(token(key, exp));
+            assert_eq!(admin.validate_auth(&request).unwrap(), DEFAULT_TENANT);
+        }
+
+        let request = TestRequest::default()
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token(&other, exp))))
+            .to_http_request();
+        assert!(admin.validate_auth(&request).is_err());
+
+        let request = TestRequest::default()
+            .insert_header((
+                AUTHORIZATION,
+                format!("Bearer {}", token(&first, exp - 3600)),
+            ))
+            .to_http_request();
+        assert!(admin.validate_auth(&request).is_err());
+
+        let request = TestRequest::default().to_http_request();
+        assert!(matches!(
+            admin.validate_auth(&request),
+            Err(AdminError::NoToken)
+        ));
+
+        // Without keys, the admin endpoints are disabled unless they are
+        // explicitly left open
+        assert!(matches!(
+            Admin::new(&[] as &[std::path::PathBuf], false)
+                .unwrap()
+                .validate_auth(&request),
+            Err(AdminError::Disabled)
+        ));
+        Admin::new(&[] as &[std::path::PathBuf], true)
+            .unwrap()
+            .validate_auth(&request)
+            .unwrap();
+    }
+
+    #[test]
+    fn tenant_of_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let (key, path) = key_pair(dir.path(), "key.pem");
+        let admin = Admin::new(&[path], false).unwrap();
+
+        let exp = time::OffsetDateTime::now_utc().unix_timestamp() + 60;
+        let tenant_token = encode(
+            &Header::new(Algorithm::ES256),
+            &json!({ "sub": "admin", "exp": exp, "tenant": "team-a" }),
+            &key,
+        )
+        .unwrap();
+        assert_eq!(
+            admin.validate_auth(&bearer(tenant_token.clone())).unwrap(),
+            "team-a"
+        );
+        assert_eq!(admin.tenant(&bearer(tenant_token)).unwrap(), "team-a");
+
+        // Open endpoints fall back to the default tenant without a token,
+        // but reject invalid ones
+        let request = TestRequest::default().to_http_request();
+        assert_eq!(admin.tenant(&request).unwrap(), DEFAULT_TENANT);
+        assert!(admin.tenant(&bearer("invalid".into())).is_err());
+    }
+
+    #[test]
+    fn invalid_admin_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key.pem");
+        std::fs::write(&path, "not a key").unwrap();
+        assert!(matches!(
+            Admin::new(&[path], false),
+            Err(AdminError::ParsePublicKey(_))
+        ));
+    }
+}
@@ -3,8 +3,9 @@ use std::{collections::HashMap, sync::Arc};
 use actix_web::{body::BoxBody, web, HttpRequest, HttpResponse, ResponseError};
 use anyhow::{anyhow, bail, Context};
 use attestation_service::{
-    AttestationService, HashAlgorithm, InitDataInput as InnerInitDataInput,
-    RuntimeData as InnerRuntimeData, VerificationRequest,
+    history::HistoryQuery, tenant::TenantError, token::TokenFormat, AttestationService,
+    HashAlgorithm, InitDataInput as InnerInitDataInput, RuntimeData as InnerRuntimeData,
+    VerificationRequest,
 };
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use kbs_types::Tee;
@@ -16,10 +17,16 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, instrument, Span};
 use uuid::Uuid;
 
+use self::admin::{Admin, AdminError};
+
+pub mod admin;
+
 #[derive(Error, Debug, AsRefStr)]
 pub enum Error {
     #[error("An internal error occured: {0}")]
     InternalError(#[from] anyhow::Error),
+    #[error("Admin authentication failed: {0}")]
+    AdminAuth(#[from] AdminError),
 }
 
 impl ResponseError for Error {
@@ -28,7 +35,13 @@ impl ResponseError for Error {
 
         error!("{self:#?}");
         let mut res = match self {
-            Error::InternalError(_) => HttpResponse::InternalServerError(),
+            Error::InternalError(e) => match e.downcast_ref::<TenantError>() {
+                Some(TenantError::QuotaExceeded { .. }) => HttpResponse::TooManyRequests(),
+                Some(TenantError::UnknownTenant(_)) => HttpResponse::Forbidden(),
+                _ => HttpResponse::InternalServerError(),
+            },
+            Error::AdminAuth(AdminError::Disabled) => HttpResponse::Forbidden(),
+            Error::AdminAuth(_) => HttpResponse::Unauthorized(),
             // _ => HttpResponse::NotImplemented(),
         };
 
@@ -42,6 +55,9 @@ type Result<T> = std::result::Result<T, Error>;
 pub struct AttestationRequest {
     verification_requests: Vec<IndividualAttestationRequest>,
     policy_ids: Vec<String>,
+    #[serde(default)]
+    token_format: TokenFormat,
+    nonce: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,24 +142,27 @@ fn parse_init_data(data: InitDataInput) -> Result<InnerInitDataInput> {
 /// This handler uses json extractor
 #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
 pub async fn attestation(
+    http_request: HttpRequest,
     request: web::Json<AttestationRequest>,
     cocoas: web::Data<Arc<RwLock<AttestationService>>>,
+    admin: web::Data<Admin>,
 ) -> Result<HttpResponse> {
     let request_id = Uuid::new_v4().to_string();
     Span::current().record("request_id", tracing::field::display(&request_id));
     info!("Attestation API called.");
+    let tenant = admin.tenant(&http_request)?;
 
     let request = request.into_inner();
     debug!("attestation: {request:#?}");
 
     let mut verification_requests: Vec<VerificationRequest> = vec![];
     for attestation_request in request.verification_requests {
-        let evidence = URL_SAFE_NO_PAD
+        let raw_evidence = URL_SAFE_NO_PAD
             .decode(&attestation_request.evidence)
             .context("base64 decode evidence")?;
 
         let evidence =
-            serde_json::from_slice(&evidence).context("failed to parse evidence as JSON")?;
+            serde_json::from_slice(&raw_evidence).context("failed to parse evidence as JSON")?;
 
         let tee = to_tee(&attestation_request.tee)?;
 
@@ -170,6 +189,7 @@ pub async fn attestation(
 
         verification_requests.push(VerificationRequest {
             evidence,
+            raw_evidence,
             tee,
             runtime_data,
             runtime_data_hash_algorithm,
@@ -187,7 +207,13 @@ pub async fn attestation(
     let token = cocoas
         .read()
         .await
-        .evaluate(verification_requests, policy_ids)
+        .evaluate(
+            &tenant,
+            verification_requests,
+            policy_ids,
+            request.token_format,
+            request.nonce,
+        )
         .await
         .context("attestation report evaluate")?;
     debug!("Attestation Token: {token}");
@@ -204,19 +230,22 @@ pub struct SetPolicyInput {
 /// This handler uses json extractor with limit
 #[instrument(skip_all, fields(request_id = tracing::field::Empty))]
 pub async fn set_policy(
+    request: HttpRequest,
     input: web::Json<SetPolicyInput>,
     cocoas: web::Data<Arc<RwLock<AttestationService>>>,
+    admin: web::Data<Admin>,
 ) -> Result<HttpResponse> {
     let request_id = Uuid::new_v4().to_string();
     Span::current().record("request_id", tracing::field::display(&request_id));
     info!("Set Policy API called.");
+    let tenant = admin.validate_auth(&request)?;
     let input = input.into_inner();
 
     debug!("set policy: {input:#?}");
     cocoas
         .write()
         .await
-        .set_policy(input.policy_id, input.policy)
+        .set_policy(&tenant, input.policy_id, input.policy)
         .await
         .context("set policy")?;
     info!("SetPolicy succeeded.");
@@ -272,10 +301,12 @@ pub async fn get_challenge(
 pub async fn get_policies(
     request: HttpRequest,
     cocoas: web::Data<Arc<RwLock<AttestationService>>>,
+    admin: web::Data<Admin>,
 ) -> Result<HttpResponse> {
     let request_id = Uuid::new_v4().to_string();
     Span::current().record("request_id", tracing::field::display(&request_id));
     info!("GetPolicy called.");
+    let tenant = admin.validate_auth(&request)?;
 
     match request.match_info().get("policy_id") {
         Some(policy_id) => {
@@ -283,7 +314,7 @@ pub async fn get_policies(
             let policy = cocoas
                 .read()
                 .await
-                .get_policy(policy_id.to_string())
+                .get_policy(&tenant, policy_id.to_string())
                 .await
                 .context("get policy")?;
             info!("GetPolicy succeeded.");
@@ -294,7 +325,7 @@ pub async fn get_policies(
             let policy_list = cocoas
                 .read()
                 .await
-                .list_policies()
+                .list_policies(&tenant)
                 .await
                 .context("get policies")?
                 .into_iter()
@@ -309,6 +340,68 @@ pub async fn get_policies(
     }
 }
 
+/// DELETE /policy/{policy_id}
+///
+/// The built-in `default_cpu` and `default_gpu` policies cannot be deleted.
+#[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+pub async fn delete_policy(
+    request: HttpRequest,
+    cocoas: web::Data<Arc<RwLock<AttestationService>>>,
+    admin: web::Data<Admin>,
+) -> Result<HttpResponse> {
+    let request_id = Uuid::new_v4().to_string();
+    Span::current().record("request_id", tracing::field::display(&request_id));
+    info!("DeletePolicy called.");
+    let tenant = admin.validate_auth(&request)?;
+
+    let policy_id = request
+        .match_info()
+        .get("policy_id")
+        .ok_or(anyhow!("Failed to get policy id"))?;
+    info!("Delete policy: {policy_id}");
+    cocoas
+        .write()
+        .await
+        .delete_policy(&tenant, policy_id.to_string())
+        .await
+        .context("delete policy")?;
+    info!("DeletePolicy succeeded.");
+    Ok(HttpResponse::Ok().body(""))
+}
+
+/// GET /history
+///
+/// Query the attestation result history. The optional query parameters
+/// `from`, `to` (seconds since the epoch), `tee`, `status`, `policy_id` and
+/// `limit` narrow down the returned records, e.g.
+/// `/history?tee=tdx&status=contraindicated&limit=10`.
+#[instrument(skip_all, fields(request_id = tracing::field::Empty))]
+pub async fn get_history(
+    request: HttpRequest,
+    query: web::Query<HistoryQuery>,
+    cocoas: web::Data<Arc<RwLock<AttestationService>>>,
+    admin: web::Data<Admin>,
+) -> Result<HttpResponse> {
+    let request_id = Uuid::new_v4().to_string();
+    Span::current().record("request_id", tracing::field::display(&request_id));
+    info!("GetHistory called.");
+    let tenant = admin.validate_auth(&request)?;
+    debug!("History query: {query:?}");
+
+    let records = cocoas
+        .read()
+        .await
+        .query_history(&tenant, &query)
+        .await
+        .context("query history")?;
+    let body = serde_json::to_string(&records).context("serialize response body")?;
+
+    info!("GetHistory succeeded.");
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(body))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RemovePolicyRequest {
     pub policy_ids: Vec<String>,
@@ -1,9 +1,12 @@
 use crate::ear_token::EarTokenConfiguration;
+use crate::history::HistoryConfig;
 use crate::rvps::RvpsConfig;
+use crate::tenant::TenantConfig;
 
 use verifier::VerifierConfig;
 
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -12,6 +15,12 @@ use thiserror::Error;
 const AS_WORK_DIR: &str = "AS_WORK_DIR";
 pub const DEFAULT_WORK_DIR: &str = "/opt/confidential-containers/attestation-service";
 
+/// Default maximum number of evidences that are verified concurrently.
+pub const DEFAULT_MAX_CONCURRENT_VERIFICATIONS: usize = 16;
+
+/// Default time limit in seconds of the verification of a single evidence.
+pub const DEFAULT_VERIFICATION_TIMEOUT_SECS: u64 = 60;
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct Config {
     /// The location for Attestation Service to store data.
@@ -29,12 +38,58 @@ pub struct Config {
     /// Optional configuration for verifier modules
     #[serde(default)]
     pub verifier_config: Option<VerifierConfig>,
+
+    /// How the evidences of an attestation are verified.
+    #[serde(default)]
+    pub verification: VerificationConfig,
+
+    /// Optional attestation result history.
+    /// If this is not specified, no results are recorded.
+    #[serde(default)]
+    pub history: Option<HistoryConfig>,
+
+    /// The tenants besides the `default` one, by tenant id. An entry for
+    /// `default` configures the default tenant, which otherwise uses the
+    /// top-level settings without quotas.
+    #[serde(default)]
+    pub tenants: HashMap<String, TenantConfig>,
 }
 
 fn default_work_dir() -> PathBuf {
     PathBuf::from(std::env::var(AS_WORK_DIR).unwrap_or_else(|_| DEFAULT_WORK_DIR.to_string()))
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct VerificationConfig {
+    /// The maximum number of evidences of an attestation, e.g. of a CPU and
+    /// its GPUs, that are verified concurrently. `0` is treated as `1`.
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+
+    /// The time limit in seconds of the verification of a single evidence,
+    /// including the round-trips of its verifier to remote services. The
+    /// attestation fails if the limit is exceeded.
+    #[serde(default = "default_verification_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_max_concurrency() -> usize {
+    DEFAULT_MAX_CONCURRENT_VERIFICATIONS
+}
+
+fn default_verification_timeout() -> u64 {
+    DEFAULT_VERIFICATION_TIMEOUT_SECS
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: default_max_concurrency(),
+            timeout_secs: default_verification_timeout(),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("io error: {0}")]
@@ -55,6 +110,9 @@ impl Default for Config {
             rvps_config: RvpsConfig::default(),
             attestation_token_broker: EarTokenConfiguration::default(),
             verifier_config: None,
+            verification: VerificationConfig::default(),
+            history: None,
+            tenants: HashMap::new(),
         }
     }
 }
@@ -78,6 +136,22 @@ impl TryFrom<&Path> for Config {
     ///                "trusted_ak_keys_dir": "/etc/tpm/trusted_ak_keys",
     ///                "max_trusted_ak_keys": 100
     ///            }
+    ///        },
+    ///        "verification": {
+    ///            "max_concurrency": 16,
+    ///            "timeout_secs": 60
+    ///        },
+    ///        "history": {
+    ///            "file_path": "/var/lib/attestation-service/history/results.jsonl",
+    ///            "redacted_claims": ["platform_id"],
+    ///            "retention_days": 30
+    ///        },
+    ///        "tenants": {
+    ///            "team-a": {
+    ///                "quota": {
+    ///                    "max_attestations_per_minute": 600
+    ///                }
+    ///            }
     ///        }
     ///    }
     type Error = ConfigError;
@@ -90,11 +164,15 @@ impl TryFrom<&Path> for Config {
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
-    use super::Config;
-    use crate::ear_token::TokenSignerConfig;
+    use super::{Config, VerificationConfig};
+    use crate::ear_token::{
+        RawEvidenceInclusion, TokenSignerConfig, DEFAULT_MAX_POLICY_CLAIMS_SIZE,
+    };
     use crate::rvps::RvpsCrateConfig;
+    use crate::tenant::{TenantConfig, TenantQuota};
     use crate::{ear_token::EarTokenConfiguration, rvps::RvpsConfig};
     use reference_value_provider_service::storage::{local_fs, ReferenceValueStorageConfig};
 
@@ -104,7 +182,9 @@ mod tests {
         rvps_config: RvpsConfig::BuiltIn(RvpsCrateConfig {
             storage: ReferenceValueStorageConfig::LocalFs(local_fs::Config::default()),
             extractors: None,
-        }),
+            signature: Default::default(),
+            allow_import: false,
+        }.into()),
         attestation_token_broker: EarTokenConfiguration {
             duration_min: 5,
             issuer_name: "test".into(),
@@ -112,16 +192,24 @@ mod tests {
             policy_dir: "/var/lib/attestation-service/policies".into(),
             developer_name: "someone".into(),
             build_name: "0.1.0".into(),
-            profile_name: "tag:github.com,2024:confidential-containers/Trustee".into()
+            profile_name: "tag:github.com,2024:confidential-containers/Trustee".into(),
+            max_policy_claims_size: DEFAULT_MAX_POLICY_CLAIMS_SIZE,
+            jwt: Default::default(),
+            raw_evidence: RawEvidenceInclusion::None,
         },
         verifier_config: None,
+        verification: VerificationConfig::default(),
+        history: None,
+        tenants: HashMap::new(),
     })]
     #[case("./tests/configs/example2.json", Config {
         work_dir: PathBuf::from("/var/lib/attestation-service/"),
         rvps_config: RvpsConfig::BuiltIn(RvpsCrateConfig {
             storage: ReferenceValueStorageConfig::LocalFs(local_fs::Config::default()),
             extractors: None,
-        }),
+            signature: Default::default(),
+            allow_import: false,
+        }.into()),
         attestation_token_broker: EarTokenConfiguration {
             duration_min: 5,
             issuer_name: "test".into(),
@@ -129,6 +217,9 @@ mod tests {
             developer_name: "someone".into(),
             build_name: "0.1.0".into(),
             profile_name: "tag:github.com,2024:confidential-containers/Trustee".into(),
+            max_policy_claims_size: 1024,
+            jwt: Default::default(),
+            raw_evidence: RawEvidenceInclusion::Hash,
             signer: Some(TokenSignerConfig {
                 key_path: "/etc/key".into(),
                 cert_url: Some("https://example.io".into()),
@@ -136,6 +227,16 @@ mod tests {
             })
         },
         verifier_config: None,
+        verification: VerificationConfig::default(),
+        history: None,
+        tenants: HashMap::from([("team-a".into(), TenantConfig {
+            quota: TenantQuota {
+                max_policies: Some(10),
+                max_attestations_per_minute: Some(600),
+                ..Default::default()
+            },
+            ..Default::default()
+        })]),
     })]
     fn read_config(#[case] config: &str, #[case] expected: Config) {
         let config = std::fs::read_to_string(config).unwrap();
@@ -5,33 +5,84 @@
 
 use anyhow::*;
 
-use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
-use ear::{Algorithm, Appraisal, Ear, Extensions, RawValue, RawValueKind, VerifierID};
-use jsonwebtoken::jwk;
+use ear::{
+    Appraisal, Bytes, Ear, Extensions, Nonce, RawValue, RawValueKind, TrustTier, VerifierID,
+};
 use kbs_types::Tee;
-use openssl::bn::{BigNum, BigNumContext};
-use openssl::ec::{EcGroup, EcKey};
-use openssl::nid::Nid;
-use openssl::pkey::{PKey, Private};
-use openssl::x509::X509;
+use serde::Deserialize;
 use serde_json::{json, Value};
 use serde_variant::to_variant_name;
+use sha2::{Digest, Sha384};
 use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::Arc;
 use time::{Duration, OffsetDateTime};
-use tracing::{debug, info, warn};
+use tracing::{debug, warn};
 
-use crate::ear_token::EarTokenConfiguration;
-use crate::policy_engine::{PolicyEngine, PolicyEngineType};
+use crate::ear_token::{EarTokenConfiguration, RawEvidenceInclusion};
+use crate::policy_engine::{is_valid_policy_id, PolicyEngine, PolicyEngineType, PolicyError};
+use crate::rvps::RvpsApi;
+use crate::token::{token_brokers, TokenBroker, TokenFormat};
 use crate::TeeClaims;
+use verifier::NormalizedClaims;
+
+/// A signed EAR together with where its appraisals came from.
+pub struct IssuedEar {
+    /// The signed EAR token.
+    pub token: String,
+    /// The EAR as it was signed.
+    pub ear: Ear,
+    /// Where the appraisal of each submodule came from.
+    pub submods: BTreeMap<String, SubmodSource>,
+}
+
+/// The TEE and the evaluated policy behind the appraisal of a submodule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmodSource {
+    /// The TEE of the submodule, `None` for the [`COMPOSITE_SUBMOD`].
+    pub tee: Option<Tee>,
+    /// The ID of the evaluated policy.
+    pub policy_id: String,
+    /// The digest of the evaluated policy, as reported by the policy engine.
+    pub policy_hash: String,
+}
+
+/// The name of the submodule that records the result of the composite policy,
+/// and the suffix of the ID of that policy.
+pub const COMPOSITE_SUBMOD: &str = "composite";
+
+/// The key of the custom claims in the result of a policy, which are added to
+/// the policy claims of the submodule.
+const POLICY_CLAIMS: &str = "claims";
+
+/// The `result` of a composite policy.
+#[derive(Deserialize)]
+struct CompositeResult {
+    /// The trust vector of the composite submodule.
+    trust_vector: BTreeMap<String, i8>,
+
+    /// The custom claims of the composite submodule.
+    claims: Option<Value>,
+
+    /// Overrides of the appraisals of the device submodules.
+    #[serde(default)]
+    submods: BTreeMap<String, SubmodOverride>,
+}
+
+#[derive(Deserialize)]
+struct SubmodOverride {
+    /// Trust claims that replace the ones set by the device policy.
+    #[serde(default)]
+    trust_vector: BTreeMap<String, i8>,
+
+    /// A status to downgrade the submodule to.
+    status: Option<TrustTier>,
+}
 
 pub struct EarAttestationTokenBroker {
     config: EarTokenConfiguration,
-    private_key: EcKey<Private>,
-    cert_url: Option<String>,
-    cert_chain: Option<Vec<X509>>,
+    token_brokers: HashMap<TokenFormat, Box<dyn TokenBroker>>,
     policy_engine: Arc<dyn PolicyEngine>,
 }
 
@@ -39,7 +90,7 @@ impl EarAttestationTokenBroker {
     pub async fn new(config: EarTokenConfiguration) -> Result<Self> {
         // TODO: delete this warning
 
-        warn!("Simple Token has been deprecated in v0.16.0. Note that the `attestation_token_broker` config field `type` is now ignored and the token will always be an EAR token.");
+        warn!("Simple Token has been deprecated in v0.16.0. Note that the `attestation_token_broker` config field `type` is now ignored and the token format is chosen per request.");
 
         let policy_engine =
             PolicyEngineType::OPA.to_policy_engine(Path::new(&config.policy_dir))?;
@@ -60,48 +111,11 @@ impl EarAttestationTokenBroker {
             .set_policy("default_gpu".to_string(), default_gpu_policy, false)
             .await?;
 
-        if config.signer.is_none() {
-            info!("No Token Signer key in config file, create an ephemeral key and without CA pubkey cert");
-            return Ok(Self {
-                private_key: generate_ec_keys()?.0,
-                config,
-                cert_url: None,
-                cert_chain: None,
-                policy_engine,
-            });
-        }
-
-        let signer = config.signer.clone().unwrap();
-        let pem_data = std::fs::read(&signer.key_path)
-            .map_err(|e| anyhow!("Read Token Signer private key failed: {:?}", e))?;
-        let private_key = EcKey::private_key_from_pem(&pem_data)?;
-
-        let cert_chain = signer
-            .cert_path
-            .as_ref()
-            .map(|cert_path| -> Result<Vec<X509>> {
-                let pem_cert_chain = std::fs::read_to_string(cert_path)
-                    .map_err(|e| anyhow!("Read Token Signer cert file failed: {:?}", e))?;
-                let mut chain = Vec::new();
-
-                for pem in pem_cert_chain.split("-----END CERTIFICATE-----") {
-                    let trimmed = format!("{}\n-----END CERTIFICATE-----", pem.trim());
-                    if !trimmed.starts_with("-----BEGIN CERTIFICATE-----") {
-                        continue;
-                    }
-                    let cert = X509::from_pem(trimmed.as_bytes())
-                        .map_err(|_| anyhow!("Invalid PEM certificate chain"))?;
-                    chain.push(cert);
-                }
-                Ok(chain)
-            })
-            .transpose()?;
+        let token_brokers = token_brokers(&config)?;
 
         Ok(Self {
             config,
-            private_key,
-            cert_url: signer.cert_url,
-            cert_chain,
+            token_brokers,
             policy_engine,
         })
     }
@@ -112,13 +126,26 @@ impl EarAttestationTokenBroker {
         &self,
         all_tee_claims: Vec<TeeClaims>,
         policy_ids: Vec<String>,
-        reference_data_map: HashMap<String, serde_json::Value>,
+        rvps: &(dyn RvpsApi + Send + Sync),
+        token_format: TokenFormat,
+        nonce: Option<String>,
     ) -> Result<String> {
-        let reference_data = json!({
-            "reference": reference_data_map,
-        });
-        let reference_data = serde_json::to_string(&reference_data)?;
+        let issued = self
+            .issue_ear(all_tee_claims, policy_ids, rvps, token_format, nonce)
+            .await?;
+        Ok(issued.token)
+    }
 
+    /// Like [`Self::issue`], but also return the EAR and the TEE and policy
+    /// behind each of its submodules.
+    pub async fn issue_ear(
+        &self,
+        all_tee_claims: Vec<TeeClaims>,
+        policy_ids: Vec<String>,
+        rvps: &(dyn RvpsApi + Send + Sync),
+        token_format: TokenFormat,
+        nonce: Option<String>,
+    ) -> Result<IssuedEar> {
         if policy_ids.len() > 1 {
             warn!("EAR token only accepts the first policy. The rest will be ignored.");
         }
@@ -127,8 +154,19 @@ impl EarAttestationTokenBroker {
             bail!("No policy is given for EAR token generation.");
         }
 
+        if !is_valid_policy_id(&policy_ids[0]) {
+            bail!(PolicyError::InvalidPolicyId);
+        }
+
+        let nonce = nonce
+            .map(|nonce| Nonce::try_from(nonce.as_str()))
+            .transpose()
+            .map_err(|e| anyhow!("Illegal nonce: {e}"))?;
+
         let mut tee_class_indices: HashMap<String, u8> = HashMap::new();
         let mut submods = BTreeMap::new();
+        let mut submod_sources = BTreeMap::new();
+        let mut reference_data_of_policies: HashMap<String, String> = HashMap::new();
 
         // Create an appraisal for each device
         for tee_claims in all_tee_claims {
@@ -138,6 +176,7 @@ impl EarAttestationTokenBroker {
                 tee_claims.claims,
                 tee_claims.init_data_claims.clone(),
                 tee_claims.runtime_data_claims.clone(),
+                tee_claims.normalized_claims,
                 tee_claims.tee,
             )?;
 
@@ -146,16 +185,25 @@ impl EarAttestationTokenBroker {
             // There is a policy for each tee class.
             // The cpu tee class is loaded as the default.
             let policy_id = format!("{}_{}", policy_ids[0], tee_claims.tee_class);
-            let policy_results = self
+            if !reference_data_of_policies.contains_key(&policy_id) {
+                let reference_data = self.reference_data(&policy_id, rvps).await?;
+                reference_data_of_policies.insert(policy_id.clone(), reference_data);
+            }
+            let reference_data = &reference_data_of_policies[&policy_id];
+            let mut policy_results = self
                 .policy_engine
-                .evaluate(&reference_data, &tcb_claims_json, &policy_id)
+                .evaluate(reference_data, &tcb_claims_json, &policy_id)
                 .await?;
 
             let result = policy_results
                 .rules_result
-                .as_object()
+                .as_object_mut()
                 .context("Policy result is not an object")?;
 
+            if let Some(claims) = result.remove(POLICY_CLAIMS) {
+                appraisal.policy_claims = self.policy_claims(claims, &policy_id)?;
+            }
+
             for (k, v) in result {
                 let claim_value = v.as_i64().context("Policy claim value not number")?;
                 debug!("Policy claim: {}: {}", k, claim_value);
@@ -175,6 +223,10 @@ impl EarAttestationTokenBroker {
             appraisal.annotated_evidence = tcb_claims;
             appraisal.policy_id = Some(policy_ids[0].clone());
 
+            if let Some(raw_evidence) = tee_claims.raw_evidence {
+                self.embed_raw_evidence(&mut appraisal, raw_evidence);
+            }
+
             if let Some(index) = tee_class_indices.get_mut(&tee_claims.tee_class) {
                 *index += 1;
             } else {
@@ -187,9 +239,45 @@ impl EarAttestationTokenBroker {
                 // We know this key will exist because of the logic above.
                 tee_class_indices.get(&tee_claims.tee_class).unwrap()
             );
+            submod_sources.insert(
+                submod_name.clone(),
+                SubmodSource {
+                    tee: Some(tee_claims.tee),
+                    policy_id,
+                    policy_hash: policy_results.policy_hash,
+                },
+            );
             submods.insert(submod_name, appraisal);
         }
 
+        // If there is a composite policy, let it appraise all the devices
+        // together.
+        let composite_policy_id = format!("{}_{COMPOSITE_SUBMOD}", policy_ids[0]);
+        let has_composite_policy = match self
+            .policy_engine
+            .get_policy(composite_policy_id.clone())
+            .await
+        {
+            Err(PolicyError::PolicyNotFound(_)) => false,
+            Err(e) => return Err(e.into()),
+            _ => true,
+        };
+        if has_composite_policy {
+            let (mut appraisal, policy_hash) = self
+                .appraise_composite(&composite_policy_id, &mut submods, &submod_sources, rvps)
+                .await?;
+            appraisal.policy_id = Some(policy_ids[0].clone());
+            submod_sources.insert(
+                COMPOSITE_SUBMOD.to_string(),
+                SubmodSource {
+                    tee: None,
+                    policy_id: composite_policy_id,
+                    policy_hash,
+                },
+            );
+            submods.insert(COMPOSITE_SUBMOD.to_string(), appraisal);
+        }
+
         let now = OffsetDateTime::now_utc();
         let exp = now
             .checked_add(Duration::minutes(self.config.duration_min))
@@ -207,19 +295,168 @@ impl EarAttestationTokenBroker {
                 developer: self.config.developer_name.clone(),
             },
             raw_evidence: None,
-            nonce: None,
+            nonce,
             submods,
             extensions,
         };
-        let mut jwt_header = ear::new_jwt_header(&Algorithm::ES256)?;
-        jwt_header.jwk = Some(self.pubkey_jwk()?);
+        let token = self
+            .token_brokers
+            .get(&token_format)
+            .ok_or(anyhow!("Token format {token_format} is not supported."))?
+            .issue(&ear)?;
+
+        Ok(IssuedEar {
+            token,
+            ear,
+            submods: submod_sources,
+        })
+    }
 
-        let pkey = PKey::from_ec_key(self.private_key.clone())?;
-        let private_key_bytes = pkey.private_key_to_pem_pkcs8()?;
+    /// Evaluate the composite policy over the appraisals of all the devices.
+    /// The policy may change the trust claims of the device submodules or
+    /// downgrade their status. Return the appraisal of the composite
+    /// submodule and the digest of the policy.
+    async fn appraise_composite(
+        &self,
+        policy_id: &str,
+        submods: &mut BTreeMap<String, Appraisal>,
+        submod_sources: &BTreeMap<String, SubmodSource>,
+        rvps: &(dyn RvpsApi + Send + Sync),
+    ) -> Result<(Appraisal, String)> {
+        let mut input = serde_json::Map::new();
+        for (name, appraisal) in submods.iter() {
+            let status: &str = (&appraisal.status).into();
+            input.insert(
+                name.clone(),
+                json!({
+                    "tee": submod_sources.get(name).and_then(|source| source.tee),
+                    "claims": appraisal.annotated_evidence,
+                    "trust_vector": appraisal.trust_vector,
+                    "status": status,
+                }),
+            );
+        }
+        let input = serde_json::to_string(&json!({ "submods": input }))?;
 
-        let signed_ear = ear.sign_jwt_pem_with_header(&jwt_header, &private_key_bytes)?;
+        let reference_data = self.reference_data(policy_id, rvps).await?;
+        let policy_results = self
+            .policy_engine
+            .evaluate(&reference_data, &input, policy_id)
+            .await?;
+        let result: CompositeResult = serde_json::from_value(policy_results.rules_result)
+            .context("Illegal composite policy result")?;
+
+        for (name, submod_override) in result.submods {
+            let appraisal = submods.get_mut(&name).ok_or(anyhow!(
+                "Composite policy result has unknown submodule {name}"
+            ))?;
+
+            if !submod_override.trust_vector.is_empty() {
+                for (k, v) in submod_override.trust_vector {
+                    debug!("Composite policy claim of {name}: {k}: {v}");
+                    appraisal
+                        .trust_vector
+                        .mut_by_name(&k)
+                        .map_err(|e| anyhow!("Illegal trust claim {k}: {e}"))?
+                        .set(v);
+                }
+                appraisal.status = TrustTier::None;
+                appraisal.update_status_from_trust_vector();
+            }
 
-        Ok(signed_ear)
+            if let Some(status) = submod_override.status {
+                if status > appraisal.status {
+                    appraisal.status = status;
+                } else if status < appraisal.status {
+                    warn!("Composite policy cannot upgrade the status of {name}, ignored.");
+                }
+            }
+        }
+
+        let mut appraisal = Appraisal::new();
+        for (k, v) in result.trust_vector {
+            debug!("Composite policy claim: {k}: {v}");
+            appraisal
+                .trust_vector
+                .mut_by_name(&k)
+                .map_err(|e| anyhow!("Illegal trust claim {k}: {e}"))?
+                .set(v);
+        }
+
+        if !appraisal.trust_vector.any_set() {
+            bail!("At least one composite policy claim must be set.");
+        }
+
+        appraisal.update_status_from_trust_vector();
+        if let Some(claims) = result.claims {
+            appraisal.policy_claims = self.policy_claims(claims, policy_id)?;
+        }
+
+        Ok((appraisal, policy_results.policy_hash))
+    }
+
+    /// Embed the raw evidence of a submodule, or its digest, in its annotated
+    /// evidence as configured.
+    fn embed_raw_evidence(&self, appraisal: &mut Appraisal, raw_evidence: Vec<u8>) {
+        match self.config.raw_evidence {
+            RawEvidenceInclusion::None => {}
+            RawEvidenceInclusion::Hash => {
+                let digest = hex::encode(Sha384::digest(&raw_evidence));
+                appraisal
+                    .annotated_evidence
+                    .insert("raw_evidence_sha384".to_string(), RawValue::String(digest));
+            }
+            RawEvidenceInclusion::Full => {
+                appraisal.annotated_evidence.insert(
+                    "raw_evidence".to_string(),
+                    RawValue::Bytes(Bytes(raw_evidence)),
+                );
+            }
+        }
+    }
+
+    /// Convert the custom claims of the result of a policy to the policy
+    /// claims of a submodule, within the configured size limit.
+    fn policy_claims(&self, claims: Value, policy_id: &str) -> Result<BTreeMap<String, RawValue>> {
+        let claims = serde_json::to_string(&claims)?;
+        if claims.len() > self.config.max_policy_claims_size {
+            bail!(
+                "Claims of policy {policy_id} are {} bytes, more than the limit of {} bytes.",
+                claims.len(),
+                self.config.max_policy_claims_size
+            );
+        }
+
+        serde_json::from_str(&claims)
+            .context(format!("Claims of policy {policy_id} are not an object"))
+    }
+
+    /// Get the reference values that the policy needs from the RVPS, as the
+    /// `data` of the policy.
+    async fn reference_data(
+        &self,
+        policy_id: &str,
+        rvps: &(dyn RvpsApi + Send + Sync),
+    ) -> Result<String> {
+        let reference_data_map = match self
+            .policy_engine
+            .reference_value_prefixes(policy_id)
+            .await?
+        {
+            Some(prefixes) => rvps.get_digests_by_prefixes(&prefixes).await,
+            None => rvps.get_digests().await,
+        }
+        .map_err(|e| anyhow!("Generate reference data failed: {:?}", e))?;
+        debug!(
+            policy_id,
+            "Reference data map get from RVPS: {}",
+            serde_json::to_string(&reference_data_map)?,
+        );
+
+        let reference_data = json!({
+            "reference": reference_data_map,
+        });
+        Ok(serde_json::to_string(&reference_data)?)
     }
 
     pub async fn set_policy(&self, policy_id: String, policy: String) -> Result<()> {
@@ -242,64 +479,15 @@ impl EarAttestationTokenBroker {
             .await
             .map_err(Error::from)
     }
-}
-
-impl EarAttestationTokenBroker {
-    // TODO: converge this with the jwk function in the simple token broker
-    fn pubkey_jwk(&self) -> Result<jwk::Jwk> {
-        let chain = self
-            .cert_chain
-            .as_ref()
-            .map(|certs| -> Result<Vec<String>> {
-                let mut chain = vec![];
-                for cert in certs {
-                    let der = cert.to_der()?;
-                    chain.push(URL_SAFE_NO_PAD.encode(der));
-                }
-                Ok(chain)
-            })
-            .transpose()?;
-
-        let common = jwk::CommonParameters {
-            key_algorithm: Some(jwk::KeyAlgorithm::ES256),
-            x509_url: self.cert_url.clone(),
-            x509_chain: chain,
-            ..Default::default()
-        };
 
-        let public_key = self.private_key.public_key();
-        let group = self.private_key.group();
-
-        let mut ctx = BigNumContext::new()?;
-        let mut x = BigNum::new()?;
-        let mut y = BigNum::new()?;
-        public_key.affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
-
-        let algorithm = jwk::AlgorithmParameters::EllipticCurve(jwk::EllipticCurveKeyParameters {
-            key_type: jwk::EllipticCurveKeyType::EC,
-            curve: jwk::EllipticCurve::P256,
-            x: URL_SAFE_NO_PAD.encode(x.to_vec()),
-            y: URL_SAFE_NO_PAD.encode(y.to_vec()),
-        });
-
-        let jwk = jwk::Jwk { common, algorithm };
-
-        Ok(jwk)
+    pub async fn delete_policy(&self, policy_id: String) -> Result<()> {
+        self.policy_engine
+            .delete_policy(policy_id)
+            .await
+            .map_err(Error::from)
     }
 }
 
-fn generate_ec_keys() -> Result<(EcKey<Private>, Vec<u8>, Vec<u8>)> {
-    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
-    let ec_key = EcKey::generate(&group)?;
-    let pkey = PKey::from_ec_key(ec_key.clone())?;
-
-    Ok((
-        ec_key,
-        pkey.private_key_to_pem_pkcs8()?,
-        pkey.public_key_to_pem()?,
-    ))
-}
-
 /// This function does three things.
 ///
 /// 1) If the input claims include an init_data claim (meaning that
@@ -312,6 +500,7 @@ fn generate_ec_keys() -> Result<(EcKey<Private>, Vec<u8>, Vec<u8>)> {
 ///
 /// 2) Move all claims from input_claims except the ones mentioned
 ///    in the previous step into their own Object under the tee name.
+///    The TEE-agnostic normalized_claims are added under `normalized`.
 ///
 /// 3) Convert the claims from serde_json Values to RawValues from the
 ///    EAR crate.
@@ -320,6 +509,7 @@ pub fn transform_claims(
     mut input_claims: Value,
     init_data_claims: Value,
     runtime_data_claims: Value,
+    normalized_claims: NormalizedClaims,
     tee: Tee,
 ) -> Result<BTreeMap<String, RawValue>> {
     let mut output_claims = BTreeMap::new();
@@ -358,6 +548,10 @@ pub fn transform_claims(
         serde_json::from_str(&serde_json::to_string(&input_claims)?)?;
     output_claims.insert(to_variant_name(&tee)?.to_string(), transformed_claims);
 
+    let transformed_claims: RawValue =
+        serde_json::from_str(&serde_json::to_string(&normalized_claims)?)?;
+    output_claims.insert("normalized".to_string(), transformed_claims);
+
     Ok(output_claims)
 }
 
@@ -368,7 +562,10 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    use crate::test_utils::test_rvps;
+    use crate::token::signer::generate_ec_keys;
     use crate::{ear_token::TokenSignerConfig, TeeClaims};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 
     use super::*;
 
@@ -378,6 +575,8 @@ mod tests {
         // this will sign the token with an ephemeral key.
         let config = EarTokenConfiguration::default();
         let broker = EarAttestationTokenBroker::new(config).await.unwrap();
+        let work_dir = tempfile::tempdir().unwrap();
+        let rvps = test_rvps(work_dir.path());
 
         let _token = broker
             .issue(
@@ -387,9 +586,13 @@ mod tests {
                     claims: json!({"claim": "claim1"}),
                     runtime_data_claims: json!({"runtime_data": "111"}),
                     init_data_claims: json!({"initdata": "111"}),
+                    normalized_claims: NormalizedClaims::default(),
+                    raw_evidence: None,
                 }],
                 vec!["default".into()],
-                HashMap::new(),
+                &rvps,
+                TokenFormat::Ear,
+                None,
             )
             .await
             .unwrap();
@@ -411,6 +614,8 @@ mod tests {
         config.signer = Some(signer);
 
         let broker = EarAttestationTokenBroker::new(config).await.unwrap();
+        let work_dir = tempfile::tempdir().unwrap();
+        let rvps = test_rvps(work_dir.path());
         let token = broker
             .issue(
                 vec![TeeClaims {
@@ -419,9 +624,13 @@ mod tests {
                     claims: json!({"claim": "claim1"}),
                     runtime_data_claims: json!({"runtime_data": "111"}),
                     init_data_claims: json!({"initdata": "111"}),
+                    normalized_claims: NormalizedClaims::default(),
+                    raw_evidence: None,
                 }],
                 vec!["default".into()],
-                HashMap::new(),
+                &rvps,
+                TokenFormat::Ear,
+                None,
             )
             .await
             .unwrap();
@@ -432,6 +641,200 @@ mod tests {
         ear.validate().unwrap();
     }
 
+    #[tokio::test]
+    async fn test_issue_ear_with_composite_policy() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let broker = EarAttestationTokenBroker::new(EarTokenConfiguration {
+            policy_dir: work_dir.path().join("policies").to_string_lossy().into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let rvps = test_rvps(work_dir.path());
+
+        let composite_policy = r#"
+package policy
+
+import rego.v1
+
+default hardware := 97
+
+hardware := 2 if {
+    input.submods.cpu0.tee == "sample"
+    input.submods.cpu0.claims.sample.claim == "claim1"
+}
+
+result := {
+    "trust_vector": {"hardware": hardware},
+    "submods": {"cpu0": {
+        "trust_vector": {"configuration": 2},
+        "status": "contraindicated",
+    }},
+}
+"#;
+        broker
+            .set_policy(
+                "default_composite".into(),
+                URL_SAFE_NO_PAD.encode(composite_policy),
+            )
+            .await
+            .unwrap();
+
+        let issued = broker
+            .issue_ear(
+                vec![TeeClaims {
+                    tee: Tee::Sample,
+                    tee_class: "cpu".to_string(),
+                    claims: json!({"claim": "claim1"}),
+                    runtime_data_claims: Value::Null,
+                    init_data_claims: Value::Null,
+                    normalized_claims: NormalizedClaims::default(),
+                    raw_evidence: None,
+                }],
+                vec!["default".into()],
+                &rvps,
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let composite = &issued.ear.submods[COMPOSITE_SUBMOD];
+        assert_eq!(composite.status, TrustTier::Affirming);
+        assert_eq!(composite.trust_vector.hardware.get(), 2);
+        let source = &issued.submods[COMPOSITE_SUBMOD];
+        assert_eq!(source.tee, None);
+        assert_eq!(source.policy_id, "default_composite");
+        assert!(!source.policy_hash.is_empty());
+
+        let cpu = &issued.ear.submods["cpu0"];
+        assert_eq!(cpu.status, TrustTier::Contraindicated);
+        assert_eq!(cpu.trust_vector.configuration.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_issue_ear_with_policy_claims() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let mut config = EarTokenConfiguration {
+            policy_dir: work_dir.path().join("policies").to_string_lossy().into(),
+            ..Default::default()
+        };
+        let rvps = test_rvps(work_dir.path());
+
+        let policy = r#"
+package policy
+
+result := {
+    "executables": 2,
+    "claims": {
+        "workload_name": "nginx",
+        "allowed_repositories": ["quay.io/example"],
+    },
+}
+"#;
+        let tee_claims = || TeeClaims {
+            tee: Tee::Sample,
+            tee_class: "cpu".to_string(),
+            claims: json!({"claim": "claim1"}),
+            runtime_data_claims: Value::Null,
+            init_data_claims: Value::Null,
+            normalized_claims: NormalizedClaims::default(),
+            raw_evidence: None,
+        };
+
+        let broker = EarAttestationTokenBroker::new(config.clone())
+            .await
+            .unwrap();
+        broker
+            .set_policy("claims_cpu".into(), URL_SAFE_NO_PAD.encode(policy))
+            .await
+            .unwrap();
+        let issued = broker
+            .issue_ear(
+                vec![tee_claims()],
+                vec!["claims".into()],
+                &rvps,
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let cpu = &issued.ear.submods["cpu0"];
+        assert_eq!(cpu.status, TrustTier::Affirming);
+        assert_eq!(
+            cpu.policy_claims["workload_name"],
+            RawValue::String("nginx".into())
+        );
+        assert_eq!(
+            cpu.policy_claims["allowed_repositories"],
+            RawValue::Array(vec![RawValue::String("quay.io/example".into())])
+        );
+
+        config.max_policy_claims_size = 16;
+        let broker = EarAttestationTokenBroker::new(config).await.unwrap();
+        assert!(broker
+            .issue_ear(
+                vec![tee_claims()],
+                vec!["claims".into()],
+                &rvps,
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_issue_ear_with_nonce_and_raw_evidence() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let broker = EarAttestationTokenBroker::new(EarTokenConfiguration {
+            policy_dir: work_dir.path().join("policies").to_string_lossy().into(),
+            raw_evidence: RawEvidenceInclusion::Hash,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let rvps = test_rvps(work_dir.path());
+        let tee_claims = || TeeClaims {
+            tee: Tee::Sample,
+            tee_class: "cpu".to_string(),
+            claims: json!({"claim": "claim1"}),
+            runtime_data_claims: Value::Null,
+            init_data_claims: Value::Null,
+            normalized_claims: NormalizedClaims::default(),
+            raw_evidence: Some(b"raw evidence".to_vec()),
+        };
+
+        let issued = broker
+            .issue_ear(
+                vec![tee_claims()],
+                vec!["default".into()],
+                &rvps,
+                TokenFormat::Ear,
+                Some("relying-party-nonce".into()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(issued.ear.nonce.unwrap().to_string(), "relying-party-nonce");
+        assert_eq!(
+            issued.ear.submods["cpu0"].annotated_evidence["raw_evidence_sha384"],
+            RawValue::String(hex::encode(Sha384::digest(b"raw evidence")))
+        );
+
+        assert!(broker
+            .issue_ear(
+                vec![tee_claims()],
+                vec!["default".into()],
+                &rvps,
+                TokenFormat::Ear,
+                Some("short".into()),
+            )
+            .await
+            .is_err());
+    }
+
     #[test]
     fn test_transform_claims() {
         let json = json!({
@@ -472,9 +875,19 @@ mod tests {
 
         let init_data_claims = Value::String("".to_string());
         let runtime_data_claims = Value::String("".to_string());
-        let transformed_claims =
-            transform_claims(json, init_data_claims, runtime_data_claims, Tee::Tdx)
-                .expect("flatten failed");
+        let normalized_claims = NormalizedClaims {
+            launch_measurement: Some("705ee9381b8633a9fbe532b52345e8433343d2868959f57889d84ca377c395b689cac1599ccea1b7d420483a9ce5f031".into()),
+            debug_enabled: Some(false),
+            ..Default::default()
+        };
+        let transformed_claims = transform_claims(
+            json,
+            init_data_claims,
+            runtime_data_claims,
+            normalized_claims,
+            Tee::Tdx,
+        )
+        .expect("flatten failed");
 
         let expected_claims = json!({
             "tdx": {
@@ -513,7 +926,11 @@ mod tests {
             "report_data": "7c71fe2c86eff65a7cf8dbc22b3275689fd0464a267baced1bf94fc1324656aeb755da3d44d098c0c87382f3a5f85b45c8a28fee1d3bdb38342bf96671501429",
             "init_data": "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
             "runtime_data_claims": "",
-            "init_data_claims": ""
+            "init_data_claims": "",
+            "normalized": {
+                "launch_measurement": "705ee9381b8633a9fbe532b52345e8433343d2868959f57889d84ca377c395b689cac1599ccea1b7d420483a9ce5f031",
+                "debug_enabled": false
+            }
         });
 
         assert_json_eq!(expected_claims, transformed_claims);
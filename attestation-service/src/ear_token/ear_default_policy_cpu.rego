@@ -5,6 +5,11 @@ import rego.v1
 # This policy validates multiple TEE platforms
 # The policy is meant to capture the TCB requirements
 # for confidential containers.
+#
+# Where possible, the rules check the TEE-agnostic claims under
+# `input.normalized` rather than the claims of each TEE. The TEE
+# specific claims are still used to select the rules of a platform
+# and for values that have no normalized counterpart.
 
 # This policy is used to generate an EAR Appraisal.
 # Specifically it generates an AR4SI result.
@@ -65,7 +70,8 @@ result := {
 executables := 3 if {
 	# The sample attester does not report any launch digest.
 	# This is an example of how a real platform might validate executables.
-	input.sample.launch_digest in data.reference.launch_digest
+	input.sample
+	input.normalized.launch_measurement in data.reference.launch_digest
 }
 
 # For the `hardware` trust claim, the value 2 stands for
@@ -73,7 +79,7 @@ executables := 3 if {
 #  verifications needed to demonstrate that these are genuine/
 #  supported.
 hardware := 2 if {
-	input.sample.svn in data.reference.svn
+	input.normalized.tcb_svns.svn in data.reference.svn
 	input.sample.platform_version.major == data.reference.major_version
 	input.sample.platform_version.minor >= data.reference.minimum_minor_version
 }
@@ -86,21 +92,25 @@ hardware := 2 if {
 # For the sample platform, the debug claim is always false.
 # The sample platform should only be used for testing.
 configuration := 2 if {
-	input.sample.debug == false
+	input.sample
+	input.normalized.debug_enabled == false
 }
 
 ##### SNP
 executables := 3 if {
 	# In the future, we might calculate this measurement here various components
+	# The reference values of the measurement are base64 encoded, so the raw
+	# claim is checked rather than the hex encoded launch_measurement.
 	input.snp.measurement in data.reference.snp_launch_measurement
 }
 
 hardware := 2 if {
 	# Check the reported TCB to validate the ASP FW
-	input.snp.reported_tcb_bootloader in data.reference.snp_bootloader
-	input.snp.reported_tcb_microcode in data.reference.snp_microcode
-	input.snp.reported_tcb_snp in data.reference.snp_snp_svn
-	input.snp.reported_tcb_tee in data.reference.snp_tee_svn
+	input.snp
+	input.normalized.tcb_svns.bootloader in data.reference.snp_bootloader
+	input.normalized.tcb_svns.microcode in data.reference.snp_microcode
+	input.normalized.tcb_svns.snp in data.reference.snp_snp_svn
+	input.normalized.tcb_svns.tee in data.reference.snp_tee_svn
 }
 
 # For the 'configuration' trust claim 2 stands for
@@ -108,7 +118,7 @@ hardware := 2 if {
 #
 # For this, we compare all the configuration fields.
 configuration := 2 if {
-	input.snp.policy_debug_allowed == false
+	input.normalized.debug_enabled == false
 	input.snp.policy_migrate_ma == false
 	input.snp.platform_smt_enabled == data.reference.snp_smt_enabled
 	input.snp.platform_tsme_enabled == data.reference.snp_tsme_enabled
@@ -126,15 +136,16 @@ configuration := 2 if {
 # configuration value, but we make sure that some key
 # configurations (like debug_allowed) are set correctly.
 else := 3 if {
-	input.snp.policy_debug_allowed == false
+	input.normalized.debug_enabled == false
 	input.snp.policy_migrate_ma == false
 }
 
 ##### TDX
 executables := 3 if {
 	# Check the kernel, initrd, and cmdline (including dmverity parameters) measurements
-	input.tdx.quote.body.rtmr_1 in data.reference.rtmr_1
-	input.tdx.quote.body.rtmr_2 in data.reference.rtmr_2
+	input.tdx
+	input.normalized.runtime_measurements.rtmr1 in data.reference.rtmr_1
+	input.normalized.runtime_measurements.rtmr2 in data.reference.rtmr_2
 	tdx_uefi_event_tdvfkernel_ok
 	tdx_uefi_event_tdvfkernelparams_ok
 }
@@ -142,8 +153,9 @@ executables := 3 if {
 # Support for Grub boot used by GKE
 else := 4 if {
 	# Check the kernel, initrd, and cmdline (including dmverity parameters) measurements
-	input.tdx.quote.body.rtmr_1 in data.reference.rtmr_1
-	input.tdx.quote.body.rtmr_2 in data.reference.rtmr_2
+	input.tdx
+	input.normalized.runtime_measurements.rtmr1 in data.reference.rtmr_1
+	input.normalized.runtime_measurements.rtmr2 in data.reference.rtmr_2
 }
 
 hardware := 2 if {
@@ -153,8 +165,8 @@ hardware := 2 if {
 
 	# Check TDX Module version and its hash. Also check OVMF code hash.
 	input.tdx.quote.body.mr_seam in data.reference.mr_seam
-	input.tdx.quote.body.tcb_svn in data.reference.tcb_svn
-	input.tdx.quote.body.mr_td in data.reference.mr_td
+	input.normalized.tcb_svns.tcb_svn in data.reference.tcb_svn
+	input.normalized.launch_measurement in data.reference.mr_td
 
 	# Check TCB status
 	input.tdx.tcb_status == "UpToDate"
@@ -175,7 +187,7 @@ hardware := 2 if {
 
 configuration := 2 if {
 	# Check the TD has the expected attributes (e.g., debug not enabled) and features.
-	input.tdx.td_attributes.debug == false
+	input.normalized.debug_enabled == false
 	input.tdx.quote.body.xfam in data.reference.xfam
 }
 
@@ -200,7 +212,7 @@ tdx_uefi_event_tdvfkernelparams_ok if {
 ##### Azure vTPM SNP
 executables := 3 if {
 	input.az_snp_vtpm.measurement in data.reference.measurement
-	input.az_snp_vtpm.tpm.pcr11 in data.reference.snp_pcr11
+	input.normalized.runtime_measurements.pcr11 in data.reference.snp_pcr11
 }
 
 hardware := 2 if {
@@ -216,6 +228,7 @@ hardware := 2 if {
 #
 # For this, we compare all the configuration fields.
 configuration := 2 if {
+	input.normalized.debug_enabled == false
 	input.az_snp_vtpm.platform_smt_enabled in data.reference.smt_enabled
 	input.az_snp_vtpm.platform_tsme_enabled in data.reference.tsme_enabled
 	input.az_snp_vtpm.policy_abi_major in data.reference.abi_major
@@ -226,7 +239,8 @@ configuration := 2 if {
 
 ##### Azure vTPM TDX
 executables := 3 if {
-	input.az_tdx_vtpm.tpm.pcr11 in data.reference.tdx_pcr11
+	input.az_tdx_vtpm
+	input.normalized.runtime_measurements.pcr11 in data.reference.tdx_pcr11
 }
 
 hardware := 2 if {
@@ -236,11 +250,12 @@ hardware := 2 if {
 
 	# Check TDX Module version and its hash. Also check OVMF code hash.
 	input.az_tdx_vtpm.quote.body.mr_seam in data.reference.mr_seam
-	input.az_tdx_vtpm.quote.body.tcb_svn in data.reference.tcb_svn
-	input.az_tdx_vtpm.quote.body.mr_td in data.reference.mr_td
+	input.normalized.tcb_svns.tcb_svn in data.reference.tcb_svn
+	input.normalized.launch_measurement in data.reference.mr_td
 }
 
 configuration := 2 if {
+	input.normalized.debug_enabled == false
 	input.az_tdx_vtpm.quote.body.xfam in data.reference.xfam
 }
 
@@ -250,7 +265,8 @@ hardware := 2 if {
 }
 
 executables := 3 if {
-	input.tpm.pcr11 in data.reference.tpm_pcr11
+	input.tpm
+	input.normalized.runtime_measurements.pcr11 in data.reference.tpm_pcr11
 }
 
 configuration := 0 if {
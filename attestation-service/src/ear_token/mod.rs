@@ -7,9 +7,10 @@ use serde::Deserialize;
 use shadow_rs::concatcp;
 
 use crate::config::DEFAULT_WORK_DIR;
+use crate::token::jwt::JwtTokenConfiguration;
 
 pub mod broker;
-pub use broker::EarAttestationTokenBroker;
+pub use broker::{EarAttestationTokenBroker, IssuedEar, SubmodSource};
 
 /// default token duration in minutes
 pub const DEFAULT_TOKEN_DURATION: i64 = 5;
@@ -23,6 +24,9 @@ pub const DEFAULT_PROFILE: &str = "tag:github.com,2024:confidential-containers/T
 /// default developer name carried in the EAR token
 pub const DEFAULT_DEVELOPER_NAME: &str = "https://confidentialcontainers.org";
 
+/// default size limit of the custom claims of a policy, in bytes
+pub const DEFAULT_MAX_POLICY_CLAIMS_SIZE: usize = 4096;
+
 /// default token work directory
 const DEFAULT_TOKEN_WORK_DIR: &str = concatcp!(DEFAULT_WORK_DIR, "/token");
 
@@ -40,6 +44,23 @@ pub struct TokenSignerConfig {
     pub cert_path: Option<String>,
 }
 
+/// How the raw evidence of a submodule is embedded in its annotated evidence,
+/// for later re-verification.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RawEvidenceInclusion {
+    /// The raw evidence is not embedded.
+    #[default]
+    None,
+
+    /// The hex SHA-384 digest of the raw evidence is embedded as
+    /// `raw_evidence_sha384`.
+    Hash,
+
+    /// The raw evidence is embedded as `raw_evidence`.
+    Full,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct EarTokenConfiguration {
     /// The Attestation Results Token duration time (in minutes)
@@ -79,6 +100,21 @@ pub struct EarTokenConfiguration {
     /// to provision the tokens.
     #[serde(default = "default_policy_dir")]
     pub policy_dir: String,
+
+    /// The size limit of the custom `claims` of a policy result, in bytes
+    /// of serialized JSON.
+    /// Default: 4096 bytes
+    #[serde(default = "default_max_policy_claims_size")]
+    pub max_policy_claims_size: usize,
+
+    /// The claims layout of the flat JWT token format.
+    #[serde(default)]
+    pub jwt: JwtTokenConfiguration,
+
+    /// Whether the raw evidence, or its digest, is embedded in the submodules.
+    /// Default: `none`
+    #[serde(default)]
+    pub raw_evidence: RawEvidenceInclusion,
 }
 
 #[inline]
@@ -111,6 +147,11 @@ fn default_policy_dir() -> String {
     DEFAULT_POLICY_DIR.to_string()
 }
 
+#[inline]
+fn default_max_policy_claims_size() -> usize {
+    DEFAULT_MAX_POLICY_CLAIMS_SIZE
+}
+
 impl Default for EarTokenConfiguration {
     fn default() -> Self {
         Self {
@@ -121,6 +162,9 @@ impl Default for EarTokenConfiguration {
             profile_name: default_profile(),
             signer: None,
             policy_dir: default_policy_dir(),
+            max_policy_claims_size: default_max_policy_claims_size(),
+            jwt: JwtTokenConfiguration::default(),
+            raw_evidence: RawEvidenceInclusion::default(),
         }
     }
 }
@@ -0,0 +1,612 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Attestation result history.
+//!
+//! When enabled, every issued attestation result is recorded as one line of
+//! a JSON lines file, so that it can later be found out which claims and
+//! policies led to a result.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ear::TrustTier;
+use kbs_types::Tee;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shadow_rs::concatcp;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use tracing::{info, warn};
+
+use crate::config::DEFAULT_WORK_DIR;
+use crate::ear_token::IssuedEar;
+use crate::tenant::DEFAULT_TENANT;
+
+/// default file of the attestation result history
+const DEFAULT_HISTORY_FILE: &str = concatcp!(DEFAULT_WORK_DIR, "/history/results.jsonl");
+
+/// Value that replaces a redacted claim
+const REDACTED: &str = "[REDACTED]";
+
+/// The retention rules are enforced again after this many appended records.
+const PRUNE_INTERVAL: usize = 100;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct HistoryConfig {
+    /// The JSON lines file that the records are appended to.
+    #[serde(default = "default_file_path")]
+    pub file_path: PathBuf,
+
+    /// Names of the normalized claims, e.g. `platform_id`, whose values
+    /// are replaced by `[REDACTED]` before a record is stored.
+    #[serde(default)]
+    pub redacted_claims: Vec<String>,
+
+    /// Records issued more than this many days ago are deleted.
+    /// Default: keep all records.
+    #[serde(default)]
+    pub retention_days: Option<i64>,
+
+    /// Only this many of the newest records are kept.
+    /// Default: keep all records.
+    #[serde(default)]
+    pub max_records: Option<usize>,
+}
+
+fn default_file_path() -> PathBuf {
+    PathBuf::from(DEFAULT_HISTORY_FILE)
+}
+
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_string()
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            file_path: default_file_path(),
+            redacted_claims: Vec::new(),
+            retention_days: None,
+            max_records: None,
+        }
+    }
+}
+
+/// The appraisal of a single submodule of an attestation result.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SubmodRecord {
+    /// The TEE of the evidence, `None` for the composite submodule.
+    pub tee: Option<Tee>,
+
+    /// The status of the appraisal, e.g. `affirming`.
+    pub status: String,
+
+    /// The trust vector of the appraisal as a claim name to value map.
+    pub trust_vector: Value,
+
+    /// The ID of the policy that was evaluated, e.g. `default_cpu`.
+    pub policy_id: String,
+
+    /// The digest of the evaluated policy, encoded as the policy list of the
+    /// service encodes it.
+    pub policy_hash: String,
+
+    /// The TEE-agnostic normalized claims of the evidence.
+    pub normalized_claims: Value,
+}
+
+/// A recorded attestation result.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AttestationRecord {
+    pub request_id: String,
+
+    /// The tenant the attestation was made for. Records written before
+    /// there were tenants belong to the default tenant.
+    #[serde(default = "default_tenant")]
+    pub tenant: String,
+
+    /// Issuing time of the token, in seconds since the epoch.
+    pub iat: i64,
+
+    /// Expiration time of the token, in seconds since the epoch.
+    pub exp: Option<i64>,
+
+    /// The worst status of all the submodules.
+    pub status: String,
+
+    /// The TEEs of the evidence that was evaluated.
+    pub tees: Vec<Tee>,
+
+    /// The policy IDs the evaluation was requested with.
+    pub policy_ids: Vec<String>,
+
+    pub submods: BTreeMap<String, SubmodRecord>,
+}
+
+impl AttestationRecord {
+    /// Build the record of an issued EAR.
+    pub fn new(
+        request_id: String,
+        tenant: String,
+        policy_ids: Vec<String>,
+        issued: &IssuedEar,
+    ) -> Result<Self> {
+        let mut tees = Vec::new();
+        let mut submods = BTreeMap::new();
+        for (name, appraisal) in &issued.ear.submods {
+            let source = issued
+                .submods
+                .get(name)
+                .context(format!("unknown submodule {name}"))?;
+            if let Some(tee) = source.tee {
+                if !tees.contains(&tee) {
+                    tees.push(tee);
+                }
+            }
+
+            let normalized_claims = match appraisal.annotated_evidence.get("normalized") {
+                Some(claims) => serde_json::to_value(claims)?,
+                None => Value::Null,
+            };
+            let status: &str = (&appraisal.status).into();
+            submods.insert(
+                name.clone(),
+                SubmodRecord {
+                    tee: source.tee,
+                    status: status.to_string(),
+                    trust_vector: serde_json::to_value(appraisal.trust_vector)?,
+                    policy_id: source.policy_id.clone(),
+                    policy_hash: URL_SAFE_NO_PAD
+                        .encode(hex::decode(&source.policy_hash).context("decode policy hash")?),
+                    normalized_claims,
+                },
+            );
+        }
+
+        let status = issued
+            .ear
+            .submods
+            .values()
+            .map(|appraisal| &appraisal.status)
+            .fold(&TrustTier::None, |worst, status| {
+                if status > worst {
+                    status
+                } else {
+                    worst
+                }
+            });
+        let status: &str = status.into();
+
+        let exp = match issued.ear.extensions.get_by_name("exp") {
+            Some(ear::RawValue::Integer(exp)) => Some(exp),
+            _ => None,
+        };
+
+        Ok(Self {
+            request_id,
+            tenant,
+            iat: issued.ear.iat,
+            exp,
+            status: status.to_string(),
+            tees,
+            policy_ids,
+            submods,
+        })
+    }
+
+    /// Replace the values of the given normalized claims.
+    fn redact(&mut self, claims: &[String]) {
+        for submod in self.submods.values_mut() {
+            let Some(normalized_claims) = submod.normalized_claims.as_object_mut() else {
+                continue;
+            };
+            for claim in claims {
+                if let Some(value) = normalized_claims.get_mut(claim) {
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Filter of a history query. Every condition that is set must hold.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Only records issued at or after this time, in seconds since the epoch.
+    pub from: Option<i64>,
+
+    /// Only records issued at or before this time, in seconds since the epoch.
+    pub to: Option<i64>,
+
+    /// Only records with evidence of this TEE.
+    pub tee: Option<Tee>,
+
+    /// Only records of this status, e.g. `contraindicated`.
+    pub status: Option<String>,
+
+    /// Only records that were requested with or evaluated this policy.
+    pub policy_id: Option<String>,
+
+    /// Return only this many of the newest matching records.
+    pub limit: Option<usize>,
+
+    /// Only records of this tenant. This is set from the caller's tenant by
+    /// the service, not by the query.
+    #[serde(skip)]
+    pub tenant: Option<String>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, record: &AttestationRecord) -> bool {
+        self.from.is_none_or(|from| record.iat >= from)
+            && self.to.is_none_or(|to| record.iat <= to)
+            && self.tee.is_none_or(|tee| record.tees.contains(&tee))
+            && self
+                .status
+                .as_ref()
+                .is_none_or(|status| record.status.eq_ignore_ascii_case(status))
+            && self
+                .tenant
+                .as_ref()
+                .is_none_or(|tenant| record.tenant == *tenant)
+            && self.policy_id.as_ref().is_none_or(|policy_id| {
+                record.policy_ids.contains(policy_id)
+                    || record
+                        .submods
+                        .values()
+                        .any(|submod| submod.policy_id == *policy_id)
+            })
+    }
+}
+
+/// Attestation result history kept in a JSON lines file.
+pub struct HistoryStore {
+    config: HistoryConfig,
+
+    /// Serializes the file accesses. Counts the records appended since the
+    /// retention rules were last enforced.
+    appended: Mutex<usize>,
+}
+
+impl HistoryStore {
+    pub async fn new(config: HistoryConfig) -> Result<Self> {
+        if let Some(parent) = config.file_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .context("create attestation history dir")?;
+        }
+
+        let store = Self {
+            config,
+            appended: Mutex::new(0),
+        };
+        let _guard = store.appended.lock().await;
+        store.prune().await?;
+        drop(_guard);
+
+        Ok(store)
+    }
+
+    /// Redact and append a record.
+    pub async fn append(&self, mut record: AttestationRecord) -> Result<()> {
+        record.redact(&self.config.redacted_claims);
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+
+        let mut appended = self.appended.lock().await;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.file_path)
+            .await
+            .context("open attestation history")?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+
+        *appended += 1;
+        if *appended >= PRUNE_INTERVAL {
+            self.prune().await?;
+            *appended = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Get the matching records, oldest first.
+    pub async fn query(&self, query: &HistoryQuery) -> Result<Vec<AttestationRecord>> {
+        let _guard = self.appended.lock().await;
+        let mut records: Vec<_> = self
+            .read()
+            .await?
+            .into_iter()
+            .filter(|record| query.matches(record))
+            .collect();
+
+        if let Some(limit) = query.limit {
+            records.drain(..records.len().saturating_sub(limit));
+        }
+
+        Ok(records)
+    }
+
+    async fn read(&self) -> Result<Vec<AttestationRecord>> {
+        let content = match fs::read_to_string(&self.config.file_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context("read attestation history"),
+        };
+
+        let mut records = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!("Skip malformed attestation history line {}: {e}", index + 1),
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Delete the records that are out of retention. The caller must hold
+    /// the lock.
+    async fn prune(&self) -> Result<()> {
+        if self.config.retention_days.is_none() && self.config.max_records.is_none() {
+            return Ok(());
+        }
+
+        let mut records = self.read().await?;
+        let total = records.len();
+        if let Some(days) = self.config.retention_days {
+            let oldest = OffsetDateTime::now_utc().unix_timestamp() - days * SECONDS_PER_DAY;
+            records.retain(|record| record.iat >= oldest);
+        }
+        if let Some(max_records) = self.config.max_records {
+            records.drain(..records.len().saturating_sub(max_records));
+        }
+        if records.len() == total {
+            return Ok(());
+        }
+
+        let mut content = Vec::new();
+        for record in &records {
+            serde_json::to_writer(&mut content, record)?;
+            content.push(b'\n');
+        }
+        let tmp_path = self.config.file_path.with_extension("tmp");
+        fs::write(&tmp_path, content)
+            .await
+            .context("write attestation history")?;
+        fs::rename(&tmp_path, &self.config.file_path)
+            .await
+            .context("replace attestation history")?;
+        info!(
+            "Deleted {} records out of retention from the attestation history",
+            total - records.len()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use verifier::NormalizedClaims;
+
+    use super::*;
+    use crate::ear_token::{EarAttestationTokenBroker, EarTokenConfiguration};
+    use crate::test_utils::test_rvps;
+    use crate::token::TokenFormat;
+    use crate::TeeClaims;
+
+    fn record(request_id: &str, iat: i64, tee: Tee, status: &str) -> AttestationRecord {
+        AttestationRecord {
+            request_id: request_id.into(),
+            tenant: DEFAULT_TENANT.into(),
+            iat,
+            exp: Some(iat + 300),
+            status: status.into(),
+            tees: vec![tee],
+            policy_ids: vec!["default".into()],
+            submods: BTreeMap::from([(
+                "cpu0".to_string(),
+                SubmodRecord {
+                    tee: Some(tee),
+                    status: status.into(),
+                    trust_vector: json!({"executables": 2}),
+                    policy_id: "default_cpu".into(),
+                    policy_hash: "hash".into(),
+                    normalized_claims: json!({"platform_id": "chip", "debug_enabled": false}),
+                },
+            )]),
+        }
+    }
+
+    #[tokio::test]
+    async fn record_of_issued_ear() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let rvps = test_rvps(work_dir.path());
+        let broker = EarAttestationTokenBroker::new(EarTokenConfiguration {
+            policy_dir: work_dir.path().join("policies").to_string_lossy().into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let issued = broker
+            .issue_ear(
+                vec![TeeClaims {
+                    tee: Tee::Sample,
+                    tee_class: "cpu".to_string(),
+                    claims: json!({"claim": "claim1"}),
+                    runtime_data_claims: Value::Null,
+                    init_data_claims: Value::Null,
+                    normalized_claims: NormalizedClaims {
+                        platform_id: Some("chip".into()),
+                        ..Default::default()
+                    },
+                    raw_evidence: None,
+                }],
+                vec!["default".into()],
+                &rvps,
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .unwrap();
+        let record = AttestationRecord::new(
+            "id".into(),
+            DEFAULT_TENANT.into(),
+            vec!["default".into()],
+            &issued,
+        )
+        .unwrap();
+
+        assert_eq!(record.iat, issued.ear.iat);
+        assert!(record.exp.unwrap() > record.iat);
+        assert_eq!(record.tees, [Tee::Sample]);
+        let submod = &record.submods["cpu0"];
+        assert_eq!(submod.policy_id, "default_cpu");
+        let policy_hashes = broker.list_policies().await.unwrap();
+        assert_eq!(submod.policy_hash, policy_hashes["default_cpu"]);
+        assert_eq!(submod.status, record.status);
+        assert_eq!(submod.normalized_claims, json!({"platform_id": "chip"}));
+        assert!(submod
+            .trust_vector
+            .as_object()
+            .is_some_and(|v| !v.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn append_and_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(HistoryConfig {
+            file_path: dir.path().join("history/results.jsonl"),
+            redacted_claims: vec!["platform_id".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        store
+            .append(record("a", 100, Tee::Sample, "affirming"))
+            .await
+            .unwrap();
+        store
+            .append(record("b", 200, Tee::Tdx, "contraindicated"))
+            .await
+            .unwrap();
+        store
+            .append(record("c", 300, Tee::Sample, "warning"))
+            .await
+            .unwrap();
+
+        let ids = |records: Vec<AttestationRecord>| {
+            records
+                .into_iter()
+                .map(|record| record.request_id)
+                .collect::<Vec<_>>()
+        };
+        let all = store.query(&HistoryQuery::default()).await.unwrap();
+        assert_eq!(
+            all[0].submods["cpu0"].normalized_claims,
+            json!({"platform_id": REDACTED, "debug_enabled": false})
+        );
+        assert_eq!(ids(all), ["a", "b", "c"]);
+
+        let query = HistoryQuery {
+            tee: Some(Tee::Sample),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["a", "c"]);
+
+        let query = HistoryQuery {
+            from: Some(150),
+            to: Some(250),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["b"]);
+
+        let query = HistoryQuery {
+            status: Some("Contraindicated".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["b"]);
+
+        let query = HistoryQuery {
+            policy_id: Some("default_cpu".into()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["b", "c"]);
+
+        let query = HistoryQuery {
+            policy_id: Some("other".into()),
+            ..Default::default()
+        };
+        assert!(store.query(&query).await.unwrap().is_empty());
+
+        store
+            .append(AttestationRecord {
+                tenant: "team-a".into(),
+                ..record("d", 400, Tee::Sample, "affirming")
+            })
+            .await
+            .unwrap();
+        let query = HistoryQuery {
+            tenant: Some(DEFAULT_TENANT.into()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["c"]);
+        let query = HistoryQuery {
+            tenant: Some("team-a".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.query(&query).await.unwrap()), ["d"]);
+    }
+
+    #[tokio::test]
+    async fn retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("results.jsonl");
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        let mut content = String::new();
+        for (request_id, iat) in [
+            ("expired", now - 3 * SECONDS_PER_DAY),
+            ("a", now - 60),
+            ("b", now - 30),
+            ("c", now),
+        ] {
+            content.push_str(
+                &serde_json::to_string(&record(request_id, iat, Tee::Sample, "affirming")).unwrap(),
+            );
+            content.push('\n');
+        }
+        content.push_str("not a record\n");
+        std::fs::write(&file_path, content).unwrap();
+
+        let store = HistoryStore::new(HistoryConfig {
+            file_path,
+            retention_days: Some(1),
+            max_records: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let records = store.query(&HistoryQuery::default()).await.unwrap();
+        let ids: Vec<_> = records.iter().map(|r| r.request_id.as_str()).collect();
+        assert_eq!(ids, ["b", "c"]);
+    }
+}
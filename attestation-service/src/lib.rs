@@ -5,8 +5,14 @@
 
 pub mod config;
 pub mod ear_token;
+pub mod history;
 pub mod policy_engine;
 pub mod rvps;
+pub mod tenant;
+pub mod token;
+
+#[cfg(test)]
+mod test_utils;
 
 use canon_json::CanonicalFormatter;
 pub use kbs_types::{Attestation, HashAlgorithm, Tee};
@@ -14,15 +20,22 @@ pub use serde_json::Value;
 
 use anyhow::{anyhow, bail, Context, Result};
 use config::Config;
-use rvps::{RvpsApi, RvpsError};
+use futures::{stream, StreamExt, TryStreamExt};
+use history::{AttestationRecord, HistoryQuery, HistoryStore};
+use rvps::{ReferenceValue, RvpsError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::fs;
-use tracing::{debug, info};
-use verifier::{InitDataHash, ReportData, TeeEvidenceParsedClaim};
+use tracing::{info, info_span, warn, Instrument};
+use verifier::{InitDataHash, NormalizedClaims, ReportData, TeeEvidenceParsedClaim};
 
-use crate::ear_token::EarAttestationTokenBroker;
+use crate::ear_token::RawEvidenceInclusion;
+use crate::policy_engine::is_valid_policy_id;
+use crate::tenant::{Tenant, TenantError, DEFAULT_TENANT};
+use crate::token::TokenFormat;
 
 fn serialize_canon_json<T: Serialize>(value: T) -> Result<Vec<u8>> {
     let mut buf = Vec::new();
@@ -43,6 +56,11 @@ pub struct TeeClaims {
     claims: TeeEvidenceParsedClaim,
     init_data_claims: serde_json::Value,
     runtime_data_claims: serde_json::Value,
+    normalized_claims: NormalizedClaims,
+    /// The evidence as received, if it is to be embedded in the token. Only
+    /// the claims of the first TEE that an evidence covers carry it.
+    #[serde(skip)]
+    raw_evidence: Option<Vec<u8>>,
 }
 
 /// Runtime Data used to check the binding relationship with report data
@@ -71,6 +89,8 @@ pub enum ServiceError {
     #[error("Create rvps failed: {0}")]
     Rvps(#[source] RvpsError),
     #[error(transparent)]
+    Tenant(#[from] TenantError),
+    #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
 }
 
@@ -106,6 +126,10 @@ pub struct VerificationRequest {
     /// TEE evidence bytes. This might not be the raw hardware evidence bytes. Definitions
     /// are in `verifier` crate.
     pub evidence: TeeEvidence,
+    /// The evidence as it was received, before it was parsed into `evidence`.
+    /// Its digest, or the evidence itself, is embedded in the token if
+    /// configured.
+    pub raw_evidence: Vec<u8>,
     /// concrete TEE type
     pub tee: Tee,
     /// These data field will be used to check against the counterpart inside the evidence.
@@ -120,10 +144,12 @@ pub struct VerificationRequest {
     pub init_data: Option<InitDataInput>,
 }
 
+/// The Attestation Service. Every call is made on behalf of a tenant, see
+/// [`tenant`]. Single-tenant callers use [`DEFAULT_TENANT`].
 pub struct AttestationService {
     config: Config,
-    rvps: Box<dyn RvpsApi + Send + Sync>,
-    token_broker: EarAttestationTokenBroker,
+    tenants: HashMap<String, Tenant>,
+    history: Option<HistoryStore>,
 }
 
 impl AttestationService {
@@ -135,141 +161,328 @@ impl AttestationService {
                 .map_err(ServiceError::CreateDir)?;
         }
 
-        let rvps = rvps::initialize_rvps_client(&config.rvps_config)
-            .await
-            .map_err(ServiceError::Rvps)?;
+        // The default tenant always exists, configured or not.
+        let mut tenant_configs = config.tenants.clone();
+        tenant_configs
+            .entry(DEFAULT_TENANT.to_string())
+            .or_default();
+        let mut tenants = HashMap::new();
+        for (id, tenant_config) in tenant_configs {
+            let tenant = Tenant::new(&id, tenant_config, &config).await?;
+            tenants.insert(id, tenant);
+        }
 
-        let token_broker =
-            EarAttestationTokenBroker::new(config.attestation_token_broker.clone()).await?;
+        let history = match &config.history {
+            Some(history_config) => Some(HistoryStore::new(history_config.clone()).await?),
+            None => None,
+        };
 
         Ok(Self {
             config,
-            rvps,
-            token_broker,
+            tenants,
+            history,
         })
     }
 
+    fn tenant(&self, tenant: &str) -> Result<&Tenant, TenantError> {
+        self.tenants
+            .get(tenant)
+            .ok_or_else(|| TenantError::UnknownTenant(tenant.to_string()))
+    }
+
+    fn tenant_mut(&mut self, tenant: &str) -> Result<&mut Tenant, TenantError> {
+        self.tenants
+            .get_mut(tenant)
+            .ok_or_else(|| TenantError::UnknownTenant(tenant.to_string()))
+    }
+
     /// Set Attestation Verification Policy.
-    pub async fn set_policy(&mut self, policy_id: String, policy: String) -> Result<()> {
-        self.token_broker.set_policy(policy_id, policy).await?;
+    pub async fn set_policy(
+        &mut self,
+        tenant: &str,
+        policy_id: String,
+        policy: String,
+    ) -> Result<()> {
+        let tenant = self.tenant(tenant)?;
+        tenant.check_policy_quota(&policy_id).await?;
+        tenant.token_broker.set_policy(policy_id, policy).await?;
         Ok(())
     }
 
     /// Get Attestation Verification Policy List.
     /// The result is a `policy-id` -> `policy hash` map.
-    pub async fn list_policies(&self) -> Result<HashMap<String, String>> {
-        self.token_broker
+    pub async fn list_policies(&self, tenant: &str) -> Result<HashMap<String, String>> {
+        self.tenant(tenant)?
+            .token_broker
             .list_policies()
             .await
             .context("Cannot List Policy")
     }
 
     /// Get a single Policy content.
-    pub async fn get_policy(&self, policy_id: String) -> Result<String> {
-        self.token_broker
+    pub async fn get_policy(&self, tenant: &str, policy_id: String) -> Result<String> {
+        self.tenant(tenant)?
+            .token_broker
             .get_policy(policy_id)
             .await
             .context("Cannot Get Policy")
     }
 
+    /// Delete an Attestation Verification Policy. The built-in
+    /// `default_cpu` and `default_gpu` policies cannot be deleted.
+    pub async fn delete_policy(&mut self, tenant: &str, policy_id: String) -> Result<()> {
+        self.tenant(tenant)?
+            .token_broker
+            .delete_policy(policy_id)
+            .await
+            .context("Cannot Delete Policy")
+    }
+
     /// Evaluate Attestation Evidence.
     /// Issue an attestation results token which contain TCB status and TEE public key.
     /// An evaluation can cover one more pieces of TEE Evidence which represent the TCB.
-    /// The results will be combined into one attestation token.
+    /// The results will be combined into one attestation token of the given
+    /// format, signed with the key of the tenant. The optional `nonce` of
+    /// the relying party is echoed in the token as `eat_nonce`.
     /// For more information, see the definition of VerificationRequest above.
     pub async fn evaluate(
         &self,
+        tenant: &str,
         verification_requests: Vec<VerificationRequest>,
         policy_ids: Vec<String>,
+        token_format: TokenFormat,
+        nonce: Option<String>,
     ) -> Result<String> {
-        let mut tee_claims: Vec<TeeClaims> = vec![];
-
         if verification_requests.is_empty() {
             bail!("No verification requests provided.")
         }
 
-        for verification_request in verification_requests {
-            let verifier = verifier::to_verifier(
-                &verification_request.tee,
-                self.config.clone().verifier_config,
-            )?;
-
-            let (report_data, runtime_data_claims) = parse_runtime_data(
-                verification_request.runtime_data,
-                &verification_request.runtime_data_hash_algorithm,
-            )
-            .context("parse runtime data")?;
-
-            let report_data = match &report_data {
-                Some(data) => ReportData::Value(data),
-                None => ReportData::NotProvided,
-            };
+        if let Some(policy_id) = policy_ids.iter().find(|id| !is_valid_policy_id(id)) {
+            bail!("Illegal policy id {policy_id}");
+        }
 
-            let (init_data, init_data_claims) =
-                parse_init_data(verification_request.init_data).context("parse init data")?;
+        let tenant = self.tenant(tenant)?;
+        tenant.count_attestation()?;
+
+        // The evidences are independent of each other, so they are verified
+        // concurrently. `buffered` keeps the results in the order of the
+        // requests, so that the submodules of the token are deterministic.
+        let timeout_secs = self.config.verification.timeout_secs;
+        let verifications =
+            verification_requests
+                .into_iter()
+                .enumerate()
+                .map(|(index, verification_request)| {
+                    let tee = verification_request.tee;
+                    let span = info_span!("verify_evidence", index, tee = ?tee);
+                    with_timeout(index, tee, timeout_secs, self.verify(verification_request))
+                        .instrument(span)
+                });
+        let tee_claims: Vec<Vec<TeeClaims>> = stream::iter(verifications)
+            .buffered(self.config.verification.max_concurrency.max(1))
+            .try_collect()
+            .await?;
+        let tee_claims = tee_claims.into_iter().flatten().collect();
 
-            let init_data_hash = match &init_data {
-                Some(data) => InitDataHash::Value(data),
-                None => InitDataHash::NotProvided,
-            };
+        let issued = tenant
+            .token_broker
+            .issue_ear(
+                tee_claims,
+                policy_ids.clone(),
+                tenant.rvps.as_ref(),
+                token_format,
+                nonce,
+            )
+            .await?;
 
-            let claims = verifier
-                .evaluate(verification_request.evidence, &report_data, &init_data_hash)
+        if let Some(history) = &self.history {
+            // A failure to record the result does not fail the attestation.
+            if let Err(e) = self
+                .record_history(history, tenant, policy_ids, &issued)
                 .await
-                .map_err(|e| anyhow!("Verifier evaluate failed: {e:?}"))?;
-
-            for (claims_from_tee_evidence, tee_class) in claims {
-                info!(
-                    tee =? verification_request.tee,
-                    tee_class = tee_class,
-                    "Verifier/endorsement check passed. claims = {}, initdata claims = {}, runtime claims = {}",
-                    serde_json::to_string(&claims_from_tee_evidence)?,
-                    serde_json::to_string(&init_data_claims)?,
-                    serde_json::to_string(&runtime_data_claims)?,
-                );
-                tee_claims.push(TeeClaims {
-                    tee: verification_request.tee,
-                    tee_class,
-                    claims: claims_from_tee_evidence,
-                    init_data_claims: init_data_claims.clone(),
-                    runtime_data_claims: runtime_data_claims.clone(),
-                });
+            {
+                warn!("Failed to record attestation result history: {e:#}");
             }
         }
 
-        let reference_data_map = self
-            .rvps
-            .get_digests()
+        Ok(issued.token)
+    }
+
+    /// Verify a single evidence. The evidence might cover multiple TEEs,
+    /// e.g. a CPU and its devices, each of which gets its own claims.
+    async fn verify(&self, verification_request: VerificationRequest) -> Result<Vec<TeeClaims>> {
+        let verifier = verifier::to_verifier(
+            &verification_request.tee,
+            self.config.clone().verifier_config,
+        )?;
+
+        let (report_data, runtime_data_claims) = parse_runtime_data(
+            verification_request.runtime_data,
+            &verification_request.runtime_data_hash_algorithm,
+        )
+        .context("parse runtime data")?;
+
+        let report_data = match &report_data {
+            Some(data) => ReportData::Value(data),
+            None => ReportData::NotProvided,
+        };
+
+        let (init_data, init_data_claims) =
+            parse_init_data(verification_request.init_data).context("parse init data")?;
+
+        let mut raw_evidence = match self.config.attestation_token_broker.raw_evidence {
+            RawEvidenceInclusion::None => None,
+            _ => Some(verification_request.raw_evidence),
+        };
+
+        let init_data_hash = match &init_data {
+            Some(data) => InitDataHash::Value(data),
+            None => InitDataHash::NotProvided,
+        };
+
+        let claims = verifier
+            .evaluate(verification_request.evidence, &report_data, &init_data_hash)
             .await
-            .map_err(|e| anyhow!("Generate reference data failed: {:?}", e))?;
-        debug!(
-            "Reference data map get from RVPS: {}",
-            serde_json::to_string(&reference_data_map)?,
-        );
+            .map_err(|e| anyhow!("Verifier evaluate failed: {e:?}"))?;
+
+        let mut tee_claims = vec![];
+        for (claims_from_tee_evidence, tee_class) in claims {
+            info!(
+                tee =? verification_request.tee,
+                tee_class = tee_class,
+                "Verifier/endorsement check passed. claims = {}, initdata claims = {}, runtime claims = {}",
+                serde_json::to_string(&claims_from_tee_evidence)?,
+                serde_json::to_string(&init_data_claims)?,
+                serde_json::to_string(&runtime_data_claims)?,
+            );
+            let normalized_claims = verifier.normalize_claims(&claims_from_tee_evidence);
+            tee_claims.push(TeeClaims {
+                tee: verification_request.tee,
+                tee_class,
+                claims: claims_from_tee_evidence,
+                init_data_claims: init_data_claims.clone(),
+                runtime_data_claims: runtime_data_claims.clone(),
+                normalized_claims,
+                raw_evidence: raw_evidence.take(),
+            });
+        }
 
-        let attestation_results_token = self
-            .token_broker
-            .issue(tee_claims, policy_ids, reference_data_map)
-            .await?;
-        Ok(attestation_results_token)
+        Ok(tee_claims)
+    }
+
+    async fn record_history(
+        &self,
+        history: &HistoryStore,
+        tenant: &Tenant,
+        policy_ids: Vec<String>,
+        issued: &ear_token::IssuedEar,
+    ) -> Result<()> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let record = AttestationRecord::new(
+            request_id.clone(),
+            tenant.id().to_string(),
+            policy_ids,
+            issued,
+        )?;
+        history.append(record).await?;
+        info!(request_id, "Attestation result recorded in history.");
+        Ok(())
+    }
+
+    /// Query the attestation result history of the tenant, oldest first.
+    pub async fn query_history(
+        &self,
+        tenant: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<AttestationRecord>> {
+        let tenant = self.tenant(tenant)?;
+        let history = self
+            .history
+            .as_ref()
+            .ok_or(anyhow!("Attestation result history is not enabled"))?;
+        let query = HistoryQuery {
+            tenant: Some(tenant.id().to_string()),
+            ..query.clone()
+        };
+        history.query(&query).await
     }
 
     /// Register a new reference value
-    pub async fn register_reference_value(&mut self, message: &str) -> Result<()> {
-        self.rvps
+    pub async fn register_reference_value(&mut self, tenant: &str, message: &str) -> Result<()> {
+        let tenant = self.tenant_mut(tenant)?;
+        tenant.check_reference_value_quota().await?;
+        tenant
+            .rvps
             .verify_and_extract(message)
             .await
             .context("register reference value")
     }
 
     /// Query Reference Values
-    pub async fn query_reference_values(&self) -> Result<HashMap<String, Value>> {
-        self.rvps
+    pub async fn query_reference_values(&self, tenant: &str) -> Result<HashMap<String, Value>> {
+        self.tenant(tenant)?
+            .rvps
             .get_digests()
             .await
             .context("query reference values")
     }
 
+    /// Delete the reference value of the given name. Return whether it existed.
+    pub async fn delete_reference_value(&mut self, tenant: &str, name: &str) -> Result<bool> {
+        self.tenant_mut(tenant)?
+            .rvps
+            .delete_reference_value(name)
+            .await
+            .context("delete reference value")
+    }
+
+    /// Delete the reference values whose name starts with the given prefix
+    pub async fn delete_reference_values_by_prefix(
+        &mut self,
+        tenant: &str,
+        prefix: &str,
+    ) -> Result<Vec<String>> {
+        self.tenant_mut(tenant)?
+            .rvps
+            .delete_reference_values_by_prefix(prefix)
+            .await
+            .context("delete reference values by prefix")
+    }
+
+    /// List Reference Values together with their metadata
+    pub async fn list_reference_values(&self, tenant: &str) -> Result<Vec<ReferenceValue>> {
+        self.tenant(tenant)?
+            .rvps
+            .list_reference_values()
+            .await
+            .context("list reference values")
+    }
+
+    /// Delete the expired Reference Values
+    pub async fn purge_expired_reference_values(&mut self, tenant: &str) -> Result<Vec<String>> {
+        self.tenant_mut(tenant)?
+            .rvps
+            .purge_expired()
+            .await
+            .context("purge expired reference values")
+    }
+
+    /// Store the Reference Values as they are, e.g. those exported from
+    /// another RVPS. Return the names of the imported ones.
+    pub async fn import_reference_values(
+        &mut self,
+        tenant: &str,
+        rvs: Vec<ReferenceValue>,
+    ) -> Result<Vec<String>> {
+        let tenant = self.tenant_mut(tenant)?;
+        tenant.check_reference_value_quota().await?;
+        tenant
+            .rvps
+            .import_reference_values(rvs)
+            .await
+            .context("import reference values")
+    }
+
     pub async fn generate_supplemental_challenge(
         &self,
         tee: Tee,
@@ -282,6 +495,20 @@ impl AttestationService {
     }
 }
 
+/// Fail the verification of the evidence at `index` of a request if it does
+/// not finish within `timeout_secs`.
+async fn with_timeout<T>(
+    index: usize,
+    tee: Tee,
+    timeout_secs: u64,
+    verification: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match tokio::time::timeout(Duration::from_secs(timeout_secs), verification).await {
+        Ok(result) => result,
+        Err(_) => bail!("Verifying evidence {index} ({tee:?}) timed out after {timeout_secs}s"),
+    }
+}
+
 /// Get the expected runtime data and potential claims due to the given input
 /// and the hash algorithm
 fn parse_runtime_data(
@@ -324,10 +551,252 @@ fn parse_init_data(data: Option<InitDataInput>) -> Result<(Option<Vec<u8>>, Valu
 #[cfg(test)]
 mod tests {
     use assert_json_diff::assert_json_eq;
+    use base64::Engine;
     use rstest::rstest;
     use serde_json::{json, Value};
+    use sha2::{Digest, Sha384};
+
+    use crate::ear_token::RawEvidenceInclusion;
+    use crate::tenant::{TenantConfig, DEFAULT_TENANT};
+    use crate::test_utils::test_config;
+    use crate::token::TokenFormat;
+    use crate::{AttestationService, HashAlgorithm, RuntimeData, Tee, VerificationRequest};
+
+    async fn test_service(work_dir: &std::path::Path) -> AttestationService {
+        AttestationService::new(test_config(work_dir))
+            .await
+            .unwrap()
+    }
 
-    use crate::{HashAlgorithm, RuntimeData};
+    fn token_part(token: &str, index: usize) -> Value {
+        let part = token.split('.').nth(index).unwrap();
+        let part = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(part)
+            .unwrap();
+        serde_json::from_slice(&part).unwrap()
+    }
+
+    fn sample_request(svn: &str) -> VerificationRequest {
+        VerificationRequest {
+            evidence: json!({"svn": svn}),
+            raw_evidence: json!({"svn": svn}).to_string().into_bytes(),
+            tee: Tee::Sample,
+            runtime_data: None,
+            runtime_data_hash_algorithm: HashAlgorithm::Sha384,
+            init_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn evaluate_keeps_submodule_order() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let service = test_service(work_dir.path()).await;
+
+        let svns = ["1", "2", "3", "4", "5"];
+        let token = service
+            .evaluate(
+                DEFAULT_TENANT,
+                svns.iter().map(|svn| sample_request(svn)).collect(),
+                vec!["default".into()],
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let payload = token_part(&token, 1);
+        for (index, svn) in svns.iter().enumerate() {
+            assert_eq!(
+                payload["submods"][format!("cpu{index}")]["ear.veraison.annotated-evidence"]
+                    ["sample"]["svn"],
+                *svn
+            );
+        }
+
+        let mut requests: Vec<_> = svns.iter().map(|svn| sample_request(svn)).collect();
+        requests.push(VerificationRequest {
+            evidence: json!({"not": "sample evidence"}),
+            ..sample_request("6")
+        });
+        assert!(service
+            .evaluate(
+                DEFAULT_TENANT,
+                requests,
+                vec!["default".into()],
+                TokenFormat::Ear,
+                None
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn evaluate_flat_jwt() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let service = test_service(work_dir.path()).await;
+
+        let tee_pubkey = json!({"kty": "EC", "crv": "P-256", "alg": "ES256", "x": "x", "y": "y"});
+        let runtime_data = || RuntimeData::Structured(json!({"tee-pubkey": tee_pubkey}));
+        let (report_data, _) =
+            crate::parse_runtime_data(Some(runtime_data()), &HashAlgorithm::Sha384).unwrap();
+        let request = VerificationRequest {
+            evidence: json!({
+                "svn": "1",
+                "report_data": base64::engine::general_purpose::STANDARD.encode(report_data.unwrap()),
+            }),
+            runtime_data: Some(runtime_data()),
+            ..sample_request("1")
+        };
+        let token = service
+            .evaluate(
+                DEFAULT_TENANT,
+                vec![request],
+                vec!["default".into()],
+                TokenFormat::Jwt,
+                Some("relying-party-nonce".into()),
+            )
+            .await
+            .unwrap();
+
+        let payload = token_part(&token, 1);
+        assert_eq!(payload["tee-pubkey"], tee_pubkey);
+        assert_eq!(payload["eat_nonce"], "relying-party-nonce");
+        assert!(payload["status"].is_string());
+        assert_eq!(payload["iss"], "CoCo-Attestation-Service");
+        assert!(payload["exp"].as_i64().unwrap() > payload["iat"].as_i64().unwrap());
+        assert!(payload.get("submods").is_none());
+    }
+
+    #[tokio::test]
+    async fn evaluate_embeds_raw_evidence_digest() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(work_dir.path());
+        config.attestation_token_broker.raw_evidence = RawEvidenceInclusion::Hash;
+        let service = AttestationService::new(config).await.unwrap();
+
+        // The digest covers the evidence as received, not as re-serialized
+        let raw_evidence = br#"{ "svn": "1" }"#.to_vec();
+        let request = VerificationRequest {
+            raw_evidence: raw_evidence.clone(),
+            ..sample_request("1")
+        };
+        let token = service
+            .evaluate(
+                DEFAULT_TENANT,
+                vec![request, sample_request("2")],
+                vec!["default".into()],
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let payload = token_part(&token, 1);
+        let digest = |raw_evidence: &[u8]| hex::encode(Sha384::digest(raw_evidence));
+        assert_eq!(
+            payload["submods"]["cpu0"]["ear.veraison.annotated-evidence"]["raw_evidence_sha384"],
+            digest(&raw_evidence)
+        );
+        assert_eq!(
+            payload["submods"]["cpu1"]["ear.veraison.annotated-evidence"]["raw_evidence_sha384"],
+            digest(&sample_request("2").raw_evidence)
+        );
+    }
+
+    #[tokio::test]
+    async fn tenants_are_isolated() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(work_dir.path());
+        config
+            .tenants
+            .insert("team-a".into(), TenantConfig::default());
+        let mut service = AttestationService::new(config).await.unwrap();
+
+        let policy = r#"
+package policy
+
+result := {
+    "executables": 2,
+    "claims": {"team": "a"},
+}
+"#;
+        service
+            .set_policy(
+                "team-a",
+                "default_cpu".into(),
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(policy),
+            )
+            .await
+            .unwrap();
+        assert_ne!(
+            service
+                .list_policies("team-a")
+                .await
+                .unwrap()
+                .get("default_cpu"),
+            service
+                .list_policies(DEFAULT_TENANT)
+                .await
+                .unwrap()
+                .get("default_cpu"),
+        );
+
+        let evaluate = |tenant| {
+            service.evaluate(
+                tenant,
+                vec![sample_request("1")],
+                vec!["default".into()],
+                TokenFormat::Ear,
+                None,
+            )
+        };
+        let team_a_token = evaluate("team-a").await.unwrap();
+        let default_token = evaluate(DEFAULT_TENANT).await.unwrap();
+        let policy_claims = |token: &str| {
+            token_part(token, 1)["submods"]["cpu0"]["ear.veraison.policy-claims"].clone()
+        };
+        assert_eq!(policy_claims(&team_a_token), json!({"team": "a"}));
+        assert_eq!(policy_claims(&default_token), Value::Null);
+
+        // Each tenant signs with its own key
+        assert_ne!(
+            token_part(&team_a_token, 0)["jwk"],
+            token_part(&default_token, 0)["jwk"]
+        );
+
+        assert!(evaluate("team-b").await.is_err());
+
+        // A policy id cannot reach out of the policies of the tenant
+        assert!(service
+            .evaluate(
+                "team-a",
+                vec![sample_request("1")],
+                vec!["../../../opa/default".into()],
+                TokenFormat::Ear,
+                None,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn verification_times_out() {
+        let err = crate::with_timeout(
+            1,
+            Tee::Sample,
+            0,
+            std::future::pending::<anyhow::Result<()>>(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Verifying evidence 1 (Sample) timed out after 0s"
+        );
+
+        let result = crate::with_timeout(0, Tee::Sample, 0, async { Ok(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+    }
 
     #[rstest]
     #[case(Some(RuntimeData::Raw(b"aaaaa".to_vec())), Some(b"aaaaa".to_vec()), HashAlgorithm::Sha384, Value::Null)]
@@ -11,6 +11,18 @@ use thiserror::Error;
 
 pub mod opa;
 
+/// Policies that the attestation service sets itself at startup, and that
+/// cannot be deleted.
+pub const BUILT_IN_POLICY_IDS: &[&str] = &["default_cpu", "default_gpu"];
+
+/// A policy id names a file in the policy directory, so it may only contain
+/// alphanumeric characters, `-` and `_`, and never leaves the directory.
+pub fn is_valid_policy_id(policy_id: &str) -> bool {
+    policy_id
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
 #[derive(Error, Debug)]
 pub enum PolicyError {
     #[error("Failed to create policy directory: {0}")]
@@ -35,6 +47,12 @@ pub enum PolicyError {
     Base64DecodeFailed(#[from] base64::DecodeError),
     #[error("Illegal policy id. Only support alphabet, numeric, `-` or `_`")]
     InvalidPolicyId,
+    #[error("Policy {0} does not exist")]
+    PolicyNotFound(String),
+    #[error("Built-in policy {0} cannot be deleted")]
+    DeleteBuiltInPolicy(String),
+    #[error("Failed to delete attestation service policy file: {0}")]
+    DeletePolicyFileFailed(#[source] io::Error),
     #[error("Illegal policy: {0}")]
     InvalidPolicy(#[source] anyhow::Error),
     #[error("Failed to load reference data: {0}")]
@@ -47,6 +65,8 @@ pub enum PolicyError {
     JsonSerializationFailed(#[source] anyhow::Error),
     #[error("Policy claim value not valid (must be between -127 and 127)")]
     InvalidClaimValue,
+    #[error("`reference_value_prefixes` of the policy must be a set or an array of strings: {0}")]
+    InvalidReferenceValuePrefixes(#[source] serde_json::Error),
 }
 
 #[derive(Debug, EnumString, Deserialize)]
@@ -111,4 +131,16 @@ pub trait PolicyEngine: Send + Sync {
     async fn list_policies(&self) -> Result<HashMap<String, PolicyDigest>, PolicyError>;
 
     async fn get_policy(&self, policy_id: String) -> Result<String, PolicyError>;
+
+    /// Delete the policy of the given id. The built-in policies, see
+    /// [`BUILT_IN_POLICY_IDS`], cannot be deleted.
+    async fn delete_policy(&self, policy_id: String) -> Result<(), PolicyError>;
+
+    /// The prefixes of the names of the reference values that the policy
+    /// needs as `data.reference`. `None` if the policy does not declare them,
+    /// s.t. it needs all the reference values.
+    async fn reference_value_prefixes(
+        &self,
+        policy_id: &str,
+    ) -> Result<Option<Vec<String>>, PolicyError>;
 }
@@ -10,17 +10,29 @@ use sha2::{Digest, Sha384};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 use tracing::warn;
 
-use super::{EvaluationResult, PolicyDigest, PolicyEngine, PolicyError};
+use super::{
+    is_valid_policy_id, EvaluationResult, PolicyDigest, PolicyEngine, PolicyError,
+    BUILT_IN_POLICY_IDS,
+};
 
 /// The rule to evaluate the policy.
 /// Note that only the result of this rule will be returned.
 pub const EVAL_RULE: &str = "data.policy.result";
 
+/// The rule of a policy that declares the prefixes of the names of the
+/// reference values it needs, e.g.
+/// `reference_value_prefixes := {"snp_", "tdx_"}`.
+pub const REFERENCE_VALUE_PREFIXES_RULE: &str = "data.policy.reference_value_prefixes";
+
 #[derive(Debug, Clone)]
 pub struct OPA {
     policy_dir_path: PathBuf,
+    /// The `reference_value_prefixes` of the policies, by policy id. They
+    /// are computed when a policy is set, or first needed after a restart.
+    reference_value_prefixes: Arc<RwLock<HashMap<String, Option<Vec<String>>>>>,
 }
 
 impl OPA {
@@ -32,13 +44,36 @@ impl OPA {
             fs::create_dir_all(&policy_dir_path).map_err(PolicyError::CreatePolicyDirFailed)?;
         }
 
-        Ok(Self { policy_dir_path })
+        Ok(Self {
+            policy_dir_path,
+            reference_value_prefixes: Arc::default(),
+        })
     }
 
-    fn is_valid_policy_id(policy_id: &str) -> bool {
-        policy_id
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    /// Evaluate the constant `reference_value_prefixes` rule of a policy.
+    fn eval_reference_value_prefixes(
+        engine: &mut regorus::Engine,
+    ) -> Result<Option<Vec<String>>, PolicyError> {
+        // Without data and input, the rule is undefined unless it is constant.
+        let results = engine
+            .eval_query(REFERENCE_VALUE_PREFIXES_RULE.to_string(), false)
+            .map_err(PolicyError::EvalPolicyFailed)?;
+        let Some(prefixes) = results
+            .result
+            .first()
+            .and_then(|result| result.expressions.first())
+        else {
+            return Ok(None);
+        };
+
+        let prefixes = prefixes
+            .value
+            .to_json_str()
+            .map_err(PolicyError::JsonSerializationFailed)?;
+        let prefixes =
+            serde_json::from_str(&prefixes).map_err(PolicyError::InvalidReferenceValuePrefixes)?;
+
+        Ok(Some(prefixes))
     }
 }
 
@@ -50,6 +85,10 @@ impl PolicyEngine for OPA {
         input: &str,
         policy_id: &str,
     ) -> Result<EvaluationResult, PolicyError> {
+        if !is_valid_policy_id(policy_id) {
+            return Err(PolicyError::InvalidPolicyId);
+        }
+
         let policy_dir_path = self
             .policy_dir_path
             .to_str()
@@ -114,19 +153,20 @@ impl PolicyEngine for OPA {
     ) -> Result<(), PolicyError> {
         let policy_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(policy)?;
 
-        if !Self::is_valid_policy_id(&policy_id) {
+        if !is_valid_policy_id(&policy_id) {
             return Err(PolicyError::InvalidPolicyId);
         }
 
         // Check if the policy is valid
-        {
+        let prefixes = {
             let policy_content = String::from_utf8(policy_bytes.clone())
                 .map_err(|e| PolicyError::InvalidPolicy(e.into()))?;
             let mut engine = regorus::Engine::new();
             engine
                 .add_policy(policy_id.clone(), policy_content)
                 .map_err(PolicyError::InvalidPolicy)?;
-        }
+            Self::eval_reference_value_prefixes(&mut engine)?
+        };
 
         let mut policy_file_path = PathBuf::from(
             &self
@@ -144,7 +184,13 @@ impl PolicyEngine for OPA {
 
         tokio::fs::write(&policy_file_path, policy_bytes)
             .await
-            .map_err(PolicyError::WritePolicyFileFailed)
+            .map_err(PolicyError::WritePolicyFileFailed)?;
+        self.reference_value_prefixes
+            .write()
+            .expect("poisoned lock")
+            .insert(policy_id, prefixes);
+
+        Ok(())
     }
 
     async fn list_policies(&self) -> Result<HashMap<String, PolicyDigest>, PolicyError> {
@@ -182,13 +228,79 @@ impl PolicyEngine for OPA {
     }
 
     async fn get_policy(&self, policy_id: String) -> Result<String, PolicyError> {
+        if !is_valid_policy_id(&policy_id) {
+            return Err(PolicyError::InvalidPolicyId);
+        }
+
         let policy_file_path = self.policy_dir_path.join(format!("{policy_id}.rego"));
-        let policy = tokio::fs::read(policy_file_path)
-            .await
-            .map_err(PolicyError::ReadPolicyFileFailed)?;
+        let policy = match tokio::fs::read(policy_file_path).await {
+            Ok(policy) => policy,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(PolicyError::PolicyNotFound(policy_id))
+            }
+            Err(e) => return Err(PolicyError::ReadPolicyFileFailed(e)),
+        };
         let base64_policy = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(policy);
         Ok(base64_policy)
     }
+
+    async fn delete_policy(&self, policy_id: String) -> Result<(), PolicyError> {
+        if !is_valid_policy_id(&policy_id) {
+            return Err(PolicyError::InvalidPolicyId);
+        }
+
+        if BUILT_IN_POLICY_IDS.contains(&policy_id.as_str()) {
+            return Err(PolicyError::DeleteBuiltInPolicy(policy_id));
+        }
+
+        self.reference_value_prefixes
+            .write()
+            .expect("poisoned lock")
+            .remove(&policy_id);
+        let policy_file_path = self.policy_dir_path.join(format!("{policy_id}.rego"));
+        match tokio::fs::remove_file(policy_file_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(PolicyError::PolicyNotFound(policy_id))
+            }
+            Err(e) => Err(PolicyError::DeletePolicyFileFailed(e)),
+        }
+    }
+
+    async fn reference_value_prefixes(
+        &self,
+        policy_id: &str,
+    ) -> Result<Option<Vec<String>>, PolicyError> {
+        if !is_valid_policy_id(policy_id) {
+            return Err(PolicyError::InvalidPolicyId);
+        }
+
+        if let Some(prefixes) = self
+            .reference_value_prefixes
+            .read()
+            .expect("poisoned lock")
+            .get(policy_id)
+        {
+            return Ok(prefixes.clone());
+        }
+
+        let policy_file_path = self.policy_dir_path.join(format!("{policy_id}.rego"));
+        let policy = tokio::fs::read_to_string(policy_file_path)
+            .await
+            .map_err(PolicyError::ReadPolicyFileFailed)?;
+
+        let mut engine = regorus::Engine::new();
+        engine
+            .add_policy(policy_id.to_string(), policy)
+            .map_err(PolicyError::LoadPolicyFailed)?;
+        let prefixes = Self::eval_reference_value_prefixes(&mut engine)?;
+        self.reference_value_prefixes
+            .write()
+            .expect("poisoned lock")
+            .insert(policy_id.to_string(), prefixes.clone());
+
+        Ok(prefixes)
+    }
 }
 
 #[cfg(test)]
@@ -219,6 +331,13 @@ mod tests {
                     "major": 1,
                     "minor": 4
                 }
+            },
+            "normalized": {
+                "launch_measurement": launch_digest,
+                "tcb_svns": {
+                    "svn": svn.to_string()
+                },
+                "debug_enabled": false
             }
         })
         .to_string()
@@ -240,6 +359,7 @@ mod tests {
     ) {
         let opa = OPA {
             policy_dir_path: PathBuf::from("./src/ear_token/"),
+            reference_value_prefixes: Arc::default(),
         };
         let default_policy_id = "ear_default_policy_cpu".to_string();
 
@@ -277,6 +397,55 @@ mod tests {
         );
     }
 
+    /// The CCA example policy compares the claims of the CCA verifier with
+    /// the reference values named by the CoRIM extractor of the RVPS.
+    #[rstest]
+    #[case("EJHTwpx6", "AAAA", "BwYF", 3, 2)]
+    #[case("EJHTwpx7", "AAAA", "BwYF", 33, 2)]
+    #[case("EJHTwpx6", "AAAB", "BwYF", 33, 2)]
+    #[case("EJHTwpx6", "AAAA", "BwYG", 3, 97)]
+    #[tokio::test]
+    async fn test_evaluate_cca(
+        #[case] rim: &str,
+        #[case] rem0: &str,
+        #[case] bl: &str,
+        #[case] ex_exp: i64,
+        #[case] hw_exp: i64,
+    ) {
+        let opa = OPA {
+            policy_dir_path: PathBuf::from("../deps/verifier/test_data/cca/conf/"),
+            reference_value_prefixes: Arc::default(),
+        };
+        let reference = json!({
+            "reference": {
+                "cca.realm.cca-realm-initial-measurement": [rim],
+                "cca.realm.cca-realm-extensible-measurements.0": [rem0],
+                "cca.platform.cca-platform-sw-components.BL": [bl],
+            }
+        });
+        let input = json!({
+            "cca": {
+                "platform": {
+                    "cca-platform-sw-components": [
+                        {"measurement-type": "BL", "measurement-value": "BwYF"},
+                        {"measurement-type": "M1", "measurement-value": "CAkK"},
+                    ]
+                },
+                "realm": {
+                    "cca-realm-initial-measurement": "EJHTwpx6",
+                    "cca-realm-extensible-measurements": ["AAAA", "AAAA"],
+                }
+            }
+        });
+
+        let output = opa
+            .evaluate(&reference.to_string(), &input.to_string(), "default")
+            .await
+            .unwrap();
+        assert_eq!(output.rules_result["executables"], ex_exp);
+        assert_eq!(output.rules_result["hardware"], hw_exp);
+    }
+
     #[tokio::test]
     async fn test_policy_management() {
         let opa = OPA::new(PathBuf::from("tests/tmp")).unwrap();
@@ -300,4 +469,104 @@ default allow = true"
         assert_eq!(test_policy, get_policy_output);
         assert!(opa.list_policies().await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_delete_policy() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let opa = OPA::new(work_dir.path().to_path_buf()).unwrap();
+        let policy = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode("package policy\ndefault allow = true");
+        for policy_id in ["test", "default_cpu"] {
+            opa.set_policy(policy_id.to_string(), policy.clone(), true)
+                .await
+                .unwrap();
+        }
+
+        opa.delete_policy("test".to_string()).await.unwrap();
+        assert!(matches!(
+            opa.get_policy("test".to_string()).await,
+            Err(PolicyError::PolicyNotFound(_))
+        ));
+        assert!(matches!(
+            opa.delete_policy("test".to_string()).await,
+            Err(PolicyError::PolicyNotFound(_))
+        ));
+        assert!(matches!(
+            opa.delete_policy("default_cpu".to_string()).await,
+            Err(PolicyError::DeleteBuiltInPolicy(_))
+        ));
+        assert!(matches!(
+            opa.delete_policy("../default_cpu".to_string()).await,
+            Err(PolicyError::InvalidPolicyId)
+        ));
+        assert!(matches!(
+            opa.get_policy("../default_cpu".to_string()).await,
+            Err(PolicyError::InvalidPolicyId)
+        ));
+        assert!(matches!(
+            opa.evaluate("{}", "{}", "../default_cpu").await,
+            Err(PolicyError::InvalidPolicyId)
+        ));
+        assert!(matches!(
+            opa.reference_value_prefixes("../default_cpu").await,
+            Err(PolicyError::InvalidPolicyId)
+        ));
+
+        let policies = opa.list_policies().await.unwrap();
+        assert_eq!(policies.keys().collect::<Vec<_>>(), vec!["default_cpu"]);
+    }
+
+    #[rstest]
+    #[case("reference_value_prefixes := {\"snp_\", \"tdx_\"}", Some(vec!["snp_", "tdx_"]))]
+    #[case("reference_value_prefixes := [\"snp_\"]", Some(vec!["snp_"]))]
+    #[case("default allow := true", None)]
+    #[case("reference_value_prefixes := [input.prefix]", None)]
+    #[tokio::test]
+    async fn test_reference_value_prefixes(
+        #[case] rule: &str,
+        #[case] expected: Option<Vec<&str>>,
+    ) {
+        let work_dir = tempfile::tempdir().unwrap();
+        let opa = OPA::new(work_dir.path().to_path_buf()).unwrap();
+        let policy = format!("package policy\nimport rego.v1\n{rule}\n");
+        opa.set_policy(
+            "test".to_string(),
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(policy),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let prefixes = opa.reference_value_prefixes("test").await.unwrap();
+        let expected = expected.map(|prefixes| prefixes.iter().map(|p| p.to_string()).collect());
+        assert_eq!(prefixes, expected);
+
+        // The prefixes are also computed for the policies set before a restart
+        let opa = OPA::new(work_dir.path().to_path_buf()).unwrap();
+        let prefixes = opa.reference_value_prefixes("test").await.unwrap();
+        assert_eq!(prefixes, expected);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_reference_value_prefixes() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let opa = OPA::new(work_dir.path().to_path_buf()).unwrap();
+        let policy = "package policy\nreference_value_prefixes := 1\n";
+        assert!(matches!(
+            opa.set_policy(
+                "test".to_string(),
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(policy),
+                true,
+            )
+            .await,
+            Err(PolicyError::InvalidReferenceValuePrefixes(_))
+        ));
+
+        // A policy written by an older version is rejected when it is loaded
+        std::fs::write(work_dir.path().join("opa/test.rego"), policy).unwrap();
+        assert!(matches!(
+            opa.reference_value_prefixes("test").await,
+            Err(PolicyError::InvalidReferenceValuePrefixes(_))
+        ));
+    }
 }
@@ -1,17 +1,68 @@
-use super::{Result, RvpsApi};
+use super::cache::{ReferenceValueCache, ReferenceValueCacheConfig};
+use super::{retain_prefixes, ReferenceValue, Result, RvpsApi};
 use async_trait::async_trait;
 use core::result::Result::Ok;
-use reference_value_provider_service::{Config, Rvps};
+use reference_value_provider_service::{Config, ReferenceValueChange, Rvps};
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tracing::warn;
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BuiltinRvpsConfig {
+    #[serde(flatten)]
+    pub config: Config,
+
+    /// Local snapshot of the reference values. The snapshot is reloaded from
+    /// the storage when it is stale, e.g. to see the changes of other RVPS
+    /// instances sharing the same storage.
+    #[serde(default)]
+    pub cache: ReferenceValueCacheConfig,
+}
+
+impl From<Config> for BuiltinRvpsConfig {
+    fn from(config: Config) -> Self {
+        Self {
+            config,
+            cache: ReferenceValueCacheConfig::default(),
+        }
+    }
+}
 
 pub struct BuiltinRvps {
     rvps: Rvps,
+    changes: Mutex<broadcast::Receiver<ReferenceValueChange>>,
+    cache: ReferenceValueCache,
 }
 
 impl BuiltinRvps {
-    pub fn new(config: Config) -> Result<Self> {
-        let rvps = Rvps::new(config)?;
-        Ok(Self { rvps })
+    pub fn new(config: BuiltinRvpsConfig) -> Result<Self> {
+        let rvps = Rvps::new(config.config)?;
+        let changes = Mutex::new(rvps.subscribe());
+        let cache = ReferenceValueCache::new(&config.cache);
+        Ok(Self {
+            rvps,
+            changes,
+            cache,
+        })
+    }
+
+    /// Apply the changes made since the last call to the snapshot.
+    fn update_cache(&self) {
+        let mut changes = self.changes.lock().expect("poisoned lock");
+        loop {
+            match changes.try_recv() {
+                Ok(change) => self
+                    .cache
+                    .apply(change.revision, change.updated, &change.removed),
+                Err(TryRecvError::Lagged(skipped)) => {
+                    warn!("{skipped} reference value changes are missed, reload the snapshot");
+                    self.cache.invalidate();
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
     }
 }
 
@@ -23,8 +74,57 @@ impl RvpsApi for BuiltinRvps {
     }
 
     async fn get_digests(&self) -> Result<HashMap<String, serde_json::Value>> {
+        if self.cache.enabled() {
+            self.update_cache();
+            if let Some(hashes) = self.cache.get() {
+                return Ok(hashes);
+            }
+        }
+
+        let revision = self.rvps.revision();
         let hashes = self.rvps.get_digests().await?;
+        self.cache.replace(Some(revision), hashes.clone());
 
         Ok(hashes)
     }
+
+    async fn get_digests_by_prefixes(
+        &self,
+        prefixes: &[String],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        // The snapshot always holds all the reference values
+        if self.cache.enabled() {
+            let mut hashes = self.get_digests().await?;
+            retain_prefixes(&mut hashes, prefixes);
+            return Ok(hashes);
+        }
+
+        let hashes = self.rvps.get_digests_by_prefixes(prefixes).await?;
+        Ok(hashes)
+    }
+
+    async fn delete_reference_value(&mut self, name: &str) -> Result<bool> {
+        let deleted = self.rvps.delete_reference_value(name).await?;
+        Ok(deleted)
+    }
+
+    async fn delete_reference_values_by_prefix(&mut self, prefix: &str) -> Result<Vec<String>> {
+        let deleted = self.rvps.delete_reference_values_by_prefix(prefix).await?;
+        Ok(deleted)
+    }
+
+    async fn list_reference_values(&self) -> Result<Vec<ReferenceValue>> {
+        let rvs = self.rvps.list_reference_values().await?;
+        Ok(rvs)
+    }
+
+    async fn purge_expired(&mut self) -> Result<Vec<String>> {
+        let purged = self.rvps.purge_expired().await?;
+        Ok(purged)
+    }
+
+    async fn import_reference_values(&mut self, rvs: Vec<ReferenceValue>) -> Result<Vec<String>> {
+        let imported = self.rvps.import_reference_values(rvs).await?;
+        Ok(imported)
+    }
 }
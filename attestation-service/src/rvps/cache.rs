@@ -0,0 +1,212 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Local snapshot of the reference values, so that the RVPS is not queried
+//! for every attestation.
+//!
+//! The snapshot is updated incrementally by the changes the RVPS notifies,
+//! and is reloaded completely once it is older than the maximum staleness.
+//! This bounds how long changes not notified are missed, e.g. reference
+//! values expiring over time, or registered by another RVPS replica sharing
+//! the same storage.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ReferenceValueCacheConfig {
+    /// Maximum time in seconds since the snapshot was loaded completely
+    /// before it is reloaded. `0` disables the snapshot, so that the RVPS is
+    /// queried for every attestation.
+    #[serde(default = "default_max_staleness_secs")]
+    pub max_staleness_secs: u64,
+
+    /// Interval in seconds to reload the snapshot when the changes cannot be
+    /// watched, e.g. the remote RVPS does not support it.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_max_staleness_secs() -> u64 {
+    60
+}
+
+fn default_poll_interval_secs() -> u64 {
+    10
+}
+
+impl Default for ReferenceValueCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_staleness_secs: default_max_staleness_secs(),
+            poll_interval_secs: default_poll_interval_secs(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Snapshot {
+    revision: u64,
+    values: HashMap<String, Value>,
+    loaded_at: Option<Instant>,
+    /// Incremented on every change of the snapshot, including when it is
+    /// dropped, so that a query racing with a change can be detected.
+    generation: u64,
+}
+
+pub struct ReferenceValueCache {
+    max_staleness: Duration,
+    snapshot: RwLock<Snapshot>,
+}
+
+impl ReferenceValueCache {
+    pub fn new(config: &ReferenceValueCacheConfig) -> Self {
+        Self {
+            max_staleness: Duration::from_secs(config.max_staleness_secs),
+            snapshot: RwLock::new(Snapshot::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.max_staleness.is_zero()
+    }
+
+    /// The reference values of the snapshot, or `None` if it is disabled,
+    /// not loaded or stale.
+    pub fn get(&self) -> Option<HashMap<String, Value>> {
+        let snapshot = self.snapshot.read().expect("poisoned lock");
+        match snapshot.loaded_at {
+            Some(loaded_at) if loaded_at.elapsed() < self.max_staleness => {
+                Some(snapshot.values.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// The generation of the snapshot, which changes whenever the snapshot does.
+    pub fn generation(&self) -> u64 {
+        self.snapshot.read().expect("poisoned lock").generation
+    }
+
+    /// Replace the snapshot with all the reference values. `revision` is the
+    /// revision of the values if known, otherwise the current one is kept.
+    pub fn replace(&self, revision: Option<u64>, values: HashMap<String, Value>) {
+        self.replace_if(None, revision, values);
+    }
+
+    /// Replace the snapshot with all the reference values, unless it changed
+    /// since the given [`Self::generation`]. The values may then be older than
+    /// the change, e.g. when they were queried before it.
+    pub fn replace_if(
+        &self,
+        generation: Option<u64>,
+        revision: Option<u64>,
+        values: HashMap<String, Value>,
+    ) {
+        if !self.enabled() {
+            return;
+        }
+
+        let mut snapshot = self.snapshot.write().expect("poisoned lock");
+        if generation.is_some_and(|generation| generation != snapshot.generation) {
+            return;
+        }
+
+        if let Some(revision) = revision {
+            snapshot.revision = revision;
+        }
+        snapshot.values = values;
+        snapshot.loaded_at = Some(Instant::now());
+        snapshot.generation += 1;
+    }
+
+    /// Apply a change of the reference values. Changes that the snapshot
+    /// already includes are skipped.
+    pub fn apply(&self, revision: u64, updated: HashMap<String, Value>, removed: &[String]) {
+        let mut snapshot = self.snapshot.write().expect("poisoned lock");
+        if snapshot.loaded_at.is_none() || revision <= snapshot.revision {
+            return;
+        }
+
+        snapshot.revision = revision;
+        snapshot.generation += 1;
+        for name in removed {
+            snapshot.values.remove(name);
+        }
+        snapshot.values.extend(updated);
+    }
+
+    /// Drop the snapshot, e.g. when changes were missed.
+    pub fn invalidate(&self) {
+        let mut snapshot = self.snapshot.write().expect("poisoned lock");
+        snapshot.loaded_at = None;
+        snapshot.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::{ReferenceValueCache, ReferenceValueCacheConfig};
+
+    #[test]
+    fn apply_changes() {
+        let cache = ReferenceValueCache::new(&ReferenceValueCacheConfig::default());
+        assert!(cache.get().is_none());
+
+        // Changes before the first snapshot cannot be applied
+        cache.apply(1, HashMap::from([("a".into(), json!(["1"]))]), &[]);
+        assert!(cache.get().is_none());
+
+        cache.replace(
+            Some(3),
+            HashMap::from([("a".into(), json!(["1"])), ("b".into(), json!(["2"]))]),
+        );
+        cache.apply(
+            4,
+            HashMap::from([("c".into(), json!(["3"]))]),
+            &["a".into()],
+        );
+        // Already included in the snapshot
+        cache.apply(2, HashMap::new(), &["b".into()]);
+
+        let values = cache.get().expect("no snapshot");
+        assert_eq!(
+            values,
+            HashMap::from([("b".into(), json!(["2"])), ("c".into(), json!(["3"]))])
+        );
+
+        cache.invalidate();
+        assert!(cache.get().is_none());
+
+        // Values queried before a change are dropped
+        let generation = cache.generation();
+        cache.replace(Some(5), HashMap::new());
+        cache.replace_if(Some(generation), None, values);
+        assert_eq!(cache.get(), Some(HashMap::new()));
+        let generation = cache.generation();
+        cache.invalidate();
+        cache.replace_if(Some(generation), None, HashMap::new());
+        assert!(cache.get().is_none());
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = ReferenceValueCache::new(&ReferenceValueCacheConfig {
+            max_staleness_secs: 0,
+            ..Default::default()
+        });
+        cache.replace(Some(1), HashMap::new());
+        assert!(!cache.enabled());
+        assert!(cache.get().is_none());
+    }
+}
@@ -1,14 +1,23 @@
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, task::JoinHandle};
+use tonic::{transport::Channel, Code};
+use tracing::{debug, warn};
 
 use self::rvps_api::{
     reference_value_provider_service_client::ReferenceValueProviderServiceClient,
-    ReferenceValueQueryRequest, ReferenceValueRegisterRequest,
+    ReferenceValueDeleteRequest, ReferenceValueImportRequest, ReferenceValueListRequest,
+    ReferenceValuePurgeRequest, ReferenceValueQueryRequest, ReferenceValueRegisterRequest,
+    ReferenceValueWatchRequest,
 };
 
-use super::{Result, RvpsApi};
+use reference_value_provider_service::client::{endpoint, TlsConfig};
+
+use super::cache::{ReferenceValueCache, ReferenceValueCacheConfig};
+use super::{retain_prefixes, ReferenceValue, Result, RvpsApi, RvpsError};
 
 pub mod rvps_api {
     tonic::include_proto!("reference");
@@ -20,6 +29,16 @@ pub struct RvpsRemoteConfig {
     /// If this field is not given, a built-in RVPS will be used.
     #[serde(default = "default_address")]
     pub address: String,
+
+    /// Local snapshot of the reference values, kept up to date by watching
+    /// the changes of the remote RVPS.
+    #[serde(default)]
+    pub cache: ReferenceValueCacheConfig,
+
+    /// TLS configuration of the connection to the remote RVPS. TLS is also
+    /// used without it if the scheme of the address is `https`.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
 }
 
 fn default_address() -> String {
@@ -36,18 +55,103 @@ pub enum GrpcRvpsError {
 }
 
 pub struct Agent {
-    client: Mutex<ReferenceValueProviderServiceClient<tonic::transport::Channel>>,
+    client: Mutex<ReferenceValueProviderServiceClient<Channel>>,
+    cache: Arc<ReferenceValueCache>,
+    watcher: Option<JoinHandle<()>>,
 }
 
 impl Agent {
-    pub async fn new(addr: &str) -> Result<Self> {
+    pub async fn new(config: &RvpsRemoteConfig) -> Result<Self> {
+        let channel = endpoint(config.address.clone(), config.tls.as_ref())?
+            .connect()
+            .await?;
+        let client = ReferenceValueProviderServiceClient::new(channel);
+        let cache = Arc::new(ReferenceValueCache::new(&config.cache));
+        let watcher = cache.enabled().then(|| {
+            tokio::spawn(watch(
+                client.clone(),
+                cache.clone(),
+                Duration::from_secs(config.cache.poll_interval_secs),
+            ))
+        });
+
         Ok(Self {
-            client: Mutex::new(
-                ReferenceValueProviderServiceClient::connect(addr.to_string()).await?,
-            ),
+            client: Mutex::new(client),
+            cache,
+            watcher,
         })
     }
 }
+
+impl Drop for Agent {
+    fn drop(&mut self) {
+        if let Some(watcher) = &self.watcher {
+            watcher.abort();
+        }
+    }
+}
+
+async fn query(
+    client: &mut ReferenceValueProviderServiceClient<Channel>,
+    prefixes: Vec<String>,
+) -> Result<HashMap<String, serde_json::Value>> {
+    let req = tonic::Request::new(ReferenceValueQueryRequest { prefixes });
+    let res = client.query_reference_value(req).await?.into_inner();
+    let trust_digest = serde_json::from_str(&res.reference_value_results)?;
+    Ok(trust_digest)
+}
+
+/// Keep the snapshot up to date with the changes of the remote RVPS until the
+/// stream breaks.
+async fn watch_changes(
+    client: &mut ReferenceValueProviderServiceClient<Channel>,
+    cache: &ReferenceValueCache,
+) -> Result<()> {
+    let mut stream = client
+        .watch_reference_values(ReferenceValueWatchRequest {})
+        .await?
+        .into_inner();
+    while let Some(res) = stream.message().await? {
+        let updated = serde_json::from_str(&res.updated)?;
+        debug!("Reference values updated to revision {}", res.revision);
+        match res.snapshot {
+            true => cache.replace(Some(res.revision), updated),
+            false => cache.apply(res.revision, updated, &res.removed),
+        }
+    }
+
+    Ok(())
+}
+
+/// Watch the changes of the remote RVPS, or poll it periodically when the
+/// changes cannot be watched.
+async fn watch(
+    mut client: ReferenceValueProviderServiceClient<Channel>,
+    cache: Arc<ReferenceValueCache>,
+    poll_interval: Duration,
+) {
+    let mut watchable = true;
+    loop {
+        if watchable {
+            match watch_changes(&mut client, &cache).await {
+                Ok(()) => warn!("Watching reference values ended, poll the RVPS instead"),
+                Err(RvpsError::Status(status)) if status.code() == Code::Unimplemented => {
+                    warn!("The RVPS does not support watching reference values, poll it instead");
+                    watchable = false;
+                }
+                Err(e) => warn!("Watching reference values failed, poll the RVPS instead: {e}"),
+            }
+        }
+
+        let generation = cache.generation();
+        match query(&mut client, vec![]).await {
+            Ok(values) => cache.replace_if(Some(generation), None, values),
+            Err(e) => warn!("Polling reference values failed: {e}"),
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
 #[async_trait::async_trait]
 impl RvpsApi for Agent {
     async fn verify_and_extract(&mut self, message: &str) -> Result<()> {
@@ -60,19 +164,114 @@ impl RvpsApi for Agent {
             .await
             .register_reference_value(req)
             .await?;
+        // Do not wait for the change to be watched
+        self.cache.invalidate();
         Ok(())
     }
 
     async fn get_digests(&self) -> Result<HashMap<String, serde_json::Value>> {
-        let req = tonic::Request::new(ReferenceValueQueryRequest {});
+        if let Some(trust_digest) = self.cache.get() {
+            return Ok(trust_digest);
+        }
+
+        // A change during the query must not be overwritten by its result
+        let generation = self.cache.generation();
+        let trust_digest = query(&mut *self.client.lock().await, vec![]).await?;
+        self.cache
+            .replace_if(Some(generation), None, trust_digest.clone());
+        Ok(trust_digest)
+    }
+
+    async fn get_digests_by_prefixes(
+        &self,
+        prefixes: &[String],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        // The snapshot always holds all the reference values
+        if self.cache.enabled() {
+            let mut trust_digest = self.get_digests().await?;
+            retain_prefixes(&mut trust_digest, prefixes);
+            return Ok(trust_digest);
+        }
+
+        // An empty list of prefixes would query all the reference values
+        if prefixes.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let trust_digest = query(&mut *self.client.lock().await, prefixes.to_vec()).await?;
+        Ok(trust_digest)
+    }
+
+    async fn delete_reference_value(&mut self, name: &str) -> Result<bool> {
+        let req = tonic::Request::new(ReferenceValueDeleteRequest {
+            name: name.to_string(),
+            prefix: String::new(),
+        });
         let res = self
             .client
             .lock()
             .await
-            .query_reference_value(req)
+            .delete_reference_value(req)
             .await?
             .into_inner();
-        let trust_digest = serde_json::from_str(&res.reference_value_results)?;
-        Ok(trust_digest)
+        self.cache.invalidate();
+        Ok(!res.deleted.is_empty())
+    }
+
+    async fn delete_reference_values_by_prefix(&mut self, prefix: &str) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueDeleteRequest {
+            name: String::new(),
+            prefix: prefix.to_string(),
+        });
+        let res = self
+            .client
+            .lock()
+            .await
+            .delete_reference_value(req)
+            .await?
+            .into_inner();
+        self.cache.invalidate();
+        Ok(res.deleted)
+    }
+
+    async fn list_reference_values(&self) -> Result<Vec<ReferenceValue>> {
+        let req = tonic::Request::new(ReferenceValueListRequest {});
+        let res = self
+            .client
+            .lock()
+            .await
+            .list_reference_values(req)
+            .await?
+            .into_inner();
+        let reference_values = serde_json::from_str(&res.reference_values)?;
+        Ok(reference_values)
+    }
+
+    async fn purge_expired(&mut self) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValuePurgeRequest {});
+        let res = self
+            .client
+            .lock()
+            .await
+            .purge_expired_reference_values(req)
+            .await?
+            .into_inner();
+        self.cache.invalidate();
+        Ok(res.purged)
+    }
+
+    async fn import_reference_values(&mut self, rvs: Vec<ReferenceValue>) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueImportRequest {
+            reference_values: serde_json::to_string(&rvs)?,
+        });
+        let res = self
+            .client
+            .lock()
+            .await
+            .import_reference_values(req)
+            .await?
+            .into_inner();
+        self.cache.invalidate();
+        Ok(res.imported)
     }
 }
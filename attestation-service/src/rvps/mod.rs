@@ -4,6 +4,7 @@
 //
 
 pub use reference_value_provider_service::config::Config as RvpsCrateConfig;
+pub use reference_value_provider_service::ReferenceValue;
 use serde::Deserialize;
 use std::collections::HashMap;
 use thiserror::Error;
@@ -13,6 +14,10 @@ use tracing::{info, instrument};
 pub mod grpc;
 
 pub mod builtin;
+pub mod cache;
+
+pub use builtin::BuiltinRvpsConfig;
+pub use cache::ReferenceValueCacheConfig;
 
 #[derive(Error, Debug)]
 pub enum RvpsError {
@@ -37,6 +42,11 @@ type Result<T> = std::result::Result<T, RvpsError>;
 /// * `verify_and_extract` is responsible for verify a message and
 /// store reference values from it.
 /// * `get_digests` gets trusted digests by the artifact's name.
+/// * `get_digests_by_prefixes` gets the trusted digests of the artifacts
+/// whose name starts with one of the given prefixes.
+/// * `delete_reference_value`, `delete_reference_values_by_prefix`,
+/// `list_reference_values`, `purge_expired` and `import_reference_values`
+/// manage the stored reference values.
 #[async_trait::async_trait]
 pub trait RvpsApi {
     /// Verify the given message and register the reference value included.
@@ -44,19 +54,47 @@ pub trait RvpsApi {
 
     /// Get the reference values / golden values / expected digests in hex.
     async fn get_digests(&self) -> Result<HashMap<String, serde_json::Value>>;
+
+    /// Get the reference values whose name starts with one of the given prefixes.
+    async fn get_digests_by_prefixes(
+        &self,
+        prefixes: &[String],
+    ) -> Result<HashMap<String, serde_json::Value>>;
+
+    /// Delete the reference value of the given name. Return whether it existed.
+    async fn delete_reference_value(&mut self, name: &str) -> Result<bool>;
+
+    /// Delete the reference values whose name starts with the given prefix.
+    /// Return the names of the deleted reference values.
+    async fn delete_reference_values_by_prefix(&mut self, prefix: &str) -> Result<Vec<String>>;
+
+    /// List all the stored reference values together with their metadata.
+    async fn list_reference_values(&self) -> Result<Vec<ReferenceValue>>;
+
+    /// Delete the expired reference values. Return the names of the purged ones.
+    async fn purge_expired(&mut self) -> Result<Vec<String>>;
+
+    /// Store the reference values as they are, with their metadata.
+    /// Return the names of the imported ones.
+    async fn import_reference_values(&mut self, rvs: Vec<ReferenceValue>) -> Result<Vec<String>>;
+}
+
+/// Keep the reference values whose name starts with one of the prefixes.
+fn retain_prefixes(values: &mut HashMap<String, serde_json::Value>, prefixes: &[String]) {
+    values.retain(|name, _| prefixes.iter().any(|prefix| name.starts_with(prefix)));
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type")]
 pub enum RvpsConfig {
-    BuiltIn(RvpsCrateConfig),
+    BuiltIn(BuiltinRvpsConfig),
     #[cfg(feature = "rvps-grpc")]
     GrpcRemote(grpc::RvpsRemoteConfig),
 }
 
 impl Default for RvpsConfig {
     fn default() -> Self {
-        Self::BuiltIn(RvpsCrateConfig::default())
+        Self::BuiltIn(BuiltinRvpsConfig::default())
     }
 }
 
@@ -71,8 +109,7 @@ pub async fn initialize_rvps_client(config: &RvpsConfig) -> Result<Box<dyn RvpsA
         #[cfg(feature = "rvps-grpc")]
         RvpsConfig::GrpcRemote(config) => {
             info!("connect to remote RVPS: {}", config.address);
-            Ok(Box::new(grpc::Agent::new(&config.address).await?)
-                as Box<dyn RvpsApi + Send + Sync>)
+            Ok(Box::new(grpc::Agent::new(config).await?) as Box<dyn RvpsApi + Send + Sync>)
         }
     }
 }
@@ -0,0 +1,281 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Tenants of the attestation service.
+//!
+//! Every call of the AS is made on behalf of a tenant. Each tenant has its
+//! own policies, reference values and token signing key, so that the teams
+//! sharing one AS cannot break each other's attestations. Callers that do
+//! not know about tenants use the [`DEFAULT_TENANT`], which is set up from
+//! the top-level configuration.
+
+use anyhow::{bail, Result};
+use reference_value_provider_service::storage::{local_json, ReferenceValueStorageConfig};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::info;
+
+use crate::config::Config;
+use crate::ear_token::{EarAttestationTokenBroker, TokenSignerConfig};
+use crate::rvps::{self, RvpsApi, RvpsConfig, RvpsCrateConfig};
+use crate::ServiceError;
+
+/// The tenant of the callers that do not give one.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// The window of [`TenantQuota::max_attestations_per_minute`].
+const ATTESTATION_QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum TenantError {
+    #[error("Illegal tenant id {0}. Only support alphabet, numeric, `-` or `_`")]
+    InvalidTenantId(String),
+    #[error("Unknown tenant {0}")]
+    UnknownTenant(String),
+    #[error("Tenant {tenant} exceeds its quota of {limit} {resource}")]
+    QuotaExceeded {
+        tenant: String,
+        resource: &'static str,
+        limit: usize,
+    },
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct TenantConfig {
+    /// The key that the tokens of the tenant are signed with. If not given,
+    /// the default tenant uses the `signer` of `attestation_token_broker`,
+    /// and the other tenants an ephemeral key of their own.
+    #[serde(default)]
+    pub signer: Option<TokenSignerConfig>,
+
+    /// The RVPS of the reference values of the tenant. If not given, the
+    /// default tenant uses the top-level `rvps_config`, and the other tenants
+    /// a built-in RVPS storing its reference values in
+    /// `<work_dir>/tenants/<tenant>/reference_values.json`.
+    #[serde(default)]
+    pub rvps_config: Option<RvpsConfig>,
+
+    #[serde(default)]
+    pub quota: TenantQuota,
+}
+
+/// Limits on the resources of a tenant. Nothing is limited by default.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct TenantQuota {
+    /// The maximum number of policies, including the built-in ones.
+    #[serde(default)]
+    pub max_policies: Option<usize>,
+
+    /// Reference values can no longer be registered once the tenant has
+    /// this many.
+    #[serde(default)]
+    pub max_reference_values: Option<usize>,
+
+    /// The maximum number of attestations per minute.
+    #[serde(default)]
+    pub max_attestations_per_minute: Option<usize>,
+}
+
+pub(crate) struct Tenant {
+    id: String,
+    pub(crate) token_broker: EarAttestationTokenBroker,
+    pub(crate) rvps: Box<dyn RvpsApi + Send + Sync>,
+    quota: TenantQuota,
+    /// The start of the current quota window and the number of
+    /// attestations in it.
+    attestations: Mutex<(Instant, usize)>,
+}
+
+impl Tenant {
+    /// Set up a tenant. The policies of a tenant other than the default one
+    /// are kept in `<policy_dir>/tenants/<tenant>`.
+    pub(crate) async fn new(
+        id: &str,
+        tenant_config: TenantConfig,
+        config: &Config,
+    ) -> Result<Self, ServiceError> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(TenantError::InvalidTenantId(id.to_string()).into());
+        }
+
+        let mut broker_config = config.attestation_token_broker.clone();
+        let mut rvps_config = config.rvps_config.clone();
+        if id != DEFAULT_TENANT {
+            info!("Set up tenant {id}");
+            broker_config.policy_dir = Path::new(&broker_config.policy_dir)
+                .join("tenants")
+                .join(id)
+                .to_string_lossy()
+                .to_string();
+            broker_config.signer = None;
+            rvps_config = RvpsConfig::BuiltIn(
+                RvpsCrateConfig {
+                    storage: ReferenceValueStorageConfig::LocalJson(local_json::Config {
+                        file_path: config
+                            .work_dir
+                            .join("tenants")
+                            .join(id)
+                            .join("reference_values.json")
+                            .to_string_lossy()
+                            .to_string(),
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            );
+        }
+        if let Some(signer) = tenant_config.signer {
+            broker_config.signer = Some(signer);
+        }
+        if let Some(config) = tenant_config.rvps_config {
+            rvps_config = config;
+        }
+
+        let rvps = rvps::initialize_rvps_client(&rvps_config)
+            .await
+            .map_err(ServiceError::Rvps)?;
+        let token_broker = EarAttestationTokenBroker::new(broker_config).await?;
+
+        Ok(Self {
+            id: id.to_string(),
+            token_broker,
+            rvps,
+            quota: tenant_config.quota,
+            attestations: Mutex::new((Instant::now(), 0)),
+        })
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn quota_exceeded(&self, resource: &'static str, limit: usize) -> TenantError {
+        TenantError::QuotaExceeded {
+            tenant: self.id.clone(),
+            resource,
+            limit,
+        }
+    }
+
+    /// Count an attestation against the quota of the tenant.
+    pub(crate) fn count_attestation(&self) -> Result<(), TenantError> {
+        let Some(limit) = self.quota.max_attestations_per_minute else {
+            return Ok(());
+        };
+
+        let mut attestations = self.attestations.lock().expect("poisoned lock");
+        let now = Instant::now();
+        if now.duration_since(attestations.0) >= ATTESTATION_QUOTA_WINDOW {
+            *attestations = (now, 0);
+        }
+        if attestations.1 >= limit {
+            return Err(self.quota_exceeded("attestations per minute", limit));
+        }
+        attestations.1 += 1;
+
+        Ok(())
+    }
+
+    /// Check that the policy of the given id can be set without exceeding the
+    /// quota of the tenant. Overwriting an existing policy is always allowed.
+    pub(crate) async fn check_policy_quota(&self, policy_id: &str) -> Result<()> {
+        let Some(limit) = self.quota.max_policies else {
+            return Ok(());
+        };
+
+        let policies = self.token_broker.list_policies().await?;
+        if !policies.contains_key(policy_id) && policies.len() >= limit {
+            bail!(self.quota_exceeded("policies", limit));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the tenant may register more reference values.
+    pub(crate) async fn check_reference_value_quota(&self) -> Result<()> {
+        let Some(limit) = self.quota.max_reference_values else {
+            return Ok(());
+        };
+
+        if self.rvps.get_digests().await?.len() >= limit {
+            bail!(self.quota_exceeded("reference values", limit));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_config;
+
+    async fn tenant(id: &str, work_dir: &Path, quota: TenantQuota) -> Result<Tenant, ServiceError> {
+        let config = test_config(work_dir);
+        let tenant_config = TenantConfig {
+            quota,
+            ..Default::default()
+        };
+        Tenant::new(id, tenant_config, &config).await
+    }
+
+    #[tokio::test]
+    async fn tenant_quota() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let tenant = tenant(
+            "team-a",
+            work_dir.path(),
+            TenantQuota {
+                max_policies: Some(3),
+                max_reference_values: Some(0),
+                max_attestations_per_minute: Some(2),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(work_dir
+            .path()
+            .join("policies/tenants/team-a/opa/default_cpu.rego")
+            .exists());
+
+        tenant.count_attestation().unwrap();
+        tenant.count_attestation().unwrap();
+        assert!(matches!(
+            tenant.count_attestation(),
+            Err(TenantError::QuotaExceeded { limit: 2, .. })
+        ));
+
+        // The two built-in policies count against the quota
+        tenant.check_policy_quota("default_cpu").await.unwrap();
+        tenant.check_policy_quota("first").await.unwrap();
+        tenant
+            .token_broker
+            .set_policy("first".into(), "cGFja2FnZSBwb2xpY3k".into())
+            .await
+            .unwrap();
+        tenant.check_policy_quota("first").await.unwrap();
+        assert!(tenant.check_policy_quota("second").await.is_err());
+
+        assert!(tenant.check_reference_value_quota().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_tenant_id() {
+        let work_dir = tempfile::tempdir().unwrap();
+        for id in ["", "../other", "team a", "tëam"] {
+            assert!(matches!(
+                tenant(id, work_dir.path(), TenantQuota::default()).await,
+                Err(ServiceError::Tenant(TenantError::InvalidTenantId(_)))
+            ));
+        }
+    }
+}
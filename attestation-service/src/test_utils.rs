@@ -0,0 +1,50 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Fixtures shared by the unit tests. They keep all the state of the service
+//! under a temporary work directory.
+
+use reference_value_provider_service::storage::{local_json, ReferenceValueStorageConfig};
+use std::path::Path;
+
+use crate::config::{Config, VerificationConfig};
+use crate::ear_token::EarTokenConfiguration;
+use crate::rvps::{builtin::BuiltinRvps, RvpsConfig, RvpsCrateConfig};
+
+/// The configuration of a built-in RVPS that stores its reference values in
+/// `work_dir`.
+pub(crate) fn rvps_config(work_dir: &Path) -> RvpsCrateConfig {
+    RvpsCrateConfig {
+        storage: ReferenceValueStorageConfig::LocalJson(local_json::Config {
+            file_path: work_dir
+                .join("reference_values.json")
+                .to_string_lossy()
+                .to_string(),
+        }),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn test_rvps(work_dir: &Path) -> BuiltinRvps {
+    BuiltinRvps::new(rvps_config(work_dir).into()).unwrap()
+}
+
+/// The configuration of a service whose policies and reference values are
+/// kept in `work_dir`.
+pub(crate) fn test_config(work_dir: &Path) -> Config {
+    Config {
+        work_dir: work_dir.to_path_buf(),
+        rvps_config: RvpsConfig::BuiltIn(rvps_config(work_dir).into()),
+        attestation_token_broker: EarTokenConfiguration {
+            policy_dir: work_dir.join("policies").to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        verification: VerificationConfig {
+            max_concurrency: 2,
+            timeout_secs: 10,
+        },
+        ..Default::default()
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ear::{Algorithm, Ear};
+use std::sync::Arc;
+
+use super::{signer::TokenSigner, TokenBroker};
+
+/// Issues the EAR as a CWT for constrained relying parties. The COSE_Sign1
+/// message does not carry the public key, so the relying party needs to know
+/// it beforehand.
+pub struct CwtTokenBroker {
+    signer: Arc<TokenSigner>,
+}
+
+impl CwtTokenBroker {
+    pub fn new(signer: Arc<TokenSigner>) -> Self {
+        Self { signer }
+    }
+}
+
+impl TokenBroker for CwtTokenBroker {
+    fn issue(&self, ear: &Ear) -> Result<String> {
+        let private_key = self.signer.private_key_pem()?;
+        let cwt = ear.sign_cose_pem(Algorithm::ES256, &private_key)?;
+        Ok(URL_SAFE_NO_PAD.encode(cwt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ear::{Appraisal, Extensions, VerifierID};
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn issue_and_verify_cwt() {
+        let signer = Arc::new(TokenSigner::new(None).unwrap());
+        let broker = CwtTokenBroker::new(signer.clone());
+
+        let ear = Ear {
+            profile: "tag:github.com,2024:confidential-containers/Trustee".into(),
+            iat: 1,
+            vid: VerifierID {
+                build: "build".into(),
+                developer: "developer".into(),
+            },
+            raw_evidence: None,
+            nonce: None,
+            submods: BTreeMap::from([("cpu0".to_string(), Appraisal::new())]),
+            extensions: Extensions::new(),
+        };
+        let token = broker.issue(&ear).unwrap();
+
+        let cwt = URL_SAFE_NO_PAD.decode(token).unwrap();
+        let jwk = serde_json::to_vec(&signer.pubkey_jwk().unwrap()).unwrap();
+        let decoded = Ear::from_cose_jwk(&cwt, Algorithm::ES256, &jwk).unwrap();
+        assert_eq!(decoded.iat, 1);
+        assert!(decoded.submods.contains_key("cpu0"));
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use anyhow::Result;
+use ear::Ear;
+use std::sync::Arc;
+
+use super::{signer::TokenSigner, TokenBroker};
+
+/// Issues the EAR as a JWT. The public key of the signer is in the `jwk`
+/// header.
+pub struct EarTokenBroker {
+    signer: Arc<TokenSigner>,
+}
+
+impl EarTokenBroker {
+    pub fn new(signer: Arc<TokenSigner>) -> Self {
+        Self { signer }
+    }
+}
+
+impl TokenBroker for EarTokenBroker {
+    fn issue(&self, ear: &Ear) -> Result<String> {
+        let jwt_header = self.signer.jwt_header()?;
+        let private_key = self.signer.private_key_pem()?;
+        Ok(ear.sign_jwt_pem_with_header(&jwt_header, &private_key)?)
+    }
+}
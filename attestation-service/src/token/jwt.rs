@@ -0,0 +1,109 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use anyhow::{Context, Result};
+use ear::Ear;
+use jsonwebtoken::EncodingKey;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::{signer::TokenSigner, TokenBroker};
+
+/// The submodule of the CPU, whose claims the default layout picks.
+const CPU_SUBMOD: &str = "/submods/cpu0";
+
+/// The nonce of the relying party.
+const NONCE: &str = "/eat_nonce";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct JwtTokenConfiguration {
+    /// The claims of the flat JWT. The key is the name of the claim, and
+    /// the value is a JSON pointer into the EAR, e.g.
+    /// `/submods/cpu0/ear.status`. Claims whose pointer does not resolve are
+    /// left out. `iss`, `iat` and `exp` are always set.
+    #[serde(default = "default_claims")]
+    pub claims: BTreeMap<String, String>,
+}
+
+impl Default for JwtTokenConfiguration {
+    fn default() -> Self {
+        Self {
+            claims: default_claims(),
+        }
+    }
+}
+
+fn default_claims() -> BTreeMap<String, String> {
+    [
+        ("status", "ear.status"),
+        ("trust_vector", "ear.trustworthiness-vector"),
+        (
+            "tee-pubkey",
+            "ear.veraison.annotated-evidence/runtime_data_claims/tee-pubkey",
+        ),
+        (
+            "init_data_claims",
+            "ear.veraison.annotated-evidence/init_data_claims",
+        ),
+        ("policy_claims", "ear.veraison.policy-claims"),
+    ]
+    .into_iter()
+    .map(|(claim, pointer)| (claim.to_string(), format!("{CPU_SUBMOD}/{pointer}")))
+    .chain([("eat_nonce".to_string(), NONCE.to_string())])
+    .collect()
+}
+
+/// Issues a JWT whose claims are picked from the EAR, s.t. relying parties
+/// do not need to know the layout of the EAR.
+pub struct JwtTokenBroker {
+    config: JwtTokenConfiguration,
+    issuer_name: String,
+    signer: Arc<TokenSigner>,
+}
+
+impl JwtTokenBroker {
+    pub fn new(
+        config: JwtTokenConfiguration,
+        issuer_name: String,
+        signer: Arc<TokenSigner>,
+    ) -> Self {
+        Self {
+            config,
+            issuer_name,
+            signer,
+        }
+    }
+
+    fn claims(&self, ear: &Ear) -> Result<Map<String, Value>> {
+        let ear = serde_json::to_value(ear)?;
+
+        let mut claims = Map::new();
+        for (claim, pointer) in &self.config.claims {
+            if let Some(value) = ear.pointer(pointer) {
+                claims.insert(claim.clone(), value.clone());
+            }
+        }
+
+        claims.insert("iss".into(), Value::String(self.issuer_name.clone()));
+        claims.insert("iat".into(), ear["iat"].clone());
+        if let Some(exp) = ear.get("exp") {
+            claims.insert("exp".into(), exp.clone());
+        }
+
+        Ok(claims)
+    }
+}
+
+impl TokenBroker for JwtTokenBroker {
+    fn issue(&self, ear: &Ear) -> Result<String> {
+        let claims = self.claims(ear)?;
+        let jwt_header = self.signer.jwt_header()?;
+        let private_key = EncodingKey::from_ec_pem(&self.signer.private_key_pem()?)
+            .context("Illegal token signer key")?;
+        Ok(jsonwebtoken::encode(&jwt_header, &claims, &private_key)?)
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Encodings of the attestation results as signed tokens.
+//!
+//! The [`crate::ear_token::EarAttestationTokenBroker`] appraises the evidence
+//! into an [`Ear`]. A [`TokenBroker`] then turns the EAR into a token of a
+//! [`TokenFormat`] that a relying party understands.
+
+use anyhow::Result;
+use ear::Ear;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use strum::{Display, EnumString};
+
+use crate::ear_token::EarTokenConfiguration;
+
+pub mod cwt;
+pub mod ear_jwt;
+pub mod jwt;
+pub mod signer;
+
+/// The format of an attestation token.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, EnumString, Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum TokenFormat {
+    /// The EAR as a JWT.
+    #[default]
+    Ear,
+
+    /// A JWT with a flat, configurable claims layout, see
+    /// [`jwt::JwtTokenConfiguration`].
+    Jwt,
+
+    /// The EAR as a CWT, i.e. a COSE_Sign1 message with a CBOR payload. The
+    /// token is the base64url encoding of the message.
+    Cwt,
+}
+
+pub trait TokenBroker: Send + Sync {
+    /// Sign the attestation results as a token.
+    fn issue(&self, ear: &Ear) -> Result<String>;
+}
+
+/// Create a [`TokenBroker`] for each [`TokenFormat`]. They all share the
+/// signer of the configuration.
+pub fn token_brokers(
+    config: &EarTokenConfiguration,
+) -> Result<HashMap<TokenFormat, Box<dyn TokenBroker>>> {
+    let signer = Arc::new(signer::TokenSigner::new(config.signer.clone())?);
+
+    let mut brokers: HashMap<TokenFormat, Box<dyn TokenBroker>> = HashMap::new();
+    brokers.insert(
+        TokenFormat::Ear,
+        Box::new(ear_jwt::EarTokenBroker::new(signer.clone())),
+    );
+    brokers.insert(
+        TokenFormat::Jwt,
+        Box::new(jwt::JwtTokenBroker::new(
+            config.jwt.clone(),
+            config.issuer_name.clone(),
+            signer.clone(),
+        )),
+    );
+    brokers.insert(TokenFormat::Cwt, Box::new(cwt::CwtTokenBroker::new(signer)));
+
+    Ok(brokers)
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use anyhow::*;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ear::Algorithm;
+use jsonwebtoken::{jwk, Header};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::X509;
+use tracing::info;
+
+use crate::ear_token::TokenSignerConfig;
+
+/// The key that signs the tokens of all the formats, and the certificates
+/// that endorse it.
+pub struct TokenSigner {
+    private_key: EcKey<Private>,
+    cert_url: Option<String>,
+    cert_chain: Option<Vec<X509>>,
+}
+
+impl TokenSigner {
+    pub fn new(config: Option<TokenSignerConfig>) -> Result<Self> {
+        let Some(signer) = config else {
+            info!("No Token Signer key in config file, create an ephemeral key and without CA pubkey cert");
+            return Ok(Self {
+                private_key: generate_ec_keys()?.0,
+                cert_url: None,
+                cert_chain: None,
+            });
+        };
+
+        let pem_data = std::fs::read(&signer.key_path)
+            .map_err(|e| anyhow!("Read Token Signer private key failed: {:?}", e))?;
+        let private_key = EcKey::private_key_from_pem(&pem_data)?;
+
+        let cert_chain = signer
+            .cert_path
+            .as_ref()
+            .map(|cert_path| -> Result<Vec<X509>> {
+                let pem_cert_chain = std::fs::read_to_string(cert_path)
+                    .map_err(|e| anyhow!("Read Token Signer cert file failed: {:?}", e))?;
+                let mut chain = Vec::new();
+
+                for pem in pem_cert_chain.split("-----END CERTIFICATE-----") {
+                    let trimmed = format!("{}\n-----END CERTIFICATE-----", pem.trim());
+                    if !trimmed.starts_with("-----BEGIN CERTIFICATE-----") {
+                        continue;
+                    }
+                    let cert = X509::from_pem(trimmed.as_bytes())
+                        .map_err(|_| anyhow!("Invalid PEM certificate chain"))?;
+                    chain.push(cert);
+                }
+                Ok(chain)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            private_key,
+            cert_url: signer.cert_url,
+            cert_chain,
+        })
+    }
+
+    /// The private key in PKCS#8 PEM.
+    pub fn private_key_pem(&self) -> Result<Vec<u8>> {
+        let pkey = PKey::from_ec_key(self.private_key.clone())?;
+        Ok(pkey.private_key_to_pem_pkcs8()?)
+    }
+
+    /// A JWT header that carries the public key, s.t. relying parties can
+    /// check it against the certificates.
+    pub fn jwt_header(&self) -> Result<Header> {
+        let mut jwt_header = ear::new_jwt_header(&Algorithm::ES256)?;
+        jwt_header.jwk = Some(self.pubkey_jwk()?);
+        Ok(jwt_header)
+    }
+
+    // TODO: converge this with the jwk function in the simple token broker
+    pub fn pubkey_jwk(&self) -> Result<jwk::Jwk> {
+        let chain = self
+            .cert_chain
+            .as_ref()
+            .map(|certs| -> Result<Vec<String>> {
+                let mut chain = vec![];
+                for cert in certs {
+                    let der = cert.to_der()?;
+                    chain.push(URL_SAFE_NO_PAD.encode(der));
+                }
+                Ok(chain)
+            })
+            .transpose()?;
+
+        let common = jwk::CommonParameters {
+            key_algorithm: Some(jwk::KeyAlgorithm::ES256),
+            x509_url: self.cert_url.clone(),
+            x509_chain: chain,
+            ..Default::default()
+        };
+
+        let public_key = self.private_key.public_key();
+        let group = self.private_key.group();
+
+        let mut ctx = BigNumContext::new()?;
+        let mut x = BigNum::new()?;
+        let mut y = BigNum::new()?;
+        public_key.affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+
+        let algorithm = jwk::AlgorithmParameters::EllipticCurve(jwk::EllipticCurveKeyParameters {
+            key_type: jwk::EllipticCurveKeyType::EC,
+            curve: jwk::EllipticCurve::P256,
+            x: URL_SAFE_NO_PAD.encode(x.to_vec()),
+            y: URL_SAFE_NO_PAD.encode(y.to_vec()),
+        });
+
+        let jwk = jwk::Jwk { common, algorithm };
+
+        Ok(jwk)
+    }
+}
+
+pub(crate) fn generate_ec_keys() -> Result<(EcKey<Private>, Vec<u8>, Vec<u8>)> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let ec_key = EcKey::generate(&group)?;
+    let pkey = PKey::from_ec_key(ec_key.clone())?;
+
+    Ok((
+        ec_key,
+        pkey.private_key_to_pem_pkcs8()?,
+        pkey.public_key_to_pem()?,
+    ))
+}
@@ -0,0 +1,26 @@
+package policy
+
+import rego.v1
+
+gpus := {name | some name, _ in input.submods; startswith(name, "gpu")}
+
+bound_gpus := {name |
+	some name in gpus
+	input.submods[name].claims.report_data == input.submods.cpu0.claims.report_data
+}
+
+affirming_gpus := {name |
+	some name in bound_gpus
+	input.submods[name].status == "affirming"
+}
+
+default hardware := 97
+
+hardware := 2 if {
+	count(affirming_gpus) >= 4
+}
+
+result := {
+	"trust_vector": {"hardware": hardware},
+	"submods": {name: {"status": "contraindicated"} | some name in gpus; not name in bound_gpus},
+}
@@ -0,0 +1,192 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Runtime event log of the guest attestation agent (AAEL).
+//!
+//! The attestation agent extends runtime events (init data, image pulls,
+//! configuration changes) into a runtime measurement register, RTMR[3] on TDX,
+//! and records them in a text log:
+//!
+//! ```text
+//! INIT sha384/000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000
+//! github.com/confidential-containers PullImage {"image":"busybox@sha256:..."}
+//! ```
+//!
+//! The first line records the hash algorithm and the initial register value.
+//! Every line, the INIT line included, is measured as `H(line)` and extended
+//! into the register as `state = H(state || H(line))`.
+
+use crate::ccel::event_tag::AaelEventEntry;
+use crate::{accumulate_hash, ccel::tcg_enum::TcgAlgorithm};
+use anyhow::{anyhow, bail, Context, Result};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AaEventlog {
+    pub hash_algorithm: TcgAlgorithm,
+    pub init_state: Vec<u8>,
+    pub events: Vec<String>,
+}
+
+impl FromStr for AaEventlog {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut lines = input.lines();
+        let init_line = lines
+            .next()
+            .ok_or_else(|| anyhow!("AA eventlog is empty"))?;
+
+        let (hash_algorithm, init_state) = init_line
+            .strip_prefix("INIT ")
+            .and_then(|init| init.trim_end().split_once('/'))
+            .ok_or_else(|| anyhow!("Illegal INIT event record: {init_line}"))?;
+        let hash_algorithm = match hash_algorithm {
+            "sha256" => TcgAlgorithm::Sha256,
+            "sha384" => TcgAlgorithm::Sha384,
+            "sha512" => TcgAlgorithm::Sha512,
+            other => bail!("Unsupported AA eventlog hash algorithm {other}"),
+        };
+        let init_state = hex::decode(init_state).context("Illegal INIT state")?;
+        if init_state.len() != hash_algorithm.get_digest_size(hash_algorithm)? {
+            bail!("INIT state does not match the digest size of {hash_algorithm:?}");
+        }
+
+        let events = lines
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+
+        Ok(Self {
+            hash_algorithm,
+            init_state,
+            events,
+        })
+    }
+}
+
+impl AaEventlog {
+    fn init_event(&self) -> String {
+        let hash_algorithm = match self.hash_algorithm {
+            TcgAlgorithm::Sha256 => "sha256",
+            TcgAlgorithm::Sha384 => "sha384",
+            _ => "sha512",
+        };
+        format!("INIT {hash_algorithm}/{}", hex::encode(&self.init_state))
+    }
+
+    /// Calculate the register value the log results in.
+    pub fn replay(&self) -> Result<Vec<u8>> {
+        let alg = self.hash_algorithm;
+        let mut state = self.init_state.clone();
+        for event in std::iter::once(self.init_event()).chain(self.events.iter().cloned()) {
+            let digest = accumulate_hash(alg, Vec::new(), event.as_bytes())?;
+            state = accumulate_hash(alg, state, &digest)?;
+        }
+
+        Ok(state)
+    }
+
+    /// Replay the log and compare the result with the quoted register value.
+    pub fn replay_and_match(&self, reference: &[u8]) -> Result<()> {
+        let calculated = self.replay()?;
+        if calculated != reference {
+            bail!(
+                "AA eventlog does not pass RTMR check. AA eventlog value: {}, Quote value: {}",
+                hex::encode(calculated),
+                hex::encode(reference)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Parse the runtime events into `domain`, `operation` and `content`.
+    pub fn entries(&self) -> Result<Vec<AaelEventEntry>> {
+        self.events
+            .iter()
+            .map(|event| {
+                AaelEventEntry::from_bytes(event)
+                    .with_context(|| format!("Illegal AA eventlog entry: {event}"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const AAEL: &str = "INIT sha384/000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000
+github.com/confidential-containers PullImage {\"image\":\"docker.io/library/busybox:latest\",\"digest\":\"sha256:7b3ccabffc97de872a30dfd234fd972a66d247c8cfc69b0550f276481852627c\"}
+github.com/confidential-containers UpdateConfig policy.rego
+";
+
+    #[test]
+    fn test_parse_aa_eventlog() {
+        let aael = AaEventlog::from_str(AAEL).unwrap();
+        assert_eq!(aael.hash_algorithm, TcgAlgorithm::Sha384);
+        assert_eq!(aael.init_state, vec![0; 48]);
+        assert_eq!(aael.events.len(), 2);
+
+        let entries = aael.entries().unwrap();
+        assert_eq!(
+            serde_json::to_value(&entries).unwrap(),
+            json!([
+                {
+                    "domain": "github.com/confidential-containers",
+                    "operation": "PullImage",
+                    "content": {
+                        "image": "docker.io/library/busybox:latest",
+                        "digest": "sha256:7b3ccabffc97de872a30dfd234fd972a66d247c8cfc69b0550f276481852627c"
+                    }
+                },
+                {
+                    "domain": "github.com/confidential-containers",
+                    "operation": "UpdateConfig",
+                    "content": "policy.rego"
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_replay_aa_eventlog() {
+        let aael = AaEventlog::from_str(AAEL).unwrap();
+        let expected = "ef5940c532f1b3344b4eccdb15ea143a144513f65ba9fc925df5aafb377a1738157b756256f161783e24c757b01329e8";
+        assert_eq!(hex::encode(aael.replay().unwrap()), expected);
+        aael.replay_and_match(&hex::decode(expected).unwrap())
+            .unwrap();
+
+        let err = aael.replay_and_match(&[0; 48]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("AA eventlog does not pass RTMR check."));
+    }
+
+    #[test]
+    fn test_parse_aa_eventlog_error() {
+        assert_eq!(
+            AaEventlog::from_str("").unwrap_err().to_string(),
+            "AA eventlog is empty"
+        );
+        assert_eq!(
+            AaEventlog::from_str("INIT sha384").unwrap_err().to_string(),
+            "Illegal INIT event record: INIT sha384"
+        );
+        assert_eq!(
+            AaEventlog::from_str("INIT md5/00").unwrap_err().to_string(),
+            "Unsupported AA eventlog hash algorithm md5"
+        );
+        assert_eq!(
+            AaEventlog::from_str("INIT sha256/00")
+                .unwrap_err()
+                .to_string(),
+            "INIT state does not match the digest size of Sha256"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use crate::GUID_SIZE;
+use anyhow::{anyhow, bail, Result};
+use scroll::{Pread, LE};
+
+/// EFI_CERT_X509_GUID `a5c059a1-94e4-4aa7-87b5-ab155c2bf072` in its on-disk byte order.
+pub const EFI_CERT_X509_GUID: [u8; GUID_SIZE] = [
+    0xa1, 0x59, 0xc0, 0xa5, 0xe4, 0x94, 0xa7, 0x4a, 0x87, 0xb5, 0xab, 0x15, 0x5c, 0x2b, 0xf0, 0x72,
+];
+
+/// EFI_CERT_SHA256_GUID `c1c41626-504c-4092-aca9-41f936934328` in its on-disk byte order.
+pub const EFI_CERT_SHA256_GUID: [u8; GUID_SIZE] = [
+    0x26, 0x16, 0xc4, 0xc1, 0x4c, 0x50, 0x92, 0x40, 0xac, 0xa9, 0x41, 0xf9, 0x36, 0x93, 0x43, 0x28,
+];
+
+/// Size of the fixed part of an EFI_SIGNATURE_LIST (type, list size, header size, signature size).
+const SIGNATURE_LIST_HEADER_SIZE: usize = GUID_SIZE + 3 * 4;
+
+/// One entry of an EFI_SIGNATURE_LIST.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EfiSignatureData {
+    /// DER encoded X.509 certificate.
+    X509(Vec<u8>),
+    /// SHA-256 digest of an image or certificate.
+    Sha256(Vec<u8>),
+    /// Any other signature type, e.g. EFI_CERT_X509_SHA256_GUID.
+    Other {
+        signature_type: [u8; GUID_SIZE],
+        data: Vec<u8>,
+    },
+}
+
+/// Parse the content of an authenticated variable like `PK`, `KEK`, `db` or `dbx`.
+///
+/// The variable is a concatenation of EFI_SIGNATURE_LISTs, defined in
+/// section 32.4.1 of the UEFI specification <https://uefi.org/specs/UEFI/2.10/32_Secure_Boot_and_Driver_Signing.html>
+/// ```text
+/// EFI_SIGNATURE_LIST {
+///     EFI_GUID SignatureType;
+///     UINT32 SignatureListSize;
+///     UINT32 SignatureHeaderSize;
+///     UINT32 SignatureSize;
+///     UINT8 SignatureHeader[SignatureHeaderSize];
+///     EFI_SIGNATURE_DATA Signatures[...][SignatureSize];
+/// }
+///
+/// EFI_SIGNATURE_DATA {
+///     EFI_GUID SignatureOwner;
+///     UINT8 SignatureData[...];
+/// }
+/// ```
+pub fn parse_efi_signature_lists(data: &[u8]) -> Result<Vec<EfiSignatureData>> {
+    let mut signatures = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let mut index = offset;
+        let signature_type: [u8; GUID_SIZE] = data
+            .get(index..index + GUID_SIZE)
+            .ok_or_else(|| anyhow!("Failed to read signature type"))?
+            .try_into()?;
+        index += GUID_SIZE;
+
+        let list_size: u32 = data
+            .gread_with(&mut index, LE)
+            .map_err(|e| anyhow!("Failed to read signature list size: {:?}", e))?;
+        let header_size: u32 = data
+            .gread_with(&mut index, LE)
+            .map_err(|e| anyhow!("Failed to read signature header size: {:?}", e))?;
+        let signature_size: u32 = data
+            .gread_with(&mut index, LE)
+            .map_err(|e| anyhow!("Failed to read signature size: {:?}", e))?;
+
+        if (list_size as usize) < SIGNATURE_LIST_HEADER_SIZE {
+            bail!("Invalid signature list size {list_size}");
+        }
+        let list_end = offset
+            .checked_add(list_size as usize)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| anyhow!("Out of bounds while reading signature list"))?;
+        let signature_size = signature_size as usize;
+        if signature_size <= GUID_SIZE {
+            bail!("Invalid signature size {signature_size}");
+        }
+
+        let signatures_start = index
+            .checked_add(header_size as usize)
+            .filter(|start| *start <= list_end)
+            .ok_or_else(|| anyhow!("Out of bounds while reading signature header"))?;
+        if (list_end - signatures_start) % signature_size != 0 {
+            bail!("Signature list size is not a multiple of the signature size");
+        }
+
+        for signature in data[signatures_start..list_end].chunks_exact(signature_size) {
+            // Skip the SignatureOwner GUID
+            let signature_data = signature[GUID_SIZE..].to_vec();
+            signatures.push(match signature_type {
+                EFI_CERT_X509_GUID => EfiSignatureData::X509(signature_data),
+                EFI_CERT_SHA256_GUID => EfiSignatureData::Sha256(signature_data),
+                _ => EfiSignatureData::Other {
+                    signature_type,
+                    data: signature_data,
+                },
+            });
+        }
+
+        offset = list_end;
+    }
+
+    Ok(signatures)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    pub(crate) fn signature_list(signature_type: [u8; GUID_SIZE], signatures: &[&[u8]]) -> Vec<u8> {
+        let signature_size = GUID_SIZE + signatures[0].len();
+        let list_size = SIGNATURE_LIST_HEADER_SIZE + signature_size * signatures.len();
+
+        let mut list = signature_type.to_vec();
+        list.extend((list_size as u32).to_le_bytes());
+        list.extend(0u32.to_le_bytes());
+        list.extend((signature_size as u32).to_le_bytes());
+        for signature in signatures {
+            list.extend([0x77; GUID_SIZE]);
+            list.extend(*signature);
+        }
+        list
+    }
+
+    #[test]
+    fn test_parse_efi_signature_lists() {
+        let mut data = signature_list(EFI_CERT_X509_GUID, &[b"certificate"]);
+        data.extend(signature_list(
+            EFI_CERT_SHA256_GUID,
+            &[&[0x11; 32], &[0x22; 32]],
+        ));
+        data.extend(signature_list([0x33; GUID_SIZE], &[b"other"]));
+
+        let signatures = parse_efi_signature_lists(&data).unwrap();
+        assert_eq!(
+            signatures,
+            vec![
+                EfiSignatureData::X509(b"certificate".to_vec()),
+                EfiSignatureData::Sha256(vec![0x11; 32]),
+                EfiSignatureData::Sha256(vec![0x22; 32]),
+                EfiSignatureData::Other {
+                    signature_type: [0x33; GUID_SIZE],
+                    data: b"other".to_vec()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_variable() {
+        assert!(parse_efi_signature_lists(&[]).unwrap().is_empty());
+    }
+
+    #[rstest]
+    #[case::truncated_type("a159c0a5", "Failed to read signature type")]
+    #[case::truncated_header(
+        "a159c0a5e494a74a87b5ab155c2bf0722c000000",
+        "Failed to read signature header size: TooBig { size: 4, len: 0 }"
+    )]
+    #[case::list_too_long(
+        "a159c0a5e494a74a87b5ab155c2bf072ff0000000000000020000000",
+        "Out of bounds while reading signature list"
+    )]
+    #[case::signature_too_small(
+        "a159c0a5e494a74a87b5ab155c2bf0721c0000000000000010000000",
+        "Invalid signature size 16"
+    )]
+    #[case::partial_signature(
+        "a159c0a5e494a74a87b5ab155c2bf07220000000000000001400000000000000",
+        "Signature list size is not a multiple of the signature size"
+    )]
+    fn test_parse_efi_signature_lists_error(#[case] test_data: &str, #[case] expected: &str) {
+        let result = parse_efi_signature_lists(&hex::decode(test_data).unwrap());
+        assert_eq!(result.unwrap_err().to_string(), expected);
+    }
+}
@@ -52,11 +52,11 @@ impl EventDataParser for EvEventTagParser {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct AaelEventEntry {
-    domain: String,
-    operation: String,
-    content: Value,
+    pub domain: String,
+    pub operation: String,
+    pub content: Value,
 }
 
 impl AaelEventEntry {
@@ -5,12 +5,14 @@
 
 use super::{ElDigest, EventDetails};
 use anyhow::bail;
+use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha384, Sha512};
 use sm3::Sm3;
 
 pub mod blank;
 pub mod boot_services_app;
 pub mod device_paths;
+pub mod efi_signature_list;
 pub mod efi_variable;
 pub mod event_tag;
 pub mod ipl;
@@ -36,6 +38,7 @@ pub trait EventDataParser: Sync + Send {
     fn compare_digests(&self, data: &Vec<u8>, digests: &Vec<ElDigest>) -> anyhow::Result<bool> {
         for digest in digests {
             let computed = match digest.alg {
+                TcgAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
                 TcgAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
                 TcgAlgorithm::Sha384 => Sha384::digest(data).to_vec(),
                 TcgAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
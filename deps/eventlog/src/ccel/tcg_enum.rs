@@ -179,6 +179,12 @@ pub enum TcgEventType {
 
     /// EV_EFI_SPDM_FIRMWARE_CONFIG
     EvEfiSpdmFirmwareConfig = 0x800000e2,
+
+    /// EV_EFI_SPDM_DEVICE_POLICY
+    EvEfiSpdmDevicePolicy = 0x800000e3,
+
+    /// EV_EFI_SPDM_DEVICE_AUTHORITY
+    EvEfiSpdmDeviceAuthority = 0x800000e4,
 }
 
 impl TryFrom<u32> for TcgEventType {
@@ -222,6 +228,8 @@ impl TryFrom<u32> for TcgEventType {
             0x800000E0 => Ok(TcgEventType::EvEfiVariableAuthority),
             0x800000E1 => Ok(TcgEventType::EvEfiSpdmFirmwareBlob),
             0x800000E2 => Ok(TcgEventType::EvEfiSpdmFirmwareConfig),
+            0x800000E3 => Ok(TcgEventType::EvEfiSpdmDevicePolicy),
+            0x800000E4 => Ok(TcgEventType::EvEfiSpdmDeviceAuthority),
             _ => Err(()),
         }
     }
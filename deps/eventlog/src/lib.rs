@@ -10,12 +10,20 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use scroll::{Pread, LE};
 use serde::{Serialize, Serializer};
 use serde_json::Value;
+use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha384, Sha512};
 use sm3::Sm3;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
+pub mod aael;
 pub mod ccel;
+pub mod secure_boot;
+pub mod tcg;
+
+pub use aael::AaEventlog;
+pub use secure_boot::SecureBootState;
+pub use tcg::{BootEvents, SpecIdEvent, TcgEventLog};
 
 pub const GUID_SIZE: usize = 16;
 
@@ -36,7 +44,7 @@ pub struct EventlogEntry {
     pub event_type: TcgEventType,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct ElDigest {
     pub alg: TcgAlgorithm,
     #[serde(serialize_with = "serialize_digest_as_hex")]
@@ -113,6 +121,12 @@ where
 }
 
 impl CcEventLog {
+    /// Compute the value of the measurement register `index` in the bank
+    /// `alg` from the events of the log.
+    pub fn replay(&self, index: u32, alg: TcgAlgorithm) -> Result<Vec<u8>> {
+        replay(&collect_digests_by_index(&self.log), index, alg)
+    }
+
     pub fn replay_and_match(&self, data: Vec<ReferenceMeasurement>) -> Result<()> {
         let digest_map = collect_digests_by_index(&self.log);
 
@@ -131,7 +145,7 @@ impl CcEventLog {
     }
 }
 
-fn collect_digests_by_index(ccel: &[EventlogEntry]) -> HashMap<u32, Vec<ElDigest>> {
+pub(crate) fn collect_digests_by_index(ccel: &[EventlogEntry]) -> HashMap<u32, Vec<ElDigest>> {
     let mut result: HashMap<u32, Vec<ElDigest>> = HashMap::new();
 
     for entry in ccel.iter() {
@@ -161,8 +175,13 @@ fn replay(
     Ok(materials)
 }
 
-fn accumulate_hash(alg: TcgAlgorithm, materials: Vec<u8>, digest: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn accumulate_hash(
+    alg: TcgAlgorithm,
+    materials: Vec<u8>,
+    digest: &[u8],
+) -> Result<Vec<u8>> {
     let result = match alg {
+        TcgAlgorithm::Sha1 => hash_with::<Sha1>(&materials, digest),
         TcgAlgorithm::Sha256 => hash_with::<Sha256>(&materials, digest),
         TcgAlgorithm::Sha384 => hash_with::<Sha384>(&materials, digest),
         TcgAlgorithm::Sha512 => hash_with::<Sha512>(&materials, digest),
@@ -189,29 +208,41 @@ impl TryFrom<Vec<u8>> for CcEventLog {
         let mut digest_size_map = HashMap::new();
 
         parse_initial_entry(&data, &mut index, &mut digest_size_map)?;
-
-        while index < data.len() {
-            let entry_opt;
-            (entry_opt, index) = parse_eventlog_entry(&data, index, &mut digest_size_map)?;
-            if let Some(entry) = entry_opt {
-                event_log.push(entry);
-            } else if index == 0 {
-                break;
-            }
-        }
+        parse_eventlog_entries(&data, index, &mut digest_size_map, &mut event_log)?;
 
         Ok(CcEventLog { log: event_log })
     }
 }
 
+/// Parse the `TCG_PCR_EVENT2` entries following the initial event until the
+/// end of `data` or a stop marker.
+pub(crate) fn parse_eventlog_entries(
+    data: &[u8],
+    mut index: usize,
+    digest_size_map: &mut HashMap<TcgAlgorithm, u16>,
+    event_log: &mut Vec<EventlogEntry>,
+) -> Result<()> {
+    while index < data.len() {
+        let entry_opt;
+        (entry_opt, index) = parse_eventlog_entry(data, index, digest_size_map)?;
+        if let Some(entry) = entry_opt {
+            event_log.push(entry);
+        } else if index == 0 {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 /// Parse the first event in the event log which is the informational event
 /// TCG_EfiSpecIdEvent (See Section 10.4.5.1 Specification ID Version Event)
 /// Docs: <https://trustedcomputinggroup.org/wp-content/uploads/TCG-PC-Client-Platform-Firmware-Profile-Version-1.06-Revision-52_pub-3.pdf>
-fn parse_initial_entry(
+pub(crate) fn parse_initial_entry(
     data: &[u8],
     index: &mut usize,
     digest_size_map: &mut HashMap<TcgAlgorithm, u16>,
-) -> Result<()> {
+) -> Result<Option<SpecIdEvent>> {
     let _pcr_index = data
         .gread_with::<u32>(index, LE)
         .map_err(|_| anyhow!("Cannot read PCR index"))?;
@@ -236,13 +267,14 @@ fn parse_initial_entry(
             .get(start..end)
             .ok_or_else(|| anyhow!("Out of bounds while reading digest_data"))?;
         *index = end;
-        let actual_size = parse_digest_sizes(digest_data, digest_size_map)?;
+        let (spec_id, actual_size) = parse_spec_id_event(digest_data, digest_size_map)?;
         if actual_size != event_data_size as usize {
             bail!("Unexpected data size consumed for detecting digests");
         }
+        return Ok(Some(spec_id));
     }
 
-    Ok(())
+    Ok(None)
 }
 
 fn parse_eventlog_entry(
@@ -283,7 +315,10 @@ fn parse_eventlog_entry(
         );
     }
 
-    let event_data_raw = data[index..(index + event_data_size)].to_vec();
+    let event_data_raw = data
+        .get(index..index.saturating_add(event_data_size))
+        .ok_or_else(|| anyhow!("Out of bounds while reading event data"))?
+        .to_vec();
     index += event_data_size;
 
     let event = STANDARD.encode(&event_data_raw);
@@ -305,10 +340,26 @@ fn parse_eventlog_entry(
     ))
 }
 
-fn parse_digest_sizes(
+/// Parse a `TCG_EfiSpecIDEvent` and record the digest size of every algorithm.
+/// Returns the event and the number of bytes it occupies.
+fn parse_spec_id_event(
     data: &[u8],
     digest_size_map: &mut HashMap<TcgAlgorithm, u16>,
-) -> Result<usize> {
+) -> Result<(SpecIdEvent, usize)> {
+    let signature = data
+        .get(..16)
+        .ok_or_else(|| anyhow!("Cannot read Spec ID event signature"))?;
+    let signature = String::from_utf8_lossy(signature)
+        .trim_end_matches('\0')
+        .to_string();
+    let platform_class = data
+        .pread_with::<u32>(16, LE)
+        .map_err(|_| anyhow!("Cannot read platform class"))?;
+    let version = data
+        .get(20..24)
+        .ok_or_else(|| anyhow!("Cannot read spec version"))?;
+
+    let mut digest_sizes = Vec::new();
     let mut struct_index = 24;
     let algo_size = data
         .gread_with::<u32>(&mut struct_index, LE)
@@ -326,11 +377,31 @@ fn parse_digest_sizes(
             .map_err(|_| anyhow!("Unknown algorithm type detected: {:x}", algo_id))?;
 
         digest_size_map.insert(algorithm, size);
+        digest_sizes.push(tcg::AlgorithmDigestSize {
+            alg: algorithm,
+            size,
+        });
     }
 
-    let vendor_size = data[struct_index] as usize;
-    struct_index += vendor_size + 1;
-    Ok(struct_index)
+    let vendor_size = *data
+        .get(struct_index)
+        .ok_or_else(|| anyhow!("Cannot read vendor info size"))? as usize;
+    struct_index += 1;
+    let vendor_info = data
+        .get(struct_index..struct_index + vendor_size)
+        .ok_or_else(|| anyhow!("Out of bounds while reading vendor info"))?
+        .to_vec();
+    struct_index += vendor_size;
+
+    let spec_id = SpecIdEvent {
+        signature,
+        platform_class,
+        spec_version: format!("{}.{}.{}", version[1], version[0], version[2]),
+        uintn_size: version[3],
+        digest_sizes,
+        vendor_info,
+    };
+    Ok((spec_id, struct_index))
 }
 
 fn parse_digests(
@@ -356,7 +427,10 @@ fn parse_digests(
             .ok_or_else(|| anyhow!("Missing digest size for algorithm: {:x}", algo_id))?
             as usize;
 
-        let digest = data[index..index + size].to_vec();
+        let digest = data
+            .get(index..index + size)
+            .ok_or_else(|| anyhow!("Out of bounds while reading digest"))?
+            .to_vec();
         index += size;
 
         digests.push(ElDigest {
@@ -0,0 +1,261 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Secure Boot state as measured by the firmware.
+//!
+//! The firmware measures the `SecureBoot`, `PK`, `KEK`, `db` and `dbx`
+//! variables as EV_EFI_VARIABLE_DRIVER_CONFIG events before any boot
+//! application is loaded. This module decodes them into claims that
+//! attestation policies can check.
+
+use crate::ccel::efi_signature_list::{parse_efi_signature_lists, EfiSignatureData};
+use crate::ccel::tcg_enum::TcgEventType;
+use crate::EventlogEntry;
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use x509_parser::prelude::*;
+
+/// EFI_GLOBAL_VARIABLE `8be4df61-93ca-11d2-aa0d-00e098032b8c`, formatted like
+/// [`crate::EventDetails::variable_name`].
+const EFI_GLOBAL_VARIABLE: &str = "61dfe48b-ca93-d211-aa0d-00e098032b8c";
+
+/// EFI_IMAGE_SECURITY_DATABASE_GUID `d719b2cb-3d3a-4596-a3bc-dad00e67656f`, formatted like
+/// [`crate::EventDetails::variable_name`].
+const EFI_IMAGE_SECURITY_DATABASE: &str = "cbb219d7-3a3d-9645-a3bc-dad00e67656f";
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SecureBootState {
+    /// Whether the `SecureBoot` variable was measured as enabled.
+    pub enabled: bool,
+    /// Platform key. `None` if the variable was not measured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pk: Option<SignatureDatabase>,
+    /// Key exchange keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kek: Option<SignatureDatabase>,
+    /// Allowed signature database.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db: Option<SignatureDatabase>,
+    /// Forbidden signature database.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dbx: Option<SignatureDatabase>,
+}
+
+/// Content of one of the `PK`, `KEK`, `db` and `dbx` variables.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SignatureDatabase {
+    pub certificates: Vec<Certificate>,
+    /// Number of EFI_CERT_SHA256 entries.
+    pub sha256_hash_count: usize,
+    /// Number of entries of any other signature type.
+    pub other_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    /// Hex encoded serial number.
+    pub serial: String,
+    /// Hex encoded SHA-256 digest of the DER encoded certificate.
+    pub sha256_fingerprint: String,
+}
+
+impl SignatureDatabase {
+    /// Decode the EFI_SIGNATURE_LISTs of a variable. Certificates that
+    /// cannot be parsed are skipped.
+    pub fn from_variable_data(data: &[u8]) -> Result<Self> {
+        let mut database = Self::default();
+        for signature in parse_efi_signature_lists(data)? {
+            match signature {
+                EfiSignatureData::X509(der) => match parse_certificate(&der) {
+                    Ok(certificate) => database.certificates.push(certificate),
+                    Err(e) => warn!("Skip a certificate of the signature database: {e:#}"),
+                },
+                EfiSignatureData::Sha256(_) => database.sha256_hash_count += 1,
+                EfiSignatureData::Other { .. } => database.other_count += 1,
+            }
+        }
+
+        Ok(database)
+    }
+}
+
+fn parse_certificate(der: &[u8]) -> Result<Certificate> {
+    let (_, cert) = X509Certificate::from_der(der)
+        .map_err(|e| anyhow!("Failed to parse X.509 certificate: {e}"))?;
+
+    Ok(Certificate {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: cert.raw_serial_as_string().replace(':', ""),
+        sha256_fingerprint: hex::encode(Sha256::digest(der)),
+    })
+}
+
+impl SecureBootState {
+    /// Collect the Secure Boot state from the EV_EFI_VARIABLE_DRIVER_CONFIG
+    /// events of an event log. If a variable is measured more than once,
+    /// the last measurement wins. Events whose data does not match the
+    /// measured digest are skipped, as their content is not covered by the
+    /// replayed registers.
+    pub fn from_entries(entries: &[EventlogEntry]) -> Result<Self> {
+        let mut state = Self::default();
+
+        for entry in entries {
+            if entry.event_type != TcgEventType::EvEfiVariableDriverConfig {
+                continue;
+            }
+
+            let details = &entry.details;
+            let (Some(name), Some(guid)) = (&details.unicode_name, &details.variable_name) else {
+                continue;
+            };
+            if !entry.digest_matches_event {
+                warn!(
+                    "Skip the {name} variable: the event data does not match the measured digest"
+                );
+                continue;
+            }
+            let data = STANDARD
+                .decode(details.variable_data.as_deref().unwrap_or_default())
+                .context("Failed to decode variable data")?;
+
+            let database = match (guid.as_str(), name.as_str()) {
+                (EFI_GLOBAL_VARIABLE, "SecureBoot") => {
+                    state.enabled = data.first() == Some(&1);
+                    continue;
+                }
+                (EFI_GLOBAL_VARIABLE, "PK") => &mut state.pk,
+                (EFI_GLOBAL_VARIABLE, "KEK") => &mut state.kek,
+                (EFI_IMAGE_SECURITY_DATABASE, "db") => &mut state.db,
+                (EFI_IMAGE_SECURITY_DATABASE, "dbx") => &mut state.dbx,
+                _ => continue,
+            };
+            *database = Some(
+                SignatureDatabase::from_variable_data(&data)
+                    .with_context(|| format!("Failed to decode the {name} variable"))?,
+            );
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ccel::efi_signature_list::{tests::signature_list, EFI_CERT_X509_GUID};
+    use crate::CcEventLog;
+
+    #[test]
+    fn test_secure_boot_state() {
+        let data = std::fs::read("./test_data/CCEL_data_grub").unwrap();
+        let ccel = CcEventLog::try_from(data).unwrap();
+        let state = SecureBootState::from_entries(&ccel.log).unwrap();
+
+        assert!(state.enabled);
+
+        let pk = state.pk.unwrap();
+        assert_eq!(pk.certificates.len(), 1);
+        assert_eq!(
+            pk.certificates[0].sha256_fingerprint,
+            "258c6e9c3b21a5d7f121636070127d4194d1b66c2bd67dbaa734d34fa8147b17"
+        );
+
+        let kek = state.kek.unwrap();
+        assert_eq!(kek.certificates.len(), 2);
+        assert_eq!(
+            kek.certificates[1].subject,
+            "C=US, ST=Washington, L=Redmond, O=Microsoft Corporation, CN=Microsoft Corporation KEK CA 2011"
+        );
+
+        let db = state.db.unwrap();
+        assert_eq!(
+            db.certificates
+                .iter()
+                .map(|cert| cert.sha256_fingerprint.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "e8e95f0733a55e8bad7be0a1413ee23c51fcea64b3c8fa6a786935fddcc71961",
+                "48e99b991f57fc52f76149599bff0a58c47154229b9f8d603ac40d3500248507",
+            ]
+        );
+        assert_eq!(db.certificates[1].serial, "6108d3c4000000000004");
+        assert_eq!(
+            db.certificates[1].issuer,
+            "C=US, ST=Washington, L=Redmond, O=Microsoft Corporation, CN=Microsoft Corporation Third Party Marketplace Root"
+        );
+
+        let dbx = state.dbx.unwrap();
+        assert!(dbx.certificates.is_empty());
+        assert_eq!(dbx.sha256_hash_count, 1);
+        assert_eq!(dbx.other_count, 0);
+    }
+
+    #[test]
+    fn test_secure_boot_disabled() {
+        let data = std::fs::read("./test_data/CCEL_data_ovmf").unwrap();
+        let ccel = CcEventLog::try_from(data).unwrap();
+        let state = SecureBootState::from_entries(&ccel.log).unwrap();
+
+        assert_eq!(
+            state,
+            SecureBootState {
+                enabled: false,
+                pk: Some(SignatureDatabase::default()),
+                kek: Some(SignatureDatabase::default()),
+                db: Some(SignatureDatabase::default()),
+                dbx: Some(SignatureDatabase::default()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_skip_tampered_variable() {
+        let data = std::fs::read("./test_data/CCEL_data_grub").unwrap();
+        let mut log = CcEventLog::try_from(data).unwrap().log;
+
+        let pk = log
+            .iter_mut()
+            .find(|entry| entry.details.unicode_name.as_deref() == Some("PK"))
+            .unwrap();
+        pk.details.variable_data = Some(STANDARD.encode(signature_list(
+            EFI_CERT_X509_GUID,
+            &[b"not the measured certificate"],
+        )));
+        pk.digest_matches_event = false;
+
+        let state = SecureBootState::from_entries(&log).unwrap();
+        assert!(state.enabled);
+        assert_eq!(state.pk, None);
+        assert!(state.kek.is_some());
+    }
+
+    #[test]
+    fn test_skip_unparsable_certificate() {
+        let data = std::fs::read("./test_data/CCEL_data_grub").unwrap();
+        let ccel = CcEventLog::try_from(data).unwrap();
+        let db = SecureBootState::from_entries(&ccel.log)
+            .unwrap()
+            .db
+            .unwrap();
+
+        let mut data = signature_list(EFI_CERT_X509_GUID, &[b"not a certificate"]);
+        let der = ccel
+            .log
+            .iter()
+            .filter(|entry| entry.details.unicode_name.as_deref() == Some("db"))
+            .find_map(|entry| entry.details.variable_data.as_deref())
+            .unwrap();
+        data.extend(STANDARD.decode(der).unwrap());
+
+        let database = SignatureDatabase::from_variable_data(&data).unwrap();
+        assert_eq!(database, db);
+    }
+}
@@ -0,0 +1,489 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use crate::ccel::tcg_enum::{TcgAlgorithm, TcgEventType};
+use crate::{
+    accumulate_hash, parse_eventlog_entries, parse_initial_entry, ElDigest, EventlogEntry,
+    ReferenceMeasurement,
+};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeSet, HashMap};
+
+/// Signature of the Spec ID event of crypto agile event logs.
+const SPEC_ID_EVENT03: &str = "Spec ID Event03";
+
+/// Event data of the EV_NO_ACTION event recording the locality the TPM was
+/// started from. See section 10.4.5.3 of the PC Client Platform Firmware
+/// Profile.
+const STARTUP_LOCALITY_SIGNATURE: &[u8] = b"StartupLocality\0";
+
+/// Highest PCR index of a PC Client TPM.
+const MAX_PCR_INDEX: u32 = 23;
+
+/// TCG PC Client crypto agile event log, as exposed by Linux in
+/// `/sys/kernel/security/tpm0/binary_bios_measurements`.
+///
+/// The format is described in section 10 of
+/// <https://trustedcomputinggroup.org/wp-content/uploads/TCG-PC-Client-Platform-Firmware-Profile-Version-1.06-Revision-52_pub-3.pdf>
+#[derive(Clone, Serialize)]
+pub struct TcgEventLog {
+    pub spec_id: SpecIdEvent,
+    #[serde(rename = "uefi_event_logs")]
+    pub log: Vec<EventlogEntry>,
+}
+
+/// The `TCG_EfiSpecIDEvent` that starts a crypto agile event log.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SpecIdEvent {
+    pub signature: String,
+    pub platform_class: u32,
+    /// `major.minor.errata`
+    pub spec_version: String,
+    pub uintn_size: u8,
+    /// The PCR banks recorded in the event log.
+    pub digest_sizes: Vec<AlgorithmDigestSize>,
+    #[serde(serialize_with = "serialize_bytes_as_hex")]
+    pub vendor_info: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AlgorithmDigestSize {
+    pub alg: TcgAlgorithm,
+    pub size: u16,
+}
+
+fn serialize_bytes_as_hex<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+impl TryFrom<Vec<u8>> for TcgEventLog {
+    type Error = anyhow::Error;
+
+    fn try_from(data: Vec<u8>) -> Result<Self> {
+        let mut index = 0;
+        let mut log = Vec::new();
+        let mut digest_size_map = HashMap::new();
+
+        let spec_id = parse_initial_entry(&data, &mut index, &mut digest_size_map)?
+            .ok_or_else(|| anyhow!("The first event of the event log is not a Spec ID event"))?;
+        if spec_id.signature != SPEC_ID_EVENT03 {
+            bail!(
+                "Unsupported event log format `{}`, only crypto agile event logs are supported",
+                spec_id.signature
+            );
+        }
+
+        parse_eventlog_entries(&data, index, &mut digest_size_map, &mut log)?;
+
+        Ok(Self { spec_id, log })
+    }
+}
+
+impl TcgEventLog {
+    /// PCRs extended by at least one event of the log.
+    pub fn measured_pcrs(&self) -> BTreeSet<u32> {
+        self.log
+            .iter()
+            .filter(|entry| entry.event_type != TcgEventType::EvNoAction)
+            .map(|entry| entry.index)
+            .collect()
+    }
+
+    /// The locality recorded by the StartupLocality event, if any.
+    pub fn startup_locality(&self) -> Option<u8> {
+        self.log
+            .iter()
+            .filter(|entry| entry.index == 0 && entry.event_type == TcgEventType::EvNoAction)
+            .filter_map(|entry| STANDARD.decode(&entry.event).ok())
+            .find_map(|data| {
+                let locality = data.strip_prefix(STARTUP_LOCALITY_SIGNATURE)?;
+                match locality {
+                    [locality] => Some(*locality),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Compute the value of the PCR `index` in the bank `alg` from the events
+    /// of the log.
+    pub fn replay(&self, index: u32, alg: TcgAlgorithm) -> Result<Vec<u8>> {
+        if index > MAX_PCR_INDEX {
+            bail!("PCR index {index} is out of range");
+        }
+        let digest_size = alg.get_digest_size(alg)?;
+        let mut pcr = vec![0u8; digest_size];
+        if index == 0 {
+            if let Some(locality) = self.startup_locality() {
+                pcr[digest_size - 1] = locality;
+            }
+        }
+
+        for entry in self
+            .log
+            .iter()
+            .filter(|entry| entry.index == index && entry.event_type != TcgEventType::EvNoAction)
+        {
+            let digest = entry
+                .digests
+                .iter()
+                .find(|digest| digest.alg == alg)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Event {:?} of PCR [{index}] has no {:?} digest",
+                        entry.event_type,
+                        alg
+                    )
+                })?;
+            pcr = accumulate_hash(alg, pcr, &digest.digest)?;
+        }
+
+        Ok(pcr)
+    }
+
+    /// Replay the log and compare the result with the given PCR values.
+    pub fn replay_and_match(&self, data: Vec<ReferenceMeasurement>) -> Result<()> {
+        for item in data.iter() {
+            let calculated_pcr = self.replay(item.index, item.algorithm)?;
+            if calculated_pcr != item.reference {
+                bail!(
+                    "TCG eventlog does not pass PCR [{}] check. Eventlog value: {}, Quote value: {}",
+                    item.index,
+                    hex::encode(calculated_pcr),
+                    hex::encode(&item.reference)
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Boot components measured into an event log, in a shape suited for
+/// attestation policies.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct BootEvents {
+    /// UEFI variables measured by EV_EFI_VARIABLE_* events.
+    pub efi_variables: Vec<EfiVariableEvent>,
+    /// EFI boot applications (shim, bootloaders, UKIs) in load order.
+    pub boot_applications: Vec<BootApplicationEvent>,
+    /// Commands executed by GRUB.
+    pub grub_commands: Vec<String>,
+    /// The last kernel command line measured by the bootloader.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel_cmdline: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EfiVariableEvent {
+    pub index: u32,
+    #[serde(rename = "type_name")]
+    pub event_type: TcgEventType,
+    pub name: String,
+    pub guid: String,
+    /// Base64 encoded variable data.
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BootApplicationEvent {
+    pub index: u32,
+    pub device_paths: Vec<String>,
+    /// Authenticode digests of the loaded image.
+    pub digests: Vec<ElDigest>,
+}
+
+/// Prefixes of the EV_IPL strings GRUB uses to measure commands.
+const GRUB_COMMAND_PREFIXES: [&str; 2] = ["grub_cmd: ", "grub_cmd "];
+
+/// Prefixes of the EV_IPL strings GRUB uses to measure the kernel command line.
+const KERNEL_CMDLINE_PREFIXES: [&str; 3] = [
+    "kernel_cmdline: ",
+    "kernel_cmdline ",
+    "grub_kernel_cmdline ",
+];
+
+/// Whether the digests of `entry` cover `value`, the part of the event data a
+/// claim is taken from. Bootloaders do not always measure the event data they
+/// log, e.g. GRUB measures a command without the `grub_cmd: ` prefix.
+fn is_measured(entry: &EventlogEntry, value: &[u8]) -> bool {
+    entry.digest_matches_event
+        || entry
+            .event_type
+            .get_parser()
+            .compare_digests(&value.to_vec(), &entry.digests)
+            .unwrap_or(false)
+}
+
+impl BootEvents {
+    /// Collect the boot events of an event log. EFI variables and bootloader
+    /// strings that are not covered by the digests of their event are
+    /// skipped. Boot applications are identified by their measured digests.
+    pub fn from_entries(entries: &[EventlogEntry]) -> Self {
+        let mut events = Self::default();
+
+        for entry in entries {
+            match entry.event_type {
+                TcgEventType::EvEfiVariableDriverConfig
+                | TcgEventType::EvEfiVariableBoot
+                | TcgEventType::EvEfiVariableBoot2
+                | TcgEventType::EvEfiVariableAuthority => {
+                    let details = &entry.details;
+                    let (Some(name), Some(guid)) = (&details.unicode_name, &details.variable_name)
+                    else {
+                        continue;
+                    };
+                    let data = details.variable_data.clone().unwrap_or_default();
+                    if !is_measured(entry, &STANDARD.decode(&data).unwrap_or_default()) {
+                        warn!("Skip the {name} variable: the event data does not match the measured digest");
+                        continue;
+                    }
+                    events.efi_variables.push(EfiVariableEvent {
+                        index: entry.index,
+                        event_type: entry.event_type,
+                        name: name.clone(),
+                        guid: guid.clone(),
+                        data,
+                    });
+                }
+                TcgEventType::EvEfiBootServicesApplication => {
+                    events.boot_applications.push(BootApplicationEvent {
+                        index: entry.index,
+                        device_paths: entry.details.device_paths.clone().unwrap_or_default(),
+                        digests: entry.digests.clone(),
+                    });
+                }
+                TcgEventType::EvIpl => {
+                    let Some(string) = &entry.details.string else {
+                        continue;
+                    };
+                    if let Some(cmdline) = KERNEL_CMDLINE_PREFIXES
+                        .iter()
+                        .find_map(|prefix| string.strip_prefix(prefix))
+                    {
+                        if !is_measured(entry, cmdline.as_bytes()) {
+                            warn!("Skip a kernel command line that does not match the measured digest");
+                            continue;
+                        }
+                        events.kernel_cmdline = Some(cmdline.to_string());
+                    } else if let Some(command) = GRUB_COMMAND_PREFIXES
+                        .iter()
+                        .find_map(|prefix| string.strip_prefix(prefix))
+                    {
+                        if !is_measured(entry, command.as_bytes()) {
+                            warn!("Skip a GRUB command that does not match the measured digest");
+                            continue;
+                        }
+                        events.grub_commands.push(command.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use sha2::{Digest, Sha256};
+    use std::fs;
+
+    const TCG_EVENTLOG: &str = "./test_data/tcg_binary_bios_measurements";
+
+    fn eventlog() -> TcgEventLog {
+        TcgEventLog::try_from(fs::read(TCG_EVENTLOG).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_spec_id_event() {
+        let eventlog = eventlog();
+        assert_eq!(
+            eventlog.spec_id,
+            SpecIdEvent {
+                signature: SPEC_ID_EVENT03.to_string(),
+                platform_class: 0,
+                spec_version: "2.0.0".to_string(),
+                uintn_size: 2,
+                digest_sizes: vec![
+                    AlgorithmDigestSize {
+                        alg: TcgAlgorithm::Sha1,
+                        size: 20
+                    },
+                    AlgorithmDigestSize {
+                        alg: TcgAlgorithm::Sha256,
+                        size: 32
+                    },
+                ],
+                vendor_info: vec![],
+            }
+        );
+        assert_eq!(eventlog.log.len(), 16);
+        assert!(eventlog.log.iter().all(|entry| entry.digests.len() == 2));
+        assert_eq!(eventlog.startup_locality(), Some(3));
+        assert_eq!(
+            eventlog.measured_pcrs(),
+            BTreeSet::from([0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
+        );
+    }
+
+    #[rstest]
+    #[case(0, TcgAlgorithm::Sha1, "8ab107b2b01ff2955589e9246d125f6272365c50")]
+    #[case(
+        0,
+        TcgAlgorithm::Sha256,
+        "7cd284b0b2994be43baab8a646fce199103c4b2717d05550643a7f1a1fcad386"
+    )]
+    #[case(
+        4,
+        TcgAlgorithm::Sha256,
+        "1b0f51a6260038f9bb9a9934a6a3969abe19d836941ea0685fbe341a24883eb9"
+    )]
+    #[case(
+        7,
+        TcgAlgorithm::Sha256,
+        "3a765fab0c4555e805964d8c75231894f45c5a6f2161738cf157015250a3e624"
+    )]
+    #[case(8, TcgAlgorithm::Sha1, "d8efa3d39a8e92cec2e800626bd5beba43fac5b4")]
+    #[case(
+        8,
+        TcgAlgorithm::Sha256,
+        "e5b919514dba8e93fed47caaa5391bf2d7e49d9914bfe39fe923cc7940b2d571"
+    )]
+    #[case(
+        9,
+        TcgAlgorithm::Sha256,
+        "4af47301e71736075d36f0860dea2333db252df4811b4d5f415981cea9c3fcee"
+    )]
+    #[case(
+        10,
+        TcgAlgorithm::Sha256,
+        "0000000000000000000000000000000000000000000000000000000000000000"
+    )]
+    fn test_replay(#[case] index: u32, #[case] alg: TcgAlgorithm, #[case] expected: &str) {
+        assert_eq!(
+            hex::encode(eventlog().replay(index, alg).unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_replay_and_match() {
+        let eventlog = eventlog();
+        let reference = |index, value: &str| ReferenceMeasurement {
+            index,
+            algorithm: TcgAlgorithm::Sha256,
+            reference: hex::decode(value).unwrap(),
+        };
+
+        eventlog
+            .replay_and_match(vec![reference(
+                7,
+                "3a765fab0c4555e805964d8c75231894f45c5a6f2161738cf157015250a3e624",
+            )])
+            .unwrap();
+
+        let err = eventlog
+            .replay_and_match(vec![reference(
+                7,
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            )])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TCG eventlog does not pass PCR [7] check. Eventlog value: 3a765fab0c4555e805964d8c75231894f45c5a6f2161738cf157015250a3e624, Quote value: 0000000000000000000000000000000000000000000000000000000000000000"
+        );
+
+        assert!(eventlog.replay(0, TcgAlgorithm::Sha384).is_err());
+        assert!(eventlog.replay(24, TcgAlgorithm::Sha256).is_err());
+    }
+
+    #[test]
+    fn test_boot_events() {
+        let events = BootEvents::from_entries(&eventlog().log);
+
+        let variables: Vec<_> = events
+            .efi_variables
+            .iter()
+            .map(|var| (var.name.as_str(), var.data.as_str()))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![("SecureBoot", "AQ=="), ("BootOrder", "AAA=")]
+        );
+
+        assert_eq!(events.boot_applications.len(), 1);
+        assert_eq!(
+            events.boot_applications[0].device_paths.last().unwrap(),
+            "File(\\EFI\\BOOT\\BOOTX64.EFI)"
+        );
+        assert_eq!(
+            events.grub_commands,
+            vec!["linux /vmlinuz root=/dev/vda1 ro".to_string()]
+        );
+        assert_eq!(
+            events.kernel_cmdline.as_deref(),
+            Some("/vmlinuz root=/dev/vda1 ro")
+        );
+    }
+
+    #[test]
+    fn test_boot_events_skip_tampered_entries() {
+        let mut log = eventlog().log;
+        for entry in log.iter_mut() {
+            match entry.details.string.as_deref() {
+                Some("grub_cmd: linux /vmlinuz root=/dev/vda1 ro") => {
+                    entry.details.string = Some("grub_cmd: linux /evil".to_string());
+                }
+                Some("kernel_cmdline: /vmlinuz root=/dev/vda1 ro") => {
+                    entry.details.string = Some("kernel_cmdline: /evil".to_string());
+                }
+                _ if entry.details.unicode_name.as_deref() == Some("SecureBoot") => {
+                    entry.details.variable_data = Some("AA==".to_string());
+                }
+                _ => continue,
+            }
+            entry.digest_matches_event = false;
+        }
+
+        let events = BootEvents::from_entries(&log);
+        assert_eq!(events.efi_variables.len(), 1);
+        assert_eq!(events.efi_variables[0].name, "BootOrder");
+        assert!(events.grub_commands.is_empty());
+        assert_eq!(events.kernel_cmdline, None);
+    }
+
+    #[test]
+    fn test_boot_events_measured_without_prefix() {
+        let mut log = eventlog().log;
+        let entry = log
+            .iter_mut()
+            .find(|entry| entry.event_type == TcgEventType::EvIpl)
+            .unwrap();
+        entry.details.string = Some("grub_cmd: insmod tpm".to_string());
+        entry.digests = vec![ElDigest {
+            alg: TcgAlgorithm::Sha256,
+            digest: Sha256::digest(b"insmod tpm").to_vec(),
+        }];
+        entry.digest_matches_event = false;
+
+        let events = BootEvents::from_entries(&log);
+        assert_eq!(events.grub_commands[0], "insmod tpm");
+    }
+
+    #[test]
+    fn test_reject_truncated_eventlog() {
+        let mut data = fs::read(TCG_EVENTLOG).unwrap();
+        data.truncate(data.len() - 4);
+        assert!(TcgEventLog::try_from(data).is_err());
+    }
+}
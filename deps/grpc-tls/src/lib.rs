@@ -0,0 +1,82 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! TLS configuration of the gRPC clients of Trustee, e.g. of the CoCo AS
+//! connecting to the RVPS and of the KBS connecting to the CoCo AS.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+/// TLS configuration of a gRPC client connection.
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct TlsConfig {
+    /// Path to the PEM encoded CA certificate to verify the server with.
+    /// The native root certificates are used if not given.
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Paths to the PEM encoded client certificate and private key, to
+    /// authenticate to the server with mutual TLS.
+    #[serde(default)]
+    pub cert: Option<PathBuf>,
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+
+    /// Name to verify the certificate of the server against, instead of the
+    /// host of the address.
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+impl TlsConfig {
+    pub fn to_client_tls_config(&self) -> Result<ClientTlsConfig> {
+        let mut tls = ClientTlsConfig::new();
+        tls = match &self.ca_cert {
+            Some(ca_cert) => {
+                let ca_cert = std::fs::read(ca_cert).context("read CA certificate")?;
+                tls.ca_certificate(Certificate::from_pem(ca_cert))
+            }
+            None => tls.with_native_roots(),
+        };
+
+        match (&self.cert, &self.key) {
+            (Some(cert), Some(key)) => {
+                let cert = std::fs::read(cert).context("read client certificate")?;
+                let key = std::fs::read(key).context("read client private key")?;
+                tls = tls.identity(Identity::from_pem(cert, key));
+            }
+            (None, None) => {}
+            _ => bail!("Both the client certificate and private key are needed for mutual TLS"),
+        }
+
+        if let Some(domain) = &self.domain {
+            tls = tls.domain_name(domain);
+        }
+
+        Ok(tls)
+    }
+}
+
+/// Endpoint of the gRPC server at `address`. TLS is used if a configuration
+/// is given, or the scheme of the address is `https`.
+pub fn endpoint(address: String, tls: Option<&TlsConfig>) -> Result<Endpoint> {
+    let mut endpoint = Endpoint::from_shared(address).context("parse server address")?;
+    let tls = match tls {
+        Some(tls) => Some(tls.clone()),
+        None if endpoint.uri().scheme_str() == Some("https") => Some(TlsConfig::default()),
+        None => None,
+    };
+    if let Some(tls) = tls {
+        endpoint = endpoint.tls_config(tls.to_client_tls_config()?)?;
+    }
+
+    Ok(endpoint)
+}
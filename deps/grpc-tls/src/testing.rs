@@ -0,0 +1,86 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Certificates for tests of TLS connections.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use openssl::{
+    asn1::Asn1Time,
+    bn::{BigNum, MsbOption},
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    x509::{
+        extension::{BasicConstraints, ExtendedKeyUsage, SubjectAlternativeName},
+        X509Builder, X509NameBuilder, X509,
+    },
+};
+
+/// Issue a certificate for `cn`, signed by `issuer` or self-signed as a CA
+/// if there is no issuer.
+pub fn certificate(
+    cn: &str,
+    issuer: Option<(&X509, &PKey<Private>)>,
+) -> Result<(X509, PKey<Private>)> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_nid(Nid::COMMONNAME, cn)?;
+    let name = name.build();
+
+    let mut builder = X509Builder::new()?;
+    builder.set_version(2)?;
+    let mut serial = BigNum::new()?;
+    serial.rand(64, MsbOption::MAYBE_ZERO, false)?;
+    let serial = serial.to_asn1_integer()?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(&name)?;
+    builder.set_pubkey(&key)?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    let not_after = Asn1Time::days_from_now(1)?;
+    builder.set_not_before(&not_before)?;
+    builder.set_not_after(&not_after)?;
+
+    let (issuer_name, issuer_key) = match issuer {
+        Some((cert, key)) => {
+            let san = SubjectAlternativeName::new()
+                .dns(cn)
+                .build(&builder.x509v3_context(Some(cert), None))?;
+            builder.append_extension(san)?;
+            builder.append_extension(
+                ExtendedKeyUsage::new()
+                    .server_auth()
+                    .client_auth()
+                    .build()?,
+            )?;
+            (cert.subject_name().to_owned()?, key.clone())
+        }
+        None => {
+            builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+            (name, key.clone())
+        }
+    };
+    builder.set_issuer_name(&issuer_name)?;
+    builder.sign(&issuer_key, MessageDigest::sha256())?;
+
+    Ok((builder.build(), key))
+}
+
+/// Write `cert` and `key` as PEM to `dir` and return their paths.
+pub fn write_certificate(
+    dir: &Path,
+    name: &str,
+    cert: &X509,
+    key: &PKey<Private>,
+) -> Result<(PathBuf, PathBuf)> {
+    let cert_path = dir.join(format!("{name}.crt"));
+    let key_path = dir.join(format!("{name}.key"));
+    std::fs::write(&cert_path, cert.to_pem()?)?;
+    std::fs::write(&key_path, key.private_key_to_pem_pkcs8()?)?;
+    Ok((cert_path, key_path))
+}
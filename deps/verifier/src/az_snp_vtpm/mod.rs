@@ -4,10 +4,12 @@
 //
 
 use super::{TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
+use crate::normalized::{self, NormalizedClaims};
 use crate::snp::{
-    get_common_name, get_oid_int, get_oid_octets, ProcessorGeneration, CERT_CHAINS, HW_ID_OID,
-    LOADER_SPL_OID, SNP_SPL_OID, TEE_SPL_OID, UCODE_SPL_OID,
+    get_common_name, get_oid_int, get_oid_octets, normalize_snp_claims, ProcessorGeneration,
+    CERT_CHAINS, HW_ID_OID, LOADER_SPL_OID, SNP_SPL_OID, TEE_SPL_OID, UCODE_SPL_OID,
 };
+use crate::tcg_eventlog::verify_tcg_eventlog;
 use crate::{InitDataHash, ReportData};
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
@@ -38,6 +40,9 @@ struct Evidence {
     quote: Quote,
     report: Vec<u8>,
     vcek: String,
+    /// TCG PC Client event log of the vTPM
+    #[serde(default)]
+    event_log: Option<Vec<u8>>,
 }
 
 pub struct AzSnpVtpm {
@@ -111,6 +116,7 @@ impl Verifier for AzSnpVtpm {
     /// 5. SNP Report is genuine
     /// 6. SNP Report has been issued in VMPL 0
     /// 7. Init data hash matches TPM PCR[INITDATA_PCR]
+    /// 8. The event log, if provided, replays to the TPM PCRs
     #[instrument(skip_all, name = "Azure vTPM SEV-SNP")]
     async fn evaluate(
         &self,
@@ -153,9 +159,54 @@ impl Verifier for AzSnpVtpm {
 
         let mut claim = parse_tee_evidence_az(&snp_report);
         extend_claim(&mut claim, &evidence.quote)?;
+        if let Some(event_log) = evidence.event_log {
+            extend_eventlog_claim(&mut claim, event_log, &pcrs)?;
+        }
 
         Ok(vec![(claim, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        normalize_az_vtpm_claims(claims, normalize_snp_claims(claims))
+    }
+}
+
+/// Add the vTPM claims of [`extend_claim`] to the claims normalized from the
+/// hardware report. The SVNs of Azure reports are encoded as strings.
+pub(crate) fn normalize_az_vtpm_claims(
+    claims: &TeeEvidenceParsedClaim,
+    hardware: NormalizedClaims,
+) -> NormalizedClaims {
+    let tcb_svns = hardware
+        .tcb_svns
+        .into_iter()
+        .map(
+            |(name, svn)| match svn.as_str().and_then(|s| s.parse::<u64>().ok()) {
+                Some(svn) => (name, Value::from(svn)),
+                None => (name, svn),
+            },
+        )
+        .collect();
+
+    NormalizedClaims {
+        runtime_measurements: normalized::pcr_claims(claims, "/tpm"),
+        tcb_svns,
+        init_data_hash: normalized::str_claim(claims, "/init_data"),
+        ..hardware
+    }
+}
+
+/// Replay the vTPM event log against `pcrs` and add the event log claims.
+pub(crate) fn extend_eventlog_claim(
+    claim: &mut TeeEvidenceParsedClaim,
+    event_log: Vec<u8>,
+    pcrs: &[&[u8; 32]],
+) -> Result<()> {
+    let Value::Object(ref mut map) = claim else {
+        bail!("failed to extend the claim, not an object");
+    };
+    map.extend(verify_tcg_eventlog(event_log, pcrs)?);
+    Ok(())
 }
 
 fn verify_nonce(quote: &Quote, report_data: &[u8]) -> Result<(), CertError> {
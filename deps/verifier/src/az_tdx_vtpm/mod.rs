@@ -3,10 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 //
 
-use super::az_snp_vtpm::{extend_claim, verify_init_data};
-use super::tdx::claims::generate_parsed_claim;
+use super::az_snp_vtpm::{
+    extend_claim, extend_eventlog_claim, normalize_az_vtpm_claims, verify_init_data,
+};
+use super::tdx::claims::{generate_parsed_claim, normalize_tdx_claims};
 use super::tdx::quote::{parse_tdx_quote, Quote as TdQuote};
-use super::{TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
+use super::{NormalizedClaims, TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
 use crate::intel_dcap::{ecdsa_quote_verification, extend_using_custom_claims};
 use crate::{InitDataHash, ReportData};
 use anyhow::{bail, Context, Result};
@@ -22,6 +24,9 @@ struct Evidence {
     tpm_quote: TpmQuote,
     hcl_report: Vec<u8>,
     td_quote: Vec<u8>,
+    /// TCG PC Client event log of the vTPM
+    #[serde(default)]
+    event_log: Option<Vec<u8>>,
 }
 
 #[derive(Default)]
@@ -36,6 +41,7 @@ impl Verifier for AzTdxVtpm {
     /// 4. TD Quote is genuine
     /// 5. TD Report's report_data field matches hashed HCL variable data
     /// 6. Init data hash matches TPM PCR[INITDATA_PCR]
+    /// 7. The event log, if provided, replays to the TPM PCRs
     #[instrument(skip_all, name = "Azure vTPM TDX")]
     async fn evaluate(
         &self,
@@ -67,10 +73,17 @@ impl Verifier for AzTdxVtpm {
 
         let mut claim = generate_parsed_claim(td_quote, None)?;
         extend_claim(&mut claim, &evidence.tpm_quote)?;
+        if let Some(event_log) = evidence.event_log {
+            extend_eventlog_claim(&mut claim, event_log, &pcrs)?;
+        }
         extend_using_custom_claims(&mut claim, custom_claims)?;
 
         Ok(vec![(claim, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        normalize_az_vtpm_claims(claims, normalize_tdx_claims(claims))
+    }
 }
 
 fn verify_hcl_var_data(hcl_report: &HclReport, td_quote: &TdQuote) -> Result<()> {
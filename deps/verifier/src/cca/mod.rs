@@ -132,6 +132,34 @@ impl Verifier for CCA {
             .map_err(|e| anyhow!("error from CCA Verifier: {:?}", e))?;
         Ok(vec![(claims, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        let runtime_measurements = claims
+            .pointer("/realm/cca-realm-extensible-measurements")
+            .and_then(|rems| rems.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, rem)| {
+                let rem = BASE64_STANDARD.decode(rem.as_str()?).ok()?;
+                Some((format!("rem{index}"), hex::encode(rem)))
+            })
+            .collect();
+
+        NormalizedClaims {
+            launch_measurement: normalized::base64_claim_as_hex(
+                claims,
+                "/realm/cca-realm-initial-measurement",
+            ),
+            runtime_measurements,
+            platform_id: normalized::base64_claim_as_hex(
+                claims,
+                "/platform/cca-platform-instance-id",
+            ),
+            init_data_hash: normalized::str_claim(claims, "/init_data"),
+            ..Default::default()
+        }
+    }
 }
 
 /// The expected evidence layout looks like below,
@@ -250,4 +278,19 @@ mod tests {
             format!("{:?}", parsed_claim.unwrap()),
         );
     }
+
+    #[test]
+    fn test_cca_normalize_claims() {
+        let s = fs::read("./test_data/cca-claims.json").unwrap();
+        let tcb = serde_json::from_slice::<EvidenceClaimsSet>(&s).unwrap();
+        let claims = cca_generate_parsed_claim(tcb).unwrap();
+        let normalized = CCA::default().normalize_claims(&claims);
+
+        assert_eq!(
+            normalized.platform_id.as_deref(),
+            Some("010202020202020202020202020202020202020202020202020202020202020202")
+        );
+        assert!(normalized.launch_measurement.is_some());
+        assert_eq!(normalized.runtime_measurements.len(), 4);
+    }
 }
@@ -4,7 +4,7 @@
 //
 
 use base64::Engine;
-use eventlog::{ccel::tcg_enum::TcgAlgorithm, CcEventLog, ReferenceMeasurement};
+use eventlog::{ccel::tcg_enum::TcgAlgorithm, CcEventLog, ReferenceMeasurement, SecureBootState};
 use reqwest::{get, Response as ReqwestResponse, StatusCode};
 use std::{io::Cursor, path::Path};
 use tokio::fs;
@@ -247,15 +247,44 @@ impl Verifier for CsvVerifier {
                     ccel.replay_and_match(compare_obj)?;
                     info!("EventLog integrity check succeeded.");
 
-                    claims.as_object_mut().expect("Must be an object").insert(
+                    let claims_map = claims.as_object_mut().expect("Must be an object");
+                    claims_map.insert(
                         "uefi_event_logs".to_string(),
                         serde_json::to_value(ccel.clone().log)?,
                     );
+                    match SecureBootState::from_entries(&ccel.log) {
+                        Ok(secure_boot) => {
+                            claims_map.insert(
+                                "secure_boot".to_string(),
+                                serde_json::to_value(secure_boot)?,
+                            );
+                        }
+                        Err(e) => warn!("Omit the Secure Boot claims: {e:#}"),
+                    }
                 }
                 Ok(vec![(claims, "cpu".to_string())])
             }
         }
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        // Only version 2 reports carry runtime measurement registers.
+        let runtime_measurements = (0..=4)
+            .filter_map(|index| {
+                let rtmr = normalized::str_claim(claims, &format!("/rtmr{index}"))?;
+                Some((format!("rtmr{index}"), rtmr))
+            })
+            .collect();
+
+        NormalizedClaims {
+            launch_measurement: normalized::str_claim(claims, "/measure"),
+            runtime_measurements,
+            debug_enabled: normalized::bool_claim(claims, "/policy/nodbg").map(|nodbg| !nodbg),
+            tcb_svns: normalized::collect_claims(claims, [("build", "/build")]),
+            platform_id: normalized::str_claim(claims, "/serial_number"),
+            ..Default::default()
+        }
+    }
 }
 
 async fn try_load_hskcek_offline(chip_id: &str) -> Option<Vec<u8>> {
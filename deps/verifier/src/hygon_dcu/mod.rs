@@ -11,8 +11,8 @@ use serde_json::{json, Map};
 use tracing::{instrument, warn};
 
 use crate::{
-    regularize_data, InitDataHash, ReportData, TeeClass, TeeEvidence, TeeEvidenceParsedClaim,
-    Verifier,
+    regularize_data, InitDataHash, NormalizedClaims, ReportData, TeeClass, TeeEvidence,
+    TeeEvidenceParsedClaim, Verifier,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -56,6 +56,24 @@ impl Verifier for HygonDcuVerifier {
         let claims = parse_tee_evidence(tee_evidence.attestation_reports)?;
         Ok(vec![(claims, "dcu".to_string())])
     }
+
+    /// The measurement of every DCU is reported as `dcu<index>`.
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        let runtime_measurements = claims
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(index, report)| {
+                let measure = report.pointer("/body/measure")?.as_str()?;
+                Some((format!("dcu{index}"), measure.to_string()))
+            })
+            .collect();
+
+        NormalizedClaims {
+            runtime_measurements,
+            ..Default::default()
+        }
+    }
 }
 
 // Dump the DCU information from the report.
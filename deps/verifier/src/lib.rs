@@ -6,9 +6,12 @@ use kbs_types::Tee;
 use serde::Deserialize;
 use tracing::debug;
 
+pub mod normalized;
 pub mod sample;
 pub mod sample_device;
 
+pub use normalized::NormalizedClaims;
+
 #[cfg(feature = "az-snp-vtpm-verifier")]
 pub mod az_snp_vtpm;
 
@@ -49,6 +52,13 @@ pub mod intel_dcap;
 #[cfg(feature = "tpm-verifier")]
 pub mod tpm;
 
+#[cfg(any(
+    feature = "tpm-verifier",
+    feature = "az-snp-vtpm-verifier",
+    feature = "az-tdx-vtpm-verifier"
+))]
+pub mod tcg_eventlog;
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
 pub struct VerifierConfig {
     #[cfg(feature = "nvidia-verifier")]
@@ -241,6 +251,14 @@ pub trait Verifier {
     async fn generate_supplemental_challenge(&self, _tee_parameters: String) -> Result<String> {
         Ok(String::new())
     }
+
+    /// Map the claims returned by [`Verifier::evaluate`] to the TEE-agnostic
+    /// [`NormalizedClaims`].
+    ///
+    /// Fields that the TEE does not report are left empty.
+    fn normalize_claims(&self, _claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        NormalizedClaims::default()
+    }
 }
 
 /// Padding or truncate the given data slice to the given `len` bytes.
@@ -0,0 +1,170 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! TEE-agnostic view of the claims returned by the verifiers.
+//!
+//! Every verifier returns claims in the shape of its own hardware evidence,
+//! e.g. `tdx.quote.body.mr_td` or `snp.measurement`. [`NormalizedClaims`]
+//! collects the security relevant parts of them under the same names for all
+//! TEEs, so that policies can check them without per-TEE branches.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::TeeEvidenceParsedClaim;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct NormalizedClaims {
+    /// Hex encoded measurement of the initial TEE image, e.g. MRTD (TDX),
+    /// MEASUREMENT (SNP) or the realm initial measurement (CCA).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_measurement: Option<String>,
+
+    /// Hex encoded runtime measurement registers keyed by register name,
+    /// e.g. `rtmr0` (TDX, CSV), `pcr11` (TPM) or `rem0` (CCA).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runtime_measurements: BTreeMap<String, String>,
+
+    /// Whether the TEE can be debugged by the host. `None` if the evidence
+    /// does not tell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_enabled: Option<bool>,
+
+    /// Security version numbers of the firmware and TCB components keyed by
+    /// component name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tcb_svns: BTreeMap<String, Value>,
+
+    /// Identifier of the platform or device, e.g. a chip id or serial number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_id: Option<String>,
+
+    /// Hex encoded init data hash bound to the evidence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_data_hash: Option<String>,
+}
+
+/// Get the string claim at the JSON `pointer`.
+pub(crate) fn str_claim(claims: &TeeEvidenceParsedClaim, pointer: &str) -> Option<String> {
+    claims.pointer(pointer)?.as_str().map(String::from)
+}
+
+/// Get the base64 encoded claim at the JSON `pointer` as hex.
+#[cfg(any(feature = "snp-verifier", feature = "cca-verifier"))]
+pub(crate) fn base64_claim_as_hex(
+    claims: &TeeEvidenceParsedClaim,
+    pointer: &str,
+) -> Option<String> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let value = claims.pointer(pointer)?.as_str()?;
+    STANDARD.decode(value).ok().map(hex::encode)
+}
+
+/// Get the boolean claim at the JSON `pointer`. Some verifiers encode
+/// booleans as `"true"` and `"false"` strings.
+pub(crate) fn bool_claim(claims: &TeeEvidenceParsedClaim, pointer: &str) -> Option<bool> {
+    match claims.pointer(pointer)? {
+        Value::Bool(value) => Some(*value),
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Copy the claims at the given JSON pointers to a map keyed by name,
+/// skipping the ones that do not exist.
+pub(crate) fn collect_claims<'a>(
+    claims: &TeeEvidenceParsedClaim,
+    pointers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> BTreeMap<String, Value> {
+    pointers
+        .into_iter()
+        .filter_map(|(name, pointer)| Some((name.to_string(), claims.pointer(pointer)?.clone())))
+        .collect()
+}
+
+/// Collect the hex encoded TPM PCRs of the object at the JSON `pointer`,
+/// stored as `pcrNN` claims.
+#[cfg(any(feature = "tpm-verifier", feature = "az-snp-vtpm-verifier"))]
+pub(crate) fn pcr_claims(
+    claims: &TeeEvidenceParsedClaim,
+    pointer: &str,
+) -> BTreeMap<String, String> {
+    let Some(Value::Object(map)) = claims.pointer(pointer) else {
+        return BTreeMap::new();
+    };
+
+    map.iter()
+        .filter(|(name, _)| {
+            name.strip_prefix("pcr")
+                .is_some_and(|index| index.parse::<u8>().is_ok())
+        })
+        .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_claim_helpers() {
+        let claims = json!({
+            "debug": "false",
+            "flags": {"debug": true},
+            "tcb": {"snp": 22},
+        });
+
+        assert_eq!(bool_claim(&claims, "/debug"), Some(false));
+        assert_eq!(bool_claim(&claims, "/flags/debug"), Some(true));
+        assert_eq!(bool_claim(&claims, "/missing"), None);
+        assert_eq!(
+            collect_claims(&claims, [("snp", "/tcb/snp"), ("tee", "/tcb/tee")]),
+            BTreeMap::from([("snp".to_string(), json!(22))])
+        );
+    }
+
+    #[cfg(any(feature = "snp-verifier", feature = "cca-verifier"))]
+    #[test]
+    fn test_base64_claim_as_hex() {
+        let claims = json!({"measurement": "3q2+7w==", "invalid": "3q2"});
+
+        assert_eq!(
+            base64_claim_as_hex(&claims, "/measurement").as_deref(),
+            Some("deadbeef")
+        );
+        assert_eq!(base64_claim_as_hex(&claims, "/invalid"), None);
+    }
+
+    #[cfg(any(feature = "tpm-verifier", feature = "az-snp-vtpm-verifier"))]
+    #[test]
+    fn test_pcr_claims() {
+        let claims = json!({"tpm": {"pcr00": "00", "pcr11": "11", "pcrx": "ff", "other": "ee"}});
+
+        assert_eq!(
+            pcr_claims(&claims, "/tpm"),
+            BTreeMap::from([
+                ("pcr00".to_string(), "00".to_string()),
+                ("pcr11".to_string(), "11".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_serialize_normalized_claims() {
+        let claims = NormalizedClaims {
+            launch_measurement: Some("aa".into()),
+            debug_enabled: Some(false),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(claims).unwrap(),
+            json!({"launch_measurement": "aa", "debug_enabled": false})
+        );
+    }
+}
@@ -256,6 +256,29 @@ impl Verifier for Nvidia {
 
         Ok(all_devices_claims)
     }
+
+    /// Only the claims of the local verifier are normalized. The claims of
+    /// NRAS are left to the policy.
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        let runtime_measurements = claims
+            .pointer("/measurements")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(index, measurement)| {
+                Some((
+                    format!("measurement{index}"),
+                    measurement.as_str()?.to_string(),
+                ))
+            })
+            .collect();
+
+        NormalizedClaims {
+            runtime_measurements,
+            platform_id: normalized::str_claim(claims, "/uuid"),
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
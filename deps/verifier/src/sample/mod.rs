@@ -36,6 +36,15 @@ impl Verifier for Sample {
         let claims = parse_tee_evidence(&tee_evidence)?;
         Ok(vec![(claims, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        NormalizedClaims {
+            launch_measurement: normalized::str_claim(claims, "/launch_digest"),
+            debug_enabled: normalized::bool_claim(claims, "/debug"),
+            tcb_svns: normalized::collect_claims(claims, [("svn", "/svn")]),
+            ..Default::default()
+        }
+    }
 }
 
 async fn verify_tee_evidence(
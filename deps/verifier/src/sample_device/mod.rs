@@ -41,6 +41,13 @@ impl Verifier for SampleDeviceVerifier {
         let claims = parse_tee_evidence(&tee_evidence)?;
         Ok(vec![(claims, "gpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        NormalizedClaims {
+            tcb_svns: normalized::collect_claims(claims, [("svn", "/svn")]),
+            ..Default::default()
+        }
+    }
 }
 
 async fn verify_tee_evidence(
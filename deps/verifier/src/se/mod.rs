@@ -9,6 +9,7 @@ use ibmse::SeVerifierImpl;
 use tokio::sync::OnceCell;
 use tracing::{instrument, warn};
 
+use crate::normalized::{self, NormalizedClaims};
 use crate::{InitDataHash, ReportData, TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
 
 pub mod ibmse;
@@ -48,4 +49,12 @@ impl Verifier for SeVerifier {
             .generate_supplemental_challenge(_tee_parameters)
             .await
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        // The SE header tag is bound to the image that was launched.
+        NormalizedClaims {
+            launch_measurement: normalized::str_claim(claims, "/tag"),
+            ..Default::default()
+        }
+    }
 }
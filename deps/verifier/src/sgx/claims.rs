@@ -41,10 +41,14 @@
 use anyhow::*;
 use serde_json::{Map, Value};
 
+use crate::normalized::{self, NormalizedClaims};
 use crate::TeeEvidenceParsedClaim;
 
 use super::types::*;
 
+/// SGX_FLAGS_DEBUG bit of the enclave attributes.
+const SGX_FLAGS_DEBUG: u8 = 0x02;
+
 macro_rules! parse_claim {
     ($map_name: ident, $key_name: literal, $field: ident) => {
         $map_name.insert($key_name.to_string(), serde_json::Value::Object($field))
@@ -109,6 +113,30 @@ pub fn generate_parsed_claims(quote: sgx_quote3_t) -> Result<TeeEvidenceParsedCl
     Ok(Value::Object(claims) as TeeEvidenceParsedClaim)
 }
 
+/// Maps the claims of [`generate_parsed_claims`] to [`NormalizedClaims`].
+pub(crate) fn normalize_sgx_claims(claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+    // The flags are a little endian u64, so the DEBUG bit is in the first byte.
+    let debug_enabled = normalized::str_claim(claims, "/body/attributes.flags")
+        .and_then(|flags| hex::decode(flags).ok())
+        .and_then(|flags| flags.first().map(|flags| flags & SGX_FLAGS_DEBUG != 0));
+
+    NormalizedClaims {
+        launch_measurement: normalized::str_claim(claims, "/body/mr_enclave"),
+        debug_enabled,
+        tcb_svns: normalized::collect_claims(
+            claims,
+            [
+                ("cpu_svn", "/body/cpu_svn"),
+                ("isv_svn", "/body/isv_svn"),
+                ("qe_svn", "/header/qe_svn"),
+                ("pce_svn", "/header/pce_svn"),
+            ],
+        ),
+        init_data_hash: normalized::str_claim(claims, "/init_data"),
+        ..Default::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_json_diff::assert_json_eq;
@@ -116,7 +144,7 @@ mod tests {
 
     use crate::sgx::parse_sgx_quote;
 
-    use super::generate_parsed_claims;
+    use super::{generate_parsed_claims, normalize_sgx_claims};
 
     #[test]
     fn parse_sgx_claims() {
@@ -158,4 +186,19 @@ mod tests {
 
         assert_json_eq!(expected, claims);
     }
+
+    #[test]
+    fn normalize_sgx_claims_from_quote() {
+        let quote_bin = include_bytes!("../../test_data/occlum_quote.dat");
+        let quote = parse_sgx_quote(quote_bin.as_slice()).expect("parse quote");
+        let claims = generate_parsed_claims(quote).expect("parse claim failed");
+        let normalized = normalize_sgx_claims(&claims);
+
+        assert_eq!(
+            normalized.launch_measurement.as_deref(),
+            Some("8f173e4613ff05c52aaf04162d234edae8c9977eae47eb2299ae16a553011c68")
+        );
+        assert_eq!(normalized.debug_enabled, Some(true));
+        assert_eq!(normalized.tcb_svns["qe_svn"], json!("0800"));
+    }
 }
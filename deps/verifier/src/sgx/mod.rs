@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
 use self::types::sgx_quote3_t;
-use super::{TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
+use super::{NormalizedClaims, TeeClass, TeeEvidence, TeeEvidenceParsedClaim, Verifier};
 use crate::intel_dcap::{ecdsa_quote_verification, extend_using_custom_claims};
 use crate::{regularize_data, InitDataHash, ReportData};
 
@@ -50,6 +50,10 @@ impl Verifier for SgxVerifier {
 
         Ok(vec![(claims, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        claims::normalize_sgx_claims(claims)
+    }
 }
 
 pub fn parse_sgx_quote(quote: &[u8]) -> Result<sgx_quote3_t> {
@@ -286,6 +286,13 @@ impl Verifier for Snp {
         let json = json!(claims_map);
         Ok(vec![(json, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        NormalizedClaims {
+            init_data_hash: normalized::base64_claim_as_hex(claims, "/init_data"),
+            ..normalize_snp_claims(claims)
+        }
+    }
 }
 
 /// Retrieves the octet string value for a given OID from a certificate's extensions.
@@ -408,6 +415,25 @@ pub(crate) fn parse_tee_evidence(report: &AttestationReport) -> TeeEvidenceParse
     claims_map as TeeEvidenceParsedClaim
 }
 
+/// Maps the claims of [`parse_tee_evidence`] to the [`NormalizedClaims`] that
+/// the SNP and Azure SNP vTPM verifiers have in common.
+pub(crate) fn normalize_snp_claims(claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+    NormalizedClaims {
+        launch_measurement: normalized::base64_claim_as_hex(claims, "/measurement"),
+        debug_enabled: normalized::bool_claim(claims, "/policy_debug_allowed"),
+        tcb_svns: normalized::collect_claims(
+            claims,
+            [
+                ("bootloader", "/reported_tcb_bootloader"),
+                ("tee", "/reported_tcb_tee"),
+                ("snp", "/reported_tcb_snp"),
+                ("microcode", "/reported_tcb_microcode"),
+            ],
+        ),
+        ..Default::default()
+    }
+}
+
 /// Extracts the common name (CN) from the subject name of a certificate.
 pub(crate) fn get_common_name(cert: &x509::X509) -> Result<String> {
     let mut entries = cert.subject_name().entries_by_nid(Nid::COMMONNAME);
@@ -0,0 +1,106 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Verification of TCG PC Client event logs against quoted TPM PCRs.
+
+use anyhow::{anyhow, Context, Result};
+use eventlog::{
+    ccel::tcg_enum::TcgAlgorithm, BootEvents, ReferenceMeasurement, SecureBootState, TcgEventLog,
+};
+use serde_json::{Map, Value};
+use tracing::{debug, warn};
+
+/// Parse a binary TCG event log, replay it and check every PCR it extends
+/// against the SHA-256 PCR values of the quote.
+///
+/// Returns the claims describing the event log:
+/// - `uefi_event_logs`: all parsed events
+/// - `boot_events`: EFI variables, boot applications, GRUB commands and the
+///   kernel command line, see [`BootEvents`]
+/// - `secure_boot`: Secure Boot state and signature databases, see [`SecureBootState`].
+///   Omitted if the signature databases cannot be decoded.
+pub fn verify_tcg_eventlog(event_log: Vec<u8>, pcrs: &[&[u8; 32]]) -> Result<Map<String, Value>> {
+    let event_log = TcgEventLog::try_from(event_log).context("Failed to parse TCG event log")?;
+
+    let references = event_log
+        .measured_pcrs()
+        .into_iter()
+        .map(|index| {
+            let pcr = pcrs
+                .get(index as usize)
+                .ok_or_else(|| anyhow!("PCR [{index}] of the event log is not quoted"))?;
+            Ok(ReferenceMeasurement {
+                index,
+                algorithm: TcgAlgorithm::Sha256,
+                reference: pcr.to_vec(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    event_log.replay_and_match(references)?;
+    debug!("TCG event log replay matches the quoted PCRs");
+
+    let mut claims = Map::new();
+    claims.insert(
+        "boot_events".to_string(),
+        serde_json::to_value(BootEvents::from_entries(&event_log.log))?,
+    );
+    match SecureBootState::from_entries(&event_log.log) {
+        Ok(secure_boot) => {
+            claims.insert(
+                "secure_boot".to_string(),
+                serde_json::to_value(secure_boot)?,
+            );
+        }
+        Err(e) => warn!("Omit the Secure Boot claims: {e:#}"),
+    }
+    claims.insert(
+        "uefi_event_logs".to_string(),
+        serde_json::to_value(event_log.log)?,
+    );
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCG_EVENTLOG: &[u8] =
+        include_bytes!("../../eventlog/test_data/tcg_binary_bios_measurements");
+
+    fn replayed_pcrs() -> Vec<[u8; 32]> {
+        let event_log = TcgEventLog::try_from(TCG_EVENTLOG.to_vec()).unwrap();
+        (0..24)
+            .map(|index| {
+                event_log
+                    .replay(index, TcgAlgorithm::Sha256)
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_tcg_eventlog() {
+        let pcrs = replayed_pcrs();
+        let pcrs: Vec<&[u8; 32]> = pcrs.iter().collect();
+        let claims = verify_tcg_eventlog(TCG_EVENTLOG.to_vec(), &pcrs).unwrap();
+        assert_eq!(
+            claims["boot_events"]["kernel_cmdline"],
+            "/vmlinuz root=/dev/vda1 ro"
+        );
+        assert_eq!(claims["uefi_event_logs"].as_array().unwrap().len(), 16);
+        assert_eq!(claims["secure_boot"]["enabled"], true);
+    }
+
+    #[test]
+    fn test_verify_tcg_eventlog_pcr_mismatch() {
+        let mut pcrs = replayed_pcrs();
+        pcrs[8][0] ^= 0xff;
+        let pcrs: Vec<&[u8; 32]> = pcrs.iter().collect();
+        let err = verify_tcg_eventlog(TCG_EVENTLOG.to_vec(), &pcrs).unwrap_err();
+        assert!(err.to_string().contains("PCR [8]"));
+    }
+}
@@ -12,10 +12,12 @@ use bitflags::{bitflags, Flags};
 use byteorder::{LittleEndian, ReadBytesExt};
 use serde_json::{Map, Value};
 use thiserror::Error;
+use tracing::warn;
 
 use super::quote::Quote;
+use crate::normalized::{self, NormalizedClaims};
 use crate::{tdx::quote::QuoteV5Body, TeeEvidenceParsedClaim};
-use eventlog::CcEventLog;
+use eventlog::{CcEventLog, SecureBootState};
 
 macro_rules! parse_claim {
     ($map_name: ident, $key_name: literal, $field: ident) => {
@@ -134,6 +136,16 @@ pub fn generate_parsed_claim(
     if let Some(ccel) = cc_eventlog {
         let result = serde_json::to_value(ccel.clone().log)?;
         claims.insert("uefi_event_logs".to_string(), result);
+
+        match SecureBootState::from_entries(&ccel.log) {
+            Ok(secure_boot) => {
+                claims.insert(
+                    "secure_boot".to_string(),
+                    serde_json::to_value(secure_boot)?,
+                );
+            }
+            Err(e) => warn!("Omit the Secure Boot claims: {e:#}"),
+        }
     }
 
     parse_claim!(claims, "quote", quote_map);
@@ -145,6 +157,31 @@ pub fn generate_parsed_claim(
     Ok(Value::Object(claims) as TeeEvidenceParsedClaim)
 }
 
+/// Maps the claims of [`generate_parsed_claim`] to [`NormalizedClaims`].
+pub(crate) fn normalize_tdx_claims(claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+    let runtime_measurements = (0..4)
+        .filter_map(|index| {
+            let rtmr = normalized::str_claim(claims, &format!("/quote/body/rtmr_{index}"))?;
+            Some((format!("rtmr{index}"), rtmr))
+        })
+        .collect();
+
+    NormalizedClaims {
+        launch_measurement: normalized::str_claim(claims, "/quote/body/mr_td"),
+        runtime_measurements,
+        debug_enabled: normalized::bool_claim(claims, "/td_attributes/debug"),
+        tcb_svns: normalized::collect_claims(
+            claims,
+            [
+                ("tcb_svn", "/quote/body/tcb_svn"),
+                ("tee_tcb_svn2", "/quote/body/tee_tcb_svn2"),
+            ],
+        ),
+        init_data_hash: normalized::str_claim(claims, "/init_data"),
+        ..Default::default()
+    }
+}
+
 bitflags! {
     #[derive(Debug, Clone)]
     struct TdAttributesFlags: u64 {
@@ -241,7 +278,7 @@ mod tests {
 
     use crate::tdx::{claims::PlatformConfigInfoError, quote::parse_tdx_quote};
 
-    use super::{generate_parsed_claim, TdShimPlatformConfigInfo};
+    use super::{generate_parsed_claim, normalize_tdx_claims, TdShimPlatformConfigInfo};
 
     use eventlog::CcEventLog;
     use rstest::rstest;
@@ -262,6 +299,30 @@ mod tests {
         assert_json_eq!(expected, claims);
     }
 
+    #[test]
+    fn normalize_tdx_claims_from_quote() {
+        let quote_bin = std::fs::read("./test_data/tdx_quote_4.dat").expect("read quote failed");
+        let quote = parse_tdx_quote(&quote_bin).expect("parse quote");
+        let claims = generate_parsed_claim(quote, None).expect("parse claim failed");
+        let normalized = normalize_tdx_claims(&claims);
+
+        assert_json_eq!(
+            serde_json::to_value(normalized).unwrap(),
+            serde_json::json!({
+                "launch_measurement": "705ee9381b8633a9fbe532b52345e8433343d2868959f57889d84ca377c395b689cac1599ccea1b7d420483a9ce5f031",
+                "runtime_measurements": {
+                    "rtmr0": "e940da7c2712d2790e2961e00484f4fa8e6f9eed71361655ae22699476b14f9e63867eb41edd4b480fef0c59f496b288",
+                    "rtmr1": "559cfcf42716ed6c40a48a73d5acb7da255435012f0a9f00fbe8c1c57612ede486a5684c4c9ff3ddf52315fcdca3a596",
+                    "rtmr2": "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                    "rtmr3": "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+                },
+                "debug_enabled": true,
+                "tcb_svns": {"tcb_svn": "03000500000000000000000000000000"},
+                "init_data_hash": "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+            })
+        );
+    }
+
     #[rstest]
     #[trace]
     #[case(b"", Err(PlatformConfigInfoError::InvalidHeader))]
@@ -1,9 +1,12 @@
-use eventlog::{ccel::tcg_enum::TcgAlgorithm, CcEventLog, ReferenceMeasurement};
+use eventlog::{
+    ccel::{event_tag::AaelEventEntry, tcg_enum::TcgAlgorithm},
+    AaEventlog, CcEventLog, ReferenceMeasurement,
+};
 
 use anyhow::anyhow;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::tdx::claims::generate_parsed_claim;
+use crate::tdx::claims::{generate_parsed_claim, normalize_tdx_claims};
 
 use super::*;
 use crate::intel_dcap::{ecdsa_quote_verification, extend_using_custom_claims};
@@ -11,6 +14,7 @@ use async_trait::async_trait;
 use base64::Engine;
 use quote::parse_tdx_quote;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 pub(crate) mod claims;
 pub(crate) mod quote;
@@ -22,6 +26,9 @@ struct TdxEvidence {
     cc_eventlog: Option<String>,
     // Base64 encoded TD quote.
     quote: String,
+    // Runtime event log of the attestation agent, extended into RTMR[3].
+    #[serde(default)]
+    aa_eventlog: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -45,6 +52,10 @@ impl Verifier for Tdx {
 
         Ok(vec![(claims, "cpu".to_string())])
     }
+
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        normalize_tdx_claims(claims)
+    }
 }
 
 async fn verify_evidence(
@@ -96,7 +107,7 @@ async fn verify_evidence(
                 .map_err(|e| anyhow!("Parse CC Eventlog failed: {:?}", e))?;
             ccel_option = Some(ccel.clone());
 
-            let compare_obj: Vec<ReferenceMeasurement> = vec![
+            let mut compare_obj: Vec<ReferenceMeasurement> = vec![
                 ReferenceMeasurement {
                     index: 1,
                     algorithm: TcgAlgorithm::Sha384,
@@ -112,12 +123,16 @@ async fn verify_evidence(
                     algorithm: TcgAlgorithm::Sha384,
                     reference: quote.rtmr_2().to_vec(),
                 },
-                ReferenceMeasurement {
+            ];
+
+            // RTMR[3] is covered by the AA eventlog if there is one.
+            if evidence.aa_eventlog.is_none() {
+                compare_obj.push(ReferenceMeasurement {
                     index: 4,
                     algorithm: TcgAlgorithm::Sha384,
                     reference: quote.rtmr_3().to_vec(),
-                },
-            ];
+                });
+            }
 
             ccel.replay_and_match(compare_obj)?;
             info!("EventLog integrity check succeeded.");
@@ -126,19 +141,48 @@ async fn verify_evidence(
             warn!("No Eventlog included inside the TDX evidence.");
         }
     }
+
+    // Verify Integrity of the AA eventlog
+    let runtime_events = match &evidence.aa_eventlog {
+        Some(aael) => {
+            let events = verify_aa_eventlog(aael, quote.rtmr_3())?;
+            info!("AA eventlog integrity check succeeded.");
+            Some(events)
+        }
+        None => None,
+    };
+
     // Return Evidence parsed claim
     let mut claim = generate_parsed_claim(quote, ccel_option)?;
+    if let Some(runtime_events) = runtime_events {
+        claim.as_object_mut().expect("Must be an object").insert(
+            "runtime_events".to_string(),
+            serde_json::to_value(runtime_events)?,
+        );
+    }
     extend_using_custom_claims(&mut claim, custom_claims)?;
 
     Ok(claim)
 }
 
+/// Replay the AA eventlog against RTMR[3] and return the verified runtime events.
+fn verify_aa_eventlog(aa_eventlog: &str, rtmr_3: &[u8]) -> Result<Vec<AaelEventEntry>> {
+    let aael = AaEventlog::from_str(aa_eventlog).context("Parse AA eventlog failed")?;
+    if aael.hash_algorithm != TcgAlgorithm::Sha384 {
+        bail!("AA eventlog must use sha384 to be replayed against RTMR[3]");
+    }
+    aael.replay_and_match(rtmr_3)?;
+    aael.entries()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::verify_aa_eventlog;
     use crate::tdx::claims::generate_parsed_claim;
     use crate::tdx::quote::parse_tdx_quote;
-    use eventlog::CcEventLog;
+    use eventlog::{AaEventlog, CcEventLog};
     use std::fs;
+    use std::str::FromStr;
 
     #[test]
     fn test_generate_parsed_claim() {
@@ -155,4 +199,23 @@ mod tests {
             format!("{:?}", parsed_claim.unwrap()),
         );
     }
+
+    #[test]
+    fn test_verify_aa_eventlog() {
+        let aael = format!(
+            "INIT sha384/{}\ngithub.com/confidential-containers PullImage {{\"image\":\"busybox\"}}\n",
+            "00".repeat(48)
+        );
+        let rtmr_3 = AaEventlog::from_str(&aael).unwrap().replay().unwrap();
+
+        let events = verify_aa_eventlog(&aael, &rtmr_3).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, "PullImage");
+        assert_eq!(events[0].content["image"], "busybox");
+
+        let err = verify_aa_eventlog(&aael, &[0; 48]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("AA eventlog does not pass RTMR check"));
+    }
 }
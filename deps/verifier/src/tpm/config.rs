@@ -6,7 +6,7 @@ use serde::Deserialize;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use super::{DEFAULT_TRUSTED_AK_KEYS_DIR, MAX_TRUSTED_AK_KEYS};
+use super::{DEFAULT_CREDENTIAL_MAX_AGE_SECS, DEFAULT_TRUSTED_AK_KEYS_DIR, MAX_TRUSTED_AK_KEYS};
 
 #[derive(Deserialize, Debug, Default)]
 pub struct Config {
@@ -20,6 +20,19 @@ pub struct TpmVerifierConfig {
     /// Maximum number of trusted AK keys to load. Defaults to 100.
     #[serde(default = "default_max_trusted_ak_keys")]
     pub max_trusted_ak_keys: usize,
+    /// Directory of PEM files with TPM manufacturer CA certificates. When set,
+    /// AKs that are bound to an EK certificate issued by one of these CAs are
+    /// trusted as well.
+    #[serde(default)]
+    pub ek_ca_bundles_dir: Option<PathBuf>,
+    /// File with the key used to derive AK binding credentials. Verifiers that
+    /// share the key can answer each other's challenges. Defaults to a random
+    /// key per process.
+    #[serde(default)]
+    pub credential_key_path: Option<PathBuf>,
+    /// Maximum age of an AK binding challenge in seconds. Defaults to 300.
+    #[serde(default = "default_credential_max_age_secs")]
+    pub credential_max_age_secs: u64,
 }
 
 fn default_trusted_ak_keys_dir() -> PathBuf {
@@ -30,11 +43,18 @@ fn default_max_trusted_ak_keys() -> usize {
     MAX_TRUSTED_AK_KEYS
 }
 
+fn default_credential_max_age_secs() -> u64 {
+    DEFAULT_CREDENTIAL_MAX_AGE_SECS
+}
+
 impl Default for TpmVerifierConfig {
     fn default() -> Self {
         Self {
             trusted_ak_keys_dir: default_trusted_ak_keys_dir(),
             max_trusted_ak_keys: default_max_trusted_ak_keys(),
+            ek_ca_bundles_dir: None,
+            credential_key_path: None,
+            credential_max_age_secs: default_credential_max_age_secs(),
         }
     }
 }
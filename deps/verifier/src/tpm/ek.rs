@@ -0,0 +1,813 @@
+// Copyright (C) Copyright Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! EK certificate based AK trust.
+//!
+//! Instead of pre-provisioning every AK public key, the attester can present
+//! its EK certificate together with the TPM public area of its AK. The
+//! verifier checks the EK certificate against the configured manufacturer CA
+//! bundles and answers with a `TPM2_MakeCredential` challenge targeted at the
+//! EK and the AK name. Only the TPM holding both keys can recover the
+//! credential with `TPM2_ActivateCredential`, so returning it in the evidence
+//! binds the AK to the EK.
+//!
+//! The credential is derived with an HMAC over the AK name, the EK certificate
+//! and the issuing time, which keeps the verifier stateless between the
+//! challenge and the evaluation.
+//!
+//! Only the standard low range EK templates are supported, i.e. RSA 2048 and
+//! ECC NIST P-256 EKs with SHA-256 name algorithm and AES-128-CFB symmetric
+//! protection.
+
+use anyhow::*;
+use base64::{engine::general_purpose, Engine};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroupRef, EcKey};
+use openssl::encrypt::Encrypter;
+use openssl::hash::{hash, MessageDigest};
+use openssl::pkey::{Id, PKey, PKeyRef, Public};
+use openssl::rsa::Padding;
+use openssl::sign::Signer;
+use openssl::stack::Stack;
+use openssl::symm::{encrypt, Cipher};
+use openssl::x509::store::{X509Store, X509StoreBuilder};
+use openssl::x509::verify::X509VerifyFlags;
+use openssl::x509::{X509Ref, X509StoreContext, X509};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::result::Result::Ok;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+use tss_esapi::interface_types::algorithm::HashingAlgorithm;
+use tss_esapi::structures::Public as TpmPublic;
+use tss_esapi::traits::UnMarshall;
+
+/// Size in bytes of the credential protected by `TPM2_MakeCredential`.
+const CREDENTIAL_SIZE: usize = 32;
+
+/// Size in bytes of the symmetric key of the standard EK templates (AES-128).
+const EK_SYMMETRIC_KEY_SIZE: usize = 16;
+
+const TPM2_ALG_SHA1: u16 = 0x0004;
+const TPM2_ALG_SHA256: u16 = 0x000B;
+const TPM2_ALG_SHA384: u16 = 0x000C;
+const TPM2_ALG_SHA512: u16 = 0x000D;
+
+/// Critical extensions that OpenSSL checks itself when verifying a chain.
+const HANDLED_CRITICAL_EXTENSIONS: [&str; 8] = [
+    "2.5.29.15", // keyUsage
+    "2.5.29.19", // basicConstraints
+    "2.5.29.30", // nameConstraints
+    "2.5.29.32", // certificatePolicies
+    "2.5.29.33", // policyMappings
+    "2.5.29.36", // policyConstraints
+    "2.5.29.37", // extKeyUsage
+    "2.5.29.54", // inhibitAnyPolicy
+];
+
+/// EK certificates with an empty subject carry a critical subject alternative
+/// name with TPM specific attributes that OpenSSL does not understand. This is
+/// the only critical extension the CA store may ignore.
+const EK_SUBJECT_ALT_NAME: &str = "2.5.29.17";
+
+/// Process wide fallback key used to derive credentials when no
+/// `credential_key_path` is configured.
+static EPHEMERAL_CREDENTIAL_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Parameters sent by the attester to request an AK binding challenge.
+#[derive(Deserialize, Debug)]
+pub struct AkBindingRequest {
+    /// Base64 encoded DER EK certificate.
+    pub ek_cert: String,
+    /// Base64 encoded DER intermediate certificates between the EK certificate
+    /// and the configured manufacturer CAs.
+    #[serde(default)]
+    pub ek_cert_chain: Vec<String>,
+    /// Base64 encoded marshalled `TPMT_PUBLIC` of the AK.
+    pub ak_tpm_public: String,
+}
+
+/// The `TPM2_MakeCredential` challenge returned to the attester.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AkBindingChallenge {
+    /// Base64 encoded contents of the `TPM2B_ID_OBJECT`.
+    pub credential_blob: String,
+    /// Base64 encoded contents of the `TPM2B_ENCRYPTED_SECRET`.
+    pub encrypted_secret: String,
+    /// Seconds since the UNIX epoch at which the challenge was issued. Must be
+    /// echoed back in the evidence.
+    pub issued_at: u64,
+}
+
+/// AK binding part of the TPM evidence.
+#[derive(Deserialize, Debug)]
+pub struct AkBinding {
+    #[serde(flatten)]
+    pub request: AkBindingRequest,
+    /// Base64 encoded credential recovered by `TPM2_ActivateCredential`.
+    pub activated_credential: String,
+    /// The `issued_at` value of the answered challenge.
+    pub issued_at: u64,
+}
+
+/// Verifies EK certificates and issues and checks AK binding credentials.
+#[derive(Debug)]
+pub struct EkTrust {
+    ca_store: X509Store,
+    credential_key: Vec<u8>,
+    credential_max_age_secs: u64,
+}
+
+/// An AK whose TPM public area has been checked against its DER public key.
+struct CheckedAk {
+    name: Vec<u8>,
+}
+
+/// An EK certificate that chains up to one of the manufacturer CAs.
+struct CheckedEk {
+    public_key: PKey<Public>,
+    cert_digest: Vec<u8>,
+}
+
+impl EkTrust {
+    pub fn new(
+        ca_bundles_dir: &Path,
+        credential_key_path: Option<&Path>,
+        credential_max_age_secs: u64,
+    ) -> Result<Self> {
+        let mut builder = X509StoreBuilder::new()?;
+        // Unhandled critical extensions are ignored by OpenSSL so that it
+        // accepts the subject alternative name of EK certificates. All the
+        // other ones are rejected by `check_critical_extensions`.
+        builder.set_flags(X509VerifyFlags::IGNORE_CRITICAL)?;
+
+        let mut loaded = 0;
+        let dir_entries = fs::read_dir(ca_bundles_dir).with_context(|| {
+            format!("Failed to read EK CA bundles directory {ca_bundles_dir:?}")
+        })?;
+        for entry in dir_entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let certs = match fs::read(&path)
+                .map_err(Error::from)
+                .and_then(|pem| X509::stack_from_pem(&pem).map_err(Error::from))
+            {
+                Ok(certs) => certs,
+                Err(e) => {
+                    warn!("Failed to load EK CA bundle from {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for cert in certs {
+                if let Err(e) = check_critical_extensions(&cert, false) {
+                    warn!("Skip an EK CA certificate of {:?}: {:#}", path, e);
+                    continue;
+                }
+                builder.add_cert(cert)?;
+                loaded += 1;
+            }
+        }
+        debug!("TPM verifier loaded {loaded} EK CA certificate(s)");
+
+        let credential_key = match credential_key_path {
+            Some(path) => {
+                fs::read(path).with_context(|| format!("Failed to read credential key {path:?}"))?
+            }
+            None => match EPHEMERAL_CREDENTIAL_KEY.get() {
+                Some(key) => key.clone(),
+                None => {
+                    let mut key = vec![0u8; 32];
+                    openssl::rand::rand_bytes(&mut key)
+                        .context("Failed to generate TPM credential key")?;
+                    EPHEMERAL_CREDENTIAL_KEY.get_or_init(|| key).clone()
+                }
+            },
+        };
+
+        Ok(Self {
+            ca_store: builder.build(),
+            credential_key,
+            credential_max_age_secs,
+        })
+    }
+
+    /// Check the EK certificate of `request` and create a credential for its AK.
+    pub fn challenge(&self, request: &AkBindingRequest) -> Result<AkBindingChallenge> {
+        let ek = self.verify_ek_cert(request)?;
+        let ak = parse_ak_tpm_public(&request.ak_tpm_public, None)?;
+
+        let issued_at = now()?;
+        let credential = self.credential(&ak, &ek, issued_at)?;
+        let (credential_blob, encrypted_secret) =
+            make_credential(&ek.public_key, &ak.name, &credential)?;
+
+        Ok(AkBindingChallenge {
+            credential_blob: general_purpose::STANDARD.encode(credential_blob),
+            encrypted_secret: general_purpose::STANDARD.encode(encrypted_secret),
+            issued_at,
+        })
+    }
+
+    /// Check that `binding` proves that `ak_public` resides in the TPM of a
+    /// genuine EK. Returns the EK certificate on success.
+    pub fn verify(&self, binding: &AkBinding, ak_public: &PKey<Public>) -> Result<X509> {
+        let ek = self.verify_ek_cert(&binding.request)?;
+        let ak = parse_ak_tpm_public(&binding.request.ak_tpm_public, Some(ak_public))?;
+
+        let age = now()?.saturating_sub(binding.issued_at);
+        if age > self.credential_max_age_secs {
+            bail!("AK binding credential expired {age} seconds after issuance");
+        }
+
+        let expected = self.credential(&ak, &ek, binding.issued_at)?;
+        let activated = general_purpose::STANDARD
+            .decode(&binding.activated_credential)
+            .context("Failed to decode activated credential from base64")?;
+        if activated.len() != expected.len() || !openssl::memcmp::eq(&activated, &expected) {
+            bail!("Activated credential does not match, the AK is not bound to the EK");
+        }
+
+        decode_cert(&binding.request.ek_cert)
+    }
+
+    fn verify_ek_cert(&self, request: &AkBindingRequest) -> Result<CheckedEk> {
+        let ek_cert = decode_cert(&request.ek_cert).context("Invalid EK certificate")?;
+        check_critical_extensions(&ek_cert, true).context("Invalid EK certificate")?;
+        let mut chain = Stack::new()?;
+        for cert in &request.ek_cert_chain {
+            let cert = decode_cert(cert).context("Invalid EK certificate chain")?;
+            check_critical_extensions(&cert, false).context("Invalid EK certificate chain")?;
+            chain.push(cert)?;
+        }
+
+        let mut context = X509StoreContext::new()?;
+        let (verified, verify_result) = context.init(&self.ca_store, &ek_cert, &chain, |c| {
+            let verified = c.verify_cert()?;
+            Ok((verified, c.error()))
+        })?;
+        if !verified {
+            bail!("EK certificate verification failed: {verify_result}");
+        }
+
+        let public_key = ek_cert.public_key()?;
+        let cert_digest = hash(MessageDigest::sha256(), &ek_cert.to_der()?)?.to_vec();
+        Ok(CheckedEk {
+            public_key,
+            cert_digest,
+        })
+    }
+
+    fn credential(&self, ak: &CheckedAk, ek: &CheckedEk, issued_at: u64) -> Result<Vec<u8>> {
+        let key = PKey::hmac(&self.credential_key)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+        signer.update(b"trustee tpm ak binding")?;
+        signer.update(&ak.name)?;
+        signer.update(&ek.cert_digest)?;
+        signer.update(&issued_at.to_be_bytes())?;
+        let mut credential = signer.sign_to_vec()?;
+        credential.truncate(CREDENTIAL_SIZE);
+        Ok(credential)
+    }
+}
+
+/// Fail if `cert` has a critical extension that OpenSSL does not check, other
+/// than the subject alternative name of an EK certificate.
+fn check_critical_extensions(cert: &X509Ref, is_ek: bool) -> Result<()> {
+    let der = cert.to_der()?;
+    let (_, cert) = x509_parser::parse_x509_certificate(&der)
+        .map_err(|e| anyhow!("Failed to parse certificate: {e}"))?;
+    for extension in cert.extensions().iter().filter(|ext| ext.critical) {
+        let oid = extension.oid.to_id_string();
+        if HANDLED_CRITICAL_EXTENSIONS.contains(&oid.as_str())
+            || (is_ek && oid == EK_SUBJECT_ALT_NAME)
+        {
+            continue;
+        }
+        bail!("Unhandled critical extension {oid}");
+    }
+    Ok(())
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+fn decode_cert(cert: &str) -> Result<X509> {
+    let der = general_purpose::STANDARD
+        .decode(cert)
+        .context("Failed to decode certificate from base64")?;
+    X509::from_der(&der).context("Failed to parse DER certificate")
+}
+
+/// Parse the marshalled `TPMT_PUBLIC` of an AK, check that it describes a
+/// restricted signing key that cannot leave the TPM and compute its name.
+///
+/// If `ak_public` is given, the key in the public area must be the same key.
+fn parse_ak_tpm_public(ak_tpm_public: &str, ak_public: Option<&PKey<Public>>) -> Result<CheckedAk> {
+    let marshalled = general_purpose::STANDARD
+        .decode(ak_tpm_public)
+        .context("Failed to decode AK TPM public area from base64")?;
+    let public =
+        TpmPublic::unmarshall(&marshalled).context("Failed to unmarshal AK TPMT_PUBLIC")?;
+
+    let attributes = public.object_attributes();
+    if !(attributes.fixed_tpm()
+        && attributes.fixed_parent()
+        && attributes.restricted()
+        && attributes.sign_encrypt())
+    {
+        bail!("AK must be a restricted signing key with fixedTPM and fixedParent set");
+    }
+
+    if let Some(ak_public) = ak_public {
+        let matches = match &public {
+            TpmPublic::Rsa { unique, .. } => {
+                let rsa = ak_public.rsa().context("AK TPM public area is RSA")?;
+                rsa.n().to_vec() == unique.value()
+            }
+            TpmPublic::Ecc { unique, .. } => {
+                let ec = ak_public.ec_key().context("AK TPM public area is ECC")?;
+                let (x, y) = point_coordinates(ec.group(), ec.public_key())?;
+                trim_leading_zeros(&x) == trim_leading_zeros(unique.x().value())
+                    && trim_leading_zeros(&y) == trim_leading_zeros(unique.y().value())
+            }
+            _ => bail!("Unsupported AK type, expected RSA or ECC"),
+        };
+        if !matches {
+            bail!("AK TPM public area does not match the AK public key");
+        }
+    }
+
+    let (alg_id, digest) = match public.name_hashing_algorithm() {
+        HashingAlgorithm::Sha1 => (TPM2_ALG_SHA1, MessageDigest::sha1()),
+        HashingAlgorithm::Sha256 => (TPM2_ALG_SHA256, MessageDigest::sha256()),
+        HashingAlgorithm::Sha384 => (TPM2_ALG_SHA384, MessageDigest::sha384()),
+        HashingAlgorithm::Sha512 => (TPM2_ALG_SHA512, MessageDigest::sha512()),
+        other => bail!("Unsupported AK name algorithm {other:?}"),
+    };
+    let mut name = alg_id.to_be_bytes().to_vec();
+    name.extend_from_slice(&hash(digest, &marshalled)?);
+
+    Ok(CheckedAk { name })
+}
+
+fn trim_leading_zeros(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    &value[start..]
+}
+
+fn point_coordinates(
+    group: &EcGroupRef,
+    point: &openssl::ec::EcPointRef,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let size = group.degree().div_ceil(8) as i32;
+    let mut ctx = BigNumContext::new()?;
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    point.affine_coordinates(group, &mut x, &mut y, &mut ctx)?;
+    Ok((x.to_vec_padded(size)?, y.to_vec_padded(size)?))
+}
+
+fn marshal_tpm2b(out: &mut Vec<u8>, data: &[u8]) -> Result<()> {
+    let size = u16::try_from(data.len()).context("TPM2B buffer too large")?;
+    out.extend_from_slice(&size.to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+/// KDFa from TPM 2.0 Part 1, section 11.4.10.2, with SHA-256.
+fn kdf_a(
+    key: &[u8],
+    label: &[u8],
+    context_u: &[u8],
+    context_v: &[u8],
+    bits: u32,
+) -> Result<Vec<u8>> {
+    let bytes = bits.div_ceil(8) as usize;
+    let hmac_key = PKey::hmac(key)?;
+    let mut out = Vec::with_capacity(bytes);
+    let mut counter: u32 = 0;
+    while out.len() < bytes {
+        counter += 1;
+        let mut signer = Signer::new(MessageDigest::sha256(), &hmac_key)?;
+        signer.update(&counter.to_be_bytes())?;
+        signer.update(label)?;
+        signer.update(&[0])?;
+        signer.update(context_u)?;
+        signer.update(context_v)?;
+        signer.update(&bits.to_be_bytes())?;
+        out.extend_from_slice(&signer.sign_to_vec()?);
+    }
+    out.truncate(bytes);
+    Ok(out)
+}
+
+/// KDFe from TPM 2.0 Part 1, section 11.4.10.3, with SHA-256.
+fn kdf_e(z: &[u8], label: &[u8], party_u: &[u8], party_v: &[u8], bits: u32) -> Result<Vec<u8>> {
+    let bytes = bits.div_ceil(8) as usize;
+    let mut out = Vec::with_capacity(bytes);
+    let mut counter: u32 = 0;
+    while out.len() < bytes {
+        counter += 1;
+        let mut input = counter.to_be_bytes().to_vec();
+        input.extend_from_slice(z);
+        input.extend_from_slice(label);
+        input.push(0);
+        input.extend_from_slice(party_u);
+        input.extend_from_slice(party_v);
+        out.extend_from_slice(&hash(MessageDigest::sha256(), &input)?);
+    }
+    out.truncate(bytes);
+    Ok(out)
+}
+
+/// Create the seed and its encryption for the EK, as `TPM2_MakeCredential`
+/// does with the `IDENTITY` label.
+fn create_seed(ek: &PKeyRef<Public>) -> Result<(Vec<u8>, Vec<u8>)> {
+    let digest_size = MessageDigest::sha256().size();
+    match ek.id() {
+        Id::RSA => {
+            let mut seed = vec![0u8; digest_size];
+            openssl::rand::rand_bytes(&mut seed)?;
+
+            let mut encrypter = Encrypter::new(ek)?;
+            encrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+            encrypter.set_rsa_oaep_md(MessageDigest::sha256())?;
+            encrypter.set_rsa_mgf1_md(MessageDigest::sha256())?;
+            encrypter.set_rsa_oaep_label(b"IDENTITY\0")?;
+            let mut encrypted = vec![0u8; encrypter.encrypt_len(&seed)?];
+            let len = encrypter.encrypt(&seed, &mut encrypted)?;
+            encrypted.truncate(len);
+            Ok((seed, encrypted))
+        }
+        Id::EC => {
+            let ek_ec = ek.ec_key()?;
+            let group = ek_ec.group();
+            let ephemeral = PKey::from_ec_key(EcKey::generate(group)?)?;
+
+            let mut deriver = Deriver::new(&ephemeral)?;
+            deriver.set_peer(ek)?;
+            let z = deriver.derive_to_vec()?;
+
+            let ephemeral_ec = ephemeral.ec_key()?;
+            let (ephemeral_x, ephemeral_y) = point_coordinates(group, ephemeral_ec.public_key())?;
+            let (ek_x, _) = point_coordinates(group, ek_ec.public_key())?;
+
+            let seed = kdf_e(
+                &z,
+                b"IDENTITY",
+                &ephemeral_x,
+                &ek_x,
+                (digest_size * 8) as u32,
+            )?;
+
+            let mut encrypted = Vec::new();
+            marshal_tpm2b(&mut encrypted, &ephemeral_x)?;
+            marshal_tpm2b(&mut encrypted, &ephemeral_y)?;
+            Ok((seed, encrypted))
+        }
+        _ => bail!("Unsupported EK type, expected RSA or ECC"),
+    }
+}
+
+/// Software implementation of `TPM2_MakeCredential`.
+///
+/// Returns the contents of the `TPM2B_ID_OBJECT` and of the
+/// `TPM2B_ENCRYPTED_SECRET` to be passed to `TPM2_ActivateCredential`.
+pub(crate) fn make_credential(
+    ek: &PKeyRef<Public>,
+    ak_name: &[u8],
+    credential: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let (seed, encrypted_secret) = create_seed(ek)?;
+    let digest_size = MessageDigest::sha256().size();
+
+    let sym_key = kdf_a(
+        &seed,
+        b"STORAGE",
+        ak_name,
+        &[],
+        (EK_SYMMETRIC_KEY_SIZE * 8) as u32,
+    )?;
+    let mut plain_identity = Vec::new();
+    marshal_tpm2b(&mut plain_identity, credential)?;
+    let enc_identity = encrypt(
+        Cipher::aes_128_cfb128(),
+        &sym_key,
+        Some(&[0u8; 16]),
+        &plain_identity,
+    )?;
+
+    let hmac_key = kdf_a(&seed, b"INTEGRITY", &[], &[], (digest_size * 8) as u32)?;
+    let hmac_key = PKey::hmac(&hmac_key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &hmac_key)?;
+    signer.update(&enc_identity)?;
+    signer.update(ak_name)?;
+    let outer_hmac = signer.sign_to_vec()?;
+
+    let mut id_object = Vec::new();
+    marshal_tpm2b(&mut id_object, &outer_hmac)?;
+    id_object.extend_from_slice(&enc_identity);
+
+    Ok((id_object, encrypted_secret))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use openssl::asn1::{Asn1Object, Asn1OctetString, Asn1Time};
+    use openssl::decrypt::Decrypter;
+    use openssl::ec::EcGroup;
+    use openssl::nid::Nid;
+    use openssl::pkey::Private;
+    use openssl::rsa::Rsa;
+    use openssl::symm::decrypt;
+    use openssl::x509::{X509Builder, X509Extension, X509NameBuilder};
+
+    /// Software implementation of `TPM2_ActivateCredential`.
+    pub(crate) fn activate_credential(
+        ek: &PKey<Private>,
+        ak_name: &[u8],
+        id_object: &[u8],
+        encrypted_secret: &[u8],
+    ) -> Result<Vec<u8>> {
+        let seed = match ek.id() {
+            Id::RSA => {
+                let mut decrypter = Decrypter::new(ek)?;
+                decrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+                decrypter.set_rsa_oaep_md(MessageDigest::sha256())?;
+                decrypter.set_rsa_mgf1_md(MessageDigest::sha256())?;
+                decrypter.set_rsa_oaep_label(b"IDENTITY\0")?;
+                let mut seed = vec![0u8; decrypter.decrypt_len(encrypted_secret)?];
+                let len = decrypter.decrypt(encrypted_secret, &mut seed)?;
+                seed.truncate(len);
+                seed
+            }
+            _ => {
+                let x_len = u16::from_be_bytes([encrypted_secret[0], encrypted_secret[1]]) as usize;
+                let x = &encrypted_secret[2..2 + x_len];
+                let y = &encrypted_secret[4 + x_len..];
+                let ek_ec = ek.ec_key()?;
+                let group = ek_ec.group();
+                let ephemeral = EcKey::from_public_key_affine_coordinates(
+                    group,
+                    &BigNum::from_slice(x)?,
+                    &BigNum::from_slice(y)?,
+                )?;
+                let ephemeral = PKey::from_ec_key(ephemeral)?;
+                let mut deriver = Deriver::new(ek)?;
+                deriver.set_peer(&ephemeral)?;
+                let z = deriver.derive_to_vec()?;
+                let (ek_x, _) = point_coordinates(group, ek_ec.public_key())?;
+                kdf_e(&z, b"IDENTITY", x, &ek_x, 256)?
+            }
+        };
+
+        let hmac_len = u16::from_be_bytes([id_object[0], id_object[1]]) as usize;
+        let outer_hmac = &id_object[2..2 + hmac_len];
+        let enc_identity = &id_object[2 + hmac_len..];
+
+        let hmac_key = PKey::hmac(&kdf_a(&seed, b"INTEGRITY", &[], &[], 256)?)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &hmac_key)?;
+        signer.update(enc_identity)?;
+        signer.update(ak_name)?;
+        ensure!(
+            signer.sign_to_vec()? == outer_hmac,
+            "integrity check failed"
+        );
+
+        let sym_key = kdf_a(&seed, b"STORAGE", ak_name, &[], 128)?;
+        let plain = decrypt(
+            Cipher::aes_128_cfb128(),
+            &sym_key,
+            Some(&[0u8; 16]),
+            enc_identity,
+        )?;
+        Ok(plain[2..].to_vec())
+    }
+
+    fn build_cert(
+        subject: &str,
+        public_key: &PKeyRef<impl openssl::pkey::HasPublic>,
+        issuer: Option<&X509>,
+        signing_key: &PKey<Private>,
+        extension: Option<X509Extension>,
+    ) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, subject).unwrap();
+        let name = name.build();
+
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        match issuer {
+            Some(issuer) => builder.set_issuer_name(issuer.subject_name()).unwrap(),
+            None => builder.set_issuer_name(&name).unwrap(),
+        }
+        builder.set_pubkey(public_key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        if issuer.is_none() {
+            builder
+                .append_extension(
+                    openssl::x509::extension::BasicConstraints::new()
+                        .critical()
+                        .ca()
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap();
+        }
+        if let Some(extension) = extension {
+            builder.append_extension(extension).unwrap();
+        }
+        builder.sign(signing_key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    /// A critical extension with the given OID and DER contents.
+    fn critical_extension(oid: &str, der: &[u8]) -> X509Extension {
+        X509Extension::new_from_der(
+            &Asn1Object::from_str(oid).unwrap(),
+            true,
+            &Asn1OctetString::new_from_bytes(der).unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Create a manufacturer CA, write it to `dir` and issue an EK
+    /// certificate for `ek`.
+    pub(crate) fn issue_ek_cert(dir: &Path, ek: &PKey<Private>) -> X509 {
+        issue_ek_cert_with_extension(dir, ek, None)
+    }
+
+    fn issue_ek_cert_with_extension(
+        dir: &Path,
+        ek: &PKey<Private>,
+        extension: Option<X509Extension>,
+    ) -> X509 {
+        let ca_key = PKey::from_ec_key(
+            EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let ca = build_cert("Test TPM Manufacturer CA", &ca_key, None, &ca_key, None);
+        fs::write(dir.join("manufacturer.pem"), ca.to_pem().unwrap()).unwrap();
+        build_cert("Test EK", ek, Some(&ca), &ca_key, extension)
+    }
+
+    /// Marshal a `TPMT_PUBLIC` for a restricted ECDSA P-256 signing key.
+    pub(crate) fn marshal_ecc_ak_public(ak: &PKeyRef<impl openssl::pkey::HasPublic>) -> Vec<u8> {
+        let ec = ak.ec_key().unwrap();
+        let (x, y) = point_coordinates(ec.group(), ec.public_key()).unwrap();
+        let mut out = Vec::new();
+        out.extend_from_slice(&0x0023u16.to_be_bytes()); // TPM2_ALG_ECC
+        out.extend_from_slice(&TPM2_ALG_SHA256.to_be_bytes());
+        out.extend_from_slice(&0x0005_0072u32.to_be_bytes()); // fixedTPM|fixedParent|sensitiveDataOrigin|userWithAuth|restricted|sign
+        marshal_tpm2b(&mut out, &[]).unwrap(); // authPolicy
+        out.extend_from_slice(&0x0010u16.to_be_bytes()); // symmetric: TPM2_ALG_NULL
+        out.extend_from_slice(&0x0018u16.to_be_bytes()); // scheme: TPM2_ALG_ECDSA
+        out.extend_from_slice(&TPM2_ALG_SHA256.to_be_bytes());
+        out.extend_from_slice(&0x0003u16.to_be_bytes()); // TPM2_ECC_NIST_P256
+        out.extend_from_slice(&0x0010u16.to_be_bytes()); // kdf: TPM2_ALG_NULL
+        marshal_tpm2b(&mut out, &x).unwrap();
+        marshal_tpm2b(&mut out, &y).unwrap();
+        out
+    }
+
+    fn roundtrip(ek: PKey<Private>) {
+        let ek_public = PKey::public_key_from_der(&ek.public_key_to_der().unwrap()).unwrap();
+        let ak_name = [&[0x00, 0x0b][..], &[0x5a; 32]].concat();
+        let credential = [0x42; CREDENTIAL_SIZE];
+
+        let (id_object, encrypted_secret) =
+            make_credential(&ek_public, &ak_name, &credential).unwrap();
+        let activated = activate_credential(&ek, &ak_name, &id_object, &encrypted_secret).unwrap();
+        assert_eq!(activated, credential);
+
+        let other_name = [&[0x00, 0x0b][..], &[0xa5; 32]].concat();
+        assert!(activate_credential(&ek, &other_name, &id_object, &encrypted_secret).is_err());
+    }
+
+    #[test]
+    fn test_make_credential_rsa_ek() {
+        roundtrip(PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_make_credential_ecc_ek() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        roundtrip(PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_ak_binding_challenge_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let ek = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let ek_cert = issue_ek_cert(dir.path(), &ek);
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let ak_public = PKey::public_key_from_der(&ak.public_key_to_der().unwrap()).unwrap();
+        let ak_tpm_public = marshal_ecc_ak_public(&ak);
+
+        let trust = EkTrust::new(dir.path(), None, 60).unwrap();
+        let request = AkBindingRequest {
+            ek_cert: general_purpose::STANDARD.encode(ek_cert.to_der().unwrap()),
+            ek_cert_chain: vec![],
+            ak_tpm_public: general_purpose::STANDARD.encode(&ak_tpm_public),
+        };
+        let challenge = trust.challenge(&request).unwrap();
+
+        let ak_name = parse_ak_tpm_public(&request.ak_tpm_public, Some(&ak_public))
+            .unwrap()
+            .name;
+        let activated = activate_credential(
+            &ek,
+            &ak_name,
+            &general_purpose::STANDARD
+                .decode(&challenge.credential_blob)
+                .unwrap(),
+            &general_purpose::STANDARD
+                .decode(&challenge.encrypted_secret)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let mut binding = AkBinding {
+            request,
+            activated_credential: general_purpose::STANDARD.encode(&activated),
+            issued_at: challenge.issued_at,
+        };
+        trust.verify(&binding, &ak_public).unwrap();
+
+        // A different AK must not be accepted with the same binding
+        let other_ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let other_ak_public =
+            PKey::public_key_from_der(&other_ak.public_key_to_der().unwrap()).unwrap();
+        assert!(trust.verify(&binding, &other_ak_public).is_err());
+
+        // Neither must a forged credential
+        binding.activated_credential = general_purpose::STANDARD.encode([0u8; CREDENTIAL_SIZE]);
+        assert!(trust.verify(&binding, &ak_public).is_err());
+    }
+
+    #[test]
+    fn test_untrusted_ek_cert() {
+        let trusted_dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        let ek = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        issue_ek_cert(trusted_dir.path(), &ek);
+        let rogue_cert = issue_ek_cert(other_dir.path(), &ek);
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let trust = EkTrust::new(trusted_dir.path(), None, 60).unwrap();
+        let request = AkBindingRequest {
+            ek_cert: general_purpose::STANDARD.encode(rogue_cert.to_der().unwrap()),
+            ek_cert_chain: vec![],
+            ak_tpm_public: general_purpose::STANDARD.encode(marshal_ecc_ak_public(&ak)),
+        };
+        assert!(trust.challenge(&request).is_err());
+    }
+
+    #[test]
+    fn test_ek_cert_critical_extensions() {
+        let ek = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        // A critical subject alternative name (`dNSName` "tpm") is accepted,
+        // a critical extension unknown to OpenSSL is not.
+        for (oid, der, accepted) in [
+            (
+                EK_SUBJECT_ALT_NAME,
+                &[0x30, 0x05, 0x82, 0x03, b't', b'p', b'm'][..],
+                true,
+            ),
+            ("1.3.6.1.4.1.99999.1", &[0x05, 0x00][..], false),
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            let ek_cert =
+                issue_ek_cert_with_extension(dir.path(), &ek, Some(critical_extension(oid, der)));
+            let trust = EkTrust::new(dir.path(), None, 60).unwrap();
+            let request = AkBindingRequest {
+                ek_cert: general_purpose::STANDARD.encode(ek_cert.to_der().unwrap()),
+                ek_cert_chain: vec![],
+                ak_tpm_public: general_purpose::STANDARD.encode(marshal_ecc_ak_public(&ak)),
+            };
+            assert_eq!(trust.challenge(&request).is_ok(), accepted, "{oid}");
+        }
+    }
+}
@@ -8,7 +8,11 @@ use async_trait::async_trait;
 use az_cvm_vtpm::vtpm::Quote as VtpmQuote;
 use base64::{engine::general_purpose, Engine};
 use hex;
-use openssl::pkey::PKey;
+use openssl::bn::BigNum;
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Public};
+use openssl::rsa::Padding;
 use serde::Deserialize;
 use serde_json::{self, json};
 use sha2::{Digest, Sha256};
@@ -16,22 +20,33 @@ use std::collections::HashSet;
 use std::fs;
 use std::result::Result::Ok;
 use tracing::{debug, info, instrument, warn};
+use tss_esapi::interface_types::algorithm::HashingAlgorithm;
 use tss_esapi::structures::Signature;
 use tss_esapi::traits::UnMarshall;
 
 use super::*;
+use crate::tcg_eventlog::verify_tcg_eventlog;
 pub mod config;
+pub mod ek;
 
 const MAX_TRUSTED_AK_KEYS: usize = 100;
 const DEFAULT_TRUSTED_AK_KEYS_DIR: &str = "/etc/tpm/trusted_ak_keys";
 const INITDATA_PCR: usize = 8;
 const TPM_REPORT_DATA_SIZE: usize = 64;
+const DEFAULT_CREDENTIAL_MAX_AGE_SECS: u64 = 300;
 
 // TPM evidence format as sent by the TPM attester
 #[derive(Deserialize, Debug)]
 pub struct Evidence {
     pub ak_public: String,
     pub tpm_quote: Quote,
+    /// Proof that the AK belongs to a TPM with a genuine EK, used when the AK
+    /// is not one of the pre-provisioned trusted AKs.
+    #[serde(default)]
+    pub ak_binding: Option<ek::AkBinding>,
+    /// Base64 encoded TCG PC Client event log (`binary_bios_measurements`).
+    #[serde(default)]
+    pub event_log: Option<String>,
 }
 
 // The TPM quote with string-encoded fields for JSON serialization
@@ -42,11 +57,58 @@ pub struct Quote {
     pub pcrs: Vec<String>, // hex-encoded strings
 }
 
+/// Signature of a TPM quote in a form openssl can verify
+#[derive(Debug, PartialEq)]
+enum QuoteSignature {
+    /// RSASSA-PKCS1-v1_5 signature bytes
+    RsaSsa {
+        hash: HashingAlgorithm,
+        signature: Vec<u8>,
+    },
+    /// RSASSA-PSS signature bytes
+    RsaPss {
+        hash: HashingAlgorithm,
+        signature: Vec<u8>,
+    },
+    /// DER encoded ECDSA signature
+    EcDsa {
+        hash: HashingAlgorithm,
+        signature: Vec<u8>,
+    },
+}
+
+impl QuoteSignature {
+    fn hash(&self) -> HashingAlgorithm {
+        match self {
+            Self::RsaSsa { hash, .. } | Self::RsaPss { hash, .. } | Self::EcDsa { hash, .. } => {
+                *hash
+            }
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::RsaSsa { signature, .. }
+            | Self::RsaPss { signature, .. }
+            | Self::EcDsa { signature, .. } => signature,
+        }
+    }
+}
+
+fn message_digest(hash: HashingAlgorithm) -> Result<MessageDigest> {
+    match hash {
+        HashingAlgorithm::Sha256 => Ok(MessageDigest::sha256()),
+        HashingAlgorithm::Sha384 => Ok(MessageDigest::sha384()),
+        HashingAlgorithm::Sha512 => Ok(MessageDigest::sha512()),
+        other => bail!("Unsupported quote signature hash algorithm {other:?}"),
+    }
+}
+
 impl Quote {
     fn to_quote(&self) -> Result<VtpmQuote> {
         // Extract raw signature bytes from the marshalled TSS signature
         // Attester marshals the entire Signature struct, but az_cvm_vtpm expects raw bytes
-        let raw_signature_bytes = self.extract_raw_signature_bytes()?;
+        let raw_signature_bytes = self.parse_signature()?.bytes().to_vec();
 
         // Decode the attestation message
         let message = general_purpose::STANDARD
@@ -67,7 +129,7 @@ impl Quote {
             .context("Failed to construct Quote from parsed data")
     }
 
-    fn extract_raw_signature_bytes(&self) -> Result<Vec<u8>> {
+    fn parse_signature(&self) -> Result<QuoteSignature> {
         let signature_marshalled = general_purpose::STANDARD
             .decode(&self.signature)
             .context("Failed to decode signature from base64")?;
@@ -76,11 +138,37 @@ impl Quote {
             .context("Failed to unmarshal TSS signature")?;
 
         match tss_signature {
-            Signature::RsaSsa(rsa_sig) => Ok(rsa_sig.signature().to_vec()),
-            _ => bail!("Unsupported signature type, expected RSA-SSA"),
+            Signature::RsaSsa(rsa_sig) => Ok(QuoteSignature::RsaSsa {
+                hash: rsa_sig.hashing_algorithm(),
+                signature: rsa_sig.signature().to_vec(),
+            }),
+            Signature::RsaPss(rsa_sig) => Ok(QuoteSignature::RsaPss {
+                hash: rsa_sig.hashing_algorithm(),
+                signature: rsa_sig.signature().to_vec(),
+            }),
+            Signature::EcDsa(ecc_sig) => {
+                // TPM ECDSA signatures carry the raw r and s values, openssl
+                // expects them DER encoded
+                let r = BigNum::from_slice(ecc_sig.signature_r().value())?;
+                let s = BigNum::from_slice(ecc_sig.signature_s().value())?;
+                let signature = EcdsaSig::from_private_components(r, s)?
+                    .to_der()
+                    .context("Failed to DER encode ECDSA signature")?;
+                Ok(QuoteSignature::EcDsa {
+                    hash: ecc_sig.hashing_algorithm(),
+                    signature,
+                })
+            }
+            _ => bail!("Unsupported signature type, expected RSA-SSA, RSA-PSS or ECDSA"),
         }
     }
 
+    fn message_bytes(&self) -> Result<Vec<u8>> {
+        general_purpose::STANDARD
+            .decode(&self.message)
+            .context("Failed to decode message from base64")
+    }
+
     fn parse_pcr_values(&self) -> Result<Vec<[u8; 32]>> {
         let mut pcrs = Vec::new();
         for pcr_str in &self.pcrs {
@@ -107,6 +195,7 @@ impl Quote {
 #[derive(Debug)]
 pub struct TpmVerifier {
     trusted_ak_hashes: HashSet<Vec<u8>>,
+    ek_trust: Option<ek::EkTrust>,
 }
 
 impl Default for TpmVerifier {
@@ -114,6 +203,7 @@ impl Default for TpmVerifier {
         let config = config::TpmVerifierConfig::default();
         Self::new(Some(config)).unwrap_or_else(|_| Self {
             trusted_ak_hashes: HashSet::new(),
+            ek_trust: None,
         })
     }
 }
@@ -136,8 +226,29 @@ impl TpmVerifier {
         let config = config.unwrap_or_default();
         let mut trusted_ak_hashes = HashSet::new();
 
+        let ek_trust = match &config.ek_ca_bundles_dir {
+            Some(ca_bundles_dir) => {
+                info!("TPM verifier EK CA bundles dir {:?}", ca_bundles_dir);
+                Some(ek::EkTrust::new(
+                    ca_bundles_dir,
+                    config.credential_key_path.as_deref(),
+                    config.credential_max_age_secs,
+                )?)
+            }
+            None => None,
+        };
+
         let keys_dir = config.trusted_ak_keys_dir;
 
+        // With EK certificate based trust, pre-provisioned AKs are optional
+        if ek_trust.is_some() && !keys_dir.exists() {
+            info!("TPM verifier trusted keys dir {:?} does not exist, only EK certificate based AK trust is available", keys_dir);
+            return Ok(Self {
+                trusted_ak_hashes,
+                ek_trust,
+            });
+        }
+
         info!("TPM verifier trusted keys dir {:?}", keys_dir);
 
         // Build a lazy iterator to filter and take valid .pub files
@@ -176,20 +287,73 @@ impl TpmVerifier {
             "TPM verifier loaded {} trusted AK key(s)",
             trusted_ak_hashes.len()
         );
-        Ok(Self { trusted_ak_hashes })
+        Ok(Self {
+            trusted_ak_hashes,
+            ek_trust,
+        })
     }
-}
 
-fn verify_signature(quote: &VtpmQuote, ak_public: &str) -> Result<()> {
-    let pub_bytes = general_purpose::STANDARD
-        .decode(ak_public)
-        .context("Base64 decode of AK public failed")?;
+    /// Check that the AK is trusted, either because it is one of the
+    /// pre-provisioned AKs or because it is bound to a genuine EK. Returns
+    /// the AK trust claims.
+    fn verify_ak(
+        &self,
+        ak_public_bytes: &[u8],
+        ak_pub: &PKey<Public>,
+        ak_binding: Option<&ek::AkBinding>,
+    ) -> Result<serde_json::Value> {
+        let ak_public_hash = Sha256::digest(ak_public_bytes).to_vec();
+        if self.trusted_ak_hashes.contains(&ak_public_hash) {
+            return Ok(json!({ "ak_trust": "trusted_ak" }));
+        }
+
+        let (Some(ek_trust), Some(ak_binding)) = (&self.ek_trust, ak_binding) else {
+            bail!("The provided AK public key is not in the list of trusted keys");
+        };
+
+        let ek_cert = ek_trust
+            .verify(ak_binding, ak_pub)
+            .context("AK binding to EK certificate failed")?;
+        debug!("TPM AK is bound to a trusted EK certificate");
+
+        let name_to_string = |name: &openssl::x509::X509NameRef| {
+            name.entries()
+                .filter_map(|entry| {
+                    let value = entry.data().as_utf8().ok()?;
+                    Some(format!(
+                        "{}={}",
+                        entry.object().nid().short_name().ok()?,
+                        value
+                    ))
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        Ok(json!({
+            "ak_trust": "ek_certificate",
+            "ek_cert_issuer": name_to_string(ek_cert.issuer_name()),
+            "ek_cert_serial": ek_cert.serial_number().to_bn()?.to_hex_str()?.to_string(),
+        }))
+    }
+}
 
-    let ak_pub = PKey::public_key_from_der(&pub_bytes).context("Failed to parse AK public key")?;
+fn verify_signature(quote: &Quote, ak_pub: &PKey<Public>) -> Result<()> {
+    let signature = quote.parse_signature()?;
+    let message = quote.message_bytes()?;
 
-    quote
-        .verify_signature(&ak_pub)
-        .context("TPM quote signature verification failed")?;
+    let mut verifier = openssl::sign::Verifier::new(message_digest(signature.hash())?, ak_pub)?;
+    match &signature {
+        QuoteSignature::RsaSsa { .. } => verifier.set_rsa_padding(Padding::PKCS1)?,
+        QuoteSignature::RsaPss { .. } => verifier.set_rsa_padding(Padding::PKCS1_PSS)?,
+        QuoteSignature::EcDsa { .. } => {}
+    }
+    verifier.update(&message)?;
+    if !verifier
+        .verify(signature.bytes())
+        .context("TPM quote signature verification failed")?
+    {
+        bail!("TPM quote signature verification failed");
+    }
 
     debug!("TPM quote signature is valid.");
     Ok(())
@@ -288,36 +452,86 @@ impl Verifier for TpmVerifier {
             .to_quote()
             .context("Failed to convert quote from string format")?;
 
-        let ak_public = guest_ev.ak_public;
-
         // 1. Check if the provided AK public key is trusted
-        let ak_public_bytes = general_purpose::STANDARD.decode(&ak_public)?;
-        let ak_public_hash = Sha256::digest(&ak_public_bytes).to_vec();
+        let ak_public_bytes = general_purpose::STANDARD
+            .decode(&guest_ev.ak_public)
+            .context("Base64 decode of AK public failed")?;
+        let ak_pub =
+            PKey::public_key_from_der(&ak_public_bytes).context("Failed to parse AK public key")?;
 
-        if !self.trusted_ak_hashes.contains(&ak_public_hash) {
-            bail!("The provided AK public key is not in the list of trusted keys");
-        }
+        let ak_claims = self.verify_ak(&ak_public_bytes, &ak_pub, guest_ev.ak_binding.as_ref())?;
 
         // 2. Verify the quote signature using the (now trusted) AK pubkey
-        verify_signature(&quote, &ak_public)?;
+        verify_signature(&guest_ev.tpm_quote, &ak_pub)?;
 
         // 3. Verify PCRs
         verify_pcrs(&quote)?;
 
-        // 4. Verify nonce/report data
+        // 4. Replay the event log against the quoted PCRs
+        let eventlog_claims = match &guest_ev.event_log {
+            Some(event_log) => {
+                let event_log = general_purpose::STANDARD
+                    .decode(event_log)
+                    .context("Failed to decode event log from base64")?;
+                let pcrs: Vec<&[u8; 32]> = quote.pcrs_sha256().collect();
+                Some(verify_tcg_eventlog(event_log, &pcrs)?)
+            }
+            None => None,
+        };
+
+        // 5. Verify nonce/report data
         if let ReportData::Value(report_data) = expected_report_data {
             verify_nonce(&quote, report_data)?;
         }
 
-        // 5. Verify init data hash
+        // 6. Verify init data hash
         verify_init_data(expected_init_data_hash, &quote)?;
 
-        // 6. Parse claims
+        // 7. Parse claims
         let mut claims = parse_tee_evidence(&quote);
         extend_claim(&mut claims, &quote)?;
+        if let (Some(map), serde_json::Value::Object(ak_claims)) =
+            (claims.as_object_mut(), ak_claims)
+        {
+            map.extend(ak_claims);
+        }
+        if let (Some(map), Some(eventlog_claims)) = (claims.as_object_mut(), eventlog_claims) {
+            map.extend(eventlog_claims);
+        }
 
         Ok(vec![(claims, "cpu".to_string())])
     }
+
+    /// Answer an AK binding request with a `TPM2_MakeCredential` challenge.
+    ///
+    /// `tee_parameters` is a JSON [`ek::AkBindingRequest`]. An empty string
+    /// means the attester relies on a pre-provisioned AK, in which case no
+    /// challenge is needed.
+    async fn generate_supplemental_challenge(&self, tee_parameters: String) -> Result<String> {
+        if tee_parameters.trim().is_empty() {
+            return Ok(String::new());
+        }
+
+        let Some(ek_trust) = &self.ek_trust else {
+            bail!("EK certificate based AK trust is not configured for the TPM verifier");
+        };
+
+        let request = serde_json::from_str::<ek::AkBindingRequest>(&tee_parameters)
+            .context("Failed to parse TPM AK binding request")?;
+        let challenge = ek_trust.challenge(&request)?;
+        serde_json::to_string(&challenge).context("Failed to serialize TPM AK binding challenge")
+    }
+
+    /// The serial number of the EK certificate identifies the TPM if the AK
+    /// is bound to an EK.
+    fn normalize_claims(&self, claims: &TeeEvidenceParsedClaim) -> NormalizedClaims {
+        NormalizedClaims {
+            runtime_measurements: normalized::pcr_claims(claims, ""),
+            platform_id: normalized::str_claim(claims, "/ek_cert_serial"),
+            init_data_hash: normalized::str_claim(claims, "/init_data"),
+            ..Default::default()
+        }
+    }
 }
 
 pub fn parse_tee_evidence(quote: &VtpmQuote) -> TeeEvidenceParsedClaim {
@@ -332,6 +546,11 @@ pub fn parse_tee_evidence(quote: &VtpmQuote) -> TeeEvidenceParsedClaim {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::nid::Nid;
+    use openssl::pkey::Private;
+    use openssl::rsa::Rsa;
+    use rstest::rstest;
     use std::path::PathBuf;
 
     const TPM_EVIDENCE: &[u8] = include_bytes!("../../test_data/tpm_evidence.json");
@@ -342,6 +561,7 @@ mod tests {
         let config = config::TpmVerifierConfig {
             trusted_ak_keys_dir: PathBuf::from("/nonexistent/directory/for/testing"),
             max_trusted_ak_keys: MAX_TRUSTED_AK_KEYS,
+            ..Default::default()
         };
         let result = TpmVerifier::new(Some(config));
         assert!(
@@ -372,6 +592,7 @@ mod tests {
         let config = config::TpmVerifierConfig {
             trusted_ak_keys_dir: PathBuf::from("/custom/path"),
             max_trusted_ak_keys: 50,
+            ..Default::default()
         };
         assert_eq!(config.max_trusted_ak_keys, 50);
         assert_eq!(config.trusted_ak_keys_dir.to_str().unwrap(), "/custom/path");
@@ -447,4 +668,183 @@ mod tests {
             "report_data should be 128 hex chars (64 bytes)"
         );
     }
+
+    fn marshal_tpm2b(out: &mut Vec<u8>, data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        out.extend_from_slice(data);
+    }
+
+    /// Build a JSON TPM evidence with a quote over all SHA-256 PCRs, signed
+    /// by the ECDSA `ak` with `hash`.
+    fn ecdsa_evidence(ak: &PKey<Private>, hash: HashingAlgorithm, report_data: &[u8]) -> Evidence {
+        let pcrs = [[0u8; 32]; 24];
+        let (hash_alg, digest) = match hash {
+            HashingAlgorithm::Sha256 => (0x000Bu16, MessageDigest::sha256()),
+            HashingAlgorithm::Sha384 => (0x000Cu16, MessageDigest::sha384()),
+            _ => unreachable!(),
+        };
+
+        // TPMS_ATTEST of type TPM_ST_ATTEST_QUOTE
+        let mut message = Vec::new();
+        message.extend_from_slice(&0xff54_4347u32.to_be_bytes());
+        message.extend_from_slice(&0x8018u16.to_be_bytes());
+        marshal_tpm2b(&mut message, &[]);
+        let mut extra_data = report_data.to_vec();
+        extra_data.resize(TPM_REPORT_DATA_SIZE, 0);
+        marshal_tpm2b(&mut message, &extra_data);
+        message.extend_from_slice(&[0u8; 17]); // clockInfo
+        message.extend_from_slice(&[0u8; 8]); // firmwareVersion
+        message.extend_from_slice(&1u32.to_be_bytes());
+        message.extend_from_slice(&0x000Bu16.to_be_bytes());
+        message.extend_from_slice(&[3, 0xff, 0xff, 0xff]);
+        marshal_tpm2b(&mut message, &Sha256::digest(pcrs.concat()));
+
+        let mut signer = openssl::sign::Signer::new(digest, ak).unwrap();
+        signer.update(&message).unwrap();
+        let der_signature = EcdsaSig::from_der(&signer.sign_to_vec().unwrap()).unwrap();
+
+        // TPMT_SIGNATURE with TPM2_ALG_ECDSA
+        let mut signature = Vec::new();
+        signature.extend_from_slice(&0x0018u16.to_be_bytes());
+        signature.extend_from_slice(&hash_alg.to_be_bytes());
+        marshal_tpm2b(&mut signature, &der_signature.r().to_vec());
+        marshal_tpm2b(&mut signature, &der_signature.s().to_vec());
+
+        Evidence {
+            ak_public: general_purpose::STANDARD.encode(ak.public_key_to_der().unwrap()),
+            tpm_quote: Quote {
+                signature: general_purpose::STANDARD.encode(signature),
+                message: general_purpose::STANDARD.encode(message),
+                pcrs: pcrs.iter().map(hex::encode).collect(),
+            },
+            ak_binding: None,
+            event_log: None,
+        }
+    }
+
+    fn evidence_to_json(evidence: &Evidence, ak_binding: Option<serde_json::Value>) -> TeeEvidence {
+        json!({
+            "ak_public": evidence.ak_public,
+            "tpm_quote": {
+                "signature": evidence.tpm_quote.signature,
+                "message": evidence.tpm_quote.message,
+                "pcrs": evidence.tpm_quote.pcrs,
+            },
+            "ak_binding": ak_binding,
+            "event_log": evidence.event_log,
+        })
+    }
+
+    #[rstest]
+    #[case(Nid::X9_62_PRIME256V1, HashingAlgorithm::Sha256)]
+    #[case(Nid::SECP384R1, HashingAlgorithm::Sha384)]
+    #[tokio::test]
+    async fn test_ecdsa_quote(#[case] curve: Nid, #[case] hash: HashingAlgorithm) {
+        let group = EcGroup::from_curve_name(curve).unwrap();
+        let ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let report_data = b"tpm ecdsa report data";
+        let evidence = ecdsa_evidence(&ak, hash, report_data);
+
+        let signature = evidence.tpm_quote.parse_signature().unwrap();
+        assert!(matches!(signature, QuoteSignature::EcDsa { .. }));
+        assert_eq!(signature.hash(), hash);
+
+        let verifier = TpmVerifier {
+            trusted_ak_hashes: HashSet::from([
+                Sha256::digest(ak.public_key_to_der().unwrap()).to_vec()
+            ]),
+            ek_trust: None,
+        };
+        let claims = verifier
+            .evaluate(
+                evidence_to_json(&evidence, None),
+                &ReportData::Value(report_data),
+                &InitDataHash::NotProvided,
+            )
+            .await
+            .unwrap();
+        assert_eq!(claims[0].0["ak_trust"], "trusted_ak");
+
+        // A quote signed by another key must be rejected
+        let other_ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut forged = ecdsa_evidence(&other_ak, hash, report_data);
+        forged.ak_public = evidence.ak_public.clone();
+        assert!(verifier
+            .evaluate(
+                evidence_to_json(&forged, None),
+                &ReportData::Value(report_data),
+                &InitDataHash::NotProvided,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ek_certificate_bound_ak() {
+        let ca_dir = tempfile::tempdir().unwrap();
+        let ek = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let ek_cert = ek::tests::issue_ek_cert(ca_dir.path(), &ek);
+
+        let config = config::TpmVerifierConfig {
+            trusted_ak_keys_dir: PathBuf::from("/nonexistent/directory/for/testing"),
+            ek_ca_bundles_dir: Some(ca_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let verifier = TpmVerifier::new(Some(config)).unwrap();
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ak = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let ak_tpm_public = ek::tests::marshal_ecc_ak_public(&ak);
+        let report_data = b"tpm ek report data";
+        let evidence = ecdsa_evidence(&ak, HashingAlgorithm::Sha256, report_data);
+
+        // Without an AK binding the AK is unknown
+        assert!(verifier
+            .evaluate(
+                evidence_to_json(&evidence, None),
+                &ReportData::Value(report_data),
+                &InitDataHash::NotProvided,
+            )
+            .await
+            .is_err());
+
+        let request = json!({
+            "ek_cert": general_purpose::STANDARD.encode(ek_cert.to_der().unwrap()),
+            "ak_tpm_public": general_purpose::STANDARD.encode(&ak_tpm_public),
+        });
+        let challenge = verifier
+            .generate_supplemental_challenge(request.to_string())
+            .await
+            .unwrap();
+        let challenge: ek::AkBindingChallenge = serde_json::from_str(&challenge).unwrap();
+
+        let mut ak_name = vec![0x00, 0x0b];
+        ak_name.extend_from_slice(&Sha256::digest(&ak_tpm_public));
+        let activated = ek::tests::activate_credential(
+            &ek,
+            &ak_name,
+            &general_purpose::STANDARD
+                .decode(&challenge.credential_blob)
+                .unwrap(),
+            &general_purpose::STANDARD
+                .decode(&challenge.encrypted_secret)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let mut ak_binding = request;
+        ak_binding["activated_credential"] = general_purpose::STANDARD.encode(activated).into();
+        ak_binding["issued_at"] = challenge.issued_at.into();
+
+        let claims = verifier
+            .evaluate(
+                evidence_to_json(&evidence, Some(ak_binding)),
+                &ReportData::Value(report_data),
+                &InitDataHash::NotProvided,
+            )
+            .await
+            .unwrap();
+        assert_eq!(claims[0].0["ak_trust"], "ek_certificate");
+        assert_eq!(claims[0].0["ek_cert_issuer"], "CN=Test TPM Manufacturer CA");
+    }
 }
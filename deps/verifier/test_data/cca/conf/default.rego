@@ -5,6 +5,13 @@ import rego.v1
 # This policy is used to generate an EAR Appraisal for Arm CCA Realms
 # See https://github.com/veraison/docs/blob/ar4si/ar4si/arm-cca.md
 
+result := {
+	"executables": executables,
+	"hardware": hardware,
+	"instance-identity": instance_identity,
+	"runtime-opaque": runtime_opaque,
+}
+
 # For the `hardware` trust claim, the value 2 stands for
 # "An Attester has passed its hardware and/or firmware
 #  verifications needed to demonstrate that these are genuine/
@@ -12,6 +19,17 @@ import rego.v1
 # Since platform appraisal is successful, this is implied.
 default hardware := 2
 
+# The value 97 stands for
+# "A Verifier does not recognize an Attester's hardware or
+#  firmware, but it should be recognized."
+# A platform software component of a measurement type with reference
+# values, e.g. `cca.platform.cca-platform-sw-components.BL`, must match one.
+hardware := 97 if {
+	some component in input.cca.platform["cca-platform-sw-components"]
+	name := sprintf("cca.platform.cca-platform-sw-components.%s", [component["measurement-type"]])
+	not component["measurement-value"] in object.get(data.reference, name, [component["measurement-value"]])
+}
+
 # The value 2 stands for
 # "The Attesting Environment is recognized, and the associated
 # instance of the Attester is not known to be compromised."
@@ -36,7 +54,13 @@ default executables := 33
 # The value 3 stands for
 # "Only a recognized genuine set of approved executables have
 #  been loaded during the boot process."
-# the RIM (realm initial measurement) must match
+# the RIM (realm initial measurement) must match, and so must every REM
+# (realm extensible measurement) with reference values, e.g.
+# `cca.realm.cca-realm-extensible-measurements.0`
 executables := 3 if {
 	input.cca.realm["cca-realm-initial-measurement"] in data.reference["cca.realm.cca-realm-initial-measurement"]
+	every index, rem in input.cca.realm["cca-realm-extensible-measurements"] {
+		name := sprintf("cca.realm.cca-realm-extensible-measurements.%d", [index])
+		rem in object.get(data.reference, name, [rem])
+	}
 }
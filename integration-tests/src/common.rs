@@ -21,24 +21,31 @@ use attestation_service::{
     rvps::{grpc::RvpsRemoteConfig, RvpsConfig, RvpsCrateConfig},
 };
 
-use reference_value_provider_service::client as rvps_client;
+use grpc_tls::testing::{certificate, write_certificate};
+use reference_value_provider_service::client::{self as rvps_client, RvpsClient};
 use reference_value_provider_service::config::Config as RVPSConfig;
 use reference_value_provider_service::rvps_api::reference::reference_value_provider_service_server::ReferenceValueProviderServiceServer;
 use reference_value_provider_service::storage::{local_json, ReferenceValueStorageConfig};
-use reference_value_provider_service::{server::RvpsServer, Rvps};
+use reference_value_provider_service::{
+    server::{RvpsServer, TlsConfig as RvpsServerTlsConfig},
+    Rvps,
+};
 
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD, Engine};
 use log::info;
 use openssl::pkey::PKey;
 use serde_json::json;
+use std::path::Path;
 use std::sync::Arc;
 use tempfile::TempDir;
-use tokio::sync::RwLock;
+use tokio::{sync::RwLock, task::JoinHandle};
 use tonic::transport::Server;
 
 const KBS_URL: &str = "http://127.0.0.1:8081";
 const RVPS_URL: &str = "http://127.0.0.1:51003";
+const RVPS_TLS_URL: &str = "https://127.0.0.1:51004";
+const RVPS_DOMAIN: &str = "rvps.test";
 const WAIT_TIME: u64 = 3000;
 
 const ALLOW_ALL_POLICY: &str = "
@@ -60,6 +67,8 @@ pub enum PolicyType {
 pub enum RvpsType {
     Builtin,
     Remote,
+    /// Remote RVPS that requires mutual TLS with self-signed certificates
+    RemoteTls,
 }
 
 /// An enum that selects between TestParameter configurations
@@ -67,6 +76,7 @@ pub enum RvpsType {
 pub enum KbsConfigType {
     EarTokenBuiltInRvps,
     EarTokenRemoteRvps,
+    EarTokenRemoteRvpsTls,
 }
 
 /// The KbsConfigType enum can be turned into TestParameters
@@ -79,6 +89,9 @@ impl Into<TestParameters> for KbsConfigType {
             KbsConfigType::EarTokenRemoteRvps => TestParameters {
                 rvps_type: RvpsType::Remote,
             },
+            KbsConfigType::EarTokenRemoteRvpsTls => TestParameters {
+                rvps_type: RvpsType::RemoteTls,
+            },
         }
     }
 }
@@ -93,6 +106,9 @@ pub struct TestHarness {
     pub kbs_config: KbsConfig,
     pub auth_privkey: String,
     kbs_server_handle: actix_web::dev::ServerHandle,
+    rvps_server_handle: Option<JoinHandle<std::result::Result<(), tonic::transport::Error>>>,
+    pub rvps_url: String,
+    pub rvps_tls: Option<rvps_client::TlsConfig>,
     _work_dir: TempDir,
 
     // Future tests will use some parameters at runtime
@@ -135,17 +151,27 @@ impl TestHarness {
         };
 
         // Setup RVPS either remotely or builtin
+        let mut rvps_server_handle = None;
+        let mut rvps_url = RVPS_URL.to_string();
+        let mut rvps_tls = None;
         let rvps_config = match &test_parameters.rvps_type {
-            RvpsType::Builtin => RvpsConfig::BuiltIn(RvpsCrateConfig {
-                extractors: None,
-                storage: ReferenceValueStorageConfig::LocalJson(local_json::Config {
-                    file_path: rv_path,
-                }),
-            }),
-            RvpsType::Remote => {
+            RvpsType::Builtin => RvpsConfig::BuiltIn(
+                RvpsCrateConfig {
+                    extractors: None,
+                    signature: Default::default(),
+                    allow_import: false,
+                    storage: ReferenceValueStorageConfig::LocalJson(local_json::Config {
+                        file_path: rv_path,
+                    }),
+                }
+                .into(),
+            ),
+            RvpsType::Remote | RvpsType::RemoteTls => {
                 info!("Starting Remote RVPS");
                 let service = Rvps::new(RVPSConfig {
                     extractors: None,
+                    signature: Default::default(),
+                    allow_import: false,
                     storage: ReferenceValueStorageConfig::LocalJson(local_json::Config {
                         file_path: rv_path,
                     }),
@@ -153,14 +179,28 @@ impl TestHarness {
                 let inner = Arc::new(RwLock::new(service));
                 let rvps_server = RvpsServer::new(inner.clone());
 
-                let rvps_future = Server::builder()
+                let mut server = Server::builder();
+                let mut socket = "127.0.0.1:51003";
+                if let RvpsType::RemoteTls = test_parameters.rvps_type {
+                    let (server_tls, client_tls) = rvps_certificates(work_dir.path())?;
+                    if let Some(tls) = server_tls.to_server_tls_config()? {
+                        server = server.tls_config(tls)?;
+                    }
+                    socket = "127.0.0.1:51004";
+                    rvps_url = RVPS_TLS_URL.to_string();
+                    rvps_tls = Some(client_tls);
+                }
+
+                let rvps_future = server
                     .add_service(ReferenceValueProviderServiceServer::new(rvps_server))
-                    .serve("127.0.0.1:51003".parse()?);
+                    .serve(socket.parse()?);
 
-                tokio::spawn(rvps_future);
+                rvps_server_handle = Some(tokio::spawn(rvps_future));
 
                 RvpsConfig::GrpcRemote(RvpsRemoteConfig {
-                    address: RVPS_URL.to_string(),
+                    address: rvps_url.clone(),
+                    cache: Default::default(),
+                    tls: rvps_tls.clone(),
                 })
             }
         };
@@ -178,6 +218,9 @@ impl TestHarness {
                     rvps_config,
                     attestation_token_broker: attestation_token_config,
                     verifier_config: None,
+                    verification: Default::default(),
+                    history: None,
+                    tenants: Default::default(),
                 }),
                 timeout: 5,
             },
@@ -213,6 +256,9 @@ impl TestHarness {
             kbs_config,
             auth_privkey,
             kbs_server_handle: kbs_handle,
+            rvps_server_handle,
+            rvps_url,
+            rvps_tls,
             _work_dir: work_dir,
             _test_parameters: test_parameters,
         })
@@ -220,6 +266,9 @@ impl TestHarness {
 
     pub async fn cleanup(&self) -> Result<()> {
         self.kbs_server_handle.stop(true).await;
+        if let Some(rvps_server_handle) = &self.rvps_server_handle {
+            rvps_server_handle.abort();
+        }
 
         Ok(())
     }
@@ -297,8 +346,37 @@ impl TestHarness {
             "payload": provenance
         });
 
-        rvps_client::register(RVPS_URL.to_string(), message.to_string()).await?;
+        RvpsClient::connect(self.rvps_url.clone(), self.rvps_tls.as_ref())
+            .await?
+            .register(message.to_string())
+            .await?;
 
         Ok(())
     }
 }
+
+/// Generate a self-signed CA that issues the certificates of the RVPS and
+/// of its clients, and return the TLS configurations of both sides.
+fn rvps_certificates(dir: &Path) -> Result<(RvpsServerTlsConfig, rvps_client::TlsConfig)> {
+    let (ca, ca_key) = certificate("rvps-test-ca", None)?;
+    let (server, server_key) = certificate(RVPS_DOMAIN, Some((&ca, &ca_key)))?;
+    let (client, client_key) = certificate("as.test", Some((&ca, &ca_key)))?;
+
+    let (ca_cert, _) = write_certificate(dir, "rvps-ca", &ca, &ca_key)?;
+    let (server_cert, server_key) = write_certificate(dir, "rvps", &server, &server_key)?;
+    let (client_cert, client_key) = write_certificate(dir, "rvps-client", &client, &client_key)?;
+
+    let server_tls = RvpsServerTlsConfig {
+        cert: Some(server_cert),
+        key: Some(server_key),
+        client_ca: Some(ca_cert.clone()),
+    };
+    let client_tls = rvps_client::TlsConfig {
+        ca_cert: Some(ca_cert),
+        cert: Some(client_cert),
+        key: Some(client_key),
+        domain: Some(RVPS_DOMAIN.to_string()),
+    };
+
+    Ok((server_tls, client_tls))
+}
@@ -0,0 +1,72 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+use anyhow::{bail, Result};
+use log::info;
+use reference_value_provider_service::client::RvpsClient;
+use rstest::rstest;
+use serde_json::json;
+use serial_test::serial;
+
+extern crate integration_tests;
+use crate::integration_tests::common::{KbsConfigType, TestHarness};
+
+//
+// Register reference values into a remote RVPS that requires mutual TLS,
+// with the client certificate and without it.
+//
+#[rstest]
+#[case::rvps_client_with_certificate(true)]
+#[case::rvps_client_without_certificate(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[serial]
+async fn rvps_mutual_tls(#[case] with_certificate: bool) -> Result<()> {
+    let _ = env_logger::try_init_from_env(env_logger::Env::new().default_filter_or("debug"));
+
+    let mut harness = TestHarness::new(KbsConfigType::EarTokenRemoteRvpsTls.into()).await?;
+    harness.wait().await;
+
+    if !with_certificate {
+        info!("TEST: removing RVPS client certificate");
+        let tls = harness.rvps_tls.as_mut().expect("RVPS uses TLS");
+        tls.cert = None;
+        tls.key = None;
+    }
+
+    info!("TEST: setting reference value");
+    let res = harness
+        .set_reference_value("svn".to_string(), json!(["1"]))
+        .await;
+
+    harness.cleanup().await?;
+    match (with_certificate, res) {
+        (true, res) => res,
+        (false, Ok(_)) => bail!("RVPS accepted a client without a certificate"),
+        (false, Err(_)) => Ok(()),
+    }
+}
+
+//
+// Connecting to the RVPS under a name its certificate is not issued for
+// fails, even with a valid client certificate.
+//
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[serial]
+async fn rvps_wrong_domain() -> Result<()> {
+    let _ = env_logger::try_init_from_env(env_logger::Env::new().default_filter_or("debug"));
+
+    let harness = TestHarness::new(KbsConfigType::EarTokenRemoteRvpsTls.into()).await?;
+    harness.wait().await;
+
+    let mut tls = harness.rvps_tls.clone().expect("RVPS uses TLS");
+    tls.domain = Some("other.test".to_string());
+    let res = RvpsClient::connect(harness.rvps_url.clone(), Some(&tls)).await;
+
+    harness.cleanup().await?;
+    if res.is_ok() {
+        bail!("RVPS certificate was accepted for another name");
+    }
+    Ok(())
+}
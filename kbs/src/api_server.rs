@@ -32,6 +32,22 @@ macro_rules! kbs_path {
     };
 }
 
+/// Query parameters of the `reference-value` endpoint.
+/// * `metadata`: on `GET`, list the reference values together with their
+///   metadata instead of only the digests.
+/// * `prefix`: on `DELETE`, delete all the reference values whose name
+///   starts with the prefix.
+/// * `expired`: on `DELETE`, purge all the expired reference values.
+#[cfg(feature = "as")]
+#[derive(serde::Deserialize)]
+struct ReferenceValueQuery {
+    #[serde(default)]
+    metadata: bool,
+    prefix: Option<String>,
+    #[serde(default)]
+    expired: bool,
+}
+
 /// The KBS API server
 #[derive(Clone)]
 pub struct ApiServer {
@@ -127,7 +143,8 @@ impl ApiServer {
                     .service(
                         web::resource([kbs_path!("{base_path}{additional_path:.*}")])
                             .route(web::get().to(api))
-                            .route(web::post().to(api)),
+                            .route(web::post().to(api))
+                            .route(web::delete().to(api)),
                     )
                     .service(
                         web::resource("/metrics")
@@ -200,17 +217,61 @@ pub(crate) async fn api(
             Ok(HttpResponse::Ok().finish())
         }
         #[cfg(feature = "as")]
+        "attestation-policy" if request.method() == Method::GET => {
+            core.admin_auth.validate_auth(&request)?;
+            let policy_id = additional_path.trim_start_matches('/');
+            let body = match policy_id.is_empty() {
+                true => serde_json::to_string(&core.attestation_service.list_policies().await?)?,
+                false => serde_json::to_string(&serde_json::json!({
+                    "policy_id": policy_id,
+                    "policy": core.attestation_service.get_policy(policy_id).await?,
+                }))?,
+            };
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/json")
+                .body(body))
+        }
+        #[cfg(feature = "as")]
+        "attestation-policy" if request.method() == Method::DELETE => {
+            core.admin_auth.validate_auth(&request)?;
+            let policy_id = additional_path.trim_start_matches('/');
+            if policy_id.is_empty() {
+                return Err(Error::InvalidRequestPath {
+                    path: request.path().to_string(),
+                });
+            }
+            core.attestation_service.delete_policy(policy_id).await?;
+
+            Ok(HttpResponse::Ok().finish())
+        }
+        #[cfg(feature = "as")]
         "reference-value" if request.method() == Method::GET => {
             core.admin_auth.validate_auth(&request)?;
-            let reference_values = serde_json::to_string(
-                &core
-                    .attestation_service
-                    .query_reference_values()
-                    .await
-                    .map_err(|e| Error::RvpsError {
-                        message: format!("Failed to get reference_values: {e}").to_string(),
-                    })?,
-            )?;
+            let params: ReferenceValueQuery =
+                serde_qs::from_str(query).map_err(|e| Error::RvpsError {
+                    message: format!("Illegal reference value query: {e}"),
+                })?;
+            let reference_values = match params.metadata {
+                true => serde_json::to_string(
+                    &core
+                        .attestation_service
+                        .list_reference_values()
+                        .await
+                        .map_err(|e| Error::RvpsError {
+                            message: format!("Failed to list reference values: {e}"),
+                        })?,
+                )?,
+                false => serde_json::to_string(
+                    &core
+                        .attestation_service
+                        .query_reference_values()
+                        .await
+                        .map_err(|e| Error::RvpsError {
+                            message: format!("Failed to get reference_values: {e}").to_string(),
+                        })?,
+                )?,
+            };
 
             Ok(HttpResponse::Ok()
                 .content_type("application/json")
@@ -234,6 +295,41 @@ pub(crate) async fn api(
 
             Ok(HttpResponse::Ok().content_type("application/json").finish())
         }
+        #[cfg(feature = "as")]
+        "reference-value" if request.method() == Method::DELETE => {
+            core.admin_auth.validate_auth(&request)?;
+            let params: ReferenceValueQuery =
+                serde_qs::from_str(query).map_err(|e| Error::RvpsError {
+                    message: format!("Illegal reference value query: {e}"),
+                })?;
+            let name = additional_path.trim_start_matches('/');
+            let deleted = match (name.is_empty(), params.prefix, params.expired) {
+                (false, None, false) => core.attestation_service.delete_reference_value(name).await,
+                // An empty prefix would match, and delete, every reference value
+                (true, Some(prefix), false) if !prefix.is_empty() => {
+                    core.attestation_service
+                        .delete_reference_values_by_prefix(&prefix)
+                        .await
+                }
+                (true, None, true) => {
+                    core.attestation_service
+                        .purge_expired_reference_values()
+                        .await
+                }
+                _ => {
+                    return Ok(HttpResponse::BadRequest().body(
+                        "Exactly one of name, a non-empty `prefix` or `expired` must be given",
+                    ))
+                }
+            }
+            .map_err(|e| Error::RvpsError {
+                message: format!("Failed to delete reference values: {e}"),
+            })?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/json")
+                .body(serde_json::to_string(&deleted)?))
+        }
 
         // TODO: consider to rename the api name for it is not only for
         // resource retrievement but for all plugins.
@@ -14,7 +14,7 @@ use log::{debug, info};
 use rand::{thread_rng, Rng};
 use semver::{BuildMetadata, Prerelease, Version, VersionReq};
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, value::RawValue};
 use std::collections::HashMap;
 
 use crate::attestation::session::KBS_SESSION_ID;
@@ -53,10 +53,26 @@ pub type TeeEvidence = serde_json::Value;
 pub struct IndependentEvidence {
     pub tee: Tee,
     pub tee_evidence: TeeEvidence,
+    /// The evidence exactly as the attester encoded it.
+    pub raw_tee_evidence: Vec<u8>,
     pub runtime_data: serde_json::Value,
     pub init_data: Option<InitData>,
 }
 
+/// The evidence of an [`Attestation`] request without parsing it, so that
+/// its original bytes can be handed to the verifiers.
+#[derive(Deserialize)]
+struct RawAttestation<'a> {
+    #[serde(borrow, rename = "tee-evidence")]
+    tee_evidence: RawCompositeEvidence<'a>,
+}
+
+#[derive(Deserialize)]
+struct RawCompositeEvidence<'a> {
+    #[serde(borrow)]
+    primary_evidence: &'a RawValue,
+}
+
 /// Number of bytes in a nonce.
 const NONCE_SIZE_BYTES: usize = 32;
 
@@ -93,6 +109,23 @@ pub trait Attest: Send + Sync {
         Err(anyhow!("Set Policy API is unimplemented"))
     }
 
+    /// List Attestation Policies
+    /// Return a map of the policy IDs to the digests of the policies
+    async fn list_policies(&self) -> anyhow::Result<HashMap<String, String>> {
+        Err(anyhow!("List Policies API is unimplemented"))
+    }
+
+    /// Get Attestation Policy
+    /// Return the base64 encoded policy
+    async fn get_policy(&self, _policy_id: &str) -> anyhow::Result<String> {
+        Err(anyhow!("Get Policy API is unimplemented"))
+    }
+
+    /// Delete Attestation Policy
+    async fn delete_policy(&self, _policy_id: &str) -> anyhow::Result<()> {
+        Err(anyhow!("Delete Policy API is unimplemented"))
+    }
+
     /// Verify Attestation Evidence
     /// Return Attestation Results Token
     async fn verify(&self, evidence_to_verify: Vec<IndependentEvidence>) -> anyhow::Result<String>;
@@ -119,6 +152,42 @@ pub trait Attest: Send + Sync {
             "Attestation Service does not support reference value configuration."
         ))
     }
+
+    /// Delete the reference value of the given name from the RVPS, if the AS
+    /// supports it. Return the names of the deleted reference values.
+    async fn delete_reference_value(&self, _name: &str) -> anyhow::Result<Vec<String>> {
+        Err(anyhow!(
+            "Attestation Service does not support reference value configuration."
+        ))
+    }
+
+    /// Delete the reference values whose name starts with the given prefix
+    /// from the RVPS, if the AS supports it. Return the names of the deleted
+    /// reference values.
+    async fn delete_reference_values_by_prefix(
+        &self,
+        _prefix: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        Err(anyhow!(
+            "Attestation Service does not support reference value configuration."
+        ))
+    }
+
+    /// List the reference values in the RVPS together with their metadata,
+    /// if the AS supports it
+    async fn list_reference_values(&self) -> anyhow::Result<Vec<serde_json::Value>> {
+        Err(anyhow!(
+            "Attestation Service does not support reference value configuration."
+        ))
+    }
+
+    /// Delete the expired reference values from the RVPS, if the AS supports
+    /// it. Return the names of the purged reference values.
+    async fn purge_expired_reference_values(&self) -> anyhow::Result<Vec<String>> {
+        Err(anyhow!(
+            "Attestation Service does not support reference value configuration."
+        ))
+    }
 }
 
 /// Attestation Service
@@ -199,6 +268,27 @@ impl AttestationService {
         self.inner.set_policy(&input.policy_id, &input.policy).await
     }
 
+    pub async fn list_policies(&self) -> Result<HashMap<String, String>> {
+        self.inner
+            .list_policies()
+            .await
+            .map_err(|e| Error::ListPolicies { source: e })
+    }
+
+    pub async fn get_policy(&self, policy_id: &str) -> Result<String> {
+        self.inner
+            .get_policy(policy_id)
+            .await
+            .map_err(|e| Error::GetPolicy { source: e })
+    }
+
+    pub async fn delete_policy(&self, policy_id: &str) -> Result<()> {
+        self.inner
+            .delete_policy(policy_id)
+            .await
+            .map_err(|e| Error::DeletePolicy { source: e })
+    }
+
     pub async fn auth(&self, request: &[u8]) -> Result<HttpResponse> {
         self.__auth(request)
             .await
@@ -263,6 +353,10 @@ impl AttestationService {
 
         let session_id = cookie.value();
 
+        let raw_attestation: RawAttestation = serde_json::from_slice(attestation)
+            .inspect_err(|_| ATTESTATION_ERRORS.inc())
+            .context("deserialize Attestation")?;
+        let raw_primary_evidence = raw_attestation.tee_evidence.primary_evidence.get();
         let attestation: Attestation = serde_json::from_slice(attestation)
             .inspect_err(|_| ATTESTATION_ERRORS.inc())
             .context("deserialize Attestation")?;
@@ -327,6 +421,7 @@ impl AttestationService {
         let mut primary_evidence = IndependentEvidence {
             tee,
             tee_evidence: attestation.tee_evidence.primary_evidence,
+            raw_tee_evidence: raw_primary_evidence.as_bytes().to_vec(),
             runtime_data: primary_runtime_data,
             init_data: None,
         };
@@ -340,13 +435,16 @@ impl AttestationService {
 
         // additional evidence
         if !attestation.tee_evidence.additional_evidence.is_empty() {
-            let additional_evidence: HashMap<Tee, TeeEvidence> =
+            let additional_evidence: HashMap<Tee, &RawValue> =
                 serde_json::from_str(&attestation.tee_evidence.additional_evidence)
                     .inspect_err(|_| ATTESTATION_ERRORS.inc())?;
-            for (tee, tee_evidence) in additional_evidence {
+            for (tee, raw_tee_evidence) in additional_evidence {
+                let tee_evidence: TeeEvidence = serde_json::from_str(raw_tee_evidence.get())
+                    .inspect_err(|_| ATTESTATION_ERRORS.inc())?;
                 evidence_to_verify.push(IndependentEvidence {
                     tee,
                     tee_evidence,
+                    raw_tee_evidence: raw_tee_evidence.get().as_bytes().to_vec(),
                     runtime_data: kbs_evidence_runtime_data.clone(),
                     init_data: None,
                 });
@@ -441,6 +539,25 @@ impl AttestationService {
 
         Ok(values)
     }
+
+    pub async fn delete_reference_value(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        self.inner.delete_reference_value(name).await
+    }
+
+    pub async fn delete_reference_values_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        self.inner.delete_reference_values_by_prefix(prefix).await
+    }
+
+    pub async fn list_reference_values(&self) -> anyhow::Result<Vec<serde_json::Value>> {
+        self.inner.list_reference_values().await
+    }
+
+    pub async fn purge_expired_reference_values(&self) -> anyhow::Result<Vec<String>> {
+        self.inner.purge_expired_reference_values().await
+    }
 }
 
 #[cfg(test)]
@@ -483,4 +600,17 @@ mod tests {
             nonces.push(nonce);
         }
     }
+
+    #[test]
+    fn test_raw_primary_evidence() {
+        let attestation = br#"{
+            "runtime-data": {"nonce": "nonce", "tee-pubkey": {"kty": "EC", "crv": "P-256", "alg": "ES256", "x": "x", "y": "y"}},
+            "tee-evidence": {"primary_evidence": { "svn" : "1" }, "additional_evidence": ""}
+        }"#;
+        let raw_attestation: RawAttestation = serde_json::from_slice(attestation).unwrap();
+        assert_eq!(
+            raw_attestation.tee_evidence.primary_evidence.get(),
+            r#"{ "svn" : "1" }"#
+        );
+    }
 }
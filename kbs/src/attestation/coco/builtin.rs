@@ -5,8 +5,8 @@
 use anyhow::*;
 use async_trait::async_trait;
 use attestation_service::{
-    config::Config as AsConfig, AttestationService, HashAlgorithm, InitDataInput, RuntimeData,
-    VerificationRequest,
+    config::Config as AsConfig, tenant::DEFAULT_TENANT, token::TokenFormat, AttestationService,
+    HashAlgorithm, InitDataInput, RuntimeData, VerificationRequest,
 };
 use kbs_types::{Challenge, Tee};
 use std::collections::HashMap;
@@ -24,7 +24,27 @@ impl Attest for BuiltInCoCoAs {
         self.inner
             .write()
             .await
-            .set_policy(policy_id.to_string(), policy.to_string())
+            .set_policy(DEFAULT_TENANT, policy_id.to_string(), policy.to_string())
+            .await
+    }
+
+    async fn list_policies(&self) -> Result<HashMap<String, String>> {
+        self.inner.read().await.list_policies(DEFAULT_TENANT).await
+    }
+
+    async fn get_policy(&self, policy_id: &str) -> Result<String> {
+        self.inner
+            .read()
+            .await
+            .get_policy(DEFAULT_TENANT, policy_id.to_string())
+            .await
+    }
+
+    async fn delete_policy(&self, policy_id: &str) -> Result<()> {
+        self.inner
+            .write()
+            .await
+            .delete_policy(DEFAULT_TENANT, policy_id.to_string())
             .await
     }
 
@@ -33,6 +53,7 @@ impl Attest for BuiltInCoCoAs {
 
         for evidence in evidence_to_verify {
             let mut request = VerificationRequest {
+                raw_evidence: evidence.raw_tee_evidence,
                 evidence: evidence.tee_evidence,
                 tee: evidence.tee,
                 runtime_data: Some(RuntimeData::Structured(evidence.runtime_data)),
@@ -53,7 +74,13 @@ impl Attest for BuiltInCoCoAs {
         self.inner
             .read()
             .await
-            .evaluate(verification_requests, policy_ids)
+            .evaluate(
+                DEFAULT_TENANT,
+                verification_requests,
+                policy_ids,
+                TokenFormat::Ear,
+                None,
+            )
             .await
     }
 
@@ -85,12 +112,59 @@ impl Attest for BuiltInCoCoAs {
         self.inner
             .write()
             .await
-            .register_reference_value(message)
+            .register_reference_value(DEFAULT_TENANT, message)
             .await
     }
 
     async fn query_reference_values(&self) -> anyhow::Result<HashMap<String, serde_json::Value>> {
-        self.inner.read().await.query_reference_values().await
+        self.inner
+            .read()
+            .await
+            .query_reference_values(DEFAULT_TENANT)
+            .await
+    }
+
+    async fn delete_reference_value(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let existed = self
+            .inner
+            .write()
+            .await
+            .delete_reference_value(DEFAULT_TENANT, name)
+            .await?;
+        match existed {
+            true => Ok(vec![name.to_string()]),
+            false => Ok(vec![]),
+        }
+    }
+
+    async fn delete_reference_values_by_prefix(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .write()
+            .await
+            .delete_reference_values_by_prefix(DEFAULT_TENANT, prefix)
+            .await
+    }
+
+    async fn list_reference_values(&self) -> anyhow::Result<Vec<serde_json::Value>> {
+        let values = self
+            .inner
+            .read()
+            .await
+            .list_reference_values(DEFAULT_TENANT)
+            .await?;
+        let values = values
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<serde_json::Result<_>>()?;
+        Ok(values)
+    }
+
+    async fn purge_expired_reference_values(&self) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .write()
+            .await
+            .purge_expired_reference_values(DEFAULT_TENANT)
+            .await
     }
 }
 
@@ -6,6 +6,7 @@ use anyhow::*;
 use async_trait::async_trait;
 use attestation::{
     reference_value_provider_service_client::ReferenceValueProviderServiceClient,
+    ReferenceValueDeleteRequest, ReferenceValueListRequest, ReferenceValuePurgeRequest,
     ReferenceValueQueryRequest, ReferenceValueQueryResponse, ReferenceValueRegisterRequest,
 };
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
@@ -14,14 +15,15 @@ use log::info;
 use mobc::{Manager, Pool};
 use serde::Deserialize;
 use std::collections::HashMap;
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 
 use crate::attestation::backend::{make_nonce, Attest, IndependentEvidence};
 
 use self::attestation::{
     attestation_service_client::AttestationServiceClient,
     individual_attestation_request::{InitData, RuntimeData},
-    AttestationRequest, ChallengeRequest, IndividualAttestationRequest, SetPolicyRequest,
+    AttestationRequest, ChallengeRequest, DeletePolicyRequest, GetPolicyRequest,
+    IndividualAttestationRequest, ListPoliciesRequest, SetPolicyRequest,
 };
 
 mod attestation {
@@ -40,8 +42,16 @@ pub struct GrpcConfig {
     pub as_addr: String,
     #[serde(default = "default_pool_size")]
     pub pool_size: u64,
+
+    /// TLS configuration of the connections to the CoCo AS. TLS is also used
+    /// without it if the scheme of `as_addr` is `https`.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
 }
 
+/// TLS configuration of the connections to the CoCo AS, see [`grpc_tls`].
+pub use grpc_tls::TlsConfig;
+
 fn default_as_addr() -> String {
     DEFAULT_AS_ADDR.to_string()
 }
@@ -55,6 +65,7 @@ impl Default for GrpcConfig {
         Self {
             as_addr: DEFAULT_AS_ADDR.to_string(),
             pool_size: DEFAULT_POOL_SIZE,
+            tls: None,
         }
     }
 }
@@ -69,9 +80,9 @@ impl GrpcClientPool {
             "connect to remote AS [{}] with pool size {}",
             config.as_addr, config.pool_size
         );
-        let manager = GrpcManager {
-            as_addr: config.as_addr,
-        };
+        let endpoint = grpc_tls::endpoint(config.as_addr, config.tls.as_ref())?;
+
+        let manager = GrpcManager { endpoint };
         let pool = Pool::builder().max_open(config.pool_size).build(manager);
 
         Ok(Self { pool })
@@ -96,6 +107,53 @@ impl Attest for GrpcClientPool {
         Ok(())
     }
 
+    async fn list_policies(&self) -> Result<HashMap<String, String>> {
+        let req = tonic::Request::new(ListPoliciesRequest {});
+
+        let mut client = self.pool.get().await?;
+        let policies = client
+            .as_rpc
+            .list_attestation_policies(req)
+            .await
+            .map_err(|e| anyhow!("List Policies Failed: {:?}", e))?
+            .into_inner()
+            .policies;
+
+        Ok(policies)
+    }
+
+    async fn get_policy(&self, policy_id: &str) -> Result<String> {
+        let req = tonic::Request::new(GetPolicyRequest {
+            policy_id: policy_id.to_string(),
+        });
+
+        let mut client = self.pool.get().await?;
+        let policy = client
+            .as_rpc
+            .get_attestation_policy(req)
+            .await
+            .map_err(|e| anyhow!("Get Policy Failed: {:?}", e))?
+            .into_inner()
+            .policy;
+
+        Ok(policy)
+    }
+
+    async fn delete_policy(&self, policy_id: &str) -> Result<()> {
+        let req = tonic::Request::new(DeletePolicyRequest {
+            policy_id: policy_id.to_string(),
+        });
+
+        let mut client = self.pool.get().await?;
+        client
+            .as_rpc
+            .delete_attestation_policy(req)
+            .await
+            .map_err(|e| anyhow!("Delete Policy Failed: {:?}", e))?;
+
+        Ok(())
+    }
+
     async fn verify(&self, evidence_to_verify: Vec<IndependentEvidence>) -> Result<String> {
         let mut verification_requests: Vec<IndividualAttestationRequest> = vec![];
 
@@ -108,7 +166,7 @@ impl Attest for GrpcClientPool {
 
             let mut request = IndividualAttestationRequest {
                 tee,
-                evidence: URL_SAFE_NO_PAD.encode(evidence.tee_evidence.to_string()),
+                evidence: URL_SAFE_NO_PAD.encode(&evidence.raw_tee_evidence),
                 runtime_data_hash_algorithm: COCO_AS_HASH_ALGORITHM.into(),
                 runtime_data: Some(RuntimeData::StructuredRuntimeData(
                     evidence.runtime_data.to_string(),
@@ -128,6 +186,8 @@ impl Attest for GrpcClientPool {
         let attestation_request = tonic::Request::new(AttestationRequest {
             verification_requests,
             policy_ids: vec!["default".to_string()],
+            token_format: "ear".to_string(),
+            nonce: String::new(),
         });
 
         let mut client = self.pool.get().await?;
@@ -190,7 +250,7 @@ impl Attest for GrpcClientPool {
     }
 
     async fn query_reference_values(&self) -> anyhow::Result<HashMap<String, serde_json::Value>> {
-        let req = tonic::Request::new(ReferenceValueQueryRequest {});
+        let req = tonic::Request::new(ReferenceValueQueryRequest { prefixes: vec![] });
 
         let mut client = self.pool.get().await?;
 
@@ -205,10 +265,80 @@ impl Attest for GrpcClientPool {
 
         Ok(serde_json::from_str(&reference_value_results)?)
     }
+
+    async fn delete_reference_value(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueDeleteRequest {
+            name: name.to_string(),
+            prefix: String::new(),
+        });
+
+        let mut client = self.pool.get().await?;
+
+        let deleted = client
+            .rvps_rpc
+            .delete_reference_value(req)
+            .await
+            .map_err(|e| anyhow!("Failed to delete reference value: {:?}", e))?
+            .into_inner()
+            .deleted;
+
+        Ok(deleted)
+    }
+
+    async fn delete_reference_values_by_prefix(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueDeleteRequest {
+            name: String::new(),
+            prefix: prefix.to_string(),
+        });
+
+        let mut client = self.pool.get().await?;
+
+        let deleted = client
+            .rvps_rpc
+            .delete_reference_value(req)
+            .await
+            .map_err(|e| anyhow!("Failed to delete reference values: {:?}", e))?
+            .into_inner()
+            .deleted;
+
+        Ok(deleted)
+    }
+
+    async fn list_reference_values(&self) -> anyhow::Result<Vec<serde_json::Value>> {
+        let req = tonic::Request::new(ReferenceValueListRequest {});
+
+        let mut client = self.pool.get().await?;
+
+        let reference_values = client
+            .rvps_rpc
+            .list_reference_values(req)
+            .await
+            .map_err(|e| anyhow!("Failed to list reference values: {:?}", e))?
+            .into_inner()
+            .reference_values;
+
+        Ok(serde_json::from_str(&reference_values)?)
+    }
+
+    async fn purge_expired_reference_values(&self) -> anyhow::Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValuePurgeRequest {});
+
+        let mut client = self.pool.get().await?;
+
+        let purged = client
+            .rvps_rpc
+            .purge_expired_reference_values(req)
+            .await
+            .map_err(|e| anyhow!("Failed to purge reference values: {:?}", e))?
+            .into_inner()
+            .purged;
+
+        Ok(purged)
+    }
 }
 
 pub struct GrpcManager {
-    as_addr: String,
+    endpoint: Endpoint,
 }
 
 pub struct AsConnection {
@@ -222,9 +352,7 @@ impl Manager for GrpcManager {
     type Error = Error;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        let connection = Channel::from_shared(self.as_addr.clone())?
-            .connect()
-            .await?;
+        let connection = self.endpoint.connect().await?;
         let as_rpc = AttestationServiceClient::new(connection.clone());
         let rvps_rpc = ReferenceValueProviderServiceClient::new(connection);
         Ok(AsConnection { as_rpc, rvps_rpc })
@@ -234,3 +362,188 @@ impl Manager for GrpcManager {
         Ok(conn)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use grpc_tls::testing::{certificate, write_certificate};
+    use tonic::{
+        transport::{Certificate, Identity, Server, ServerTlsConfig},
+        Request, Response, Status,
+    };
+
+    use super::attestation::{
+        attestation_service_server::{AttestationService, AttestationServiceServer},
+        AttestationResponse, ChallengeRequest, ChallengeResponse, DeletePolicyResponse,
+        GetPolicyResponse, ListPoliciesResponse, SetPolicyResponse,
+    };
+    use super::*;
+
+    /// Attestation service that records the IDs of the policies set.
+    #[derive(Default)]
+    struct MockAttestationService {
+        policies: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[tonic::async_trait]
+    impl AttestationService for MockAttestationService {
+        async fn attestation_evaluate(
+            &self,
+            _request: Request<AttestationRequest>,
+        ) -> Result<Response<AttestationResponse>, Status> {
+            Err(Status::unimplemented("attestation"))
+        }
+
+        async fn set_attestation_policy(
+            &self,
+            request: Request<SetPolicyRequest>,
+        ) -> Result<Response<SetPolicyResponse>, Status> {
+            let policy_id = request.into_inner().policy_id;
+            self.policies.lock().unwrap().push(policy_id);
+            std::result::Result::Ok(Response::new(SetPolicyResponse {}))
+        }
+
+        async fn list_attestation_policies(
+            &self,
+            _request: Request<ListPoliciesRequest>,
+        ) -> Result<Response<ListPoliciesResponse>, Status> {
+            let policies = self
+                .policies
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|id| (id.clone(), format!("digest-{id}")))
+                .collect();
+            std::result::Result::Ok(Response::new(ListPoliciesResponse { policies }))
+        }
+
+        async fn get_attestation_policy(
+            &self,
+            request: Request<GetPolicyRequest>,
+        ) -> Result<Response<GetPolicyResponse>, Status> {
+            let policy_id = request.into_inner().policy_id;
+            if !self.policies.lock().unwrap().contains(&policy_id) {
+                return Err(Status::not_found(policy_id));
+            }
+            std::result::Result::Ok(Response::new(GetPolicyResponse {
+                policy: "policy".into(),
+            }))
+        }
+
+        async fn delete_attestation_policy(
+            &self,
+            request: Request<DeletePolicyRequest>,
+        ) -> Result<Response<DeletePolicyResponse>, Status> {
+            let policy_id = request.into_inner().policy_id;
+            let mut policies = self.policies.lock().unwrap();
+            let Some(index) = policies.iter().position(|id| *id == policy_id) else {
+                return Err(Status::not_found(policy_id));
+            };
+            policies.remove(index);
+            std::result::Result::Ok(Response::new(DeletePolicyResponse {}))
+        }
+
+        async fn get_attestation_challenge(
+            &self,
+            _request: Request<ChallengeRequest>,
+        ) -> Result<Response<ChallengeResponse>, Status> {
+            Err(Status::unimplemented("challenge"))
+        }
+    }
+
+    #[tokio::test]
+    async fn mutual_tls() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ca, ca_key) = certificate("test-ca", None).unwrap();
+        let (server, server_key) = certificate("as.test", Some((&ca, &ca_key))).unwrap();
+        let (client, client_key) = certificate("kbs.test", Some((&ca, &ca_key))).unwrap();
+        let (ca_cert, _) = write_certificate(dir.path(), "ca", &ca, &ca_key).unwrap();
+        let (client_cert, client_key) =
+            write_certificate(dir.path(), "client", &client, &client_key).unwrap();
+
+        let service = MockAttestationService::default();
+        let policies = service.policies.clone();
+        let tls = ServerTlsConfig::new()
+            .identity(Identity::from_pem(
+                server.to_pem().unwrap(),
+                server_key.private_key_to_pem_pkcs8().unwrap(),
+            ))
+            .client_ca_root(Certificate::from_pem(ca.to_pem().unwrap()));
+        let socket = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        tokio::spawn(
+            Server::builder()
+                .tls_config(tls)
+                .unwrap()
+                .add_service(AttestationServiceServer::new(service))
+                .serve(socket),
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let mut config = GrpcConfig {
+            as_addr: format!("https://127.0.0.1:{}", socket.port()),
+            pool_size: 1,
+            tls: Some(TlsConfig {
+                ca_cert: Some(ca_cert),
+                cert: Some(client_cert),
+                key: Some(client_key),
+                domain: Some("as.test".into()),
+            }),
+        };
+        let pool = GrpcClientPool::new(config.clone()).await.unwrap();
+        pool.set_policy("default", "policy").await.unwrap();
+        assert_eq!(*policies.lock().unwrap(), vec!["default".to_string()]);
+
+        // The server certificate is not accepted for another name
+        let mut other_domain = config.clone();
+        other_domain.tls.as_mut().unwrap().domain = Some("other.test".into());
+        let pool = GrpcClientPool::new(other_domain).await.unwrap();
+        assert!(pool.set_policy("other", "policy").await.is_err());
+
+        // Without a client certificate the handshake is rejected
+        let tls = config.tls.as_mut().unwrap();
+        tls.cert = None;
+        tls.key = None;
+        let pool = GrpcClientPool::new(config).await.unwrap();
+        assert!(pool.set_policy("other", "policy").await.is_err());
+        assert_eq!(policies.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn manage_policies() {
+        let service = MockAttestationService::default();
+        let socket = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(AttestationServiceServer::new(service))
+                .serve(socket),
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let pool = GrpcClientPool::new(GrpcConfig {
+            as_addr: format!("http://127.0.0.1:{}", socket.port()),
+            pool_size: 1,
+            tls: None,
+        })
+        .await
+        .unwrap();
+        pool.set_policy("default", "policy").await.unwrap();
+        pool.set_policy("other", "policy").await.unwrap();
+
+        let listed = pool.list_policies().await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed["other"], "digest-other");
+        assert_eq!(pool.get_policy("other").await.unwrap(), "policy");
+
+        pool.delete_policy("other").await.unwrap();
+        assert!(pool.get_policy("other").await.is_err());
+        assert!(pool.delete_policy("other").await.is_err());
+        assert_eq!(pool.list_policies().await.unwrap().len(), 1);
+    }
+}
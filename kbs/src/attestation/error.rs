@@ -39,4 +39,22 @@ pub enum Error {
         #[source]
         source: anyhow::Error,
     },
+
+    #[error("List Attestation Policies failed")]
+    ListPolicies {
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error("Get Attestation Policy failed")]
+    GetPolicy {
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error("Delete Attestation Policy failed: {source}")]
+    DeletePolicy {
+        #[source]
+        source: anyhow::Error,
+    },
 }
@@ -147,6 +147,7 @@ mod tests {
                     crate::attestation::coco::grpc::GrpcConfig {
                         as_addr: "http://127.0.0.1:50001".into(),
                         pool_size: 100,
+                        tls: None,
                     },
                 ),
             timeout: 600,
@@ -189,6 +190,8 @@ mod tests {
                         work_dir: "/opt/coco/attestation-service".into(),
                         rvps_config: RvpsConfig::GrpcRemote(RvpsRemoteConfig {
                             address: "http://127.0.0.1:50003".into(),
+                            cache: Default::default(),
+                            tls: None,
                         }),
                         attestation_token_broker: EarTokenConfiguration {
                             duration_min: DEFAULT_TOKEN_DURATION,
@@ -197,6 +200,9 @@ mod tests {
                             ..Default::default()
                         },
                         verifier_config: None,
+                        verification: Default::default(),
+                        history: None,
+                        tenants: Default::default(),
                     }
                 ),
             timeout: crate::attestation::config::DEFAULT_TIMEOUT,
@@ -272,6 +278,7 @@ mod tests {
                     crate::attestation::coco::grpc::GrpcConfig {
                         as_addr: "http://as:50004".into(),
                         pool_size: crate::attestation::coco::grpc::DEFAULT_POOL_SIZE,
+                        tls: None,
                     },
                 ),
             timeout: crate::attestation::config::DEFAULT_TIMEOUT,
@@ -308,12 +315,17 @@ mod tests {
                                 file_path: "/opt/confidential-containers/attestation-service/reference_values".into(),
                             }),
                             extractors: None,
-                        }),
+                            signature: Default::default(),
+                            allow_import: false,
+                        }.into()),
                         attestation_token_broker: EarTokenConfiguration {
                             duration_min: 5,
                             ..Default::default()
                         },
                         verifier_config: None,
+                        verification: Default::default(),
+                        history: None,
+                        tenants: Default::default(),
                     }
                 ),
             timeout: crate::attestation::config::DEFAULT_TIMEOUT,
@@ -376,8 +388,14 @@ mod tests {
             attestation_service:
                 crate::attestation::config::AttestationServiceConfig::CoCoASGrpc(
                     crate::attestation::coco::grpc::GrpcConfig {
-                        as_addr: "http://127.0.0.1:50004".into(),
+                        as_addr: "https://127.0.0.1:50004".into(),
                         pool_size: 100,
+                        tls: Some(crate::attestation::coco::grpc::TlsConfig {
+                            ca_cert: Some("/etc/kbs/as-ca.pem".into()),
+                            cert: Some("/etc/kbs/as-client.pem".into()),
+                            key: Some("/etc/kbs/as-client.key".into()),
+                            domain: Some("as.example.com".into()),
+                        }),
                     },
                 ),
             timeout: crate::attestation::config::DEFAULT_TIMEOUT,
@@ -438,13 +456,16 @@ mod tests {
                 crate::attestation::config::AttestationServiceConfig::CoCoASBuiltIn(
                     attestation_service::config::Config {
                         work_dir: "/opt/confidential-containers/attestation-service".into(),
-                        rvps_config: RvpsConfig::BuiltIn(RvpsCrateConfig::default()),
+                        rvps_config: RvpsConfig::BuiltIn(RvpsCrateConfig::default().into()),
                         attestation_token_broker: EarTokenConfiguration {
                             duration_min: 5,
                             policy_dir: "/opt/confidential-containers/attestation-service/ear-policies".into(),
                             ..Default::default()
                         },
                         verifier_config: None,
+                        verification: Default::default(),
+                        history: None,
+                        tenants: Default::default(),
                     }
                 ),
             timeout: crate::attestation::config::DEFAULT_TIMEOUT,
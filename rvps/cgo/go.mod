@@ -1,12 +0,0 @@
-module cgo
-
-go 1.23.0
-
-require github.com/in-toto/in-toto-golang v0.9.0
-
-require (
-	github.com/secure-systems-lab/go-securesystemslib v0.7.0 // indirect
-	github.com/shibumi/go-pathspec v1.3.0 // indirect
-	golang.org/x/crypto v0.35.0 // indirect
-	golang.org/x/sys v0.30.0 // indirect
-)
@@ -0,0 +1,11 @@
+-- Reference values, keyed by name. `expiration` is a UNIX timestamp in
+-- seconds, and `reference_value` the JSON serialized reference value.
+-- Names are compared bytewise, so that prefix queries are range scans
+-- of the primary key.
+CREATE TABLE IF NOT EXISTS reference_values (
+    name TEXT COLLATE "C" PRIMARY KEY NOT NULL,
+    expiration BIGINT NOT NULL,
+    reference_value TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS reference_values_expiration ON reference_values (expiration);
@@ -0,0 +1,9 @@
+-- Reference values, keyed by name. `expiration` is a UNIX timestamp in
+-- seconds, and `reference_value` the JSON serialized reference value.
+CREATE TABLE IF NOT EXISTS reference_values (
+    name TEXT PRIMARY KEY NOT NULL,
+    expiration BIGINT NOT NULL,
+    reference_value TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS reference_values_expiration ON reference_values (expiration);
@@ -1,10 +1,12 @@
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use clap::Parser;
 use log::{info, warn};
 use shadow_rs::shadow;
 
 use reference_value_provider_service::config::Config;
-use reference_value_provider_service::server;
+use reference_value_provider_service::server::{self, TlsConfig};
 
 shadow!(build);
 
@@ -27,6 +29,20 @@ pub struct Cli {
     /// `--address 127.0.0.1:55554`
     #[arg(short = 'a', long, default_value = DEFAULT_ADDRESS)]
     pub address: String,
+
+    /// Path to the PEM encoded certificate chain of the server. TLS is
+    /// enabled if given together with `--tls-key`.
+    #[arg(long)]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key of the server.
+    #[arg(long)]
+    pub tls_key: Option<PathBuf>,
+
+    /// Path to the PEM encoded CA certificate that issues the client
+    /// certificates. If given, clients must authenticate with mutual TLS.
+    #[arg(long)]
+    pub tls_client_ca: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -55,5 +71,11 @@ async fn main() -> Result<()> {
 
     let socket = cli.address.parse().context("parse socket addr failed")?;
 
-    server::start(socket, config).await
+    let tls = TlsConfig {
+        cert: cli.tls_cert,
+        key: cli.tls_key,
+        client_ca: cli.tls_client_ca,
+    };
+
+    server::start(socket, config, tls).await
 }
@@ -5,30 +5,155 @@
 // Helpers for building a client for the RVPS
 
 use anyhow::*;
+use tonic::transport::Channel;
 
 use crate::rvps_api::reference::{
     reference_value_provider_service_client::ReferenceValueProviderServiceClient,
-    ReferenceValueQueryRequest, ReferenceValueRegisterRequest,
+    ReferenceValueDeleteRequest, ReferenceValueImportRequest, ReferenceValueListRequest,
+    ReferenceValuePurgeRequest, ReferenceValueQueryRequest, ReferenceValueRegisterRequest,
 };
 
-pub async fn register(address: String, message: String) -> Result<()> {
-    let mut client = ReferenceValueProviderServiceClient::connect(address).await?;
-    let req = tonic::Request::new(ReferenceValueRegisterRequest { message });
+/// TLS configuration of the gRPC connection to the RVPS, see [`grpc_tls`].
+pub use grpc_tls::{endpoint, TlsConfig};
+
+/// A client of the RVPS.
+pub struct RvpsClient {
+    client: ReferenceValueProviderServiceClient<Channel>,
+}
+
+impl RvpsClient {
+    /// Connect to the RVPS at `address`. TLS is used if a configuration is
+    /// given, or the scheme of the address is `https`.
+    pub async fn connect(address: String, tls: Option<&TlsConfig>) -> Result<Self> {
+        let channel = endpoint(address, tls)?
+            .connect()
+            .await
+            .context("connect to RVPS")?;
+        Ok(Self {
+            client: ReferenceValueProviderServiceClient::new(channel),
+        })
+    }
+
+    pub async fn register(&mut self, message: String) -> Result<()> {
+        let req = tonic::Request::new(ReferenceValueRegisterRequest { message });
+
+        self.client.register_reference_value(req).await?;
+
+        Ok(())
+    }
+
+    /// Query the reference values whose name starts with one of the
+    /// `prefixes`, or all of them if `prefixes` is empty.
+    pub async fn query(&mut self, prefixes: Vec<String>) -> Result<String> {
+        let req = tonic::Request::new(ReferenceValueQueryRequest { prefixes });
+
+        let rvs = self
+            .client
+            .query_reference_value(req)
+            .await?
+            .into_inner()
+            .reference_value_results;
+
+        Ok(rvs)
+    }
+
+    /// Delete the reference value of the given `name`, or all the reference
+    /// values whose name starts with `prefix`. Return the deleted names.
+    pub async fn delete(
+        &mut self,
+        name: Option<String>,
+        prefix: Option<String>,
+    ) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueDeleteRequest {
+            name: name.unwrap_or_default(),
+            prefix: prefix.unwrap_or_default(),
+        });
+
+        let deleted = self
+            .client
+            .delete_reference_value(req)
+            .await?
+            .into_inner()
+            .deleted;
+
+        Ok(deleted)
+    }
+
+    pub async fn list(&mut self) -> Result<String> {
+        let req = tonic::Request::new(ReferenceValueListRequest {});
+
+        let rvs = self
+            .client
+            .list_reference_values(req)
+            .await?
+            .into_inner()
+            .reference_values;
 
-    client.register_reference_value(req).await?;
+        Ok(rvs)
+    }
 
-    Ok(())
+    pub async fn purge(&mut self) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValuePurgeRequest {});
+
+        let purged = self
+            .client
+            .purge_expired_reference_values(req)
+            .await?
+            .into_inner()
+            .purged;
+
+        Ok(purged)
+    }
+
+    /// Store the reference values, a JSON array in the format of [`Self::list`],
+    /// as they are. Return the imported names.
+    pub async fn import(&mut self, reference_values: String) -> Result<Vec<String>> {
+        let req = tonic::Request::new(ReferenceValueImportRequest { reference_values });
+
+        let imported = self
+            .client
+            .import_reference_values(req)
+            .await?
+            .into_inner()
+            .imported;
+
+        Ok(imported)
+    }
+}
+
+pub async fn register(address: String, message: String) -> Result<()> {
+    RvpsClient::connect(address, None)
+        .await?
+        .register(message)
+        .await
 }
 
-pub async fn query(address: String) -> Result<String> {
-    let mut client = ReferenceValueProviderServiceClient::connect(address).await?;
-    let req = tonic::Request::new(ReferenceValueQueryRequest {});
+/// Query the reference values whose name starts with one of the `prefixes`,
+/// or all of them if `prefixes` is empty.
+pub async fn query(address: String, prefixes: Vec<String>) -> Result<String> {
+    RvpsClient::connect(address, None)
+        .await?
+        .query(prefixes)
+        .await
+}
 
-    let rvs = client
-        .query_reference_value(req)
+/// Delete the reference value of the given `name`, or all the reference
+/// values whose name starts with `prefix`. Return the deleted names.
+pub async fn delete(
+    address: String,
+    name: Option<String>,
+    prefix: Option<String>,
+) -> Result<Vec<String>> {
+    RvpsClient::connect(address, None)
         .await?
-        .into_inner()
-        .reference_value_results;
+        .delete(name, prefix)
+        .await
+}
+
+pub async fn list(address: String) -> Result<String> {
+    RvpsClient::connect(address, None).await?.list().await
+}
 
-    Ok(rvs)
+pub async fn purge(address: String) -> Result<Vec<String>> {
+    RvpsClient::connect(address, None).await?.purge().await
 }
@@ -6,6 +6,7 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 
 use crate::extractors::ExtractorsConfig;
+use crate::signature::SignatureConfig;
 use crate::storage::ReferenceValueStorageConfig;
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Default)]
@@ -15,6 +16,15 @@ pub struct Config {
 
     #[serde(default)]
     pub extractors: Option<ExtractorsConfig>,
+
+    #[serde(default)]
+    pub signature: SignatureConfig,
+
+    /// Whether reference values can be imported as they are, without any
+    /// verification. Off by default, as anyone who can call the RVPS could
+    /// then replace any reference value.
+    #[serde(default)]
+    pub allow_import: bool,
 }
 
 impl Config {
@@ -0,0 +1,665 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Extractor for IETF CoRIM (Concise Reference Integrity Manifest).
+//!
+//! The provenance is a base64 encoded signed CoRIM, i.e. a COSE_Sign1
+//! whose payload is an unsigned CoRIM carrying CoMID tags. The reference
+//! value triples of the CoMIDs are mapped to the reference value names
+//! consumed by the default EAR policies, see the README for the mapping.
+
+use std::collections::HashMap;
+
+use anyhow::*;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ciborium::Value;
+use coset::{
+    iana, CborSerializable, CoseSign1, Label, RegisteredLabelWithPrivate, TaggedCborSerializable,
+};
+use log::{debug, info};
+use openssl::{
+    bn::BigNum,
+    ecdsa::EcdsaSig,
+    hash::MessageDigest,
+    pkey::{PKey, Public},
+    sign::Verifier,
+};
+use serde::Deserialize;
+
+use crate::ReferenceValue;
+
+use super::Extractor;
+
+/// CBOR tag of an unsigned CoRIM.
+const TAG_UNSIGNED_CORIM: u64 = 501;
+/// CBOR tag of a CoMID inside a CoRIM.
+const TAG_COMID: u64 = 506;
+/// CBOR tag of an epoch-based date/time.
+const TAG_EPOCH_TIME: u64 = 1;
+/// CBOR tag of tagged bytes, e.g. a `raw-value`.
+const TAG_BYTES: u64 = 560;
+/// Protected header label of the CoRIM meta map.
+const HEADER_CORIM_META: i64 = 8;
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct CorimExtractorConfig {
+    /// Map of key ids to the paths of PEM encoded public keys that
+    /// CoRIM signatures are verified against. The `kid` header of
+    /// the COSE_Sign1, if any, selects the key.
+    #[serde(default)]
+    pub trusted_keys: HashMap<String, String>,
+}
+
+#[derive(Default)]
+pub struct CorimExtractor {
+    trusted_keys: HashMap<String, PKey<Public>>,
+}
+
+/// The kind of Attester environment that a reference value triple
+/// describes, derived from the `vendor` of the environment class.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Platform {
+    Intel,
+    Amd,
+    Arm,
+    Other,
+}
+
+impl CorimExtractor {
+    pub fn new(config: Option<CorimExtractorConfig>) -> Result<Self> {
+        let mut trusted_keys = HashMap::new();
+        for (keyid, path) in config.unwrap_or_default().trusted_keys {
+            let pem = std::fs::read(&path)
+                .with_context(|| format!("read CoRIM key {keyid} from {path}"))?;
+            let key = PKey::public_key_from_pem(&pem)
+                .with_context(|| format!("parse CoRIM key {keyid}"))?;
+            trusted_keys.insert(keyid, key);
+        }
+
+        Ok(Self { trusted_keys })
+    }
+
+    /// Verify the signature of the COSE_Sign1 and return the id of the
+    /// trusted key that made it.
+    fn verify_signature(&self, sign1: &CoseSign1) -> Result<String> {
+        if self.trusted_keys.is_empty() {
+            bail!("No trusted keys are configured for the CoRIM extractor");
+        }
+
+        let alg = match &sign1.protected.header.alg {
+            Some(RegisteredLabelWithPrivate::Assigned(alg)) => *alg,
+            other => bail!("Unsupported CoRIM signature algorithm {other:?}"),
+        };
+
+        let kid = String::from_utf8(sign1.protected.header.key_id.clone())
+            .ok()
+            .or_else(|| String::from_utf8(sign1.unprotected.key_id.clone()).ok())
+            .filter(|kid| !kid.is_empty());
+        let candidates: Vec<_> = match &kid {
+            Some(kid) => self.trusted_keys.get_key_value(kid).into_iter().collect(),
+            None => self.trusted_keys.iter().collect(),
+        };
+
+        for (keyid, key) in candidates {
+            if sign1
+                .verify_signature(b"", |sig, data| verify_cose(alg, key, sig, data))
+                .is_ok()
+            {
+                return Ok(keyid.clone());
+            }
+        }
+
+        bail!("CoRIM signature does not match any trusted key")
+    }
+}
+
+/// Verify a COSE signature, which is `r || s` for ECDSA.
+fn verify_cose(alg: iana::Algorithm, key: &PKey<Public>, sig: &[u8], data: &[u8]) -> Result<()> {
+    let (digest, sig) = match alg {
+        iana::Algorithm::ES256 | iana::Algorithm::ES384 | iana::Algorithm::ES512 => {
+            let digest = match alg {
+                iana::Algorithm::ES256 => MessageDigest::sha256(),
+                iana::Algorithm::ES384 => MessageDigest::sha384(),
+                _ => MessageDigest::sha512(),
+            };
+            let (r, s) = sig.split_at(sig.len() / 2);
+            let sig =
+                EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?
+                    .to_der()?;
+            (Some(digest), sig)
+        }
+        iana::Algorithm::EdDSA => (None, sig.to_vec()),
+        iana::Algorithm::PS256 => (Some(MessageDigest::sha256()), sig.to_vec()),
+        other => bail!("Unsupported CoRIM signature algorithm {other:?}"),
+    };
+
+    let mut verifier = match digest {
+        Some(digest) => Verifier::new(digest, key)?,
+        None => Verifier::new_without_digest(key)?,
+    };
+    if alg == iana::Algorithm::PS256 {
+        verifier.set_rsa_padding(openssl::rsa::Padding::PKCS1_PSS)?;
+    }
+
+    if !verifier.verify_oneshot(&sig, data)? {
+        bail!("signature mismatch");
+    }
+
+    Ok(())
+}
+
+/// Look up an integer key in a CBOR map.
+fn map_get(map: &Value, key: i64) -> Option<&Value> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k.as_integer().and_then(|k| i64::try_from(k).ok()) == Some(key))
+        .map(|(_, v)| v)
+}
+
+/// Strip a CBOR tag if there is one.
+fn untag(value: &Value) -> &Value {
+    match value {
+        Value::Tag(_, inner) => untag(inner),
+        other => other,
+    }
+}
+
+/// Parse a CoRIM `time`, i.e. an epoch-based date/time.
+fn parse_time(value: &Value) -> Result<DateTime<Utc>> {
+    let secs = match value {
+        Value::Tag(TAG_EPOCH_TIME, inner) => inner.as_integer(),
+        other => other.as_integer(),
+    }
+    .and_then(|secs| i64::try_from(secs).ok())
+    .ok_or_else(|| anyhow!("Illegal CoRIM time {value:?}"))?;
+
+    DateTime::from_timestamp(secs, 0).ok_or_else(|| anyhow!("Illegal CoRIM time {secs}"))
+}
+
+/// Check a `validity-map` against the current time and return its
+/// `not-after`.
+fn check_validity(validity: &Value) -> Result<DateTime<Utc>> {
+    let now = Utc::now();
+    if let Some(not_before) = map_get(validity, 0) {
+        let not_before = parse_time(not_before)?;
+        if now < not_before {
+            bail!("CoRIM is not valid before {not_before}");
+        }
+    }
+
+    let not_after = map_get(validity, 1)
+        .ok_or_else(|| anyhow!("CoRIM validity without `not-after`"))
+        .and_then(parse_time)?;
+    if now > not_after {
+        bail!("CoRIM expired at {not_after}");
+    }
+
+    Ok(not_after)
+}
+
+/// Classify the environment of a reference value triple by its vendor.
+fn platform(environment: &Value) -> Platform {
+    let vendor = map_get(environment, 0)
+        .and_then(|class| map_get(class, 1))
+        .and_then(Value::as_text)
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if vendor.contains("intel") {
+        Platform::Intel
+    } else if vendor.contains("amd") || vendor.contains("advanced micro devices") {
+        Platform::Amd
+    } else if vendor
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|w| w == "arm")
+    {
+        Platform::Arm
+    } else {
+        Platform::Other
+    }
+}
+
+/// The measured values of a `measurement-values-map`, i.e. the `digests`
+/// and the `raw-value`.
+fn measured_values(mval: &Value) -> Vec<Vec<u8>> {
+    let mut values = Vec::new();
+    if let Some(digests) = map_get(mval, 2).and_then(Value::as_array) {
+        values.extend(digests.iter().filter_map(digest_value));
+    }
+
+    let raw = match map_get(mval, 4) {
+        Some(Value::Tag(TAG_BYTES, raw)) => raw.as_bytes(),
+        Some(raw) => raw.as_bytes(),
+        None => None,
+    };
+    if let Some(raw) = raw {
+        values.push(raw.clone());
+    }
+
+    values
+}
+
+/// The value of a `digest`, i.e. `[alg, val]`.
+fn digest_value(digest: &Value) -> Option<Vec<u8>> {
+    digest.as_array()?.get(1)?.as_bytes().cloned()
+}
+
+/// The name of the reference value of a measurement with the given `mkey`,
+/// and whether its values are base64 (rather than hex) encoded.
+fn measurement_name(platform: Platform, mkey: &str) -> Option<(String, bool)> {
+    let lowercase = mkey.to_lowercase();
+    match (platform, lowercase.as_str()) {
+        (_, "mrtd") => Some(("mr_td".into(), false)),
+        (_, "mrseam") => Some(("mr_seam".into(), false)),
+        (_, "rtmr0" | "rtmr1" | "rtmr2" | "rtmr3") => {
+            Some((format!("rtmr_{}", &lowercase[4..]), false))
+        }
+        (_, "rim") => Some(("cca.realm.cca-realm-initial-measurement".into(), true)),
+        (_, "rem0" | "rem1" | "rem2" | "rem3") => Some((
+            format!(
+                "cca.realm.cca-realm-extensible-measurements.{}",
+                &lowercase[3..]
+            ),
+            true,
+        )),
+        (Platform::Amd, "measurement") => Some(("snp_launch_measurement".into(), true)),
+        (Platform::Arm, _) => Some((
+            format!("cca.platform.cca-platform-sw-components.{mkey}"),
+            true,
+        )),
+        _ => None,
+    }
+}
+
+/// The name of the reference value of an integrity register.
+fn register_name(platform: Platform, key: &Value) -> Option<(String, bool)> {
+    match (platform, key) {
+        (Platform::Intel, Value::Integer(index)) => {
+            let index = u8::try_from(*index).ok().filter(|i| *i < 4)?;
+            Some((format!("rtmr_{index}"), false))
+        }
+        (platform, Value::Text(name)) => measurement_name(platform, name)
+            .filter(|(name, _)| name.starts_with("rtmr_") || name.starts_with("cca.realm.")),
+        _ => None,
+    }
+}
+
+/// Collect the reference values of a `reference-triple-record`, i.e.
+/// `[environment-map, [+ measurement-map]]`, into `values`.
+fn collect_triple(triple: &Value, values: &mut HashMap<String, Vec<String>>) -> Result<()> {
+    let triple = triple
+        .as_array()
+        .filter(|t| t.len() == 2)
+        .ok_or_else(|| anyhow!("Illegal reference triple"))?;
+    let platform = platform(&triple[0]);
+    let measurements = triple[1]
+        .as_array()
+        .ok_or_else(|| anyhow!("Illegal measurements in reference triple"))?;
+
+    let mut push = |name: String, base64: bool, value: &[u8]| {
+        let value = match base64 {
+            true => base64::engine::general_purpose::STANDARD.encode(value),
+            false => hex::encode(value),
+        };
+        let entry = values.entry(name).or_default();
+        if !entry.contains(&value) {
+            entry.push(value);
+        }
+    };
+
+    for measurement in measurements {
+        let Some(mval) = map_get(measurement, 1) else {
+            continue;
+        };
+
+        let mkey = map_get(measurement, 0)
+            .map(untag)
+            .and_then(Value::as_text)
+            .map(str::to_string)
+            .or_else(|| (platform == Platform::Amd).then(|| "measurement".to_string()));
+        if let Some((name, base64)) = mkey.and_then(|mkey| measurement_name(platform, &mkey)) {
+            for value in measured_values(mval) {
+                push(name.clone(), base64, &value);
+            }
+        }
+
+        if let Some(registers) = map_get(mval, 14).and_then(Value::as_map) {
+            for (key, digests) in registers {
+                let Some((name, base64)) = register_name(platform, key) else {
+                    debug!("Skip unknown integrity register {key:?}");
+                    continue;
+                };
+                for value in digests
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(digest_value)
+                {
+                    push(name.clone(), base64, &value);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Extractor for CorimExtractor {
+    fn verify_and_extract(&self, provenance_base64: &str) -> Result<Vec<ReferenceValue>> {
+        info!("Extracting reference values from CoRIM.");
+
+        let corim = base64::engine::general_purpose::STANDARD
+            .decode(provenance_base64)
+            .context("Failed to decode CoRIM as base 64")?;
+        let sign1 = CoseSign1::from_tagged_slice(&corim)
+            .or_else(|_| CoseSign1::from_slice(&corim))
+            .map_err(|e| anyhow!("Failed to parse signed CoRIM: {e:?}"))?;
+
+        let signer = self.verify_signature(&sign1)?;
+        info!("CoRIM signature of {signer} is verified.");
+
+        let mut expiration = None;
+        if let Some((_, meta)) = sign1
+            .protected
+            .header
+            .rest
+            .iter()
+            .find(|(label, _)| *label == Label::Int(HEADER_CORIM_META))
+        {
+            let meta: Value = match meta.as_bytes() {
+                Some(bytes) => ciborium::from_reader(&bytes[..]).context("parse CoRIM meta")?,
+                None => meta.clone(),
+            };
+            if let Some(validity) = map_get(&meta, 1) {
+                expiration = Some(check_validity(validity)?);
+            }
+        }
+
+        let payload = sign1
+            .payload
+            .as_ref()
+            .ok_or_else(|| anyhow!("Detached CoRIM payload is not supported"))?;
+        let unsigned: Value = ciborium::from_reader(&payload[..]).context("parse CoRIM")?;
+        let unsigned = match unsigned {
+            Value::Tag(TAG_UNSIGNED_CORIM, inner) => *inner,
+            other => other,
+        };
+
+        if let Some(validity) = map_get(&unsigned, 4) {
+            let not_after = check_validity(validity)?;
+            expiration = Some(expiration.map_or(not_after, |e: DateTime<Utc>| e.min(not_after)));
+        }
+
+        let tags = map_get(&unsigned, 1)
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("CoRIM without tags"))?;
+
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        for tag in tags {
+            let Value::Tag(TAG_COMID, comid) = tag else {
+                debug!("Skip non-CoMID tag in CoRIM");
+                continue;
+            };
+            let comid = comid
+                .as_bytes()
+                .ok_or_else(|| anyhow!("Illegal CoMID encoding"))?;
+            let comid: Value = ciborium::from_reader(&comid[..]).context("parse CoMID")?;
+
+            let reference_triples = map_get(&comid, 4)
+                .and_then(|triples| map_get(triples, 0))
+                .and_then(Value::as_array);
+            for triple in reference_triples.into_iter().flatten() {
+                collect_triple(triple, &mut values)?;
+            }
+        }
+
+        let mut rvs = Vec::new();
+        for (name, value) in values {
+            let mut rv = ReferenceValue::new()?
+                .set_name(&name)
+                .set_value(serde_json::json!(value))
+                .set_signer(&signer);
+            if let Some(expiration) = expiration {
+                rv = rv.set_expiration(expiration);
+            }
+            rvs.push(rv);
+        }
+
+        debug!("Reference Values Extracted: {:?}", rvs);
+        Ok(rvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base64::Engine;
+    use ciborium::Value;
+    use coset::{iana, CoseSign1, CoseSign1Builder, HeaderBuilder, TaggedCborSerializable};
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        ecdsa::EcdsaSig,
+        hash::{hash, MessageDigest},
+        nid::Nid,
+        pkey::{PKey, Private},
+    };
+    use serde_json::json;
+
+    use super::{CorimExtractor, CorimExtractorConfig, TAG_BYTES, TAG_COMID, TAG_EPOCH_TIME};
+    use crate::extractors::Extractor;
+
+    fn int(i: i64) -> Value {
+        Value::Integer(i.into())
+    }
+
+    fn map(entries: Vec<(i64, Value)>) -> Value {
+        Value::Map(entries.into_iter().map(|(k, v)| (int(k), v)).collect())
+    }
+
+    fn to_cbor(value: &Value) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(value, &mut buf).unwrap();
+        buf
+    }
+
+    fn environment(vendor: &str) -> Value {
+        map(vec![(0, map(vec![(1, Value::Text(vendor.into()))]))])
+    }
+
+    fn digests(value: &[u8]) -> Value {
+        Value::Array(vec![Value::Array(vec![
+            int(7),
+            Value::Bytes(value.to_vec()),
+        ])])
+    }
+
+    fn measurement(mkey: &str, value: &[u8]) -> Value {
+        map(vec![
+            (0, Value::Text(mkey.into())),
+            (1, map(vec![(2, digests(value))])),
+        ])
+    }
+
+    fn corim(not_after: i64) -> Vec<u8> {
+        let registers = Value::Map(vec![
+            (int(1), digests(&[0x11; 48])),
+            (int(2), digests(&[0x22; 48])),
+        ]);
+        let tdx = Value::Array(vec![
+            environment("Intel Corporation"),
+            Value::Array(vec![
+                measurement("MRTD", &[0xaa; 48]),
+                map(vec![(1, map(vec![(14, registers)]))]),
+            ]),
+        ]);
+        let snp = Value::Array(vec![
+            environment("AMD"),
+            Value::Array(vec![map(vec![(
+                1,
+                map(vec![(
+                    4,
+                    Value::Tag(TAG_BYTES, Box::new(Value::Bytes(vec![0xbb; 48]))),
+                )]),
+            )])]),
+        ]);
+        let realm = Value::Array(vec![
+            environment("Workload Client Ltd"),
+            Value::Array(vec![map(vec![(
+                1,
+                map(vec![(
+                    14,
+                    Value::Map(vec![
+                        (Value::Text("rim".into()), digests(&[0xcc; 32])),
+                        (Value::Text("rem0".into()), digests(&[0xdd; 32])),
+                    ]),
+                )]),
+            )])]),
+        ]);
+        let platform = Value::Array(vec![
+            environment("ARM Ltd"),
+            Value::Array(vec![measurement("BL", &[0xee; 32])]),
+        ]);
+
+        let comid = map(vec![
+            (1, map(vec![(0, Value::Text("test-comid".into()))])),
+            (
+                4,
+                map(vec![(0, Value::Array(vec![tdx, snp, realm, platform]))]),
+            ),
+        ]);
+        let unsigned = Value::Tag(
+            501,
+            Box::new(map(vec![
+                (0, Value::Text("test-corim".into())),
+                (
+                    1,
+                    Value::Array(vec![Value::Tag(
+                        TAG_COMID,
+                        Box::new(Value::Bytes(to_cbor(&comid))),
+                    )]),
+                ),
+                (
+                    4,
+                    map(vec![(
+                        1,
+                        Value::Tag(TAG_EPOCH_TIME, Box::new(int(not_after))),
+                    )]),
+                ),
+            ])),
+        );
+        to_cbor(&unsigned)
+    }
+
+    fn sign(payload: Vec<u8>, key: &PKey<Private>, kid: &str) -> String {
+        let protected = HeaderBuilder::new()
+            .algorithm(iana::Algorithm::ES384)
+            .key_id(kid.as_bytes().to_vec())
+            .build();
+        let ec_key = key.ec_key().unwrap();
+        let sign1 = CoseSign1Builder::new()
+            .protected(protected)
+            .payload(payload)
+            .create_signature(b"", |data| {
+                let digest = hash(MessageDigest::sha384(), data).unwrap();
+                let sig = EcdsaSig::sign(&digest, &ec_key).unwrap();
+                let mut raw = sig.r().to_vec_padded(48).unwrap();
+                raw.extend(sig.s().to_vec_padded(48).unwrap());
+                raw
+            })
+            .build();
+        base64::engine::general_purpose::STANDARD.encode(sign1.to_tagged_vec().unwrap())
+    }
+
+    fn extractor(key: &PKey<Private>, dir: &tempfile::TempDir) -> CorimExtractor {
+        let path = dir.path().join("corim.pem");
+        std::fs::write(&path, key.public_key_to_pem().unwrap()).unwrap();
+        CorimExtractor::new(Some(CorimExtractorConfig {
+            trusted_keys: HashMap::from([(
+                "vendor".to_string(),
+                path.to_string_lossy().to_string(),
+            )]),
+        }))
+        .unwrap()
+    }
+
+    fn new_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn extract_corim() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = new_key();
+        let extractor = extractor(&key, &dir);
+        let not_after = chrono::Utc::now().timestamp() + 3600;
+
+        let rvs = extractor
+            .verify_and_extract(&sign(corim(not_after), &key, "vendor"))
+            .unwrap();
+        let rvs: HashMap<_, _> = rvs
+            .into_iter()
+            .map(|rv| {
+                assert_eq!(rv.signer(), Some("vendor"));
+                assert_eq!(rv.expiration.timestamp(), not_after);
+                (rv.name.clone(), rv.value())
+            })
+            .collect();
+
+        assert_eq!(rvs["mr_td"], json!([hex::encode([0xaa; 48])]));
+        assert_eq!(rvs["rtmr_1"], json!([hex::encode([0x11; 48])]));
+        assert_eq!(rvs["rtmr_2"], json!([hex::encode([0x22; 48])]));
+        assert_eq!(
+            rvs["snp_launch_measurement"],
+            json!([base64::engine::general_purpose::STANDARD.encode([0xbb; 48])])
+        );
+        assert_eq!(
+            rvs["cca.realm.cca-realm-initial-measurement"],
+            json!([base64::engine::general_purpose::STANDARD.encode([0xcc; 32])])
+        );
+        assert_eq!(
+            rvs["cca.realm.cca-realm-extensible-measurements.0"],
+            json!([base64::engine::general_purpose::STANDARD.encode([0xdd; 32])])
+        );
+        assert_eq!(
+            rvs["cca.platform.cca-platform-sw-components.BL"],
+            json!([base64::engine::general_purpose::STANDARD.encode([0xee; 32])])
+        );
+        assert_eq!(rvs.len(), 7);
+    }
+
+    #[test]
+    fn reject_untrusted_corim() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = new_key();
+        let extractor = extractor(&key, &dir);
+        let not_after = chrono::Utc::now().timestamp() + 3600;
+
+        let untrusted = sign(corim(not_after), &new_key(), "vendor");
+        assert!(extractor.verify_and_extract(&untrusted).is_err());
+
+        let unknown_kid = sign(corim(not_after), &key, "other");
+        assert!(extractor.verify_and_extract(&unknown_kid).is_err());
+
+        let expired = sign(corim(1), &key, "vendor");
+        assert!(extractor.verify_and_extract(&expired).is_err());
+
+        let no_keys = CorimExtractor::new(None).unwrap();
+        assert!(no_keys
+            .verify_and_extract(&sign(corim(not_after), &key, "vendor"))
+            .is_err());
+
+        let signed = base64::engine::general_purpose::STANDARD
+            .decode(sign(corim(not_after), &key, "vendor"))
+            .unwrap();
+        let mut sign1 = CoseSign1::from_tagged_slice(&signed).unwrap();
+        sign1.payload = Some(corim(not_after + 1));
+        let tampered =
+            base64::engine::general_purpose::STANDARD.encode(sign1.to_tagged_vec().unwrap());
+        assert!(extractor.verify_and_extract(&tampered).is_err());
+    }
+}
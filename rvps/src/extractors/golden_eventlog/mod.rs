@@ -0,0 +1,345 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Extractor that derives reference values from the event log of a
+//! known-good boot.
+//!
+//! The provenance is a base64 encoded JSON [`GoldenEventLog`] carrying a CC
+//! event log (CCEL) or a TCG event log captured in a trusted environment.
+//! Each measured component becomes a reference value named after the
+//! register, the type and the description of its events, and each measured
+//! register becomes a reference value with its replayed value.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::*;
+use base64::Engine;
+use eventlog::{
+    ccel::tcg_enum::{TcgAlgorithm, TcgEventType},
+    CcEventLog, EventlogEntry, TcgEventLog,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::ReferenceValue;
+
+use super::Extractor;
+
+/// Prefixes of the `EV_IPL` strings that GRUB measures commands and the
+/// kernel command line with. The events of each are merged into one
+/// component.
+const GRUB_IPL_PREFIXES: [&str; 3] = ["grub_cmd", "grub_kernel_cmdline", "kernel_cmdline"];
+
+/// Format of the event log.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventLogFormat {
+    /// CC event log, whose register 0 is `MRTD` and registers 1 - 4 are
+    /// `RTMR[0]` - `RTMR[3]`.
+    Ccel,
+    /// TCG PC Client crypto agile event log of a TPM.
+    Tcg,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct GoldenEventLog {
+    pub format: EventLogFormat,
+
+    /// Base64 encoded binary event log.
+    pub eventlog: String,
+
+    /// Bank of the digests, `sha384` for a CCEL and `sha256` for a TCG
+    /// event log by default.
+    #[serde(default)]
+    pub algorithm: Option<String>,
+
+    /// Prefix of the names of the reference values, e.g. to register the
+    /// event logs of several images side by side.
+    #[serde(default)]
+    pub prefix: String,
+}
+
+#[derive(Default)]
+pub struct GoldenEventLogExtractor;
+
+fn algorithm(name: &str) -> Result<TcgAlgorithm> {
+    match name.to_lowercase().replace('-', "").as_str() {
+        "sha1" => Ok(TcgAlgorithm::Sha1),
+        "sha256" => Ok(TcgAlgorithm::Sha256),
+        "sha384" => Ok(TcgAlgorithm::Sha384),
+        "sha512" => Ok(TcgAlgorithm::Sha512),
+        "sm3" => Ok(TcgAlgorithm::Sm3),
+        _ => bail!("Unsupported digest algorithm {name}"),
+    }
+}
+
+/// Name of the component that an event measures, i.e. its type and a
+/// description of what it measures, if any.
+fn component(entry: &EventlogEntry) -> Result<String> {
+    let event_type = serde_json::to_value(entry.event_type)?;
+    let event_type = event_type
+        .as_str()
+        .ok_or_else(|| anyhow!("Invalid event type {:?}", entry.event_type))?;
+
+    let details = &entry.details;
+    let description = if let Some(name) = &details.unicode_name {
+        Some(name.clone())
+    } else if let Some(paths) = details.device_paths.as_ref().filter(|p| !p.is_empty()) {
+        // The file is the part of the device path that identifies an image
+        let file = paths.iter().rev().find(|path| path.starts_with("File("));
+        Some(file.cloned().unwrap_or_else(|| paths.join("/")))
+    } else if let Some(string) = &details.string {
+        let prefix = GRUB_IPL_PREFIXES.iter().find(|prefix| {
+            string
+                .strip_prefix(**prefix)
+                .is_some_and(|rest| rest.starts_with([':', ' ']))
+        });
+        Some(prefix.map_or_else(|| string.clone(), |prefix| prefix.to_string()))
+    } else {
+        None
+    };
+
+    Ok(match description {
+        Some(description) => format!("{event_type}/{description}"),
+        None => event_type.to_string(),
+    })
+}
+
+/// Collect the digests of the measured components in the order of the
+/// events. The digests of the same component are merged. `register` names
+/// the register of the given index that an event extends.
+fn components(
+    log: &[EventlogEntry],
+    alg: TcgAlgorithm,
+    register: impl Fn(u32) -> String,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let mut components: Vec<(String, Vec<String>)> = Vec::new();
+    for entry in log {
+        if entry.event_type == TcgEventType::EvNoAction {
+            continue;
+        }
+        let Some(digest) = entry.digests.iter().find(|digest| digest.alg == alg) else {
+            continue;
+        };
+
+        let name = format!("{}/{}", register(entry.index), component(entry)?);
+        let digest = hex::encode(&digest.digest);
+        match components.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, digests)) if digests.contains(&digest) => {}
+            Some((_, digests)) => digests.push(digest),
+            None => components.push((name, vec![digest])),
+        }
+    }
+
+    Ok(components)
+}
+
+/// Name of a CC measurement register, whose index 0 is `MRTD` and 1 - 4
+/// are `RTMR[0]` - `RTMR[3]`.
+fn ccel_register(index: u32) -> String {
+    match index {
+        0 => "mr_td".to_string(),
+        index => format!("rtmr_{}", index - 1),
+    }
+}
+
+fn tcg_register(index: u32) -> String {
+    format!("tpm_pcr{index}")
+}
+
+impl GoldenEventLog {
+    /// Derive the reference values from the event log, by name.
+    fn reference_values(&self) -> Result<HashMap<String, Value>> {
+        let eventlog = base64::engine::general_purpose::STANDARD
+            .decode(&self.eventlog)
+            .context("base64 decode event log")?;
+
+        let mut rvs = HashMap::new();
+        let components = match self.format {
+            EventLogFormat::Ccel => {
+                let alg = algorithm(self.algorithm.as_deref().unwrap_or("sha384"))?;
+                let ccel = CcEventLog::try_from(eventlog).context("parse CCEL")?;
+
+                // Register 0 is MRTD, which is not extended by events
+                let indexes: BTreeSet<u32> = ccel
+                    .log
+                    .iter()
+                    .map(|entry| entry.index)
+                    .filter(|index| (1..=4).contains(index))
+                    .collect();
+                for index in indexes {
+                    let rtmr = ccel.replay(index, alg)?;
+                    rvs.insert(ccel_register(index), json!([hex::encode(rtmr)]));
+                }
+
+                // The kernel and its parameters of a direct boot, as checked
+                // by the default policy
+                for entry in &ccel.log {
+                    let Some(digest) = entry.digests.iter().find(|d| d.alg == alg) else {
+                        continue;
+                    };
+                    let name = match component(entry)?.as_str() {
+                        "EV_EFI_BOOT_SERVICES_APPLICATION/File(kernel)" => "tdvfkernel",
+                        "EV_EVENT_TAG/LOADED_IMAGE::LoadOptions" => "tdvfkernelparams",
+                        _ => continue,
+                    };
+                    rvs.insert(name.to_string(), json!(hex::encode(&digest.digest)));
+                }
+
+                components(&ccel.log, alg, ccel_register)?
+            }
+            EventLogFormat::Tcg => {
+                let alg = algorithm(self.algorithm.as_deref().unwrap_or("sha256"))?;
+                let tcg = TcgEventLog::try_from(eventlog).context("parse TCG event log")?;
+                for index in tcg.measured_pcrs() {
+                    let pcr = tcg.replay(index, alg)?;
+                    rvs.insert(tcg_register(index), json!([hex::encode(pcr)]));
+                }
+
+                components(&tcg.log, alg, tcg_register)?
+            }
+        };
+
+        for (name, digests) in components {
+            rvs.insert(name, json!(digests));
+        }
+
+        Ok(rvs
+            .into_iter()
+            .map(|(name, value)| (format!("{}{name}", self.prefix), value))
+            .collect())
+    }
+}
+
+impl Extractor for GoldenEventLogExtractor {
+    fn verify_and_extract(&self, provenance_base64: &str) -> Result<Vec<ReferenceValue>> {
+        let provenance = base64::engine::general_purpose::STANDARD
+            .decode(provenance_base64)
+            .context("base64 decode")?;
+        let golden: GoldenEventLog =
+            serde_json::from_slice(&provenance).context("deserialize golden event log")?;
+
+        golden
+            .reference_values()?
+            .into_iter()
+            .map(|(name, value)| Ok(ReferenceValue::new()?.set_name(&name).set_value(value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use serde_json::json;
+
+    use super::*;
+
+    fn provenance(format: &str, path: &str, prefix: &str) -> String {
+        let eventlog = std::fs::read(path).unwrap();
+        let golden = json!({
+            "format": format,
+            "eventlog": base64::engine::general_purpose::STANDARD.encode(eventlog),
+            "prefix": prefix,
+        });
+        base64::engine::general_purpose::STANDARD.encode(golden.to_string())
+    }
+
+    fn extract(provenance: &str) -> HashMap<String, Value> {
+        GoldenEventLogExtractor
+            .verify_and_extract(provenance)
+            .unwrap()
+            .into_iter()
+            .map(|rv| (rv.name().to_string(), rv.value()))
+            .collect()
+    }
+
+    #[test]
+    fn extract_ccel() {
+        let rvs = extract(&provenance(
+            "ccel",
+            "../deps/eventlog/test_data/CCEL_data_grub",
+            "",
+        ));
+
+        assert_eq!(
+            rvs["rtmr_0"],
+            json!(["cec0a104f691f60da2387fea3c2de00c4ac035e2bb479ff02edcce69039d9e9907f0b3e55031da3dc7038f423adebd79"])
+        );
+        assert_eq!(
+            rvs["rtmr_1"],
+            json!(["6c289e0c62182d41ebe97bdbc9872d10998a08eaa86adcdc684001a363207ee72942c7522cdf00a4bbc3d784bed7b670"])
+        );
+        assert_eq!(
+            rvs["rtmr_2"],
+            json!(["08919d017ba0e52cd6d966351c7de16fe76c1d3d3d3da4554239e4c7d16cb8b82a94e7eaea3a0e6e18eb690b999fd31e"])
+        );
+        assert!(!rvs.contains_key("rtmr_3"));
+
+        // shim is loaded twice, so both boot applications have one digest
+        assert_eq!(
+            rvs["rtmr_1/EV_EFI_BOOT_SERVICES_APPLICATION/File(\\EFI\\ubuntu\\grubx64.efi)"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            rvs["rtmr_0/EV_EFI_VARIABLE_DRIVER_CONFIG/SecureBoot"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        let load_options = &rvs["rtmr_2/EV_EVENT_TAG/LOADED_IMAGE::LoadOptions"];
+        assert_eq!(load_options[0], rvs["tdvfkernelparams"]);
+        assert!(rvs.contains_key("rtmr_2/EV_EVENT_TAG/Linux initrd"));
+        assert!(rvs.contains_key("rtmr_0/EV_SEPARATOR"));
+        // The kernel is loaded by GRUB rather than from a file by the TDVF
+        assert!(!rvs.contains_key("tdvfkernel"));
+    }
+
+    #[test]
+    fn extract_tcg() {
+        let rvs = extract(&provenance(
+            "tcg",
+            "../deps/eventlog/test_data/tcg_binary_bios_measurements",
+            "golden/",
+        ));
+
+        assert_eq!(
+            rvs["golden/tpm_pcr7"],
+            json!(["3a765fab0c4555e805964d8c75231894f45c5a6f2161738cf157015250a3e624"])
+        );
+        assert_eq!(
+            rvs["golden/tpm_pcr9"],
+            json!(["4af47301e71736075d36f0860dea2333db252df4811b4d5f415981cea9c3fcee"])
+        );
+        assert!(!rvs.contains_key("golden/tpm_pcr10"));
+        assert!(rvs.contains_key(
+            "golden/tpm_pcr4/EV_EFI_BOOT_SERVICES_APPLICATION/File(\\EFI\\BOOT\\BOOTX64.EFI)"
+        ));
+        assert!(rvs.contains_key("golden/tpm_pcr7/EV_EFI_VARIABLE_DRIVER_CONFIG/SecureBoot"));
+        // The separators of the registers are distinct components
+        assert!(rvs.contains_key("golden/tpm_pcr0/EV_SEPARATOR"));
+        assert!(rvs.contains_key("golden/tpm_pcr7/EV_SEPARATOR"));
+        assert!(rvs.keys().all(|name| name.starts_with("golden/")));
+        assert!(rvs.keys().all(|name| !name.contains("EV_NO_ACTION")));
+    }
+
+    #[test]
+    fn invalid_eventlog() {
+        let golden = json!({
+            "format": "tcg",
+            "eventlog": base64::engine::general_purpose::STANDARD.encode(b"not an event log"),
+        });
+        let provenance = base64::engine::general_purpose::STANDARD.encode(golden.to_string());
+        assert!(GoldenEventLogExtractor
+            .verify_and_extract(&provenance)
+            .is_err());
+
+        assert!(algorithm("md5").is_err());
+        assert_eq!(algorithm("SHA-384").unwrap(), TcgAlgorithm::Sha384);
+    }
+}
@@ -0,0 +1,458 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! in-toto layout and link metadata, and the verification of a
+//! supply chain against its layout.
+//!
+//! Both the legacy metablock format (`{"signed": ..., "signatures": ...}`,
+//! signed over the canonical JSON of `signed`) and DSSE envelopes with
+//! payload type `application/vnd.in-toto+json` are supported.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use openssl::{
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{Id, PKey, Public},
+    rsa::Padding,
+    sign::{RsaPssSaltlen, Verifier},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::rules::{verify_rules, ArtifactType};
+use crate::signature::pae;
+
+/// DSSE payload type of in-toto metadata.
+pub const PAYLOAD_TYPE_IN_TOTO: &str = "application/vnd.in-toto+json";
+
+/// Artifacts recorded in a link, i.e. a map of paths to the
+/// digests of the files, indexed by hash algorithm.
+pub type Artifacts = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A public key together with the digest its signatures are made with.
+#[derive(Clone)]
+pub struct VerificationKey {
+    key: PKey<Public>,
+    digest: MessageDigest,
+}
+
+impl VerificationKey {
+    /// Load a PEM encoded public key. The digest is derived from the key,
+    /// i.e. SHA-384 for P-384 keys and SHA-256 otherwise.
+    pub fn from_pem(pem: &[u8]) -> Result<Self> {
+        let key = PKey::public_key_from_pem(pem)?;
+        let digest = match key.ec_key().ok().and_then(|ec| ec.group().curve_name()) {
+            Some(Nid::SECP384R1) => MessageDigest::sha384(),
+            Some(Nid::SECP521R1) => MessageDigest::sha512(),
+            _ => MessageDigest::sha256(),
+        };
+        Ok(Self { key, digest })
+    }
+
+    /// Verify a signature. RSA signatures are RSASSA-PSS as in-toto makes
+    /// them, or PKCS#1 v1.5. ECDSA signatures are DER encoded.
+    fn verify(&self, data: &[u8], sig: &[u8]) -> bool {
+        let res = match self.key.id() {
+            Id::ED25519 | Id::ED448 => Verifier::new_without_digest(&self.key)
+                .and_then(|mut verifier| verifier.verify_oneshot(sig, data)),
+            Id::RSA => {
+                let pss = Verifier::new(self.digest, &self.key).and_then(|mut verifier| {
+                    verifier.set_rsa_padding(Padding::PKCS1_PSS)?;
+                    verifier.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                    verifier.verify_oneshot(sig, data)
+                });
+                match pss {
+                    Ok(true) => Ok(true),
+                    _ => Verifier::new(self.digest, &self.key)
+                        .and_then(|mut verifier| verifier.verify_oneshot(sig, data)),
+                }
+            }
+            _ => Verifier::new(self.digest, &self.key)
+                .and_then(|mut verifier| verifier.verify_oneshot(sig, data)),
+        };
+
+        res.unwrap_or(false)
+    }
+}
+
+/// A public key as defined in the `keys` of a layout.
+#[derive(Deserialize, Debug)]
+struct Key {
+    keytype: String,
+    scheme: String,
+    keyval: KeyVal,
+}
+
+#[derive(Deserialize, Debug)]
+struct KeyVal {
+    public: String,
+}
+
+impl TryFrom<&Key> for VerificationKey {
+    type Error = anyhow::Error;
+
+    fn try_from(key: &Key) -> Result<Self> {
+        let digest = if key.scheme.ends_with("384") {
+            MessageDigest::sha384()
+        } else if key.scheme.ends_with("512") {
+            MessageDigest::sha512()
+        } else {
+            MessageDigest::sha256()
+        };
+
+        let key = match key.keytype.as_str() {
+            "ed25519" => {
+                let raw = hex::decode(&key.keyval.public).context("decode ed25519 key")?;
+                PKey::public_key_from_raw_bytes(&raw, Id::ED25519)?
+            }
+            "rsa" | "ecdsa" | "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" => {
+                PKey::public_key_from_pem(key.keyval.public.as_bytes())?
+            }
+            other => bail!("Unsupported key type {other}"),
+        };
+
+        Ok(Self { key, digest })
+    }
+}
+
+struct Signature {
+    keyid: String,
+    sig: Vec<u8>,
+}
+
+/// Signed in-toto metadata, with the bytes its signatures are made over.
+pub struct Envelope {
+    signed: Value,
+    signed_bytes: Vec<u8>,
+    signatures: Vec<Signature>,
+}
+
+#[derive(Deserialize)]
+struct RawSignature {
+    #[serde(default)]
+    keyid: String,
+    sig: String,
+}
+
+impl Envelope {
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let document: Value = serde_json::from_slice(bytes)?;
+
+        if let Some(payload_type) = document.get("payloadType") {
+            if payload_type != PAYLOAD_TYPE_IN_TOTO {
+                bail!("Unsupported payload type {payload_type}, only `{PAYLOAD_TYPE_IN_TOTO}` is supported");
+            }
+            let payload = document["payload"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Unexpected payload, expected a base64 encoded string"))?;
+            let payload = STANDARD.decode(payload).context("base64 decode payload")?;
+            let signatures: Vec<RawSignature> =
+                serde_json::from_value(document["signatures"].clone())
+                    .context("parse signatures")?;
+            let signatures = signatures
+                .into_iter()
+                .map(|s| {
+                    Ok(Signature {
+                        keyid: s.keyid,
+                        sig: STANDARD.decode(s.sig).context("base64 decode signature")?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            return Ok(Self {
+                signed: serde_json::from_slice(&payload).context("parse payload")?,
+                signed_bytes: pae(PAYLOAD_TYPE_IN_TOTO, &payload),
+                signatures,
+            });
+        }
+
+        let signed = document
+            .get("signed")
+            .ok_or_else(|| anyhow!("Neither a DSSE envelope nor a signed metablock"))?;
+        let signatures: Vec<RawSignature> =
+            serde_json::from_value(document["signatures"].clone()).context("parse signatures")?;
+        let signatures = signatures
+            .into_iter()
+            .map(|s| {
+                Ok(Signature {
+                    keyid: s.keyid,
+                    sig: hex::decode(s.sig).context("hex decode signature")?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            signed_bytes: canonical_json(signed)?.into_bytes(),
+            signed: signed.clone(),
+            signatures,
+        })
+    }
+
+    /// The signed content.
+    pub fn signed(&self) -> &Value {
+        &self.signed
+    }
+
+    /// Return the ids of all the given keys that made a valid signature.
+    /// The `keyid` of a signature selects the key if it is one of the
+    /// given ones, otherwise all of the keys are tried.
+    pub fn verified_by(&self, keys: &HashMap<String, VerificationKey>) -> BTreeSet<String> {
+        let mut signers = BTreeSet::new();
+        for signature in &self.signatures {
+            let candidates: Vec<_> = match keys.get_key_value(&signature.keyid) {
+                Some(key) => vec![key],
+                None => keys.iter().collect(),
+            };
+            for (keyid, key) in candidates {
+                if key.verify(&self.signed_bytes, &signature.sig) {
+                    signers.insert(keyid.clone());
+                    break;
+                }
+            }
+        }
+
+        signers
+    }
+}
+
+/// Canonical JSON as in-toto signs it: sorted keys, no whitespace,
+/// and only `"` and `\` escaped in strings.
+fn canonical_json(value: &Value) -> Result<String> {
+    let res = match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) if n.is_i64() || n.is_u64() => n.to_string(),
+        Value::Number(n) => bail!("Floating point number {n} is not canonical JSON"),
+        Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(canonical_json)
+                .collect::<Result<Vec<_>>>()?;
+            format!("[{}]", items.join(","))
+        }
+        Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            let items = keys
+                .into_iter()
+                .map(|k| {
+                    Ok(format!(
+                        "{}:{}",
+                        canonical_json(&Value::String(k.clone()))?,
+                        canonical_json(&map[k])?
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!("{{{}}}", items.join(","))
+        }
+    };
+
+    Ok(res)
+}
+
+fn default_threshold() -> usize {
+    1
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Step {
+    pub name: String,
+    #[serde(default)]
+    pub expected_materials: Vec<Vec<String>>,
+    #[serde(default)]
+    pub expected_products: Vec<Vec<String>>,
+    #[serde(default)]
+    pub pubkeys: Vec<String>,
+    #[serde(default)]
+    pub expected_command: Vec<String>,
+    #[serde(default = "default_threshold")]
+    pub threshold: usize,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Layout {
+    #[serde(rename = "_type")]
+    r#type: String,
+    pub steps: Vec<Step>,
+    #[serde(default)]
+    inspect: Vec<Value>,
+    #[serde(default)]
+    keys: HashMap<String, Key>,
+    pub expires: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Link {
+    #[serde(rename = "_type")]
+    r#type: String,
+    pub name: String,
+    #[serde(default)]
+    pub materials: Artifacts,
+    #[serde(default)]
+    pub products: Artifacts,
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+/// The result of a successful verification of a supply chain.
+pub struct VerifiedLayout {
+    /// The id of the trusted key that signed the layout
+    pub signer: String,
+    pub expires: DateTime<Utc>,
+    /// The products of the last step of the layout
+    pub products: Artifacts,
+}
+
+/// Verify the layout and the links of a supply chain:
+/// * The layout is signed by one of the trusted keys and not expired.
+/// * Every step has links signed by at least `threshold` distinct keys
+///   authorized for the step, all reporting the same artifacts.
+/// * The materials and products of every step follow its artifact rules.
+///
+/// Inspections are not run.
+pub fn verify(
+    layout: &Envelope,
+    trusted_keys: &HashMap<String, VerificationKey>,
+    links: &[Envelope],
+) -> Result<VerifiedLayout> {
+    let signer = layout
+        .verified_by(trusted_keys)
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("The layout is not signed by any trusted key"))?;
+    debug!("in-toto layout signature of {signer} is verified.");
+
+    let layout: Layout = serde_json::from_value(layout.signed().clone()).context("parse layout")?;
+    if layout.r#type != "layout" {
+        bail!("Unexpected type {} of the layout", layout.r#type);
+    }
+    if layout.expires < Utc::now() {
+        bail!("The layout expired at {}", layout.expires);
+    }
+    if !layout.inspect.is_empty() {
+        warn!(
+            "The {} inspections of the layout are not run",
+            layout.inspect.len()
+        );
+    }
+
+    let keys = layout
+        .keys
+        .iter()
+        .map(|(keyid, key)| {
+            let key = VerificationKey::try_from(key)
+                .with_context(|| format!("load layout key {keyid}"))?;
+            Ok((keyid.clone(), key))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let mut step_links = HashMap::new();
+    for step in &layout.steps {
+        let link = verify_step_links(step, &keys, links)?;
+        step_links.insert(step.name.clone(), link);
+    }
+
+    for step in &layout.steps {
+        let link = &step_links[&step.name];
+        verify_rules(
+            &step.expected_materials,
+            ArtifactType::Materials,
+            link,
+            &step_links,
+        )
+        .with_context(|| format!("verify materials of step {}", step.name))?;
+        verify_rules(
+            &step.expected_products,
+            ArtifactType::Products,
+            link,
+            &step_links,
+        )
+        .with_context(|| format!("verify products of step {}", step.name))?;
+    }
+
+    let products = match layout.steps.last() {
+        Some(step) => step_links[&step.name].products.clone(),
+        None => Artifacts::new(),
+    };
+
+    Ok(VerifiedLayout {
+        signer,
+        expires: layout.expires,
+        products,
+    })
+}
+
+/// Find the links of the step signed by the keys authorized for it,
+/// check the threshold and return the agreed link.
+fn verify_step_links(
+    step: &Step,
+    keys: &HashMap<String, VerificationKey>,
+    links: &[Envelope],
+) -> Result<Link> {
+    let authorized: HashMap<_, _> = step
+        .pubkeys
+        .iter()
+        .filter_map(|keyid| keys.get_key_value(keyid))
+        .map(|(keyid, key)| (keyid.clone(), key.clone()))
+        .collect();
+
+    let mut signed_links: BTreeMap<String, Link> = BTreeMap::new();
+    for envelope in links {
+        if envelope.signed()["name"] != step.name.as_str() {
+            continue;
+        }
+        let signers = envelope.verified_by(&authorized);
+        if signers.is_empty() {
+            warn!(
+                "Ignore a link of step {} not signed by an authorized key",
+                step.name
+            );
+            continue;
+        }
+
+        let link: Link = serde_json::from_value(envelope.signed().clone())
+            .with_context(|| format!("parse link of step {}", step.name))?;
+        if link.r#type != "link" {
+            bail!("Unexpected type {} of a link", link.r#type);
+        }
+        for signer in signers {
+            signed_links.insert(signer, link.clone());
+        }
+    }
+
+    if signed_links.len() < step.threshold {
+        bail!(
+            "Step {} requires links of {} authorized keys, found {}",
+            step.name,
+            step.threshold,
+            signed_links.len()
+        );
+    }
+
+    let mut signed_links = signed_links.into_values();
+    let link = signed_links
+        .next()
+        .ok_or_else(|| anyhow!("No link of step {}", step.name))?;
+    if signed_links
+        .any(|other| other.materials != link.materials || other.products != link.products)
+    {
+        bail!("The links of step {} report different artifacts", step.name);
+    }
+
+    if !step.expected_command.is_empty() && step.expected_command != link.command {
+        warn!(
+            "Step {} expected command {:?}, but {:?} was run",
+            step.name, step.expected_command, link.command
+        );
+    }
+
+    Ok(link)
+}
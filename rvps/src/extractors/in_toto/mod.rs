@@ -3,34 +3,35 @@
 // SPDX-License-Identifier: Apache-2.0
 //
 
-// Copyright (c) 2022 Alibaba Cloud
-//
-// SPDX-License-Identifier: Apache-2.0
-//
-
 //! # in-toto Extractor
 //!
-//! This Extractor helps to verify in-toto metadata and extract
-//! related reference value from link file.
+//! This Extractor verifies in-toto supply chain metadata and extracts
+//! the digests of the artifacts as reference values. Two kinds of
+//! provenance are supported:
+//! * a layout together with the links of its steps, whose products of
+//!   the last step become reference values.
+//! * a DSSE envelope of an in-toto attestation statement with a SLSA
+//!   provenance predicate, whose subjects become reference values.
 
-pub mod shim;
+pub mod layout;
+pub mod rules;
+pub mod statement;
 
-use std::{
-    collections::HashMap,
-    env,
-    fs::{create_dir_all, File},
-    io::Write,
-};
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{anyhow, bail, Context, Result};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use chrono::{DateTime, Utc};
-use log::debug;
+use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::ReferenceValue;
 
+use self::{
+    layout::{Envelope, VerificationKey},
+    statement::Statement,
+};
+
 use super::Extractor;
 
 /// The default in-toto metadata version
@@ -39,14 +40,16 @@ static INTOTO_VERSION: &str = "0.9";
 /// Provenance contains information including the following:
 /// * `version`: version field of the given in-toto metadata
 /// * `line_normalization`: whether Windows-style line separators
-/// (CRLF) are normalized to Unix-style line separators (LF) for
-/// cross-platform consistency.
+///   (CRLF) are normalized to Unix-style line separators (LF) for
+///   cross-platform consistency. Only kept for compatibility, as the
+///   artifacts are not hashed again during verification.
 /// * `files`: a key-value map. Keys are relative paths and the
-/// values are base64-encoded content of the file.
+///   values are base64-encoded content of the file.
 #[derive(Serialize, Deserialize)]
 pub struct Provenance {
     #[serde(default = "default_version")]
     version: String,
+    #[serde(default)]
     line_normalization: bool,
     files: HashMap<String, String>,
 }
@@ -56,190 +59,191 @@ fn default_version() -> String {
     INTOTO_VERSION.into()
 }
 
-pub struct InTotoExtractor;
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct InTotoExtractorConfig {
+    /// Map of key ids to the paths of PEM encoded public keys that
+    /// layouts and attestation statements are verified against.
+    #[serde(default)]
+    pub trusted_keys: HashMap<String, String>,
+
+    /// Ids of the builders whose SLSA provenance is accepted.
+    /// If empty, provenance of any builder is accepted.
+    #[serde(default)]
+    pub trusted_builders: Vec<String>,
+}
 
-impl InTotoExtractor {
-    pub fn new() -> Self {
-        InTotoExtractor
-    }
+#[derive(Default)]
+pub struct InTotoExtractor {
+    trusted_keys: HashMap<String, VerificationKey>,
+    trusted_builders: Vec<String>,
 }
 
-impl Extractor for InTotoExtractor {
-    /// In-toto's Extractor.
-    ///
-    /// It will verify given provenance of in-toto using
-    /// Rust-wrappered in-toto-golang. If the verification
-    /// succeeds, the ReferenceValues of the resulted link
-    /// file will be extracted.
-    ///
-    /// The verification process will create a tempdir
-    /// to store the metadata, and do the verification.
-    fn verify_and_extract(&self, provenance: &str) -> Result<Vec<ReferenceValue>> {
-        // Deserialize Provenance
-        let payload: Provenance = serde_json::from_str(provenance)?;
+impl InTotoExtractor {
+    pub fn new(config: Option<InTotoExtractorConfig>) -> Result<Self> {
+        let config = config.unwrap_or_default();
+        let mut trusted_keys = HashMap::new();
+        for (keyid, path) in config.trusted_keys {
+            let pem = std::fs::read(&path)
+                .with_context(|| format!("read in-toto key {keyid} from {path}"))?;
+            let key = VerificationKey::from_pem(&pem)
+                .with_context(|| format!("parse in-toto key {keyid}"))?;
+            trusted_keys.insert(keyid, key);
+        }
+
+        Ok(Self {
+            trusted_keys,
+            trusted_builders: config.trusted_builders,
+        })
+    }
 
-        // Judge version
+    /// Verify a layout and the links of its steps, and extract the
+    /// products of the last step.
+    fn extract_layout(&self, payload: Provenance) -> Result<Vec<ReferenceValue>> {
         if payload.version != INTOTO_VERSION {
-            return Err(anyhow!(
+            bail!(
                 "Version unmatched! Need {}, given {}.",
                 INTOTO_VERSION,
                 payload.version
-            ));
+            );
         }
 
-        // Create tempdir and put the files
-        let tempdir = tempfile::tempdir()?;
-        let tempdir_path = tempdir.path().to_owned();
-        let tempdir_str = tempdir.path().to_string_lossy().to_string();
+        let mut layouts = Vec::new();
+        let mut links = Vec::new();
+        for (path, content_base64) in &payload.files {
+            let content = STANDARD
+                .decode(content_base64)
+                .with_context(|| format!("base64 decode {path}"))?;
+            if path.ends_with(".layout") {
+                layouts.push(Envelope::from_slice(&content).context("parse layout")?);
+            } else if path.ends_with(".link") {
+                links
+                    .push(Envelope::from_slice(&content).with_context(|| format!("parse {path}"))?);
+            }
+        }
 
-        let mut file_paths = Vec::new();
-        for (relative_path, content_base64) in &payload.files {
-            let (file_path, dir) = get_file_path(&tempdir_str[..], relative_path);
-            create_dir_all(dir)?;
-            let mut file = File::create(&file_path)?;
-            let bytes = STANDARD.decode(content_base64)?;
-            file.write_all(&bytes)?;
+        let layout = match layouts.len() {
+            1 => layouts.remove(0),
+            0 => bail!("Layout file not found."),
+            _ => bail!("Only one layout file is supported."),
+        };
 
-            file_paths.push(file_path);
+        if self.trusted_keys.is_empty() {
+            bail!("No trusted keys are configured for in-toto layouts");
         }
 
-        // get link dir (temp dir)
-        debug!(
-            "tempdir_path = {:?}, use temp path to store metadata",
-            tempdir.path()
+        let verified = layout::verify(&layout, &self.trusted_keys, &links)?;
+        info!(
+            "in-toto supply chain of layout signed by {} is verified.",
+            verified.signer
         );
 
-        // get layout file
-        let layout_path = file_paths
+        verified
+            .products
             .iter()
-            .find(|&k| k.ends_with(".layout"))
-            .ok_or_else(|| anyhow!("Layout file not found."))?
-            .to_owned();
+            .map(|(path, digests)| {
+                Ok(reference_value(path, digests)?
+                    .set_expiration(verified.expires)
+                    .set_signer(&verified.signer))
+            })
+            .collect()
+    }
 
-        // get pub keys
-        let pub_key_paths = file_paths
-            .iter()
-            .filter(|&k| k.ends_with(".pub"))
-            .map(|k| k.to_owned())
-            .collect();
-
-        let intermediate_paths = Vec::new();
-
-        let line_normalization = payload.line_normalization;
-
-        // Store and change current dir to the tmp dir
-        let cwd = env::current_dir()?;
-
-        // Read layout for the expired time
-        let layout_file = std::fs::File::open(&layout_path)?;
-
-        // A layout's expired time will be in signed.expires
-        let _expires = {
-            // fit up with the newest in-toto provenance format
-            let envelope = serde_json::from_reader::<_, Value>(layout_file)?;
-            let layout_base64 = &envelope["payload"];
-            let payload_type = &envelope["payloadType"];
-            if *payload_type != Value::String(String::from("application/vnd.in-toto+json")) {
-                bail!(
-                    "Unsupported payload type {}, only support `application/vnd.in-toto+json` now",
-                    payload_type
-                );
-            }
+    /// Verify an attestation statement with a SLSA provenance predicate,
+    /// and extract its subjects.
+    fn extract_statement(&self, provenance: &str) -> Result<Vec<ReferenceValue>> {
+        if self.trusted_keys.is_empty() {
+            bail!("No trusted keys are configured for in-toto attestations");
+        }
 
-            let layout = match layout_base64 {
-                Value::String(inner) => STANDARD.decode(inner),
-                _ => bail!("Unexpected payload, expected a base64 encoded string"),
-            }?;
+        let envelope = Envelope::from_slice(provenance.as_bytes())?;
+        let signer = envelope
+            .verified_by(&self.trusted_keys)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("The attestation is not signed by any trusted key"))?;
+
+        let statement: Statement =
+            serde_json::from_value(envelope.signed().clone()).context("parse statement")?;
+        let builder = statement.verify()?;
+        if !self.trusted_builders.is_empty() && !self.trusted_builders.iter().any(|b| b == builder)
+        {
+            bail!("Builder {builder} is not trusted");
+        }
+        info!("in-toto attestation of {signer} built by {builder} is verified.");
+
+        statement
+            .subject
+            .iter()
+            .map(|subject| Ok(reference_value(&subject.name, &subject.digest)?.set_signer(&signer)))
+            .collect()
+    }
+}
 
-            let layout = serde_json::from_slice::<Value>(&layout).context("parse layout")?;
-            let expire_str = layout["expires"]
-                .as_str()
-                .ok_or_else(|| anyhow!("failed to get expired time"))?;
+/// A reference value of an artifact, whose values are its digests
+/// like `sha256:<hex>`.
+fn reference_value(name: &str, digests: &BTreeMap<String, String>) -> Result<ReferenceValue> {
+    let digests: Vec<_> = digests
+        .iter()
+        .map(|(alg, digest)| format!("{alg}:{}", digest.to_lowercase()))
+        .collect();
+    Ok(ReferenceValue::new()?
+        .set_name(name)
+        .set_value(serde_json::json!(digests)))
+}
 
-            expire_str.parse::<DateTime<Utc>>()?
-        };
+impl Extractor for InTotoExtractor {
+    /// In-toto's Extractor.
+    ///
+    /// The provenance is either a [`Provenance`] carrying a layout and
+    /// its links, or a DSSE envelope of an attestation statement.
+    fn verify_and_extract(&self, provenance: &str) -> Result<Vec<ReferenceValue>> {
+        let document: Value = serde_json::from_str(provenance)?;
 
-        env::set_current_dir(tempdir_path)?;
-
-        // The newest in-toto api does not return an link file to sum up
-        // the outputs of the supplychain. We will only verify the provenance
-        // here. Because the reference value gathered from an in-toto
-        // pipeline is the hash digest of the binary, while the expected
-        // measurement is a hash digest of a slice of memory area. The
-        // difference is as the followiing equation:
-        // memory area = binary + paddings
-        // So we need a full design to include the final reference value
-        // inside the provenance, which we will work on later. Before that,
-        // in-toto will not be usable.
-        //
-        // Related issue:
-        // https://github.com/confidential-containers/attestation-service/issues/42
-        match shim::verify(
-            layout_path,
-            pub_key_paths,
-            intermediate_paths,
-            "".into(),
-            line_normalization,
-        ) {
-            Ok(summary_link) => summary_link,
-            Err(e) => {
-                env::set_current_dir(cwd)?;
-                bail!(e);
-            }
+        let rvs = if document.get("payloadType").is_some() {
+            self.extract_statement(provenance)?
+        } else {
+            let payload: Provenance = serde_json::from_value(document)?;
+            self.extract_layout(payload)?
         };
 
-        // Change back working dir
-        env::set_current_dir(cwd)?;
-
-        Ok(vec![])
+        debug!("Reference Values Extracted: {:?}", rvs);
+        Ok(rvs)
     }
 }
 
-/// Given a directory of tempdir and a file's relative path,
-/// output the abs path of the file that will be put in the tempdir
-/// and its parent dir. For example:
-/// * `/tmp/tempdir` and `dir1/file` will output `/tmp/tempdir/dir1/file` and `/tmp/tempdir/dir1`
-fn get_file_path(tempdir: &str, relative_file_path: &str) -> (String, String) {
-    let mut abs_path = tempdir.to_string();
-    abs_path.push('/');
-    abs_path.push_str(relative_file_path);
-    let abs_path = path_clean::clean(&abs_path[..]);
-    let abs_path = abs_path.to_string_lossy();
-    let dir = abs_path
-        .rsplit_once('/')
-        .unwrap_or((&abs_path[..], ""))
-        .0
-        .to_string();
-    (abs_path.to_string(), dir)
-}
-
 #[cfg(test)]
 pub mod test {
     use std::{collections::HashMap, fs};
 
     use base64::{engine::general_purpose::STANDARD, Engine};
     use chrono::{TimeZone, Utc};
-    use serial_test::serial;
-    use sha2::{Digest, Sha256};
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::{PKey, Private},
+        sha::sha256,
+        sign::Signer,
+    };
+    use serde_json::json;
     use walkdir::WalkDir;
 
-    use crate::{extractors::Extractor, ReferenceValue};
+    use crate::{extractors::Extractor, signature::pae, ReferenceValue};
 
-    use super::{InTotoExtractor, Provenance, INTOTO_VERSION};
+    use super::{
+        layout::PAYLOAD_TYPE_IN_TOTO, InTotoExtractor, InTotoExtractorConfig, Provenance,
+        INTOTO_VERSION,
+    };
 
     /// Helps to get sha256 digest of the artifact
     pub fn sha256_for_in_toto_test_artifact() -> String {
         let content = fs::read("tests/in-toto/foo.tar.gz").unwrap();
-        let mut hasher = Sha256::new();
-        hasher.update(&content);
-        let result = hasher.finalize();
-        let result = format!("{:x}", result);
-        result
+        hex::encode(sha256(&content))
     }
 
     /// Helps to generate a in-toto provenance encoded
-    /// in Base64. All related files are in `<git-repo>/tests/in-toto`
-    pub fn generate_in_toto_provenance() -> String {
+    /// in Base64. All related files are in `<git-repo>/rvps/tests/in-toto`
+    pub fn generate_in_toto_provenance() -> Provenance {
         let mut files = HashMap::new();
 
         for path in WalkDir::new("tests/in-toto") {
@@ -249,7 +253,7 @@ pub mod test {
             }
 
             let ent = path.path();
-            let content = fs::read(&ent).unwrap();
+            let content = fs::read(ent).unwrap();
             let file_name = ent
                 .to_string_lossy()
                 .to_string()
@@ -261,33 +265,148 @@ pub mod test {
             files.insert(file_name, content_base64);
         }
 
-        let p = Provenance {
+        Provenance {
             version: INTOTO_VERSION.into(),
             line_normalization: true,
             files,
-        };
-
-        let provenance = serde_json::to_string(&p).unwrap();
-        provenance
+        }
     }
 
     #[test]
-    #[serial]
-    #[ignore]
     fn in_toto_extractor() {
-        let e = InTotoExtractor::new();
+        let e = InTotoExtractor::new(Some(InTotoExtractorConfig {
+            trusted_keys: HashMap::from([("alice".into(), "tests/in-toto/alice.pub".into())]),
+            trusted_builders: vec![],
+        }))
+        .unwrap();
         let rv = ReferenceValue::new()
             .expect("create ReferenceValue failed")
             .set_name("foo.tar.gz")
             .set_expiration(Utc.with_ymd_and_hms(2030, 11, 18, 16, 6, 36).unwrap())
-            .set_version("0.1.0")
-            .set_value(serde_json::Value::String(sha256_for_in_toto_test_artifact()));
-        let rv = vec![rv];
-        let provenance = generate_in_toto_provenance();
+            .set_value(json!([format!(
+                "sha256:{}",
+                sha256_for_in_toto_test_artifact()
+            )]))
+            .set_signer("alice");
+        let provenance = serde_json::to_string(&generate_in_toto_provenance()).unwrap();
         let res = e
             .verify_and_extract(&provenance)
             .expect("verify and extract");
+        assert_eq!(res, vec![rv]);
+
+        // Without trusted keys, the layout is rejected
+        assert!(InTotoExtractor::default()
+            .verify_and_extract(&provenance)
+            .is_err());
+    }
+
+    #[test]
+    fn reject_broken_supply_chain() {
+        let e = InTotoExtractor::new(Some(InTotoExtractorConfig {
+            trusted_keys: HashMap::from([("alice".into(), "tests/in-toto/alice.pub".into())]),
+            trusted_builders: vec![],
+        }))
+        .unwrap();
+
+        // The layout is not signed by a trusted key
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("bob.pem");
+        std::fs::write(&key_path, key.public_key_to_pem().unwrap()).unwrap();
+        let untrusted = InTotoExtractor::new(Some(InTotoExtractorConfig {
+            trusted_keys: HashMap::from([("bob".into(), key_path.to_string_lossy().to_string())]),
+            trusted_builders: vec![],
+        }))
+        .unwrap();
+        assert!(untrusted
+            .verify_and_extract(&serde_json::to_string(&generate_in_toto_provenance()).unwrap())
+            .is_err());
+
+        // A step has no link
+        let mut provenance = generate_in_toto_provenance();
+        provenance.files.remove("write-code.b7d643de.link");
+        assert!(e
+            .verify_and_extract(&serde_json::to_string(&provenance).unwrap())
+            .is_err());
+
+        // The products of a link were modified after signing
+        let mut provenance = generate_in_toto_provenance();
+        let link = STANDARD
+            .decode(&provenance.files["package.d3ffd108.link"])
+            .unwrap();
+        let link = String::from_utf8(link)
+            .unwrap()
+            .replace("52947cb7", "00000000");
+        provenance
+            .files
+            .insert("package.d3ffd108.link".into(), STANDARD.encode(link));
+        assert!(e
+            .verify_and_extract(&serde_json::to_string(&provenance).unwrap())
+            .is_err());
+    }
+
+    fn sign_statement(statement: &serde_json::Value, key: &PKey<Private>) -> String {
+        let payload = serde_json::to_vec(statement).unwrap();
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        let sig = signer
+            .sign_oneshot_to_vec(&pae(PAYLOAD_TYPE_IN_TOTO, &payload))
+            .unwrap();
+        json!({
+            "payloadType": PAYLOAD_TYPE_IN_TOTO,
+            "payload": STANDARD.encode(payload),
+            "signatures": [{"keyid": "", "sig": STANDARD.encode(sig)}]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn slsa_provenance() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("builder.pem");
+        fs::write(&key_path, key.public_key_to_pem().unwrap()).unwrap();
+        let e = InTotoExtractor::new(Some(InTotoExtractorConfig {
+            trusted_keys: HashMap::from([("ci".into(), key_path.to_string_lossy().to_string())]),
+            trusted_builders: vec!["https://example.com/builder".into()],
+        }))
+        .unwrap();
+
+        let statement = |builder: &str| {
+            json!({
+                "_type": "https://in-toto.io/Statement/v1",
+                "subject": [{
+                    "name": "example.com/image",
+                    "digest": {"sha256": "AB12", "sha512": "cd34"}
+                }],
+                "predicateType": "https://slsa.dev/provenance/v1",
+                "predicate": {
+                    "buildDefinition": {"buildType": "https://example.com/build"},
+                    "runDetails": {"builder": {"id": builder}}
+                }
+            })
+        };
+
+        let res = e
+            .verify_and_extract(&sign_statement(
+                &statement("https://example.com/builder"),
+                &key,
+            ))
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].name(), "example.com/image");
+        assert_eq!(res[0].signer(), Some("ci"));
+        assert_eq!(
+            res[0].clone().value(),
+            json!(["sha256:ab12", "sha512:cd34"])
+        );
+
+        let untrusted_builder = sign_statement(&statement("https://example.com/other"), &key);
+        assert!(e.verify_and_extract(&untrusted_builder).is_err());
 
-        assert_eq!(res, rv);
+        let other_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let untrusted_key = sign_statement(&statement("https://example.com/builder"), &other_key);
+        assert!(e.verify_and_extract(&untrusted_key).is_err());
     }
 }
@@ -0,0 +1,366 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Artifact rules of in-toto layout steps, see
+//! <https://github.com/in-toto/docs/blob/master/in-toto-spec.md#433-artifact-rules>.
+//!
+//! The rules of a step consume the artifacts of its link one after the
+//! other. Artifacts that no rule consumes are allowed.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::layout::{Artifacts, Link};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArtifactType {
+    Materials,
+    Products,
+}
+
+#[derive(Debug, PartialEq)]
+enum Rule {
+    Match {
+        pattern: String,
+        src_prefix: String,
+        dst_type: ArtifactType,
+        dst_prefix: String,
+        dst_step: String,
+    },
+    Allow(String),
+    Disallow(String),
+    Require(String),
+    Create(String),
+    Delete(String),
+    Modify(String),
+}
+
+impl TryFrom<&[String]> for Rule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: &[String]) -> Result<Self> {
+        let upper: Vec<_> = rule.iter().map(|t| t.to_uppercase()).collect();
+        let upper: Vec<_> = upper.iter().map(String::as_str).collect();
+        let res = match (upper.as_slice(), rule) {
+            (["MATCH", _, rest @ ..], [_, pattern, ..]) => {
+                // MATCH <pattern> [IN <prefix>] WITH <type> [IN <prefix>] FROM <step>
+                let (src_prefix, rest) = match rest {
+                    ["IN", _, rest @ ..] => (rule[3].clone(), rest),
+                    rest => (String::new(), rest),
+                };
+                let offset = rule.len() - rest.len();
+                let (dst_type, rest) = match rest {
+                    ["WITH", "MATERIALS", rest @ ..] => (ArtifactType::Materials, rest),
+                    ["WITH", "PRODUCTS", rest @ ..] => (ArtifactType::Products, rest),
+                    _ => bail!("Malformed MATCH rule {rule:?}"),
+                };
+                let (dst_prefix, rest) = match rest {
+                    ["IN", _, rest @ ..] => (rule[offset + 3].clone(), rest),
+                    rest => (String::new(), rest),
+                };
+                let ["FROM", _] = rest else {
+                    bail!("Malformed MATCH rule {rule:?}");
+                };
+
+                Rule::Match {
+                    pattern: pattern.clone(),
+                    src_prefix,
+                    dst_type,
+                    dst_prefix,
+                    dst_step: rule[rule.len() - 1].clone(),
+                }
+            }
+            (["ALLOW", _], [_, pattern]) => Rule::Allow(pattern.clone()),
+            (["DISALLOW", _], [_, pattern]) => Rule::Disallow(pattern.clone()),
+            (["REQUIRE", _], [_, pattern]) => Rule::Require(pattern.clone()),
+            (["CREATE", _], [_, pattern]) => Rule::Create(pattern.clone()),
+            (["DELETE", _], [_, pattern]) => Rule::Delete(pattern.clone()),
+            (["MODIFY", _], [_, pattern]) => Rule::Modify(pattern.clone()),
+            _ => bail!("Malformed artifact rule {rule:?}"),
+        };
+
+        Ok(res)
+    }
+}
+
+/// Verify the materials or products of the link against the rules.
+/// `links` are the links of all the steps, which MATCH rules refer to.
+pub fn verify_rules(
+    rules: &[Vec<String>],
+    artifact_type: ArtifactType,
+    link: &Link,
+    links: &HashMap<String, Link>,
+) -> Result<()> {
+    let artifacts = match artifact_type {
+        ArtifactType::Materials => &link.materials,
+        ArtifactType::Products => &link.products,
+    };
+
+    let created: BTreeSet<_> = link
+        .products
+        .keys()
+        .filter(|path| !link.materials.contains_key(*path))
+        .collect();
+    let deleted: BTreeSet<_> = link
+        .materials
+        .keys()
+        .filter(|path| !link.products.contains_key(*path))
+        .collect();
+    let modified: BTreeSet<_> = link
+        .products
+        .iter()
+        .filter(|(path, digests)| {
+            link.materials
+                .get(*path)
+                .is_some_and(|material| material != *digests)
+        })
+        .map(|(path, _)| path)
+        .collect();
+
+    let mut queue: BTreeSet<&String> = artifacts.keys().collect();
+    for rule in rules {
+        let rule = Rule::try_from(rule.as_slice())?;
+        match &rule {
+            Rule::Match {
+                pattern,
+                src_prefix,
+                dst_type,
+                dst_prefix,
+                dst_step,
+            } => {
+                let dst_link = links
+                    .get(dst_step)
+                    .ok_or_else(|| anyhow!("MATCH rule refers to unknown step {dst_step}"))?;
+                let dst_artifacts = match dst_type {
+                    ArtifactType::Materials => &dst_link.materials,
+                    ArtifactType::Products => &dst_link.products,
+                };
+                queue.retain(|path| {
+                    !matches_artifact(
+                        path,
+                        pattern,
+                        src_prefix,
+                        dst_prefix,
+                        artifacts,
+                        dst_artifacts,
+                    )
+                });
+            }
+            Rule::Allow(pattern) => queue.retain(|path| !glob_match(pattern, path)),
+            Rule::Disallow(pattern) => {
+                if let Some(path) = queue.iter().find(|path| glob_match(pattern, path)) {
+                    bail!("Artifact {path} is disallowed by rule {rule:?}");
+                }
+            }
+            Rule::Require(path) => {
+                if !artifacts.contains_key(path) {
+                    bail!("Required artifact {path} is missing");
+                }
+            }
+            Rule::Create(pattern) => {
+                queue.retain(|path| !(created.contains(path) && glob_match(pattern, path)))
+            }
+            Rule::Delete(pattern) => {
+                queue.retain(|path| !(deleted.contains(path) && glob_match(pattern, path)))
+            }
+            Rule::Modify(pattern) => {
+                queue.retain(|path| !(modified.contains(path) && glob_match(pattern, path)))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the artifact matches the pattern of a MATCH rule, and the
+/// destination artifact of the same path has the same digests.
+fn matches_artifact(
+    path: &str,
+    pattern: &str,
+    src_prefix: &str,
+    dst_prefix: &str,
+    src_artifacts: &Artifacts,
+    dst_artifacts: &Artifacts,
+) -> bool {
+    let src_prefix = src_prefix.trim_end_matches('/');
+    let base_path = match src_prefix {
+        "" => path,
+        prefix => match path.strip_prefix(&format!("{prefix}/")) {
+            Some(base_path) => base_path,
+            None => return false,
+        },
+    };
+    if !glob_match(pattern, base_path) {
+        return false;
+    }
+
+    let dst_path = match dst_prefix.trim_end_matches('/') {
+        "" => base_path.to_string(),
+        prefix => format!("{prefix}/{base_path}"),
+    };
+    match (src_artifacts.get(path), dst_artifacts.get(&dst_path)) {
+        (Some(src), Some(dst)) => src == dst,
+        _ => false,
+    }
+}
+
+/// Unix shell-style wildcard matching, where `*` matches any sequence
+/// of characters including `/`, and `?` matches a single character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Position in the pattern after the last `*`, and in the name where
+    // that `*` started to match.
+    let mut star = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use rstest::rstest;
+
+    use super::{glob_match, verify_rules, ArtifactType, Rule};
+    use crate::extractors::in_toto::layout::{Artifacts, Link};
+
+    fn artifacts(items: &[(&str, &str)]) -> Artifacts {
+        items
+            .iter()
+            .map(|(path, digest)| {
+                (
+                    path.to_string(),
+                    BTreeMap::from([("sha256".to_string(), digest.to_string())]),
+                )
+            })
+            .collect()
+    }
+
+    fn link(name: &str, materials: &[(&str, &str)], products: &[(&str, &str)]) -> Link {
+        serde_json::from_value(serde_json::json!({
+            "_type": "link",
+            "name": name,
+            "materials": artifacts(materials),
+            "products": artifacts(products),
+        }))
+        .unwrap()
+    }
+
+    fn rules(rules: &[&[&str]]) -> Vec<Vec<String>> {
+        rules
+            .iter()
+            .map(|rule| rule.iter().map(|t| t.to_string()).collect())
+            .collect()
+    }
+
+    #[rstest]
+    #[case("*", "dir/foo.py", true)]
+    #[case("*.py", "dir/foo.py", true)]
+    #[case("foo.?y", "foo.py", true)]
+    #[case("foo.py", "foo.py", true)]
+    #[case("*.py", "foo.pyc", false)]
+    #[case("dir/*", "foo.py", false)]
+    #[case("a*b*c", "axxbyyc", true)]
+    #[case("a*b*c", "axxbyy", false)]
+    fn glob(#[case] pattern: &str, #[case] name: &str, #[case] expected: bool) {
+        assert_eq!(glob_match(pattern, name), expected);
+    }
+
+    #[test]
+    fn parse_match_rule() {
+        let rule = rules(&[&[
+            "MATCH", "*", "IN", "src", "WITH", "PRODUCTS", "IN", "dst", "FROM", "build",
+        ]]);
+        assert_eq!(
+            Rule::try_from(rule[0].as_slice()).unwrap(),
+            Rule::Match {
+                pattern: "*".into(),
+                src_prefix: "src".into(),
+                dst_type: ArtifactType::Products,
+                dst_prefix: "dst".into(),
+                dst_step: "build".into(),
+            }
+        );
+
+        let malformed = rules(&[&["MATCH", "*", "WITH", "PRODUCTS", "build"]]);
+        assert!(Rule::try_from(malformed[0].as_slice()).is_err());
+        let unknown = rules(&[&["IGNORE", "*"]]);
+        assert!(Rule::try_from(unknown[0].as_slice()).is_err());
+    }
+
+    #[test]
+    fn match_and_disallow() {
+        let build = link("build", &[("src/foo.c", "aa")], &[("foo", "bb")]);
+        let links = HashMap::from([("build".to_string(), build)]);
+        let package = link("package", &[("bin/foo", "bb")], &[("foo.tar", "cc")]);
+        let expected = rules(&[
+            &[
+                "MATCH", "foo", "IN", "bin", "WITH", "PRODUCTS", "FROM", "build",
+            ],
+            &["DISALLOW", "*"],
+        ]);
+        verify_rules(&expected, ArtifactType::Materials, &package, &links).unwrap();
+
+        let tampered = link("package", &[("bin/foo", "ff")], &[("foo.tar", "cc")]);
+        assert!(verify_rules(&expected, ArtifactType::Materials, &tampered, &links).is_err());
+    }
+
+    #[test]
+    fn create_delete_modify_require() {
+        let step = link(
+            "step",
+            &[("kept", "aa"), ("changed", "bb"), ("removed", "cc")],
+            &[("kept", "aa"), ("changed", "dd"), ("new", "ee")],
+        );
+        let links = HashMap::new();
+
+        let products = rules(&[
+            &["CREATE", "new"],
+            &["MODIFY", "changed"],
+            &["ALLOW", "kept"],
+            &["REQUIRE", "new"],
+            &["DISALLOW", "*"],
+        ]);
+        verify_rules(&products, ArtifactType::Products, &step, &links).unwrap();
+
+        let materials = rules(&[
+            &["DELETE", "removed"],
+            &["MODIFY", "*"],
+            &["ALLOW", "kept"],
+            &["DISALLOW", "*"],
+        ]);
+        verify_rules(&materials, ArtifactType::Materials, &step, &links).unwrap();
+
+        // A created artifact is not modified, and nothing removed is required
+        let products = rules(&[&["MODIFY", "*"], &["ALLOW", "kept"], &["DISALLOW", "*"]]);
+        assert!(verify_rules(&products, ArtifactType::Products, &step, &links).is_err());
+        let products = rules(&[&["REQUIRE", "removed"]]);
+        assert!(verify_rules(&products, ArtifactType::Products, &step, &links).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! [in-toto attestation](https://github.com/in-toto/attestation) statements
+//! carrying [SLSA provenance](https://slsa.dev/spec/v1.0/provenance) predicates.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+const STATEMENT_V1: &str = "https://in-toto.io/Statement/v1";
+const STATEMENT_V01: &str = "https://in-toto.io/Statement/v0.1";
+
+const SLSA_PROVENANCE_V1: &str = "https://slsa.dev/provenance/v1";
+const SLSA_PROVENANCE_V02: &str = "https://slsa.dev/provenance/v0.2";
+
+#[derive(Deserialize, Debug)]
+pub struct Subject {
+    #[serde(default)]
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    r#type: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    predicate_type: String,
+    #[serde(default)]
+    predicate: Value,
+}
+
+impl Statement {
+    /// Check the statement and its SLSA provenance predicate, and return
+    /// the id of the builder that produced the subjects.
+    pub fn verify(&self) -> Result<&str> {
+        if self.r#type != STATEMENT_V1 && self.r#type != STATEMENT_V01 {
+            bail!("Unsupported statement type {}", self.r#type);
+        }
+
+        if self.subject.is_empty() {
+            bail!("The statement has no subject");
+        }
+        for subject in &self.subject {
+            if subject.name.is_empty() || subject.digest.is_empty() {
+                bail!("Subjects need a name and at least one digest");
+            }
+        }
+
+        let (build_type, builder_id) = match self.predicate_type.as_str() {
+            SLSA_PROVENANCE_V1 => (
+                &self.predicate["buildDefinition"]["buildType"],
+                &self.predicate["runDetails"]["builder"]["id"],
+            ),
+            SLSA_PROVENANCE_V02 => (
+                &self.predicate["buildType"],
+                &self.predicate["builder"]["id"],
+            ),
+            other => bail!("Unsupported predicate type {other}, only SLSA provenance is supported"),
+        };
+
+        if !build_type.is_string() {
+            bail!("The SLSA provenance has no build type");
+        }
+        builder_id
+            .as_str()
+            .ok_or_else(|| anyhow!("The SLSA provenance has no builder id"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::Statement;
+
+    #[rstest]
+    #[case(json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{"name": "image", "digest": {"sha256": "aa"}}],
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {"buildType": "https://example.com/build"},
+            "runDetails": {"builder": {"id": "https://example.com/builder"}}
+        }
+    }), Some("https://example.com/builder"))]
+    #[case(json!({
+        "_type": "https://in-toto.io/Statement/v0.1",
+        "subject": [{"name": "image", "digest": {"sha256": "aa"}}],
+        "predicateType": "https://slsa.dev/provenance/v0.2",
+        "predicate": {
+            "buildType": "https://example.com/build",
+            "builder": {"id": "https://example.com/builder"}
+        }
+    }), Some("https://example.com/builder"))]
+    #[case(json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{"name": "image", "digest": {"sha256": "aa"}}],
+        "predicateType": "https://in-toto.io/attestation/vulns/v0.1",
+        "predicate": {}
+    }), None)]
+    #[case(json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{"name": "image", "digest": {"sha256": "aa"}}],
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {"buildDefinition": {"buildType": "https://example.com/build"}}
+    }), None)]
+    #[case(json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{"digest": {"sha256": "aa"}}],
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {"buildType": "https://example.com/build"},
+            "runDetails": {"builder": {"id": "https://example.com/builder"}}
+        }
+    }), None)]
+    fn verify_statement(#[case] statement: serde_json::Value, #[case] builder: Option<&str>) {
+        let statement: Statement = serde_json::from_value(statement).unwrap();
+        assert_eq!(statement.verify().ok(), builder);
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! The kernel image that the firmware measures for a direct kernel boot.
+//!
+//! QEMU patches the setup header of a bzImage before passing it to the
+//! firmware, which then measures the Authenticode hash of the patched image.
+
+use anyhow::*;
+use openssl::sha::Sha384;
+
+/// Size of the ACPI data that QEMU reserves below 4 GiB on a q35 machine.
+const ACPI_DATA_SIZE: u64 = 0x20000 + 0x8000;
+/// Memory below 4 GiB of a q35 machine with more memory than that.
+const Q35_LOWMEM: u64 = 0x8000_0000;
+/// Memory size from which a q35 machine splits memory around 4 GiB.
+const Q35_LOWMEM_SPLIT: u64 = 0xb000_0000;
+/// `xloadflags` bit telling that the initrd can be above 4 GiB.
+const XLF_CAN_BE_LOADED_ABOVE_4G: u16 = 1 << 1;
+/// Index of the certificate table in the PE data directories.
+const CERTIFICATE_TABLE: u32 = 4;
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or_else(|| anyhow!("Image is truncated at offset {offset:#x}"))?;
+    Ok(u16::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("Image is truncated at offset {offset:#x}"))?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Patch the setup header of a bzImage the way QEMU does when it loads the
+/// kernel for a direct boot of a q35 machine with `memory` bytes of memory,
+/// see `x86_load_linux()` of QEMU.
+pub fn patch_for_qemu(kernel: &[u8], initrd_size: u64, memory: u64) -> Result<Vec<u8>> {
+    let mut kernel = kernel.to_vec();
+    if kernel.len() < 0x1000 || kernel.get(0x202..0x206) != Some(b"HdrS") {
+        bail!("Kernel is not a bzImage");
+    }
+
+    let protocol = read_u16(&kernel, 0x206)?;
+    if protocol < 0x202 || kernel[0x211] & 0x01 == 0 {
+        bail!("Kernel boot protocol {protocol:#x} is too old");
+    }
+    let real_addr: u32 = 0x10000;
+    let cmdline_addr: u32 = 0x20000;
+
+    let mut initrd_max =
+        if protocol >= 0x20c && read_u16(&kernel, 0x236)? & XLF_CAN_BE_LOADED_ABOVE_4G != 0 {
+            u64::from(u32::MAX)
+        } else if protocol >= 0x203 {
+            u64::from(read_u32(&kernel, 0x22c)?)
+        } else {
+            0x37ff_ffff
+        };
+    let below_4g_memory = if memory >= Q35_LOWMEM_SPLIT {
+        Q35_LOWMEM
+    } else {
+        memory
+    };
+    if initrd_max >= below_4g_memory.saturating_sub(ACPI_DATA_SIZE) {
+        initrd_max = below_4g_memory.saturating_sub(ACPI_DATA_SIZE + 1);
+    }
+
+    write_u32(&mut kernel, 0x228, cmdline_addr);
+    // Type of loader
+    kernel[0x210] = 0xb0;
+    // CAN_USE_HEAP and the end of the heap
+    kernel[0x211] |= 0x80;
+    write_u16(
+        &mut kernel,
+        0x224,
+        (cmdline_addr - real_addr - 0x200) as u16,
+    );
+
+    if initrd_size > 0 {
+        if initrd_size >= initrd_max {
+            bail!("Initrd of {initrd_size} bytes does not fit into the memory");
+        }
+        let initrd_addr = (initrd_max - initrd_size) & !0xfff;
+        write_u32(&mut kernel, 0x218, initrd_addr as u32);
+        write_u32(&mut kernel, 0x21c, initrd_size as u32);
+    }
+
+    Ok(kernel)
+}
+
+/// Authenticode SHA-384 hash of a PE image, as the firmware measures it
+/// into the `EV_EFI_BOOT_SERVICES_APPLICATION` event.
+pub fn authenticode_sha384(image: &[u8]) -> Result<[u8; 48]> {
+    let pe_offset = read_u32(image, 0x3c)? as usize;
+    if image.get(pe_offset..pe_offset + 4) != Some(b"PE\0\0") {
+        bail!("Kernel is not a PE image");
+    }
+    let sections = read_u16(image, pe_offset + 6)? as usize;
+    let optional_header_size = read_u16(image, pe_offset + 20)? as usize;
+    let optional_header = pe_offset + 24;
+    let data_directories = match read_u16(image, optional_header)? {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        magic => bail!("Unknown PE optional header magic {magic:#x}"),
+    };
+    let headers_size = read_u32(image, optional_header + 60)? as usize;
+    let checksum = optional_header + 64;
+    let rva_and_sizes = read_u32(image, data_directories - 4)?;
+    if headers_size > image.len() || checksum + 4 > headers_size {
+        bail!("PE headers are truncated");
+    }
+
+    let mut hasher = Sha384::new();
+    hasher.update(&image[..checksum]);
+    let certificate_table = data_directories + CERTIFICATE_TABLE as usize * 8;
+    let mut certificate_size = 0;
+    if rva_and_sizes > CERTIFICATE_TABLE {
+        if certificate_table + 8 > headers_size {
+            bail!("PE headers are truncated");
+        }
+        hasher.update(&image[checksum + 4..certificate_table]);
+        hasher.update(&image[certificate_table + 8..headers_size]);
+        certificate_size = read_u32(image, certificate_table + 4)? as usize;
+    } else {
+        hasher.update(&image[checksum + 4..headers_size]);
+    }
+
+    // Sections are hashed in the order of their data in the image
+    let section_table = optional_header + optional_header_size;
+    let mut raw_data = (0..sections)
+        .map(|index| {
+            let header = section_table + index * 40;
+            let size = read_u32(image, header + 16)? as usize;
+            let offset = read_u32(image, header + 20)? as usize;
+            Ok((offset, size))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    raw_data.sort();
+
+    let mut hashed = headers_size;
+    for (offset, size) in raw_data.into_iter().filter(|(_, size)| *size > 0) {
+        let data = image
+            .get(offset..offset + size)
+            .ok_or_else(|| anyhow!("PE section is truncated"))?;
+        hasher.update(data);
+        hashed += size;
+    }
+
+    // Data after the sections, except for the certificates
+    if image.len() > hashed + certificate_size {
+        hasher.update(&image[hashed..image.len() - certificate_size]);
+    }
+
+    Ok(hasher.finish())
+}
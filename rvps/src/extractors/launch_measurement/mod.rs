@@ -0,0 +1,214 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Extractor that computes the launch measurements of a guest image.
+//!
+//! The provenance is a base64 encoded JSON [`GuestImage`], which describes
+//! the firmware, kernel, initrd and kernel command line of a guest together
+//! with the launch parameters of each TEE. The expected measurements are
+//! computed the way `sev-snp-measure` and `tdx-measure` do, and named after
+//! the reference values consumed by the default EAR policy.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::*;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ReferenceValue;
+
+use super::Extractor;
+
+mod kernel;
+mod snp;
+mod tdx;
+
+pub use snp::SnpLaunchConfig;
+pub use tdx::TdxLaunchConfig;
+
+/// Description of a guest image. Relative paths are resolved against the
+/// image directory of the extractor, or the directory of the description
+/// for `rvps-tool compute`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct GuestImage {
+    /// Path to the OVMF (SNP) or TDVF (TDX) firmware binary.
+    pub firmware: String,
+
+    /// Path to the kernel for a direct kernel boot.
+    #[serde(default)]
+    pub kernel: Option<String>,
+
+    /// Path to the initrd for a direct kernel boot.
+    #[serde(default)]
+    pub initrd: Option<String>,
+
+    /// Kernel command line for a direct kernel boot.
+    #[serde(default)]
+    pub cmdline: String,
+
+    /// Launch parameters of an SNP guest. If set, `snp_launch_measurement`
+    /// is computed.
+    #[serde(default)]
+    pub snp: Option<SnpLaunchConfig>,
+
+    /// Launch parameters of a TD. If set, `mr_td` is computed, and with a
+    /// kernel also `rtmr_1`, `rtmr_2`, `tdvfkernel` and `tdvfkernelparams`.
+    #[serde(default)]
+    pub tdx: Option<TdxLaunchConfig>,
+}
+
+impl GuestImage {
+    fn paths(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.firmware)
+            .chain(self.kernel.iter())
+            .chain(self.initrd.iter())
+    }
+}
+
+/// Compute the launch measurements of the guest image for each TEE it has
+/// launch parameters for, as reference values by name. Relative paths of
+/// the image are resolved against `dir`.
+pub fn compute(image: &GuestImage, dir: &Path) -> Result<HashMap<String, Value>> {
+    let mut rvs = HashMap::new();
+    if let Some(config) = &image.snp {
+        rvs.extend(snp::measure(image, config, dir).context("compute SNP measurement")?);
+    }
+
+    if let Some(config) = &image.tdx {
+        rvs.extend(tdx::measure(image, config, dir).context("compute TDX measurements")?);
+    }
+
+    if rvs.is_empty() {
+        bail!("No launch parameters of any TEE are given for the guest image");
+    }
+
+    Ok(rvs)
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct LaunchMeasurementExtractorConfig {
+    /// Directory containing the guest images. The paths of a description
+    /// must be relative to it.
+    pub image_dir: Option<String>,
+}
+
+#[derive(Default)]
+pub struct LaunchMeasurementExtractor {
+    image_dir: Option<PathBuf>,
+}
+
+impl LaunchMeasurementExtractor {
+    pub fn new(config: Option<LaunchMeasurementExtractorConfig>) -> Result<Self> {
+        let image_dir = config.and_then(|c| c.image_dir).map(PathBuf::from);
+        Ok(Self { image_dir })
+    }
+}
+
+impl Extractor for LaunchMeasurementExtractor {
+    fn verify_and_extract(&self, provenance_base64: &str) -> Result<Vec<ReferenceValue>> {
+        let Some(image_dir) = &self.image_dir else {
+            bail!("No image directory is configured for the launch measurement extractor");
+        };
+
+        let provenance = base64::engine::general_purpose::STANDARD
+            .decode(provenance_base64)
+            .context("base64 decode")?;
+        let image: GuestImage =
+            serde_json::from_slice(&provenance).context("deserialize guest image")?;
+
+        // Do not let a description read files outside of the image directory
+        for path in image.paths() {
+            if !Path::new(path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+            {
+                bail!("Path {path} of the guest image is not inside the image directory");
+            }
+        }
+
+        let rvs = compute(&image, image_dir)?
+            .into_iter()
+            .map(|(name, value)| Ok(ReferenceValue::new()?.set_name(&name).set_value(value)))
+            .collect::<Result<_>>()?;
+        Ok(rvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use serde_json::json;
+
+    use super::*;
+
+    const IMAGE_DIR: &str = "tests/launch-measurement";
+
+    fn provenance(image: Value) -> String {
+        base64::engine::general_purpose::STANDARD.encode(image.to_string())
+    }
+
+    #[test]
+    fn extract_tdx() {
+        let extractor = LaunchMeasurementExtractor::new(Some(LaunchMeasurementExtractorConfig {
+            image_dir: Some(IMAGE_DIR.into()),
+        }))
+        .unwrap();
+        let rvs = extractor
+            .verify_and_extract(&provenance(json!({
+                "firmware": "tdvf.fd",
+                "kernel": "vmlinuz",
+                "initrd": "initrd.img",
+                "cmdline": "console=hvc0 root=/dev/vda1",
+                "tdx": { "memory_mib": 2048 },
+            })))
+            .unwrap();
+
+        let rvs: HashMap<_, _> = rvs
+            .into_iter()
+            .map(|rv| (rv.name().to_string(), rv.value()))
+            .collect();
+        assert_eq!(
+            rvs,
+            HashMap::from([
+                ("mr_td".to_string(), json!([tdx::tests::MR_TD])),
+                ("rtmr_1".to_string(), json!([tdx::tests::RTMR_1])),
+                ("rtmr_2".to_string(), json!([tdx::tests::RTMR_2])),
+                ("tdvfkernel".to_string(), json!(tdx::tests::TDVF_KERNEL)),
+                (
+                    "tdvfkernelparams".to_string(),
+                    json!(tdx::tests::TDVF_KERNEL_PARAMS)
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn reject_paths_outside_image_dir() {
+        let extractor = LaunchMeasurementExtractor::new(Some(LaunchMeasurementExtractorConfig {
+            image_dir: Some(IMAGE_DIR.into()),
+        }))
+        .unwrap();
+        for firmware in ["/dev/null", "../Cargo.toml", "./tdvf.fd"] {
+            let image = json!({ "firmware": firmware, "tdx": { "memory_mib": 2048 } });
+            assert!(extractor.verify_and_extract(&provenance(image)).is_err());
+        }
+
+        let image = json!({ "firmware": "tdvf.fd", "tdx": { "memory_mib": 2048 } });
+        assert!(LaunchMeasurementExtractor::default()
+            .verify_and_extract(&provenance(image))
+            .is_err());
+    }
+
+    #[test]
+    fn no_tee() {
+        let image = GuestImage {
+            firmware: "tdvf.fd".into(),
+            ..Default::default()
+        };
+        assert!(compute(&image, Path::new(IMAGE_DIR)).is_err());
+    }
+}
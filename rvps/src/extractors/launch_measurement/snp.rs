@@ -0,0 +1,121 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Launch digest of an SNP guest, as computed by `sev-snp-measure`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::*;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sev::measurement::{
+    snp::{snp_calc_launch_digest, SnpMeasurementArgs},
+    vcpu_types::CpuType,
+    vmsa::{GuestFeatures, VMMType},
+};
+
+use super::GuestImage;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnpLaunchConfig {
+    /// Number of vCPUs of the guest.
+    pub vcpus: u32,
+
+    /// QEMU vCPU type of the guest, e.g. `EPYC-v4` or `EPYC-Milan`.
+    pub vcpu_type: String,
+
+    /// SEV features enabled for the guest, `0x1` (SNP active) by default.
+    #[serde(default = "default_guest_features")]
+    pub guest_features: u64,
+
+    /// VMM launching the guest, one of `qemu` (default), `ec2` or `krun`.
+    #[serde(default)]
+    pub vmm_type: Option<String>,
+}
+
+fn default_guest_features() -> u64 {
+    0x1
+}
+
+pub fn measure(
+    image: &GuestImage,
+    config: &SnpLaunchConfig,
+    dir: &Path,
+) -> Result<HashMap<String, Value>> {
+    let vmm_type = config
+        .vmm_type
+        .as_deref()
+        .map(VMMType::from_str)
+        .transpose()
+        .map_err(|e| anyhow!("Unknown VMM type: {e}"))?;
+
+    let digest = snp_calc_launch_digest(SnpMeasurementArgs {
+        vcpus: config.vcpus,
+        vcpu_type: CpuType::try_from(config.vcpu_type.as_str())
+            .map_err(|e| anyhow!("Unknown vCPU type: {e}"))?,
+        ovmf_file: dir.join(&image.firmware),
+        guest_features: GuestFeatures(config.guest_features),
+        kernel_file: image.kernel.as_ref().map(|kernel| dir.join(kernel)),
+        initrd_file: image.initrd.as_ref().map(|initrd| dir.join(initrd)),
+        append: Some(&image.cmdline),
+        ovmf_hash_str: None,
+        vmm_type,
+    })
+    .map_err(|e| anyhow!("{e}"))?;
+    let digest: Vec<u8> = digest.try_into().map_err(|e| anyhow!("{e}"))?;
+
+    // The default policy compares the raw measurement claim, which is base64
+    let measurement = base64::engine::general_purpose::STANDARD.encode(digest);
+    Ok(HashMap::from([(
+        "snp_launch_measurement".to_string(),
+        json!([measurement]),
+    )]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use base64::Engine;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Vectors of the `sev` crate, which match `sev-snp-measure`.
+    #[rstest]
+    #[case(0x1, None, "", "ddc5224521617a536ee7ce9dd6224d1b58a8d4fda1c741f3ac99fc4bfa04ba6e9fc98646d4a07a9079397fa3852819b5")]
+    #[case(0x21, Some("qemu"), "", "329c8ce0972ae52343b64d34a434a86f245dfd74f5ed7aae15d22efc78fb9683632b9b50e4e1d7fa41179ef98a7ef198")]
+    #[case(0x1, None, "console=ttyS0 loglevel=7", "6d287813eb5222d770f75005c664e34c204f385ce832cc2ce7d0d6f354454362f390ef83a92046c042e706363b4b08fa")]
+    #[case(0x21, None, "console=ttyS0 loglevel=7", "803f691094946e42068aaa3a8f9e26a5c89f36f7b73ecfb28c653360fe4b3aba7e534442e7e1e17895dfe778d0228977")]
+    fn launch_digest(
+        #[case] guest_features: u64,
+        #[case] vmm_type: Option<&str>,
+        #[case] cmdline: &str,
+        #[case] expected: &str,
+    ) {
+        let image = GuestImage {
+            firmware: "ovmf_AmdSev_suffix.bin".into(),
+            kernel: Some("/dev/null".into()),
+            initrd: Some("/dev/null".into()),
+            cmdline: cmdline.into(),
+            ..Default::default()
+        };
+        let config = SnpLaunchConfig {
+            vcpus: 1,
+            vcpu_type: "EPYC-v4".into(),
+            guest_features,
+            vmm_type: vmm_type.map(String::from),
+        };
+
+        let rvs = measure(&image, &config, Path::new("tests/launch-measurement")).unwrap();
+        let expected =
+            base64::engine::general_purpose::STANDARD.encode(hex::decode(expected).unwrap());
+        assert_eq!(rvs["snp_launch_measurement"], json!([expected]));
+    }
+}
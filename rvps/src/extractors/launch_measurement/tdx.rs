@@ -0,0 +1,358 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Measurements of a TD launched by QEMU from a TDVF, as computed by
+//! `tdx-measure`.
+//!
+//! `MRTD` covers the pages of the TDVF sections that are added to the TD
+//! before it is launched. For a direct kernel boot, `RTMR[1]` and `RTMR[2]`
+//! are replayed from the events that the TDVF and the EFI stub of the kernel
+//! log. `RTMR[0]` is not computed, as it depends on the configuration data
+//! of the VMM, e.g. the ACPI tables.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::*;
+use openssl::sha::{sha384, Sha384};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{kernel, GuestImage};
+
+/// `96b582de-1fb2-45f7-baea-a366c55a082d`, the footer of the GUIDed table
+/// at the end of the firmware.
+const TABLE_FOOTER_GUID: [u8; 16] = [
+    0xde, 0x82, 0xb5, 0x96, 0xb2, 0x1f, 0xf7, 0x45, 0xba, 0xea, 0xa3, 0x66, 0xc5, 0x5a, 0x08, 0x2d,
+];
+/// `e47a6535-984a-4798-865e-4685a7bf8ec2`, the table entry holding the
+/// offset of the TDVF metadata from the end of the firmware.
+const TDX_METADATA_OFFSET_GUID: [u8; 16] = [
+    0x35, 0x65, 0x7a, 0xe4, 0x4a, 0x98, 0x98, 0x47, 0x86, 0x5e, 0x46, 0x85, 0xa7, 0xbf, 0x8e, 0xc2,
+];
+/// Size of the GUID and length of a table entry.
+const TABLE_ENTRY_HEADER_SIZE: usize = 18;
+
+/// The section is extended into `MRTD`.
+const ATTRIBUTE_MR_EXTEND: u32 = 1 << 0;
+/// The pages of the section are accepted by the TD, instead of being added
+/// and measured before it is launched.
+const ATTRIBUTE_PAGE_AUG: u32 = 1 << 1;
+
+const PAGE_SIZE: u64 = 0x1000;
+/// Size of the chunks of a page that `TDH.MR.EXTEND` measures.
+const MR_EXTEND_CHUNK_SIZE: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TdxLaunchConfig {
+    /// Memory of the TD in MiB, which determines where QEMU loads the
+    /// initrd, and thus the kernel image that is measured.
+    pub memory_mib: u64,
+}
+
+/// A section of the TDVF metadata.
+#[derive(Debug)]
+struct TdvfSection {
+    data_offset: usize,
+    raw_data_size: usize,
+    memory_address: u64,
+    memory_data_size: u64,
+    attributes: u32,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("Firmware is truncated at offset {offset:#x}"))?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or_else(|| anyhow!("Firmware is truncated at offset {offset:#x}"))?;
+    Ok(u64::from_le_bytes(bytes.try_into()?))
+}
+
+/// Find the data of an entry of the GUIDed table at the end of the
+/// firmware, which ends 32 bytes before the end of the firmware.
+fn table_entry<'a>(firmware: &'a [u8], guid: &[u8; 16]) -> Result<Option<&'a [u8]>> {
+    let entry_header = |end: usize| -> Result<(usize, &[u8])> {
+        let header = firmware
+            .get(end - TABLE_ENTRY_HEADER_SIZE..end)
+            .ok_or_else(|| anyhow!("Firmware GUIDed table is truncated"))?;
+        let size = u16::from_le_bytes([header[0], header[1]]) as usize;
+        Ok((size, &header[2..]))
+    };
+
+    let footer_end = firmware
+        .len()
+        .checked_sub(32)
+        .filter(|end| *end >= TABLE_ENTRY_HEADER_SIZE)
+        .ok_or_else(|| anyhow!("Firmware is too small"))?;
+    let (table_size, footer_guid) = entry_header(footer_end)?;
+    if footer_guid != TABLE_FOOTER_GUID {
+        bail!("Firmware has no GUIDed table");
+    }
+    if table_size < TABLE_ENTRY_HEADER_SIZE || table_size > footer_end {
+        bail!("Invalid size of the firmware GUIDed table");
+    }
+
+    let table_start = footer_end - table_size;
+    let mut end = footer_end - TABLE_ENTRY_HEADER_SIZE;
+    while end >= table_start + TABLE_ENTRY_HEADER_SIZE {
+        let (size, entry_guid) = entry_header(end)?;
+        if size < TABLE_ENTRY_HEADER_SIZE || size > end - table_start {
+            bail!("Invalid size of a firmware GUIDed table entry");
+        }
+        if entry_guid == guid {
+            return Ok(Some(&firmware[end - size..end - TABLE_ENTRY_HEADER_SIZE]));
+        }
+        end -= size;
+    }
+
+    Ok(None)
+}
+
+/// Parse the sections of the TDVF metadata.
+fn tdvf_sections(firmware: &[u8]) -> Result<Vec<TdvfSection>> {
+    let entry = table_entry(firmware, &TDX_METADATA_OFFSET_GUID)?
+        .ok_or_else(|| anyhow!("Firmware has no TDVF metadata, it is not a TDVF"))?;
+    let offset = firmware
+        .len()
+        .checked_sub(read_u32(entry, 0)? as usize)
+        .ok_or_else(|| anyhow!("Invalid offset of the TDVF metadata"))?;
+
+    if firmware.get(offset..offset + 4) != Some(b"TDVF") {
+        bail!("Invalid signature of the TDVF metadata");
+    }
+    let count = read_u32(firmware, offset + 12)? as usize;
+
+    (0..count)
+        .map(|index| {
+            let entry = offset + 16 + index * 32;
+            let section = TdvfSection {
+                data_offset: read_u32(firmware, entry)? as usize,
+                raw_data_size: read_u32(firmware, entry + 4)? as usize,
+                memory_address: read_u64(firmware, entry + 8)?,
+                memory_data_size: read_u64(firmware, entry + 16)?,
+                attributes: read_u32(firmware, entry + 28)?,
+            };
+
+            if section.memory_address % PAGE_SIZE != 0
+                || section.memory_data_size % PAGE_SIZE != 0
+                || section.raw_data_size as u64 > section.memory_data_size
+                || section.data_offset + section.raw_data_size > firmware.len()
+            {
+                bail!("Invalid TDVF section {section:?}");
+            }
+            Ok(section)
+        })
+        .collect()
+}
+
+/// The 128 bytes of a `TDH.MEM.PAGE.ADD` or `TDH.MR.EXTEND` that the TDX
+/// module extends into `MRTD`.
+fn mrtd_operation(name: &[u8], gpa: u64) -> [u8; 128] {
+    let mut buffer = [0u8; 128];
+    buffer[..name.len()].copy_from_slice(name);
+    buffer[16..24].copy_from_slice(&gpa.to_le_bytes());
+    buffer
+}
+
+/// Compute `MRTD` from the TDVF. Each page is added and then extended, as
+/// KVM does.
+pub fn mrtd(firmware: &[u8]) -> Result<[u8; 48]> {
+    let mut hasher = Sha384::new();
+    for section in tdvf_sections(firmware)? {
+        if section.attributes & ATTRIBUTE_PAGE_AUG != 0 {
+            continue;
+        }
+
+        let data = &firmware[section.data_offset..section.data_offset + section.raw_data_size];
+        for index in 0..section.memory_data_size / PAGE_SIZE {
+            let gpa = section.memory_address + index * PAGE_SIZE;
+            hasher.update(&mrtd_operation(b"MEM.PAGE.ADD", gpa));
+
+            if section.attributes & ATTRIBUTE_MR_EXTEND == 0 {
+                continue;
+            }
+
+            // The page is zero beyond the raw data of the section
+            let mut page = [0u8; PAGE_SIZE as usize];
+            let start = (index * PAGE_SIZE) as usize;
+            if start < data.len() {
+                let end = data.len().min(start + PAGE_SIZE as usize);
+                page[..end - start].copy_from_slice(&data[start..end]);
+            }
+            for (index, chunk) in page.chunks(MR_EXTEND_CHUNK_SIZE).enumerate() {
+                let gpa = gpa + (index * MR_EXTEND_CHUNK_SIZE) as u64;
+                hasher.update(&mrtd_operation(b"MR.EXTEND", gpa));
+                hasher.update(chunk);
+            }
+        }
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Replay the extension of an RTMR by the given event digests.
+fn replay_rtmr(digests: &[[u8; 48]]) -> [u8; 48] {
+    digests.iter().fold([0u8; 48], |rtmr, digest| {
+        let mut hasher = Sha384::new();
+        hasher.update(&rtmr);
+        hasher.update(digest);
+        hasher.finish()
+    })
+}
+
+/// Replay `RTMR[1]`, which the TDVF extends by the Authenticode digest of the
+/// kernel and the boot actions. The separator goes to `RTMR[0]`.
+fn rtmr_1(kernel_digest: &[u8; 48]) -> [u8; 48] {
+    replay_rtmr(&[
+        *kernel_digest,
+        sha384(b"Calling EFI Application from Boot Option"),
+        sha384(b"Exit Boot Services Invocation"),
+        sha384(b"Exit Boot Services Returned with Success"),
+    ])
+}
+
+/// Replay `RTMR[2]`, which the EFI stub of the kernel extends by its load
+/// options and the initrd.
+fn rtmr_2(load_options_digest: &[u8; 48], initrd_digest: Option<&[u8; 48]>) -> [u8; 48] {
+    let mut digests = vec![*load_options_digest];
+    digests.extend(initrd_digest);
+    replay_rtmr(&digests)
+}
+
+/// The load options that the TDVF passes to the kernel, in UTF-16 with a
+/// terminating NUL, which the EFI stub of the kernel measures.
+fn load_options(cmdline: &str, initrd: bool) -> Vec<u8> {
+    let options = match initrd {
+        true => format!("{cmdline} initrd=initrd"),
+        false => cmdline.to_string(),
+    };
+    options
+        .encode_utf16()
+        .chain([0])
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+pub fn measure(
+    image: &GuestImage,
+    config: &TdxLaunchConfig,
+    dir: &Path,
+) -> Result<HashMap<String, Value>> {
+    let firmware = std::fs::read(dir.join(&image.firmware)).context("read firmware")?;
+    let mut rvs = HashMap::from([("mr_td".to_string(), json!([hex::encode(mrtd(&firmware)?)]))]);
+
+    let Some(kernel) = &image.kernel else {
+        return Ok(rvs);
+    };
+    let kernel = std::fs::read(dir.join(kernel)).context("read kernel")?;
+    let initrd = match &image.initrd {
+        Some(initrd) => Some(std::fs::read(dir.join(initrd)).context("read initrd")?),
+        None => None,
+    };
+
+    let initrd_size = initrd.as_ref().map_or(0, |initrd| initrd.len() as u64);
+    let kernel = kernel::patch_for_qemu(&kernel, initrd_size, config.memory_mib << 20)?;
+    let kernel_digest = kernel::authenticode_sha384(&kernel)?;
+    let rtmr_1 = rtmr_1(&kernel_digest);
+
+    let load_options_digest = sha384(&load_options(&image.cmdline, initrd.is_some()));
+    let initrd_digest = initrd.as_deref().map(sha384);
+    let rtmr_2 = rtmr_2(&load_options_digest, initrd_digest.as_ref());
+
+    // The default policy compares the digests of the events by equality
+    rvs.extend([
+        ("rtmr_1".to_string(), json!([hex::encode(rtmr_1)])),
+        ("rtmr_2".to_string(), json!([hex::encode(rtmr_2)])),
+        ("tdvfkernel".to_string(), json!(hex::encode(kernel_digest))),
+        (
+            "tdvfkernelparams".to_string(),
+            json!(hex::encode(load_options_digest)),
+        ),
+    ]);
+    Ok(rvs)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    // The test images are synthetic, and the expected values are computed
+    // by an independent implementation of the TDX module, QEMU, TDVF and
+    // Authenticode behaviors. The replay of the RTMRs is checked against a
+    // real event log in `replay_tdvf_event_log`.
+    pub const MR_TD: &str = "2786b0148bfebf51dc36318fc261edd58afdd48552d6d17d0da39e88a4f6ac3d3423ccca02b76fdbfaec71e1e81baec3";
+    pub const RTMR_1: &str = "011388d8e81c29b06ac8c84c104910f96fec677d128737b7292d717d2862200c4e224ccf60af48696abc1586a2b771b6";
+    pub const RTMR_2: &str = "babbbefb56f6f051c625de094deb56594c8a26e731d23a2dc96ab45606016d148c40a75ac68f9165c3f37e6170d84903";
+    pub const TDVF_KERNEL: &str = "9c36383e49d813665a3da7278649e0011d5ab1c6fa4779c343576b368899d3a87485a52534f48935b136037411c08a64";
+    pub const TDVF_KERNEL_PARAMS: &str = "cb2744b431ec500423bbfb5ba55555e384a6017c13958f19e443cd8d6983d182f1ff54c831db8e0658952f591bde602f";
+
+    const IMAGE_DIR: &str = "tests/launch-measurement";
+
+    /// The events of the direct boot of a TD by a real TDVF, from the CCEL of
+    /// `deps/eventlog/test_data/CCEL_data_ovmf`, replay to its RTMRs.
+    #[test]
+    fn replay_tdvf_event_log() {
+        let digest = |hex: &str| -> [u8; 48] { hex::decode(hex).unwrap().try_into().unwrap() };
+
+        let kernel = digest("a2ccae1e7d6c668ca325bb09c882d8ce44d26d714ba6f58d2e8083fe291a704646afe24a2368bca3341728d78ec80a80");
+        assert_eq!(
+            hex::encode(rtmr_1(&kernel)),
+            "775b9f6bfe99f8a31396f0d0218e67ffa796d3b96ccf961cbb0deba48c79c00f082cda1a5567c1c16305f1fc210c13c6"
+        );
+
+        let load_options = digest("4230f84885a6f3f305e91a1955045398bd9edd8ffd2aaf2aab8ad3ac53476c4ac82a3675ef559c4ae949a06e84119fc2");
+        let initrd = digest("b15af9286108d3d8c9f794a51409e55bad6334f5d96a1e4469f8df2d75fd69aac648d939e13daf6800e82e6c1f6628c4");
+        assert_eq!(
+            hex::encode(rtmr_2(&load_options, Some(&initrd))),
+            "94eaf7a7bf398ed8d888c91057ae0261802e4f3df084213a76ca7f0b5055ac9d2241de43cd58d9e8b49c503bbf25f34a"
+        );
+    }
+
+    #[test]
+    fn measure_without_initrd() {
+        let image = GuestImage {
+            firmware: "tdvf.fd".into(),
+            kernel: Some("vmlinuz".into()),
+            cmdline: "console=hvc0 root=/dev/vda1".into(),
+            ..Default::default()
+        };
+        let config = TdxLaunchConfig { memory_mib: 2048 };
+        let rvs = measure(&image, &config, Path::new(IMAGE_DIR)).unwrap();
+
+        assert_eq!(rvs["mr_td"], json!([MR_TD]));
+        assert_eq!(
+            rvs["tdvfkernel"],
+            json!("c4e7888a7113bd1e13f801a8db8850843496d51dadf824ed50333055b107d30bd5f7f9a88dbbc059be362d0038b4e8ed")
+        );
+        assert_eq!(
+            rvs["rtmr_2"],
+            json!(["7c6213ced62d5318851a17d97ca10d8007090b3462606511d2a9dbc0a98c640ab67ea2a3e2ca6c4d5fe17c8e0e5df615"])
+        );
+    }
+
+    #[test]
+    fn measure_firmware_only() {
+        let image = GuestImage {
+            firmware: "tdvf.fd".into(),
+            ..Default::default()
+        };
+        let config = TdxLaunchConfig { memory_mib: 2048 };
+        let rvs = measure(&image, &config, Path::new(IMAGE_DIR)).unwrap();
+        assert_eq!(rvs, HashMap::from([("mr_td".to_string(), json!([MR_TD]))]));
+    }
+
+    #[test]
+    fn not_a_tdvf() {
+        let firmware = std::fs::read(Path::new(IMAGE_DIR).join("ovmf_AmdSev_suffix.bin")).unwrap();
+        assert!(mrtd(&firmware).is_err());
+        assert!(mrtd(&[0; 16]).is_err());
+    }
+}
@@ -12,12 +12,19 @@ use std::collections::HashMap;
 
 use super::{Message, ReferenceValue};
 
+pub mod corim;
 pub mod sample;
 pub mod swid;
 
+#[cfg(feature = "golden-eventlog")]
+pub mod golden_eventlog;
+
 #[cfg(feature = "in-toto")]
 pub mod in_toto;
 
+#[cfg(feature = "launch-measurement")]
+pub mod launch_measurement;
+
 /// Extractor is a standard interface that all provenance extractors
 /// need to implement. Here reference_value can be modified in the
 /// handler, added any field if needed.
@@ -30,11 +37,29 @@ pub type ExtractorInstance = Box<dyn Extractor + Sync + Send>;
 pub struct Extractors {
     /// A map of provenance types to Extractor instances
     extractor_map: HashMap<String, ExtractorInstance>,
+
+    /// A map of provenance types to whether unsigned messages are accepted
+    accept_unsigned: HashMap<String, bool>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Default)]
 pub struct ExtractorsConfig {
     swid_extractor: Option<swid::SwidExtractorConfig>,
+
+    corim_extractor: Option<corim::CorimExtractorConfig>,
+
+    #[cfg(feature = "in-toto")]
+    in_toto_extractor: Option<in_toto::InTotoExtractorConfig>,
+
+    #[cfg(feature = "launch-measurement")]
+    launch_measurement_extractor: Option<launch_measurement::LaunchMeasurementExtractorConfig>,
+
+    /// Whether messages without a trusted signature are accepted,
+    /// per provenance type. If a provenance type is not listed,
+    /// unsigned messages are only accepted when no trusted provider
+    /// keys or CA certificates are configured.
+    #[serde(default)]
+    accept_unsigned: HashMap<String, bool>,
 }
 
 impl Extractors {
@@ -44,6 +69,10 @@ impl Extractors {
         extractor_map.insert("sample".to_string(), Box::new(sample::SampleExtractor));
 
         let swid_config = config.clone().map(|c| c.swid_extractor).unwrap_or(None);
+        let accept_unsigned = config
+            .clone()
+            .map(|c| c.accept_unsigned)
+            .unwrap_or_default();
         if config.is_none() {
             warn!("No configuration for SWID extractor provided. Default will be used.");
         }
@@ -53,13 +82,49 @@ impl Extractors {
             Box::new(swid::SwidExtractor::new(swid_config)?),
         );
 
-        #[cfg(feature = "in-toto")]
+        let corim_config = config.clone().and_then(|c| c.corim_extractor);
+        extractor_map.insert(
+            "corim".to_string(),
+            Box::new(corim::CorimExtractor::new(corim_config)?),
+        );
+
+        #[cfg(feature = "golden-eventlog")]
         extractor_map.insert(
-            "in-toto".to_string(),
-            Box::new(in_toto::InTotoExtractor::new()),
+            "golden-eventlog".to_string(),
+            Box::new(golden_eventlog::GoldenEventLogExtractor),
         );
 
-        Ok(Extractors { extractor_map })
+        #[cfg(feature = "in-toto")]
+        {
+            let in_toto_config = config.clone().and_then(|c| c.in_toto_extractor);
+            extractor_map.insert(
+                "in-toto".to_string(),
+                Box::new(in_toto::InTotoExtractor::new(in_toto_config)?),
+            );
+        }
+
+        #[cfg(feature = "launch-measurement")]
+        {
+            let launch_measurement_config =
+                config.clone().and_then(|c| c.launch_measurement_extractor);
+            extractor_map.insert(
+                "launch-measurement".to_string(),
+                Box::new(launch_measurement::LaunchMeasurementExtractor::new(
+                    launch_measurement_config,
+                )?),
+            );
+        }
+
+        Ok(Extractors {
+            extractor_map,
+            accept_unsigned,
+        })
+    }
+
+    /// Whether unsigned messages of the given provenance type are accepted,
+    /// if it is configured explicitly.
+    pub fn accept_unsigned(&self, extractor_type: &str) -> Option<bool> {
+        self.accept_unsigned.get(extractor_type).copied()
     }
 
     /// Process the message, by verifying the provenance
@@ -71,7 +136,12 @@ impl Extractors {
         let extractor_type = message.r#type;
 
         if let Some(extractor) = self.extractor_map.get_mut(&extractor_type) {
-            return extractor.verify_and_extract(&message.payload);
+            let rvs = extractor
+                .verify_and_extract(&message.payload)?
+                .into_iter()
+                .map(|rv| rv.set_extractor(&extractor_type))
+                .collect();
+            return Ok(rvs);
         }
 
         bail!("Could not find extractor for {extractor_type}");
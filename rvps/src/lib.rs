@@ -9,6 +9,7 @@ pub mod extractors;
 pub mod reference_value;
 pub mod rvps_api;
 pub mod server;
+pub mod signature;
 pub mod storage;
 
 pub use config::Config;
@@ -16,16 +17,18 @@ pub use reference_value::{ReferenceValue, TrustedDigest};
 pub use storage::ReferenceValueStorage;
 
 use extractors::Extractors;
+use signature::{DsseSignature, SignatureVerifier};
 
 pub use serde_json::Value;
 
 use anyhow::{bail, Context, Result};
-use log::{info, warn};
+use log::info;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tokio::sync::broadcast;
 
 /// Default version of Message
-static MESSAGE_VERSION: &str = "0.1.0";
+pub static MESSAGE_VERSION: &str = "0.1.0";
 
 /// Message is an overall packet that Reference Value Provider Service
 /// receives. It will contain payload (content of different provenance,
@@ -35,12 +38,16 @@ static MESSAGE_VERSION: &str = "0.1.0";
 /// * `version`: version of this message.
 /// * `payload`: content of the provenance, JSON encoded.
 /// * `type`: provenance type of the payload.
+/// * `signatures`: optional DSSE signatures over the payload, see
+///   [`signature`].
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Message {
     #[serde(default = "default_version")]
     version: String,
     payload: String,
     r#type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signatures: Vec<DsseSignature>,
 }
 
 /// Set the default version for Message
@@ -48,24 +55,76 @@ fn default_version() -> String {
     MESSAGE_VERSION.into()
 }
 
+/// Capacity of the channel of reference value changes. Watchers that lag
+/// behind by more changes need to load a new snapshot.
+const CHANGES_CAPACITY: usize = 64;
+
+/// A change of the reference values, sent to the watchers of a [`Rvps`].
+/// * `revision`: revision of the reference values after the change.
+/// * `updated`: the added or replaced reference values, by name.
+/// * `removed`: names of the deleted, purged or expired reference values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReferenceValueChange {
+    pub revision: u64,
+    pub updated: HashMap<String, Value>,
+    pub removed: Vec<String>,
+}
+
 /// The core of the RVPS, s.t. componants except communication componants.
 pub struct Rvps {
     extractors: Extractors,
+    signature_verifier: SignatureVerifier,
     storage: Box<dyn ReferenceValueStorage + Send + Sync>,
+    allow_import: bool,
+    revision: u64,
+    changes: broadcast::Sender<ReferenceValueChange>,
 }
 
 impl Rvps {
     /// Instantiate a new RVPS
     pub fn new(config: Config) -> Result<Self> {
         let extractors = Extractors::new(config.extractors)?;
+        let signature_verifier = SignatureVerifier::new(config.signature)?;
         let storage = config.storage.to_storage()?;
 
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+
         Ok(Rvps {
             extractors,
+            signature_verifier,
             storage,
+            allow_import: config.allow_import,
+            revision: 0,
+            changes,
         })
     }
 
+    /// Revision of the reference values. It starts from 0 and is increased
+    /// by every change made through this instance.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Subscribe to the changes of the reference values made after this call.
+    /// Values expiring over time are not notified.
+    pub fn subscribe(&self) -> broadcast::Receiver<ReferenceValueChange> {
+        self.changes.subscribe()
+    }
+
+    fn notify(&mut self, updated: HashMap<String, Value>, removed: Vec<String>) {
+        if updated.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        self.revision += 1;
+        // Sending only fails if nobody is watching.
+        let _ = self.changes.send(ReferenceValueChange {
+            revision: self.revision,
+            updated,
+            removed,
+        });
+    }
+
     pub async fn verify_and_extract(&mut self, message: &str) -> Result<()> {
         let message: Message = serde_json::from_str(message).context("parse message")?;
 
@@ -78,29 +137,374 @@ impl Rvps {
             );
         }
 
-        let rv = self.extractors.process(message)?;
-        for v in rv.iter() {
-            let old = self.storage.set(v.name().to_string(), v.clone()).await?;
+        let signer = self.signature_verifier.verify(
+            &message.r#type,
+            &message.payload,
+            &message.signatures,
+        )?;
+        if signer.is_none()
+            && !self
+                .extractors
+                .accept_unsigned(&message.r#type)
+                .unwrap_or(!self.signature_verifier.has_trust_anchors())
+        {
+            bail!(
+                "Unsigned message of type {} is not accepted.",
+                message.r#type
+            );
+        }
+
+        let mut rv = self.extractors.process(message)?;
+        if let Some(signer) = signer {
+            rv = rv.into_iter().map(|v| v.set_signer(&signer)).collect();
+        }
+
+        self.store(rv).await
+    }
+
+    /// Store the reference values as they are, e.g. those exported from
+    /// another RVPS, with their expiration and metadata. Unlike messages,
+    /// they are neither verified nor extracted, so importing must be allowed
+    /// explicitly by `allow_import` of the [`Config`]. Return the names of
+    /// the imported reference values.
+    pub async fn import_reference_values(
+        &mut self,
+        rvs: Vec<ReferenceValue>,
+    ) -> Result<Vec<String>> {
+        if !self.allow_import {
+            bail!("Importing reference values is not allowed by the RVPS configuration");
+        }
+
+        let imported = rvs.iter().map(|rv| rv.name().to_string()).collect();
+        self.store(rvs).await?;
+        info!("Reference values imported: {imported:?}");
+
+        Ok(imported)
+    }
+
+    async fn store(&mut self, rv: Vec<ReferenceValue>) -> Result<()> {
+        let mut updated = HashMap::new();
+        let mut removed = Vec::new();
+        for v in rv.into_iter() {
+            let name = v.name().to_string();
+            let expired = v.expired();
+            let old = self.storage.set(name.clone(), v.clone()).await?;
             if let Some(old) = old {
                 info!("Old Reference value of {} is replaced.", old.name());
             }
+
+            match expired {
+                true => removed.push(name),
+                false => {
+                    updated.insert(name, v.value());
+                }
+            }
         }
+        self.notify(updated, removed);
 
         Ok(())
     }
 
+    /// Get all the unexpired reference values.
     pub async fn get_digests(&self) -> Result<HashMap<String, Value>> {
-        let mut rv_map = HashMap::new();
-        let reference_values = self.storage.get_values().await?;
+        self.get_digests_by_prefixes(&[String::new()]).await
+    }
 
-        for rv in reference_values {
-            if rv.expired() {
-                warn!("Reference value of {} is expired.", rv.name());
-                continue;
+    /// Get the unexpired reference values whose name starts with one of the
+    /// given prefixes.
+    pub async fn get_digests_by_prefixes(
+        &self,
+        prefixes: &[String],
+    ) -> Result<HashMap<String, Value>> {
+        let mut rv_map = HashMap::new();
+        for prefix in prefixes {
+            let reference_values = self.storage.get_unexpired_values_by_prefix(prefix).await?;
+            for rv in reference_values {
+                rv_map.insert(rv.name().to_string(), rv.value());
             }
-
-            rv_map.insert(rv.name().to_string(), rv.value());
         }
         Ok(rv_map)
     }
+
+    /// Delete the reference value of the given name. Return whether it existed.
+    pub async fn delete_reference_value(&mut self, name: &str) -> Result<bool> {
+        let deleted = self.storage.delete(name).await?;
+        if deleted.is_some() {
+            info!("Reference value of {name} is deleted.");
+            self.notify(HashMap::new(), vec![name.to_string()]);
+        }
+
+        Ok(deleted.is_some())
+    }
+
+    /// Delete all the reference values whose name starts with the given
+    /// prefix. Return the names of the deleted reference values.
+    pub async fn delete_reference_values_by_prefix(&mut self, prefix: &str) -> Result<Vec<String>> {
+        let deleted: Vec<String> = self
+            .storage
+            .delete_by_prefix(prefix)
+            .await?
+            .into_iter()
+            .map(|rv| rv.name)
+            .collect();
+        info!("Reference values deleted by prefix {prefix}: {deleted:?}");
+        self.notify(HashMap::new(), deleted.clone());
+
+        Ok(deleted)
+    }
+
+    /// List all the stored reference values together with their metadata
+    /// (version, expiration and source extractor), including expired ones.
+    pub async fn list_reference_values(&self) -> Result<Vec<ReferenceValue>> {
+        self.storage.get_values().await
+    }
+
+    /// Delete all the expired reference values. Return the names of the
+    /// purged reference values.
+    pub async fn purge_expired(&mut self) -> Result<Vec<String>> {
+        let purged: Vec<String> = self
+            .storage
+            .purge_expired()
+            .await?
+            .into_iter()
+            .map(|rv| rv.name)
+            .collect();
+        info!("Expired reference values purged: {purged:?}");
+        self.notify(HashMap::new(), purged.clone());
+
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use chrono::{TimeZone, Utc};
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::PKey,
+        sign::Signer,
+    };
+    use serde_json::json;
+
+    use crate::signature::pae;
+    use crate::storage::{local_json, ReferenceValueStorageConfig};
+
+    use super::{Config, ReferenceValue, Rvps};
+
+    #[tokio::test]
+    async fn list_and_delete_reference_values() {
+        let temp_dir = tempfile::tempdir().expect("create tempdir failed");
+        let file_path = temp_dir
+            .path()
+            .join("reference_values.json")
+            .to_string_lossy()
+            .to_string();
+        let config = Config {
+            storage: ReferenceValueStorageConfig::LocalJson(local_json::Config { file_path }),
+            ..Default::default()
+        };
+        let mut rvps = Rvps::new(config).expect("create rvps failed");
+
+        let provenance = json!({
+            "kernel-a": ["aaa"],
+            "kernel-b": ["bbb"],
+            "initrd": ["ccc"],
+        });
+        let message = json!({
+            "version": "0.1.0",
+            "type": "sample",
+            "payload": base64::engine::general_purpose::STANDARD.encode(provenance.to_string()),
+        });
+        rvps.verify_and_extract(&message.to_string())
+            .await
+            .expect("register failed");
+
+        let listed = rvps.list_reference_values().await.expect("list failed");
+        assert_eq!(listed.len(), 3);
+        assert!(listed.iter().all(|rv| rv.extractor() == Some("sample")));
+
+        let mut scoped: Vec<_> = rvps
+            .get_digests_by_prefixes(&["kernel-".into(), "kernel-a".into(), "foo".into()])
+            .await
+            .expect("query by prefixes failed")
+            .into_keys()
+            .collect();
+        scoped.sort();
+        assert_eq!(scoped, vec!["kernel-a", "kernel-b"]);
+
+        let mut deleted = rvps
+            .delete_reference_values_by_prefix("kernel-")
+            .await
+            .expect("delete by prefix failed");
+        deleted.sort();
+        assert_eq!(deleted, vec!["kernel-a", "kernel-b"]);
+
+        assert!(rvps
+            .delete_reference_value("initrd")
+            .await
+            .expect("delete failed"));
+        assert!(!rvps
+            .delete_reference_value("initrd")
+            .await
+            .expect("delete failed"));
+
+        assert!(rvps.get_digests().await.expect("query failed").is_empty());
+        assert!(rvps.purge_expired().await.expect("purge failed").is_empty());
+    }
+
+    #[tokio::test]
+    async fn watch_changes() {
+        let temp_dir = tempfile::tempdir().expect("create tempdir failed");
+        let file_path = temp_dir
+            .path()
+            .join("reference_values.json")
+            .to_string_lossy()
+            .to_string();
+        let config = Config {
+            storage: ReferenceValueStorageConfig::LocalJson(local_json::Config { file_path }),
+            ..Default::default()
+        };
+        let mut rvps = Rvps::new(config).expect("create rvps failed");
+        let mut changes = rvps.subscribe();
+
+        let provenance = json!({
+            "kernel": ["aaa"],
+            "initrd": ["bbb"],
+        });
+        let message = json!({
+            "type": "sample",
+            "payload": base64::engine::general_purpose::STANDARD.encode(provenance.to_string()),
+        });
+        rvps.verify_and_extract(&message.to_string())
+            .await
+            .expect("register failed");
+
+        let change = changes.try_recv().expect("no change");
+        assert_eq!(change.revision, 1);
+        let mut updated: Vec<_> = change.updated.keys().cloned().collect();
+        updated.sort();
+        assert_eq!(updated, vec!["initrd", "kernel"]);
+        assert!(change.removed.is_empty());
+
+        assert!(rvps
+            .delete_reference_value("kernel")
+            .await
+            .expect("delete failed"));
+        let change = changes.try_recv().expect("no change");
+        assert_eq!(change.revision, 2);
+        assert!(change.updated.is_empty());
+        assert_eq!(change.removed, vec!["kernel"]);
+
+        // Nothing changes, so nothing is notified
+        assert!(!rvps
+            .delete_reference_value("kernel")
+            .await
+            .expect("delete failed"));
+        assert!(changes.try_recv().is_err());
+        assert_eq!(rvps.revision(), 2);
+    }
+
+    #[tokio::test]
+    async fn signed_and_unsigned_messages() {
+        let temp_dir = tempfile::tempdir().expect("create tempdir failed");
+        let file_path = temp_dir
+            .path()
+            .join("reference_values.json")
+            .to_string_lossy()
+            .to_string();
+        let key = PKey::from_ec_key(
+            EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let key_path = temp_dir.path().join("provider.pem");
+        std::fs::write(&key_path, key.public_key_to_pem().unwrap()).unwrap();
+
+        let config: Config = serde_json::from_value(json!({
+            "storage": {
+                "type": "LocalJson",
+                "file_path": file_path,
+            },
+            "signature": {
+                "trusted_keys": {
+                    "provider": key_path,
+                },
+            },
+        }))
+        .unwrap();
+        let mut rvps = Rvps::new(config.clone()).expect("create rvps failed");
+
+        let provenance = json!({"kernel": ["aaa"]}).to_string();
+        let payload = base64::engine::general_purpose::STANDARD.encode(&provenance);
+        let unsigned = json!({
+            "type": "sample",
+            "payload": payload,
+        });
+        assert!(rvps
+            .verify_and_extract(&unsigned.to_string())
+            .await
+            .is_err());
+
+        // Self-signed provenance types are no exception unless opted in
+        let unsigned_corim = json!({
+            "type": "corim",
+            "payload": payload,
+        });
+        let err = rvps
+            .verify_and_extract(&unsigned_corim.to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unsigned message of type corim is not accepted."
+        );
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        let sig = signer
+            .sign_oneshot_to_vec(&pae("sample", provenance.as_bytes()))
+            .unwrap();
+        let signed = json!({
+            "type": "sample",
+            "payload": payload,
+            "signatures": [{
+                "keyid": "provider",
+                "sig": base64::engine::general_purpose::STANDARD.encode(sig),
+            }],
+        });
+        rvps.verify_and_extract(&signed.to_string())
+            .await
+            .expect("register failed");
+
+        let listed = rvps.list_reference_values().await.expect("list failed");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].signer(), Some("provider"));
+
+        // Imported reference values are stored as they are, even though
+        // unsigned messages are not accepted, but only if importing is allowed
+        let imported = ReferenceValue::new()
+            .unwrap()
+            .set_name("initrd")
+            .set_expiration(Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap())
+            .set_value(json!(["bbb"]))
+            .set_signer("other-rvps");
+        assert!(rvps
+            .import_reference_values(vec![imported.clone()])
+            .await
+            .is_err());
+
+        let mut rvps = Rvps::new(Config {
+            allow_import: true,
+            ..config
+        })
+        .expect("create rvps failed");
+        let names = rvps
+            .import_reference_values(vec![imported.clone()])
+            .await
+            .expect("import failed");
+        assert_eq!(names, ["initrd"]);
+        let listed = rvps.list_reference_values().await.expect("list failed");
+        assert!(listed.contains(&imported));
+    }
 }
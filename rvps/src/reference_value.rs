@@ -50,6 +50,16 @@ pub struct ReferenceValue {
     /// The reference value can be any type suported by
     /// serde_json, including nested types.
     pub value: serde_json::Value,
+    /// The type of the extractor that produced this reference value,
+    /// e.g. `sample`. Reference values stored before this field was
+    /// introduced do not have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extractor: Option<String>,
+    /// The identity of the provider that signed the message carrying
+    /// this reference value, i.e. the id of the trusted key or the
+    /// subject of the trusted certificate. `None` if it was unsigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
 }
 
 /// Set the default version for ReferenceValue
@@ -71,6 +81,8 @@ impl ReferenceValue {
                 .and_then(|t| t.checked_add_months(Months::new(MONTHS_BEFORE_EXPIRATION)))
                 .ok_or_else(|| anyhow!("Failed to set time."))?,
             value: serde_json::Value::Null,
+            extractor: None,
+            signer: None,
         })
     }
 
@@ -120,6 +132,28 @@ impl ReferenceValue {
     pub fn name(&self) -> &String {
         &self.name
     }
+
+    /// Set the type of the extractor that produced the ReferenceValue.
+    pub fn set_extractor(mut self, extractor: &str) -> Self {
+        self.extractor = Some(extractor.into());
+        self
+    }
+
+    /// Get the type of the extractor that produced the ReferenceValue.
+    pub fn extractor(&self) -> Option<&str> {
+        self.extractor.as_deref()
+    }
+
+    /// Set the identity of the signer of the ReferenceValue.
+    pub fn set_signer(mut self, signer: &str) -> Self {
+        self.signer = Some(signer.into());
+        self
+    }
+
+    /// Get the identity of the signer of the ReferenceValue.
+    pub fn signer(&self) -> Option<&str> {
+        self.signer.as_deref()
+    }
 }
 
 /// Trusted Digest is what RVPS actually delivered to
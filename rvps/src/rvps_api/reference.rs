@@ -1,6 +1,11 @@
 // This file is @generated by prost-build.
-#[derive(Clone, Copy, PartialEq, ::prost::Message)]
-pub struct ReferenceValueQueryRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueQueryRequest {
+    /// Only return the reference values whose name starts with one of these
+    /// prefixes. If empty, all the reference values are returned.
+    #[prost(string, repeated, tag = "1")]
+    pub prefixes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReferenceValueQueryResponse {
     #[prost(string, tag = "1")]
@@ -13,6 +18,71 @@ pub struct ReferenceValueRegisterRequest {
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct ReferenceValueRegisterResponse {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueDeleteRequest {
+    /// Delete the reference value with exactly this name.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Delete all the reference values whose name starts with this prefix.
+    /// Exactly one of `name` and `prefix` must be set.
+    #[prost(string, tag = "2")]
+    pub prefix: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueDeleteResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub deleted: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ReferenceValueListRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueListResponse {
+    /// JSON array of the stored reference values with their metadata
+    /// (version, expiration and source extractor), including expired ones.
+    #[prost(string, tag = "1")]
+    pub reference_values: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ReferenceValuePurgeRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValuePurgeResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub purged: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueImportRequest {
+    /// JSON array of reference values with their metadata, in the format of
+    /// `ReferenceValueListResponse.reference_values`. They are stored as they
+    /// are, without going through an extractor.
+    #[prost(string, tag = "1")]
+    pub reference_values: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueImportResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub imported: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ReferenceValueWatchRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReferenceValueWatchResponse {
+    /// Revision of the reference values after this update. It is increased
+    /// by every change made through the RVPS instance, and restarts from 0
+    /// when the RVPS restarts.
+    #[prost(uint64, tag = "1")]
+    pub revision: u64,
+    /// Whether `updated` contains all the unexpired reference values and
+    /// replaces the previous ones. The first response is always a snapshot.
+    #[prost(bool, tag = "2")]
+    pub snapshot: bool,
+    /// JSON map of the names to the values of the added or replaced
+    /// reference values, in the format of `reference_value_results`.
+    #[prost(string, tag = "3")]
+    pub updated: ::prost::alloc::string::String,
+    /// Names of the deleted, purged or expired reference values.
+    #[prost(string, repeated, tag = "4")]
+    pub removed: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
 /// Generated client implementations.
 pub mod reference_value_provider_service_client {
     #![allow(
@@ -143,6 +213,103 @@ pub mod reference_value_provider_service_client {
             ));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn delete_reference_value(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReferenceValueDeleteRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueDeleteResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reference.ReferenceValueProviderService/DeleteReferenceValue",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "reference.ReferenceValueProviderService",
+                "DeleteReferenceValue",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_reference_values(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReferenceValueListRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueListResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reference.ReferenceValueProviderService/ListReferenceValues",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "reference.ReferenceValueProviderService",
+                "ListReferenceValues",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn purge_expired_reference_values(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReferenceValuePurgeRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValuePurgeResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reference.ReferenceValueProviderService/PurgeExpiredReferenceValues",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "reference.ReferenceValueProviderService",
+                "PurgeExpiredReferenceValues",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_reference_values(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReferenceValueImportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueImportResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reference.ReferenceValueProviderService/ImportReferenceValues",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "reference.ReferenceValueProviderService",
+                "ImportReferenceValues",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn watch_reference_values(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReferenceValueWatchRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ReferenceValueWatchResponse>>,
+            tonic::Status,
+        > {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reference.ReferenceValueProviderService/WatchReferenceValues",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "reference.ReferenceValueProviderService",
+                "WatchReferenceValues",
+            ));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -171,6 +338,31 @@ pub mod reference_value_provider_service_server {
             tonic::Response<super::ReferenceValueRegisterResponse>,
             tonic::Status,
         >;
+        async fn delete_reference_value(
+            &self,
+            request: tonic::Request<super::ReferenceValueDeleteRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueDeleteResponse>, tonic::Status>;
+        async fn list_reference_values(
+            &self,
+            request: tonic::Request<super::ReferenceValueListRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueListResponse>, tonic::Status>;
+        async fn purge_expired_reference_values(
+            &self,
+            request: tonic::Request<super::ReferenceValuePurgeRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValuePurgeResponse>, tonic::Status>;
+        async fn import_reference_values(
+            &self,
+            request: tonic::Request<super::ReferenceValueImportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReferenceValueImportResponse>, tonic::Status>;
+        /// Server streaming response type for the WatchReferenceValues method.
+        type WatchReferenceValuesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ReferenceValueWatchResponse, tonic::Status>,
+            > + std::marker::Send
+            + 'static;
+        async fn watch_reference_values(
+            &self,
+            request: tonic::Request<super::ReferenceValueWatchRequest>,
+        ) -> std::result::Result<tonic::Response<Self::WatchReferenceValuesStream>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ReferenceValueProviderServiceServer<T> {
@@ -335,6 +527,236 @@ pub mod reference_value_provider_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reference.ReferenceValueProviderService/DeleteReferenceValue" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteReferenceValueSvc<T: ReferenceValueProviderService>(pub Arc<T>);
+                    impl<T: ReferenceValueProviderService>
+                        tonic::server::UnaryService<super::ReferenceValueDeleteRequest>
+                        for DeleteReferenceValueSvc<T>
+                    {
+                        type Response = super::ReferenceValueDeleteResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReferenceValueDeleteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ReferenceValueProviderService>::delete_reference_value(
+                                    &inner, request,
+                                )
+                                .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeleteReferenceValueSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reference.ReferenceValueProviderService/ListReferenceValues" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListReferenceValuesSvc<T: ReferenceValueProviderService>(pub Arc<T>);
+                    impl<T: ReferenceValueProviderService>
+                        tonic::server::UnaryService<super::ReferenceValueListRequest>
+                        for ListReferenceValuesSvc<T>
+                    {
+                        type Response = super::ReferenceValueListResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReferenceValueListRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ReferenceValueProviderService>::list_reference_values(
+                                    &inner, request,
+                                )
+                                .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListReferenceValuesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reference.ReferenceValueProviderService/PurgeExpiredReferenceValues" => {
+                    #[allow(non_camel_case_types)]
+                    struct PurgeExpiredReferenceValuesSvc<T: ReferenceValueProviderService>(
+                        pub Arc<T>,
+                    );
+                    impl<T: ReferenceValueProviderService>
+                        tonic::server::UnaryService<super::ReferenceValuePurgeRequest>
+                        for PurgeExpiredReferenceValuesSvc<T>
+                    {
+                        type Response = super::ReferenceValuePurgeResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReferenceValuePurgeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ReferenceValueProviderService>::purge_expired_reference_values(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PurgeExpiredReferenceValuesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reference.ReferenceValueProviderService/ImportReferenceValues" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportReferenceValuesSvc<T: ReferenceValueProviderService>(pub Arc<T>);
+                    impl<T: ReferenceValueProviderService>
+                        tonic::server::UnaryService<super::ReferenceValueImportRequest>
+                        for ImportReferenceValuesSvc<T>
+                    {
+                        type Response = super::ReferenceValueImportResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReferenceValueImportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ReferenceValueProviderService>::import_reference_values(
+                                    &inner, request,
+                                )
+                                .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ImportReferenceValuesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reference.ReferenceValueProviderService/WatchReferenceValues" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchReferenceValuesSvc<T: ReferenceValueProviderService>(pub Arc<T>);
+                    impl<T: ReferenceValueProviderService>
+                        tonic::server::ServerStreamingService<super::ReferenceValueWatchRequest>
+                        for WatchReferenceValuesSvc<T>
+                    {
+                        type Response = super::ReferenceValueWatchResponse;
+                        type ResponseStream = T::WatchReferenceValuesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReferenceValueWatchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ReferenceValueProviderService>::watch_reference_values(
+                                    &inner, request,
+                                )
+                                .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchReferenceValuesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     let mut response = http::Response::new(empty_body());
                     let headers = response.headers_mut();
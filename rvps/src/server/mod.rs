@@ -1,21 +1,30 @@
-use anyhow::{Context, Result};
-use log::{debug, info};
+use anyhow::{bail, Context, Result};
+use log::{debug, info, warn};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tonic::transport::Server;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tonic::{Request, Response, Status};
 
-use crate::{Config, Rvps};
+use crate::{Config, ReferenceValueChange, Rvps};
 
 use crate::rvps_api::reference::reference_value_provider_service_server::{
     ReferenceValueProviderService, ReferenceValueProviderServiceServer,
 };
 use crate::rvps_api::reference::{
-    ReferenceValueQueryRequest, ReferenceValueQueryResponse, ReferenceValueRegisterRequest,
-    ReferenceValueRegisterResponse,
+    ReferenceValueDeleteRequest, ReferenceValueDeleteResponse, ReferenceValueImportRequest,
+    ReferenceValueImportResponse, ReferenceValueListRequest, ReferenceValueListResponse,
+    ReferenceValuePurgeRequest, ReferenceValuePurgeResponse, ReferenceValueQueryRequest,
+    ReferenceValueQueryResponse, ReferenceValueRegisterRequest, ReferenceValueRegisterResponse,
+    ReferenceValueWatchRequest, ReferenceValueWatchResponse,
 };
 
+/// Number of updates buffered for each watcher.
+const WATCH_BUFFER: usize = 16;
+
 pub struct RvpsServer {
     rvps: Arc<RwLock<Rvps>>,
 }
@@ -26,19 +35,48 @@ impl RvpsServer {
     }
 }
 
+/// Snapshot of all the unexpired reference values of the RVPS.
+async fn snapshot(rvps: &Rvps) -> Result<ReferenceValueWatchResponse, Status> {
+    let rvs = rvps
+        .get_digests()
+        .await
+        .map_err(|e| Status::aborted(format!("Query reference value: {e}")))?;
+    let updated = serde_json::to_string(&rvs)
+        .map_err(|e| Status::aborted(format!("Serde reference value: {e}")))?;
+
+    Ok(ReferenceValueWatchResponse {
+        revision: rvps.revision(),
+        snapshot: true,
+        updated,
+        removed: vec![],
+    })
+}
+
+fn change(change: ReferenceValueChange) -> Result<ReferenceValueWatchResponse, Status> {
+    let updated = serde_json::to_string(&change.updated)
+        .map_err(|e| Status::aborted(format!("Serde reference value: {e}")))?;
+
+    Ok(ReferenceValueWatchResponse {
+        revision: change.revision,
+        snapshot: false,
+        updated,
+        removed: change.removed,
+    })
+}
+
 #[tonic::async_trait]
 impl ReferenceValueProviderService for RvpsServer {
     async fn query_reference_value(
         &self,
-        _request: Request<ReferenceValueQueryRequest>,
+        request: Request<ReferenceValueQueryRequest>,
     ) -> Result<Response<ReferenceValueQueryResponse>, Status> {
-        let rvs = self
-            .rvps
-            .read()
-            .await
-            .get_digests()
-            .await
-            .map_err(|e| Status::aborted(format!("Query reference value: {e}")))?;
+        let request = request.into_inner();
+        let rvps = self.rvps.read().await;
+        let rvs = match request.prefixes.is_empty() {
+            true => rvps.get_digests().await,
+            false => rvps.get_digests_by_prefixes(&request.prefixes).await,
+        }
+        .map_err(|e| Status::aborted(format!("Query reference value: {e}")))?;
 
         let reference_value_results = serde_json::to_string(&rvs)
             .map_err(|e| Status::aborted(format!("Serde reference value: {e}")))?;
@@ -68,14 +106,186 @@ impl ReferenceValueProviderService for RvpsServer {
         let res = ReferenceValueRegisterResponse {};
         Ok(Response::new(res))
     }
+
+    async fn delete_reference_value(
+        &self,
+        request: Request<ReferenceValueDeleteRequest>,
+    ) -> Result<Response<ReferenceValueDeleteResponse>, Status> {
+        let request = request.into_inner();
+        let mut rvps = self.rvps.write().await;
+
+        let deleted = match (request.name.is_empty(), request.prefix.is_empty()) {
+            (false, true) => {
+                debug!("delete reference value: {}", request.name);
+                match rvps
+                    .delete_reference_value(&request.name)
+                    .await
+                    .map_err(|e| Status::aborted(format!("Delete reference value: {e}")))?
+                {
+                    true => vec![request.name],
+                    false => vec![],
+                }
+            }
+            (true, false) => {
+                debug!("delete reference values by prefix: {}", request.prefix);
+                rvps.delete_reference_values_by_prefix(&request.prefix)
+                    .await
+                    .map_err(|e| Status::aborted(format!("Delete reference values: {e}")))?
+            }
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Exactly one of `name` and `prefix` must be given",
+                ))
+            }
+        };
+
+        let res = ReferenceValueDeleteResponse { deleted };
+        Ok(Response::new(res))
+    }
+
+    async fn list_reference_values(
+        &self,
+        _request: Request<ReferenceValueListRequest>,
+    ) -> Result<Response<ReferenceValueListResponse>, Status> {
+        let rvs = self
+            .rvps
+            .read()
+            .await
+            .list_reference_values()
+            .await
+            .map_err(|e| Status::aborted(format!("List reference values: {e}")))?;
+
+        let reference_values = serde_json::to_string(&rvs)
+            .map_err(|e| Status::aborted(format!("Serde reference value: {e}")))?;
+
+        let res = ReferenceValueListResponse { reference_values };
+        Ok(Response::new(res))
+    }
+
+    async fn purge_expired_reference_values(
+        &self,
+        _request: Request<ReferenceValuePurgeRequest>,
+    ) -> Result<Response<ReferenceValuePurgeResponse>, Status> {
+        let purged = self
+            .rvps
+            .write()
+            .await
+            .purge_expired()
+            .await
+            .map_err(|e| Status::aborted(format!("Purge reference values: {e}")))?;
+
+        let res = ReferenceValuePurgeResponse { purged };
+        Ok(Response::new(res))
+    }
+
+    async fn import_reference_values(
+        &self,
+        request: Request<ReferenceValueImportRequest>,
+    ) -> Result<Response<ReferenceValueImportResponse>, Status> {
+        let request = request.into_inner();
+        let rvs = serde_json::from_str(&request.reference_values)
+            .map_err(|e| Status::invalid_argument(format!("Parse reference values: {e}")))?;
+
+        let imported = self
+            .rvps
+            .write()
+            .await
+            .import_reference_values(rvs)
+            .await
+            .map_err(|e| Status::aborted(format!("Import reference values: {e}")))?;
+
+        let res = ReferenceValueImportResponse { imported };
+        Ok(Response::new(res))
+    }
+
+    type WatchReferenceValuesStream = ReceiverStream<Result<ReferenceValueWatchResponse, Status>>;
+
+    async fn watch_reference_values(
+        &self,
+        _request: Request<ReferenceValueWatchRequest>,
+    ) -> Result<Response<Self::WatchReferenceValuesStream>, Status> {
+        // Subscribe and take the snapshot under the same lock, so that no
+        // change is missed in between.
+        let (mut changes, first) = {
+            let rvps = self.rvps.read().await;
+            (rvps.subscribe(), snapshot(&rvps).await?)
+        };
+        debug!("watch reference values from revision {}", first.revision);
+
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        let rvps = self.rvps.clone();
+        tokio::spawn(async move {
+            let mut next = Ok(first);
+            loop {
+                let failed = next.is_err();
+                if tx.send(next).await.is_err() || failed {
+                    break;
+                }
+
+                next = tokio::select! {
+                    _ = tx.closed() => break,
+                    res = changes.recv() => match res {
+                        Ok(c) => change(c),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Watcher lagged behind by {skipped} changes, send a new snapshot");
+                            snapshot(&*rvps.read().await).await
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// TLS configuration of the RVPS server.
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    /// Paths to the PEM encoded certificate chain and private key of the
+    /// server. TLS is enabled if both are given.
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+
+    /// Path to the PEM encoded CA certificate that issues the client
+    /// certificates. If given, clients must authenticate with mutual TLS.
+    pub client_ca: Option<PathBuf>,
 }
 
-pub async fn start(socket: SocketAddr, config: Config) -> Result<()> {
+impl TlsConfig {
+    pub fn to_server_tls_config(&self) -> Result<Option<ServerTlsConfig>> {
+        let (Some(cert), Some(key)) = (&self.cert, &self.key) else {
+            if self.cert.is_some() || self.key.is_some() || self.client_ca.is_some() {
+                bail!("Both the server certificate and private key are needed for TLS");
+            }
+            return Ok(None);
+        };
+
+        let cert = std::fs::read(cert).context("read server certificate")?;
+        let key = std::fs::read(key).context("read server private key")?;
+        let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(client_ca) = &self.client_ca {
+            let client_ca = std::fs::read(client_ca).context("read client CA certificate")?;
+            tls = tls.client_ca_root(Certificate::from_pem(client_ca));
+        }
+
+        Ok(Some(tls))
+    }
+}
+
+pub async fn start(socket: SocketAddr, config: Config, tls: TlsConfig) -> Result<()> {
     let service = Rvps::new(config)?;
     let inner = Arc::new(RwLock::new(service));
     let rvps_server = RvpsServer::new(inner.clone());
 
-    Server::builder()
+    let mut server = Server::builder();
+    if let Some(tls) = tls.to_server_tls_config()? {
+        info!("TLS is enabled");
+        server = server.tls_config(tls).context("TLS configuration")?;
+    }
+
+    server
         .add_service(ReferenceValueProviderServiceServer::new(rvps_server))
         .serve(socket)
         .await
@@ -0,0 +1,445 @@
+// Copyright (c) 2025 Confidential Containers Project Authors.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+
+//! Verification of the signature envelope of a [`crate::Message`].
+//!
+//! A message can carry one or more [DSSE](https://github.com/secure-systems-lab/dsse)
+//! signatures over its `payload`. The signed bytes are the DSSE pre-authentication
+//! encoding (PAE) of the message `type` and the base64 decoded `payload`:
+//!
+//! ```text
+//! "DSSEv1" SP LEN(type) SP type SP LEN(payload) SP payload
+//! ```
+//!
+//! A signature is trusted either if it verifies against one of the configured
+//! provider public keys, or if it carries a certificate chaining up to one of
+//! the configured CA certificates.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use log::{debug, warn};
+use openssl::{
+    hash::MessageDigest,
+    pkey::{Id, PKey, PKeyRef, Public},
+    sign::Verifier,
+    stack::Stack,
+    x509::{store::X509StoreBuilder, X509NameRef, X509StoreContext, X509},
+};
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the trust anchors for signed messages.
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SignatureConfig {
+    /// Map of key ids to the paths of PEM encoded public keys of the
+    /// trusted reference value providers.
+    #[serde(default)]
+    pub trusted_keys: HashMap<String, String>,
+
+    /// Paths of PEM encoded CA certificates. A signature carrying a
+    /// certificate issued by one of them is trusted.
+    #[serde(default)]
+    pub trusted_ca_certs: Vec<String>,
+}
+
+/// A DSSE signature over the payload of a message.
+/// * `keyid`: optional hint of which trusted key made the signature.
+/// * `sig`: base64 encoded signature. ECDSA (DER) and RSA (PKCS#1 v1.5)
+///   signatures are made over the SHA-256 digest of the PAE, Ed25519
+///   signatures over the PAE itself.
+/// * `cert`: optional PEM encoded signing certificate, followed by the
+///   intermediate certificates if there are any.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DsseSignature {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyid: Option<String>,
+    pub sig: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+}
+
+/// DSSE pre-authentication encoding of the payload.
+pub fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut res = format!(
+        "DSSEv1 {} {payload_type} {} ",
+        payload_type.len(),
+        payload.len()
+    )
+    .into_bytes();
+    res.extend_from_slice(payload);
+    res
+}
+
+pub struct SignatureVerifier {
+    trusted_keys: HashMap<String, PKey<Public>>,
+    trusted_ca_certs: Vec<X509>,
+}
+
+impl SignatureVerifier {
+    pub fn new(config: SignatureConfig) -> Result<Self> {
+        let mut trusted_keys = HashMap::new();
+        for (keyid, path) in config.trusted_keys {
+            let pem = std::fs::read(&path)
+                .with_context(|| format!("read trusted key {keyid} from {path}"))?;
+            let key = PKey::public_key_from_pem(&pem)
+                .with_context(|| format!("parse trusted key {keyid}"))?;
+            trusted_keys.insert(keyid, key);
+        }
+
+        let mut trusted_ca_certs = Vec::new();
+        for path in config.trusted_ca_certs {
+            let pem = std::fs::read(&path).with_context(|| format!("read CA cert {path}"))?;
+            let certs =
+                X509::stack_from_pem(&pem).with_context(|| format!("parse CA cert {path}"))?;
+            trusted_ca_certs.extend(certs);
+        }
+
+        Ok(Self {
+            trusted_keys,
+            trusted_ca_certs,
+        })
+    }
+
+    /// Whether any provider key or CA certificate is configured.
+    pub fn has_trust_anchors(&self) -> bool {
+        !self.trusted_keys.is_empty() || !self.trusted_ca_certs.is_empty()
+    }
+
+    /// Verify the signatures over the payload. Return the identity of the
+    /// signer of the first trusted signature, i.e. the key id of a trusted
+    /// key or the subject of a trusted certificate. Return `None` if there
+    /// are no signatures at all. As in a DSSE envelope, the `payload` is
+    /// base64 encoded and the signatures are made over the decoded bytes.
+    pub fn verify(
+        &self,
+        payload_type: &str,
+        payload: &str,
+        signatures: &[DsseSignature],
+    ) -> Result<Option<String>> {
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        if !self.has_trust_anchors() {
+            bail!("The message is signed but no trusted provider keys or CA certificates are configured");
+        }
+
+        let payload = base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .context("base64 decode payload")?;
+        let pae = pae(payload_type, &payload);
+        for signature in signatures {
+            match self.verify_signature(&pae, signature) {
+                Ok(signer) => {
+                    debug!("Message signature of {signer} is verified.");
+                    return Ok(Some(signer));
+                }
+                Err(e) => warn!("Untrusted message signature: {e:#}"),
+            }
+        }
+
+        bail!("None of the message signatures is trusted")
+    }
+
+    fn verify_signature(&self, pae: &[u8], signature: &DsseSignature) -> Result<String> {
+        let sig = base64::engine::general_purpose::STANDARD
+            .decode(&signature.sig)
+            .context("base64 decode signature")?;
+
+        if let Some(cert) = &signature.cert {
+            let mut chain = X509::stack_from_pem(cert.as_bytes()).context("parse certificate")?;
+            if chain.is_empty() {
+                bail!("empty certificate");
+            }
+            let leaf = chain.remove(0);
+            self.verify_cert(&leaf, chain)?;
+            verify_with_key(leaf.public_key()?.as_ref(), pae, &sig)?;
+            return Ok(subject(leaf.subject_name()));
+        }
+
+        match &signature.keyid {
+            Some(keyid) => {
+                let key = self
+                    .trusted_keys
+                    .get(keyid)
+                    .ok_or_else(|| anyhow!("unknown key id {keyid}"))?;
+                verify_with_key(key, pae, &sig)?;
+                Ok(keyid.clone())
+            }
+            None => self
+                .trusted_keys
+                .iter()
+                .find(|(_, key)| verify_with_key(key, pae, &sig).is_ok())
+                .map(|(keyid, _)| keyid.clone())
+                .ok_or_else(|| anyhow!("signature does not match any trusted key")),
+        }
+    }
+
+    fn verify_cert(&self, leaf: &X509, intermediates: Vec<X509>) -> Result<()> {
+        let mut store = X509StoreBuilder::new()?;
+        for ca in &self.trusted_ca_certs {
+            store.add_cert(ca.clone())?;
+        }
+        let store = store.build();
+
+        let mut chain = Stack::new()?;
+        for cert in intermediates {
+            chain.push(cert)?;
+        }
+
+        let mut context = X509StoreContext::new()?;
+        let verified = context.init(&store, leaf, &chain, |c| {
+            let res = c.verify_cert()?;
+            if !res {
+                debug!("certificate verification error: {}", c.error());
+            }
+            Ok(res)
+        })?;
+        if !verified {
+            bail!("certificate is not issued by a trusted CA");
+        }
+
+        Ok(())
+    }
+}
+
+fn verify_with_key(key: &PKeyRef<Public>, data: &[u8], sig: &[u8]) -> Result<()> {
+    let mut verifier = match key.id() {
+        Id::ED25519 | Id::ED448 => Verifier::new_without_digest(key)?,
+        _ => Verifier::new(MessageDigest::sha256(), key)?,
+    };
+
+    if !verifier.verify_oneshot(sig, data)? {
+        bail!("signature mismatch");
+    }
+
+    Ok(())
+}
+
+/// Render the subject of a certificate like `CN=provider,O=example`.
+fn subject(name: &X509NameRef) -> String {
+    name.entries()
+        .filter_map(|entry| {
+            let key = entry.object().nid().short_name().ok()?;
+            let value = String::from_utf8_lossy(entry.data().as_slice());
+            Some(format!("{key}={value}"))
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base64::Engine;
+    use openssl::{
+        asn1::Asn1Time,
+        bn::BigNum,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::{PKey, Private},
+        sign::Signer,
+        x509::{extension::BasicConstraints, X509NameBuilder, X509},
+    };
+
+    use super::{pae, DsseSignature, SignatureConfig, SignatureVerifier};
+
+    const PAYLOAD_TYPE: &str = "sample";
+    const PAYLOAD: &str = "eyJ0ZXN0IjogWyJhYmMiXX0=";
+    const DECODED_PAYLOAD: &[u8] = br#"{"test": ["abc"]}"#;
+
+    /// Public key and signature over the PAE of [`PAYLOAD_TYPE`] and
+    /// [`DECODED_PAYLOAD`], produced with the `openssl` command line:
+    ///
+    /// ```text
+    /// printf 'DSSEv1 6 sample 17 {"test": ["abc"]}' > pae
+    /// openssl dgst -sha256 -sign key.pem pae | base64 -w0
+    /// ```
+    const OPENSSL_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEghy/G9vrLD9dtjxa3srOssrpC83J
+rftsyUjp9Ns4yFTQ1zMegBLLXGLhGdeRZ6F6YyxyL/0B0y4LKTbquT58QQ==
+-----END PUBLIC KEY-----
+";
+    const OPENSSL_SIG: &str =
+        "MEQCIFlUDmxTaO6ld0CnYcDk/xLYKzyx4ecIdM98uPGutLbAAiArKdfGpVGi+cZ+MXUe80i3c1XffcLIZSz24jt+7aa9zg==";
+
+    fn new_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn sign(key: &PKey<Private>) -> String {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        let sig = signer
+            .sign_oneshot_to_vec(&pae(PAYLOAD_TYPE, DECODED_PAYLOAD))
+            .unwrap();
+        base64::engine::general_purpose::STANDARD.encode(sig)
+    }
+
+    fn new_cert(cn: &str, key: &PKey<Private>, issuer: Option<(&X509, &PKey<Private>)>) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", cn).unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder
+            .set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_pubkey(key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        match issuer {
+            Some((issuer_cert, issuer_key)) => {
+                builder.set_issuer_name(issuer_cert.subject_name()).unwrap();
+                builder.sign(issuer_key, MessageDigest::sha256()).unwrap();
+            }
+            None => {
+                builder
+                    .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                    .unwrap();
+                builder.set_issuer_name(&name).unwrap();
+                builder.sign(key, MessageDigest::sha256()).unwrap();
+            }
+        }
+
+        builder.build()
+    }
+
+    fn write_tmp(dir: &tempfile::TempDir, name: &str, content: &[u8]) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn pae_of_dsse_spec_example() {
+        assert_eq!(
+            pae("http://example.com/HelloWorld", b"hello world"),
+            b"DSSEv1 29 http://example.com/HelloWorld 11 hello world"
+        );
+    }
+
+    #[test]
+    fn verify_external_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = write_tmp(&dir, "provider.pem", OPENSSL_KEY.as_bytes());
+        let verifier = SignatureVerifier::new(SignatureConfig {
+            trusted_keys: HashMap::from([("provider".to_string(), key_path)]),
+            trusted_ca_certs: vec![],
+        })
+        .unwrap();
+
+        let signature = DsseSignature {
+            keyid: Some("provider".to_string()),
+            sig: OPENSSL_SIG.to_string(),
+            cert: None,
+        };
+        let signer = verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[signature])
+            .unwrap();
+        assert_eq!(signer.as_deref(), Some("provider"));
+    }
+
+    #[test]
+    fn verify_with_trusted_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = new_key();
+        let key_path = write_tmp(&dir, "provider.pem", &key.public_key_to_pem().unwrap());
+        let verifier = SignatureVerifier::new(SignatureConfig {
+            trusted_keys: HashMap::from([("provider".to_string(), key_path)]),
+            trusted_ca_certs: vec![],
+        })
+        .unwrap();
+
+        let signature = DsseSignature {
+            keyid: None,
+            sig: sign(&key),
+            cert: None,
+        };
+        let signer = verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[signature.clone()])
+            .unwrap();
+        assert_eq!(signer.as_deref(), Some("provider"));
+
+        // Unsigned messages are not rejected here
+        assert_eq!(verifier.verify(PAYLOAD_TYPE, PAYLOAD, &[]).unwrap(), None);
+
+        // The payload type is covered by the signature
+        assert!(verifier.verify("swid", PAYLOAD, &[signature]).is_err());
+
+        let untrusted = DsseSignature {
+            keyid: None,
+            sig: sign(&new_key()),
+            cert: None,
+        };
+        assert!(verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[untrusted])
+            .is_err());
+
+        let unknown = DsseSignature {
+            keyid: Some("unknown".to_string()),
+            sig: sign(&key),
+            cert: None,
+        };
+        assert!(verifier.verify(PAYLOAD_TYPE, PAYLOAD, &[unknown]).is_err());
+    }
+
+    #[test]
+    fn verify_with_trusted_ca() {
+        let dir = tempfile::tempdir().unwrap();
+        let ca_key = new_key();
+        let ca = new_cert("Test CA", &ca_key, None);
+        let ca_path = write_tmp(&dir, "ca.pem", &ca.to_pem().unwrap());
+        let verifier = SignatureVerifier::new(SignatureConfig {
+            trusted_keys: HashMap::new(),
+            trusted_ca_certs: vec![ca_path],
+        })
+        .unwrap();
+
+        let key = new_key();
+        let cert = new_cert("provider", &key, Some((&ca, &ca_key)));
+        let signature = DsseSignature {
+            keyid: None,
+            sig: sign(&key),
+            cert: Some(String::from_utf8(cert.to_pem().unwrap()).unwrap()),
+        };
+        let signer = verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[signature])
+            .unwrap();
+        assert_eq!(signer.as_deref(), Some("CN=provider"));
+
+        let self_signed = new_cert("provider", &key, None);
+        let signature = DsseSignature {
+            keyid: None,
+            sig: sign(&key),
+            cert: Some(String::from_utf8(self_signed.to_pem().unwrap()).unwrap()),
+        };
+        assert!(verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[signature])
+            .is_err());
+    }
+
+    #[test]
+    fn reject_signed_without_trust_anchors() {
+        let verifier = SignatureVerifier::new(SignatureConfig::default()).unwrap();
+        let signature = DsseSignature {
+            keyid: None,
+            sig: sign(&new_key()),
+            cert: None,
+        };
+        assert!(verifier
+            .verify(PAYLOAD_TYPE, PAYLOAD, &[signature])
+            .is_err());
+    }
+}
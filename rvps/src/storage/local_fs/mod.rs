@@ -88,10 +88,62 @@ impl ReferenceValueStorage for LocalFs {
 
         Ok(values)
     }
+
+    async fn get_values_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>> {
+        let mut values = Vec::new();
+
+        for (_k, v) in self.engine.scan_prefix(prefix).flatten() {
+            values.push(serde_json::from_slice(&v)?);
+        }
+
+        Ok(values)
+    }
+
+    async fn delete(&self, name: &str) -> Result<Option<ReferenceValue>> {
+        let res = match self.engine.remove(name).context("remove from sled")? {
+            Some(v) => {
+                let v = serde_json::from_slice(&v)?;
+                Some(v)
+            }
+            None => None,
+        };
+
+        self.engine.flush()?;
+        Ok(res)
+    }
+
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>> {
+        let mut deleted = Vec::new();
+
+        for (k, _v) in self.engine.scan_prefix(prefix).flatten() {
+            if let Some(v) = self.engine.remove(k).context("remove from sled")? {
+                deleted.push(serde_json::from_slice(&v)?);
+            }
+        }
+
+        self.engine.flush()?;
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self) -> Result<Vec<ReferenceValue>> {
+        let mut purged = Vec::new();
+
+        for (k, v) in self.engine.iter().flatten() {
+            let rv: ReferenceValue = serde_json::from_slice(&v)?;
+            if rv.expired() {
+                self.engine.remove(k).context("remove from sled")?;
+                purged.push(rv);
+            }
+        }
+
+        self.engine.flush()?;
+        Ok(purged)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::{TimeZone, Utc};
     use serial_test::serial;
 
     use crate::{ReferenceValue, ReferenceValueStorage};
@@ -196,4 +248,79 @@ mod tests {
             assert_eq!(got, rv);
         }
     }
+
+    /// This test will test the `delete` and `delete_by_prefix`
+    /// interfaces for [`LocalFs`].
+    #[tokio::test]
+    #[serial]
+    async fn delete() {
+        let temp_dir = tempfile::tempdir().expect("create tempdir failed");
+        let dir_str = temp_dir.path().to_string_lossy().to_string();
+        let storage =
+            LocalFs::new(Config { file_path: dir_str }).expect("create local fs store failed.");
+        for name in ["kernel-1", "kernel-2", "initrd", "test1"] {
+            let rv = ReferenceValue::new()
+                .expect("create ReferenceValue failed.")
+                .set_name(name);
+            storage
+                .set(name.to_owned(), rv)
+                .await
+                .expect("set rv failed.");
+        }
+
+        let deleted = storage
+            .delete(KEY)
+            .await
+            .expect("delete rv failed.")
+            .expect("get None from LocalFs Store");
+        assert_eq!(deleted.name(), KEY);
+        assert!(storage.get(KEY).await.expect("get rv failed.").is_none());
+        assert!(storage
+            .delete(KEY)
+            .await
+            .expect("delete rv failed.")
+            .is_none());
+
+        let deleted = storage
+            .delete_by_prefix("kernel-")
+            .await
+            .expect("delete rvs failed.");
+        assert_eq!(deleted.len(), 2);
+
+        let left = storage.get_values().await.expect("get rvs failed.");
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].name(), "initrd");
+    }
+
+    /// This test will test the `purge_expired` interface
+    /// for [`LocalFs`].
+    #[tokio::test]
+    #[serial]
+    async fn purge_expired() {
+        let temp_dir = tempfile::tempdir().expect("create tempdir failed");
+        let dir_str = temp_dir.path().to_string_lossy().to_string();
+        let storage =
+            LocalFs::new(Config { file_path: dir_str }).expect("create local fs store failed.");
+        let expired = ReferenceValue::new()
+            .expect("create ReferenceValue failed.")
+            .set_name("expired")
+            .set_expiration(Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap());
+        let valid = ReferenceValue::new()
+            .expect("create ReferenceValue failed.")
+            .set_name("valid");
+        storage
+            .set("expired".to_owned(), expired.clone())
+            .await
+            .expect("set rv failed.");
+        storage
+            .set("valid".to_owned(), valid.clone())
+            .await
+            .expect("set rv failed.");
+
+        let purged = storage.purge_expired().await.expect("purge rvs failed.");
+        assert_eq!(purged, vec![expired]);
+
+        let left = storage.get_values().await.expect("get rvs failed.");
+        assert_eq!(left, vec![valid]);
+    }
 }
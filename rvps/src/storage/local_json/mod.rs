@@ -89,4 +89,41 @@ impl ReferenceValueStorage for LocalJson {
         let rvs: Vec<ReferenceValue> = serde_json::from_slice(&file)?;
         Ok(rvs)
     }
+
+    async fn delete(&self, name: &str) -> Result<Option<ReferenceValue>> {
+        let _guard = self.lock.write().await;
+        let file = tokio::fs::read(&self.file_path).await?;
+        let mut rvs: Vec<ReferenceValue> = serde_json::from_slice(&file)?;
+        let Some(index) = rvs.iter().position(|rv| rv.name == name) else {
+            return Ok(None);
+        };
+
+        let res = rvs.remove(index);
+        let contents = serde_json::to_vec(&rvs)?;
+        tokio::fs::write(&self.file_path, contents).await?;
+        Ok(Some(res))
+    }
+
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>> {
+        let _guard = self.lock.write().await;
+        let file = tokio::fs::read(&self.file_path).await?;
+        let rvs: Vec<ReferenceValue> = serde_json::from_slice(&file)?;
+        let (deleted, kept): (Vec<_>, Vec<_>) =
+            rvs.into_iter().partition(|rv| rv.name.starts_with(prefix));
+
+        let contents = serde_json::to_vec(&kept)?;
+        tokio::fs::write(&self.file_path, contents).await?;
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self) -> Result<Vec<ReferenceValue>> {
+        let _guard = self.lock.write().await;
+        let file = tokio::fs::read(&self.file_path).await?;
+        let rvs: Vec<ReferenceValue> = serde_json::from_slice(&file)?;
+        let (purged, kept): (Vec<_>, Vec<_>) = rvs.into_iter().partition(|rv| rv.expired());
+
+        let contents = serde_json::to_vec(&kept)?;
+        tokio::fs::write(&self.file_path, contents).await?;
+        Ok(purged)
+    }
 }
@@ -12,17 +12,23 @@ use strum::Display;
 
 use self::local_fs::LocalFs;
 use self::local_json::LocalJson;
+#[cfg(feature = "sql")]
+use self::sql::Sql;
 
 use super::ReferenceValue;
 
 pub mod local_fs;
 pub mod local_json;
+#[cfg(feature = "sql")]
+pub mod sql;
 
 #[derive(Clone, Debug, Deserialize, Display, PartialEq)]
 #[serde(tag = "type")]
 pub enum ReferenceValueStorageConfig {
     LocalFs(local_fs::Config),
     LocalJson(local_json::Config),
+    #[cfg(feature = "sql")]
+    Sql(sql::Config),
 }
 
 impl Default for ReferenceValueStorageConfig {
@@ -40,6 +46,11 @@ impl ReferenceValueStorageConfig {
                 Ok(Box::new(LocalJson::new(cfg.clone())?)
                     as Box<dyn ReferenceValueStorage + Send + Sync>)
             }
+            #[cfg(feature = "sql")]
+            ReferenceValueStorageConfig::Sql(cfg) => {
+                Ok(Box::new(Sql::new(cfg.clone())?)
+                    as Box<dyn ReferenceValueStorage + Send + Sync>)
+            }
         }
     }
 }
@@ -57,4 +68,30 @@ pub trait ReferenceValueStorage {
 
     // Retrieve reference values
     async fn get_values(&self) -> Result<Vec<ReferenceValue>>;
+
+    /// Retrieve the reference values whose name starts with `prefix`.
+    async fn get_values_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>> {
+        let mut values = self.get_values().await?;
+        values.retain(|rv| rv.name().starts_with(prefix));
+        Ok(values)
+    }
+
+    /// Retrieve the unexpired reference values whose name starts with
+    /// `prefix`. The empty prefix retrieves all the unexpired ones.
+    async fn get_unexpired_values_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>> {
+        let mut values = self.get_values_by_prefix(prefix).await?;
+        values.retain(|rv| !rv.expired());
+        Ok(values)
+    }
+
+    /// Delete the reference value of the given `name`. If it exists,
+    /// return the deleted `Some<ReferenceValue>`, otherwise return `None`
+    async fn delete(&self, name: &str) -> Result<Option<ReferenceValue>>;
+
+    /// Delete all the reference values whose name starts with `prefix`
+    /// and return the deleted ones.
+    async fn delete_by_prefix(&self, prefix: &str) -> Result<Vec<ReferenceValue>>;
+
+    /// Delete all the expired reference values and return the deleted ones.
+    async fn purge_expired(&self) -> Result<Vec<ReferenceValue>>;
 }
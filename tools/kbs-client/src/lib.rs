@@ -12,7 +12,7 @@ use kbs_protocol::evidence_provider::NativeEvidenceProvider;
 use kbs_protocol::token_provider::TestTokenProvider;
 use kbs_protocol::KbsClientBuilder;
 use kbs_protocol::KbsClientCapabilities;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 const KBS_URL_PREFIX: &str = "kbs/v0";
@@ -156,6 +156,118 @@ pub async fn set_attestation_policy(
     }
 }
 
+/// List attestation policies
+/// Input parameters:
+/// - url: KBS server root URL.
+/// - auth_key: KBS owner's authenticate private key (PEM string).
+/// - kbs_root_certs_pem: Custom HTTPS root certificate of KBS server. It can be left blank.
+///
+/// Returns a JSON map of the policy IDs to the digests of the policies.
+pub async fn list_attestation_policies(
+    url: &str,
+    auth_key: String,
+    kbs_root_certs_pem: Vec<String>,
+) -> Result<String> {
+    let auth_private_key = Ed25519KeyPair::from_pem(&auth_key)?;
+    let claims = Claims::create(Duration::from_hours(2));
+    let token = auth_private_key.sign(claims)?;
+
+    let http_client = build_http_client(kbs_root_certs_pem)?;
+
+    let policy_url = format!("{}/{KBS_URL_PREFIX}/attestation-policy", url);
+
+    let res = http_client
+        .get(policy_url)
+        .bearer_auth(token.clone())
+        .send()
+        .await?;
+
+    match res.status() {
+        reqwest::StatusCode::OK => Ok(res.text().await?),
+        _ => {
+            bail!("Request Failed, Response: {:?}", res.text().await?)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GetPolicyOutput {
+    policy: String,
+}
+
+/// Get attestation policy
+/// Input parameters:
+/// - url: KBS server root URL.
+/// - auth_key: KBS owner's authenticate private key (PEM string).
+/// - policy_id: Policy ID.
+/// - kbs_root_certs_pem: Custom HTTPS root certificate of KBS server. It can be left blank.
+///
+/// Returns the content of the policy.
+pub async fn get_attestation_policy(
+    url: &str,
+    auth_key: String,
+    policy_id: &str,
+    kbs_root_certs_pem: Vec<String>,
+) -> Result<Vec<u8>> {
+    let auth_private_key = Ed25519KeyPair::from_pem(&auth_key)?;
+    let claims = Claims::create(Duration::from_hours(2));
+    let token = auth_private_key.sign(claims)?;
+
+    let http_client = build_http_client(kbs_root_certs_pem)?;
+
+    let policy_url = format!("{}/{KBS_URL_PREFIX}/attestation-policy/{policy_id}", url);
+
+    let res = http_client
+        .get(policy_url)
+        .bearer_auth(token.clone())
+        .send()
+        .await?;
+
+    match res.status() {
+        reqwest::StatusCode::OK => {
+            let output = res.json::<GetPolicyOutput>().await?;
+            Ok(URL_SAFE_NO_PAD.decode(output.policy)?)
+        }
+        _ => {
+            bail!("Request Failed, Response: {:?}", res.text().await?)
+        }
+    }
+}
+
+/// Delete attestation policy
+/// Input parameters:
+/// - url: KBS server root URL.
+/// - auth_key: KBS owner's authenticate private key (PEM string).
+/// - policy_id: Policy ID. The built-in default policies cannot be deleted.
+/// - kbs_root_certs_pem: Custom HTTPS root certificate of KBS server. It can be left blank.
+pub async fn delete_attestation_policy(
+    url: &str,
+    auth_key: String,
+    policy_id: &str,
+    kbs_root_certs_pem: Vec<String>,
+) -> Result<()> {
+    let auth_private_key = Ed25519KeyPair::from_pem(&auth_key)?;
+    let claims = Claims::create(Duration::from_hours(2));
+    let token = auth_private_key.sign(claims)?;
+
+    let http_client = build_http_client(kbs_root_certs_pem)?;
+
+    let policy_url = format!("{}/{KBS_URL_PREFIX}/attestation-policy/{policy_id}", url);
+
+    let res = http_client
+        .delete(policy_url)
+        .bearer_auth(token.clone())
+        .send()
+        .await?;
+
+    match res.status() {
+        reqwest::StatusCode::OK => Ok(()),
+        _ => {
+            bail!("Request Failed, Response: {:?}", res.text().await?)
+        }
+    }
+}
+
 #[derive(Clone, Serialize)]
 struct ResourcePolicyData {
     pub policy: String,
@@ -99,6 +99,24 @@ enum ConfigCommands {
         policy_file: PathBuf,
     },
 
+    /// List attestation verification policies and their digests
+    ListAttestationPolicies,
+
+    /// Get attestation verification policy
+    GetAttestationPolicy {
+        /// Policy ID, e.g "default"
+        #[clap(long, value_parser)]
+        id: String,
+    },
+
+    /// Delete attestation verification policy
+    DeleteAttestationPolicy {
+        /// Policy ID. The built-in `default_cpu` and `default_gpu` policies
+        /// cannot be deleted
+        #[clap(long, value_parser)]
+        id: String,
+    },
+
     /// Set resource policy
     SetResourcePolicy {
         /// Policy file path
@@ -260,6 +278,35 @@ async fn main() -> Result<()> {
                         STANDARD.encode(policy_bytes)
                     );
                 }
+                ConfigCommands::ListAttestationPolicies => {
+                    let policies = kbs_client::list_attestation_policies(
+                        &cli.url,
+                        auth_key.clone(),
+                        kbs_cert.clone(),
+                    )
+                    .await?;
+                    println!("{policies}");
+                }
+                ConfigCommands::GetAttestationPolicy { id } => {
+                    let policy = kbs_client::get_attestation_policy(
+                        &cli.url,
+                        auth_key.clone(),
+                        &id,
+                        kbs_cert.clone(),
+                    )
+                    .await?;
+                    println!("{}", String::from_utf8_lossy(&policy));
+                }
+                ConfigCommands::DeleteAttestationPolicy { id } => {
+                    kbs_client::delete_attestation_policy(
+                        &cli.url,
+                        auth_key.clone(),
+                        &id,
+                        kbs_cert.clone(),
+                    )
+                    .await?;
+                    println!("Delete attestation policy success \n policy_id: {id}");
+                }
                 ConfigCommands::SetResourcePolicy {
                     policy_file,
                     allow_all,
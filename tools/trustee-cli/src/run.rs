@@ -63,7 +63,7 @@ fn get_config(
         {
             if let reference_value_provider_service::storage::ReferenceValueStorageConfig::LocalFs(
                 local_fs_config,
-            ) = &mut rvps_config.storage
+            ) = &mut rvps_config.config.storage
             {
                 local_fs_config.file_path =
                     replace_base_dir(Path::new(&local_fs_config.file_path), trustee_home_dir)